@@ -1,8 +1,9 @@
 use std::net::Ipv4Addr;
+use std::time::Duration;
 
 use dns_test::{
     FQDN, Forwarder, Network, Resolver, Result,
-    client::{Client, DigSettings},
+    client::{Client, DigSettings, DigStatus},
     name_server::{Graph, NameServer, Sign},
     record::{Record, RecordType},
 };
@@ -73,3 +74,77 @@ fn nxdomain() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn first_upstream_unreachable() -> Result<()> {
+    let network = Network::new()?;
+
+    let expected_ipv4_addr = Ipv4Addr::new(1, 2, 3, 4);
+    let mut leaf_ns = NameServer::new(&dns_test::PEER, FQDN::TEST_DOMAIN, &network)?;
+    leaf_ns.add(Record::a(FQDN::EXAMPLE_SUBDOMAIN, expected_ipv4_addr));
+
+    let Graph {
+        nameservers: _nameservers,
+        root,
+        ..
+    } = Graph::build(leaf_ns, Sign::No)?;
+
+    let resolver = Resolver::new(&network, root).start_with_subject(&dns_test::PEER)?;
+    let client = Client::new(&network)?;
+    // the client container does not run a DNS server
+    let unreachable = client.ipv4_addr();
+    let forwarder =
+        Forwarder::with_upstreams(&network, [unreachable, resolver.ipv4_addr()]).start()?;
+
+    let settings = *DigSettings::default()
+        .recurse()
+        .timeout(Duration::from_secs(10));
+    let output = client.dig(
+        settings,
+        forwarder.ipv4_addr(),
+        RecordType::A,
+        &FQDN::EXAMPLE_SUBDOMAIN,
+    )?;
+
+    assert!(output.status.is_noerror(), "{:?}", output.status);
+
+    let [answer] = output.answer.try_into().unwrap();
+    let a = answer.try_into_a().unwrap();
+
+    assert_eq!(a.fqdn, FQDN::EXAMPLE_SUBDOMAIN);
+    assert_eq!(a.ipv4_addr, expected_ipv4_addr);
+
+    Ok(())
+}
+
+#[test]
+fn refuses_clients_outside_allowed_netmasks() -> Result<()> {
+    let network = Network::new()?;
+    let ns = NameServer::new(&dns_test::PEER, FQDN::ROOT, &network)?.start()?;
+
+    let resolver = Resolver::new(&network, ns.root_hint()).start_with_subject(&dns_test::PEER)?;
+    let allowed_client = Client::new(&network)?;
+    let refused_client = Client::new(&network)?;
+    let forwarder = Forwarder::new(&network, &resolver)
+        .allow_netmask(&format!("{}/32", allowed_client.ipv4_addr()))
+        .start()?;
+
+    let settings = *DigSettings::default().recurse();
+    let output = allowed_client.dig(
+        settings,
+        forwarder.ipv4_addr(),
+        RecordType::SOA,
+        &FQDN::ROOT,
+    )?;
+    assert!(output.status.is_noerror(), "{:?}", output.status);
+
+    let output = refused_client.dig(
+        settings,
+        forwarder.ipv4_addr(),
+        RecordType::SOA,
+        &FQDN::ROOT,
+    )?;
+    assert_eq!(DigStatus::REFUSED, output.status);
+
+    Ok(())
+}
@@ -5,7 +5,7 @@ use dns_test::{
     client::{Client, DigSettings},
     name_server::{Graph, NameServer, Sign},
     record::{Record, RecordType},
-    zone_file::{Nsec, SignSettings},
+    zone_file::{DenialOfExistence, SignSettings},
 };
 
 mod bogus;
@@ -57,6 +57,62 @@ fn noerror() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn relays_authenticated_data() -> Result<()> {
+    relays_authenticated_data_test(false)
+}
+
+// hickory-dns does not support forward first
+#[ignore]
+#[test]
+fn relays_authenticated_data_forward_first() -> Result<()> {
+    relays_authenticated_data_test(true)
+}
+
+fn relays_authenticated_data_test(forward_first: bool) -> Result<()> {
+    let network = Network::new()?;
+
+    let expected_ipv4_addr = Ipv4Addr::new(1, 2, 3, 4);
+    let mut leaf_ns = NameServer::new(&dns_test::PEER, FQDN::TEST_DOMAIN, &network)?;
+    leaf_ns.add(Record::a(FQDN::EXAMPLE_SUBDOMAIN, expected_ipv4_addr));
+
+    let Graph {
+        nameservers: _nameservers,
+        root,
+        trust_anchor,
+    } = Graph::build(
+        leaf_ns,
+        Sign::Yes {
+            settings: SignSettings::default(),
+        },
+    )?;
+    let trust_anchor = trust_anchor.unwrap();
+
+    let resolver = Resolver::new(&network, root)
+        .trust_anchor(&trust_anchor)
+        .start_with_subject(&dns_test::PEER)?;
+    let mut forwarder = Forwarder::new(&network, &resolver);
+    forwarder.trust_anchor(&trust_anchor);
+    if forward_first {
+        forwarder.forward_first();
+    }
+    let forwarder = forwarder.start()?;
+    let client = Client::new(&network)?;
+
+    let settings = *DigSettings::default().recurse().authentic_data();
+    let output = client.dig(
+        settings,
+        forwarder.ipv4_addr(),
+        RecordType::A,
+        &FQDN::EXAMPLE_SUBDOMAIN,
+    )?;
+
+    assert!(output.status.is_noerror(), "{:?}", output.status);
+    assert!(output.flags.authenticated_data);
+
+    Ok(())
+}
+
 #[test]
 fn nxdomain_nsec3() -> Result<()> {
     let network = Network::new()?;
@@ -110,7 +166,7 @@ fn nxdomain_nsec() -> Result<()> {
     } = Graph::build(
         leaf_ns,
         Sign::Yes {
-            settings: SignSettings::default().nsec(Nsec::_1),
+            settings: SignSettings::default().nsec(DenialOfExistence::Nsec),
         },
     )?;
     let trust_anchor = trust_anchor.unwrap();
@@ -1,7 +1,14 @@
-use dns_test::client::{Client, DigSettings};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::thread;
+use std::time::Duration;
+
+use dns_test::client::{
+    Client, ClientImplementation, DigOutput, DigSettings, DigStatus, NativeClient,
+};
 use dns_test::name_server::NameServer;
-use dns_test::record::RecordType;
-use dns_test::{FQDN, Network, Result};
+use dns_test::record::{Record, RecordType};
+use dns_test::tshark::{Capture, Direction};
+use dns_test::{AnyPolicy, FQDN, Network, Result, Rrl, ZonemdScheme};
 
 #[test]
 fn authoritative_answer() -> Result<()> {
@@ -21,3 +28,341 @@ fn authoritative_answer() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn serves_records_added_by_a_zone_reload() -> Result<()> {
+    if !dns_test::SUBJECT.supports_reload() {
+        return Ok(());
+    }
+
+    let network = &Network::new()?;
+    let mut ns = NameServer::new(&dns_test::SUBJECT, FQDN::TEST_DOMAIN, network)?.start()?;
+
+    let needle_fqdn = FQDN::EXAMPLE_SUBDOMAIN;
+    let expected = Ipv4Addr::new(1, 2, 3, 4);
+    let mut zone_file = ns.zone_file().clone();
+    zone_file.add(Record::a(needle_fqdn.clone(), expected));
+    ns.reload_zone(zone_file)?;
+
+    // some implementations load the zone file in the background
+    let client = Client::new(network)?;
+    let mut output = None;
+    for _ in 0..10 {
+        let ans = client.dig(
+            DigSettings::default(),
+            ns.ipv4_addr(),
+            RecordType::A,
+            &needle_fqdn,
+        )?;
+        if ans.status.is_noerror() {
+            output = Some(ans);
+            break;
+        }
+        thread::sleep(Duration::from_millis(500));
+    }
+
+    let output = output.ok_or("the record added by the reload was not served")?;
+    assert!(output.flags.authoritative_answer);
+    let [answer] = output.answer.try_into().unwrap();
+    assert_eq!(expected, answer.try_into_a().unwrap().ipv4_addr);
+
+    Ok(())
+}
+
+// the ZONEMD record the server adds to the zone it loads matches the zone it serves, as checked by
+// a validator independent of the server; the same record does not match a tampered zone
+#[test]
+fn zonemd_matches_the_served_zone() -> Result<()> {
+    if !dns_test::SUBJECT.supports_zonemd() {
+        return Ok(());
+    }
+
+    let network = Network::new()?;
+    let mut ns = NameServer::builder(
+        dns_test::SUBJECT.clone(),
+        FQDN::TEST_DOMAIN,
+        network.clone(),
+    )
+    .zonemd(ZonemdScheme::SimpleSha384)
+    .build()?;
+    ns.add(Record::a(
+        FQDN::EXAMPLE_SUBDOMAIN,
+        Ipv4Addr::new(1, 2, 3, 4),
+    ));
+    let ns = ns.start()?;
+
+    let client = Client::new(&network)?;
+    let dig = |record_type| {
+        client.dig(
+            DigSettings::default(),
+            ns.ipv4_addr(),
+            record_type,
+            &FQDN::TEST_DOMAIN,
+        )
+    };
+    let output = dig(RecordType::ZONEMD)?;
+    assert!(output.status.is_noerror());
+    let [zonemd] = output.answer.try_into().unwrap();
+    let zonemd = zonemd.try_into_zonemd().unwrap();
+    assert_eq!((1, 1), (zonemd.scheme, zonemd.hash_algorithm));
+
+    // the server may have bumped the SOA serial when it added the record
+    let output = dig(RecordType::SOA)?;
+    let [soa] = output.answer.try_into().unwrap();
+    let mut zone_file = ns.zone_file().clone();
+    zone_file.soa = soa.try_into_soa().unwrap();
+    zone_file.add(zonemd);
+    client.verify_zonemd(&zone_file)?;
+
+    zone_file.add(Record::a(
+        FQDN::TEST_DOMAIN.push_label("tampered"),
+        Ipv4Addr::new(5, 6, 7, 8),
+    ));
+    assert!(client.verify_zonemd(&zone_file).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn views_answer_depending_on_the_client_network() -> Result<()> {
+    if !dns_test::SUBJECT.supports_views() {
+        return Ok(());
+    }
+
+    let internal = Network::with_label("internal")?;
+    let public = Network::with_label("public")?;
+    let mut ns = NameServer::builder(
+        dns_test::SUBJECT.clone(),
+        FQDN::TEST_DOMAIN,
+        internal.clone(),
+    )
+    .connect(&public)
+    .build()?;
+
+    let fqdn = FQDN::TEST_DOMAIN.push_label("www");
+    let internal_addr = Ipv4Addr::new(10, 0, 0, 1);
+    let public_addr = Ipv4Addr::new(192, 0, 2, 1);
+    let mut internal_zone = ns.zone_file().clone();
+    internal_zone.add(Record::a(fqdn.clone(), internal_addr));
+    ns.add_view(internal.netmask(), internal_zone)?;
+    ns.add(Record::a(fqdn.clone(), public_addr));
+    let ns = ns.start()?;
+
+    for (network, expected) in [(&internal, internal_addr), (&public, public_addr)] {
+        let client = Client::new(network)?;
+        let server = ns.ipv4_addr_in(network).unwrap();
+        let ans = client.dig(DigSettings::default(), server, RecordType::A, &fqdn)?;
+
+        assert!(ans.status.is_noerror());
+        let [answer] = ans.answer.try_into().unwrap();
+        let a = answer.try_into_a().unwrap();
+        assert_eq!(expected, a.ipv4_addr);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn rrl_limits_the_responses_to_a_flooding_client() -> Result<()> {
+    if !dns_test::SUBJECT.supports_rrl() {
+        return Ok(());
+    }
+
+    const RESPONSES_PER_SECOND: usize = 5;
+    const QUERIES: usize = 40;
+
+    let network = Network::new()?;
+    let mut ns = NameServer::builder(dns_test::SUBJECT.clone(), FQDN::TEST_DOMAIN, network)
+        .rrl(Rrl {
+            responses_per_second: RESPONSES_PER_SECOND as u32,
+            window: 1,
+        })
+        .build()?;
+    let fqdn = FQDN::TEST_DOMAIN.push_label("www");
+    ns.add(Record::a(fqdn.clone(), Ipv4Addr::new(192, 0, 2, 1)));
+    let ns = ns.start()?;
+
+    let mut tshark = ns.eavesdrop()?;
+    // all the queries come from the host, i.e. from a single source
+    let settings = *DigSettings::default()
+        .nocookie()
+        .timeout(Duration::from_secs(1));
+    let responses =
+        NativeClient.query_burst(settings, ns.ipv4_addr(), RecordType::A, &fqdn, QUERIES)?;
+
+    let mut answered = 0;
+    let mut truncated = 0;
+    for response in responses.iter().flatten() {
+        if response.flags.truncation {
+            assert!(response.answer.is_empty());
+            truncated += 1;
+        } else {
+            assert_eq!(0, response.rcode);
+            assert_eq!(1, response.answer.len());
+            answered += 1;
+        }
+    }
+    let dropped = QUERIES - answered - truncated;
+
+    // the rate may be measured across two windows
+    assert!(
+        (1..=2 * RESPONSES_PER_SECOND).contains(&answered),
+        "{answered} of {QUERIES} queries were answered"
+    );
+    // past the limit, responses alternate between being dropped and being truncated
+    assert!(truncated > 0, "no response was truncated");
+    assert!(dropped > 0, "no response was dropped");
+
+    tshark.wait_for_new_packets(QUERIES + answered + truncated)?;
+    let captures = tshark.terminate()?;
+
+    let mut queries = 0;
+    let mut sent = 0;
+    let mut sent_truncated = 0;
+    for Capture { message, direction } in &captures {
+        match direction {
+            Direction::Incoming { .. } => queries += 1,
+            Direction::Outgoing { .. } => {
+                sent += 1;
+                if message.is_tc_flag_set() {
+                    sent_truncated += 1;
+                }
+            }
+        }
+    }
+
+    // the client saw every response the name server sent
+    assert_eq!(QUERIES, queries);
+    assert_eq!(answered + truncated, sent);
+    assert_eq!(truncated, sent_truncated);
+
+    Ok(())
+}
+
+// RFC8482: a name server may answer a query of type ANY with a single RRset, or refuse to answer it
+// over UDP
+#[test]
+fn any_policy_answers_any_queries_minimally() -> Result<()> {
+    for any_policy in [AnyPolicy::Minimal, AnyPolicy::Refuse] {
+        if !dns_test::SUBJECT.supports_any_policy(any_policy) {
+            continue;
+        }
+
+        let network = Network::new()?;
+        let mut ns = NameServer::builder(
+            dns_test::SUBJECT.clone(),
+            FQDN::TEST_DOMAIN,
+            network.clone(),
+        )
+        .any_policy(any_policy)
+        .build()?;
+        ns.add(Record::a(
+            FQDN::EXAMPLE_SUBDOMAIN,
+            Ipv4Addr::new(192, 0, 2, 1),
+        ));
+        ns.add(Record::aaaa(
+            FQDN::EXAMPLE_SUBDOMAIN,
+            Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1),
+        ));
+        let ns = ns.start()?;
+
+        let client = Client::new(&network)?;
+        // the meta-type ANY is not part of `RecordType`
+        let any = RecordType::Unknown(255);
+        let output = client.dig(
+            *DigSettings::default().ignore(),
+            ns.ipv4_addr(),
+            any,
+            &FQDN::EXAMPLE_SUBDOMAIN,
+        )?;
+
+        match any_policy {
+            AnyPolicy::Minimal => {
+                assert!(output.status.is_noerror());
+                let a = output
+                    .answer
+                    .iter()
+                    .filter(|record| matches!(record, Record::A(_)))
+                    .count();
+                let aaaa = output
+                    .answer
+                    .iter()
+                    .filter(|record| matches!(record, Record::AAAA(_)))
+                    .count();
+                assert_eq!(output.answer.len(), a + aaaa, "{:?}", output.answer);
+                assert!((a == 0) != (aaaa == 0), "{:?}", output.answer);
+            }
+
+            AnyPolicy::Refuse => {
+                assert!(output.answer.is_empty(), "{:?}", output.answer);
+                assert!(
+                    output.flags.truncation
+                        || [DigStatus::REFUSED, DigStatus::NOTIMP].contains(&output.status),
+                    "{output:?}"
+                );
+            }
+
+            AnyPolicy::Normal => unreachable!(),
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn client_tools_agree_on_the_responses() -> Result<()> {
+    let network = Network::new()?;
+    let mut ns = NameServer::new(&dns_test::SUBJECT, FQDN::TEST_DOMAIN, &network)?;
+    let fqdn = FQDN::TEST_DOMAIN.push_label("www");
+    ns.add(Record::a(fqdn.clone(), Ipv4Addr::new(192, 0, 2, 1)));
+    let ns = ns.start()?;
+
+    // what is compared: the records as `Display`ed, so that the parsing quirks of each tool
+    // show up
+    fn summary(output: &DigOutput) -> (String, Vec<String>, Vec<String>) {
+        let records = |records: &[Record]| records.iter().map(Record::to_string).collect();
+        (
+            format!("{:?} {:?}", output.status, output.flags),
+            records(&output.answer),
+            records(&output.authority),
+        )
+    }
+
+    let clients = ClientImplementation::ALL
+        .into_iter()
+        .map(|implementation| Client::with_implementation(&network, implementation))
+        .collect::<Result<Vec<_>>>()?;
+
+    for (record_type, fqdn) in [
+        (RecordType::A, fqdn),
+        (RecordType::A, FQDN::TEST_DOMAIN.push_label("nonexistent")),
+        (RecordType::SOA, FQDN::TEST_DOMAIN),
+    ] {
+        let mut summaries = vec![];
+        for client in &clients {
+            let output = client.dig(
+                DigSettings::default(),
+                ns.ipv4_addr(),
+                record_type.clone(),
+                &fqdn,
+            )?;
+            assert_eq!(
+                Some(IpAddr::from(ns.ipv4_addr())),
+                output.server,
+                "{}",
+                client.implementation()
+            );
+            summaries.push((client.implementation(), summary(&output)));
+        }
+
+        let (reference, expected) = &summaries[0];
+        for (implementation, actual) in &summaries[1..] {
+            assert_eq!(
+                expected, actual,
+                "{implementation} disagrees with {reference} on {record_type} {fqdn}"
+            );
+        }
+    }
+
+    Ok(())
+}
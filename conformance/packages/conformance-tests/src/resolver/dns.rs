@@ -1,6 +1,14 @@
 //! plain DNS functionality
 
 mod rfc1035;
+mod rfc2308;
 mod rfc3597;
+mod rfc7858;
+mod rfc7871;
+mod rfc7873;
+mod rfc8484;
+mod rfc8767;
 mod rfc8906;
+mod rfc9156;
+mod rfc9460;
 mod scenarios;
@@ -3,7 +3,8 @@
 //! response is truncated, and a resolver doesn't know whether it has a complete set, it should not
 //! cache a possibly partial set of RRs."
 
-use std::{fs, thread, time::Duration};
+use std::fs;
+use std::time::Duration;
 
 use dns_test::{
     FQDN, Implementation, Network, PEER, Resolver, Result,
@@ -20,7 +21,9 @@ fn truncated_response_caching_with_tcp_fallback() -> Result<()> {
     let (resolver, client, _graph) =
         setup("src/resolver/dns/rfc1035/truncated_with_tcp_fallback.py")?;
 
-    let dig_settings = *DigSettings::default().recurse().timeout(7);
+    let dig_settings = *DigSettings::default()
+        .recurse()
+        .timeout(Duration::from_secs(7));
 
     let result_1 = client.dig(
         dig_settings,
@@ -68,7 +71,9 @@ fn truncated_response_caching_udp_only() -> Result<()> {
     let target_fqdn = FQDN("example.testing.")?;
     let (resolver, client, _graph) = setup("src/resolver/dns/rfc1035/truncated_udp_only.py")?;
 
-    let dig_settings = *DigSettings::default().recurse().timeout(7);
+    let dig_settings = *DigSettings::default()
+        .recurse()
+        .timeout(Duration::from_secs(7));
 
     let result_1 = client.dig(
         dig_settings,
@@ -128,8 +133,6 @@ fn setup(script_path: &str) -> Result<(Resolver, Client, Graph)> {
     let root_ns = root_ns.start()?;
     let leaf_ns = leaf_ns.start()?;
 
-    thread::sleep(Duration::from_secs(2));
-
     let graph = Graph {
         nameservers: vec![root_ns, leaf_ns],
         root: root_hint,
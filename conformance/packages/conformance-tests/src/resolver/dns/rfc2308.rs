@@ -0,0 +1,83 @@
+//! Negative Caching of DNS Queries (DNS NCACHE)
+
+use std::thread;
+use std::time::Duration;
+
+use dns_test::client::{Client, DigSettings};
+use dns_test::name_server::{Graph, NameServer, Sign};
+use dns_test::record::{RecordType, SoaSettings};
+use dns_test::tshark::{Capture, Direction};
+use dns_test::{FQDN, Network, Resolver, Result};
+
+#[test]
+fn caches_nxdomain_for_soa_minimum() -> Result<()> {
+    let needle_fqdn = FQDN::TEST_DOMAIN.push_label("unicorn");
+    let minimum = 3;
+
+    let network = Network::new()?;
+
+    let leaf_ns = NameServer::builder(dns_test::PEER.clone(), FQDN::TEST_DOMAIN, network.clone())
+        .soa_settings(SoaSettings {
+            minimum,
+            ..SoaSettings::default()
+        })
+        .build()?;
+    let leaf_ns_addr = leaf_ns.ipv4_addr();
+
+    let Graph {
+        nameservers: _nameservers,
+        root,
+        ..
+    } = Graph::build(leaf_ns, Sign::No)?;
+
+    let resolver = Resolver::new(&network, root).start()?;
+    let client = Client::new(&network)?;
+
+    let mut tshark = resolver.eavesdrop()?;
+    let settings = *DigSettings::default().recurse();
+    let dig = || -> Result<()> {
+        let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+        assert!(output.status.is_nxdomain());
+        Ok(())
+    };
+
+    dig()?;
+    // answered from the negative cache
+    dig()?;
+    // the negative answer expires after the SOA minimum (the SOA TTL is larger)
+    thread::sleep(Duration::from_secs(u64::from(minimum) + 1));
+    dig()?;
+
+    tshark.wait_for_capture()?;
+    let captures = tshark.terminate()?;
+
+    let mut queries_for_needle = 0;
+    for Capture { message, direction } in &captures {
+        let Direction::Outgoing { destination } = direction else {
+            continue;
+        };
+
+        if *destination != leaf_ns_addr {
+            continue;
+        }
+
+        // keys look like "unicorn.hickory-dns.testing: type A, class IN"; the case of the name
+        // may be randomized
+        if let Some(queries) = message.as_value()["Queries"].as_object() {
+            let needle = needle_fqdn.as_str().trim_end_matches('.');
+            queries_for_needle += queries
+                .keys()
+                .filter(|query| {
+                    query
+                        .split_once(": ")
+                        .is_some_and(|(name, _)| name.eq_ignore_ascii_case(needle))
+                })
+                .count();
+        }
+    }
+
+    // one query when the cache is empty and one after the negative answer expires
+    assert_eq!(2, queries_for_needle);
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+//! DNS over Transport Layer Security (TLS)
+
+use std::net::Ipv4Addr;
+
+use dns_test::client::{Client, DigSettings};
+use dns_test::name_server::{Graph, NameServer, Sign};
+use dns_test::record::{Record, RecordType};
+use dns_test::{FQDN, Network, PEER, Resolver, Result, Role, SUBJECT, Transport};
+
+#[test]
+fn resolves_over_tls() -> Result<()> {
+    if !SUBJECT.supports_transport(Role::Resolver, Transport::DoT) {
+        // the resolver does not support DoT
+        return Ok(());
+    }
+
+    if !PEER.supports_transport(Role::NameServer, Transport::DoT) {
+        // the name server does not support DoT
+        return Ok(());
+    }
+
+    let expected_ipv4_addr = Ipv4Addr::new(1, 2, 3, 4);
+    let needle_fqdn = FQDN::EXAMPLE_SUBDOMAIN;
+
+    let network = Network::new()?;
+
+    let mut leaf_ns = NameServer::builder(PEER.clone(), FQDN::TEST_DOMAIN, network.clone())
+        .transport(Transport::DoT)
+        .build()?;
+    leaf_ns.add(Record::a(needle_fqdn.clone(), expected_ipv4_addr));
+
+    let Graph {
+        nameservers, root, ..
+    } = Graph::build(leaf_ns, Sign::No)?;
+
+    let resolver = Resolver::new(&network, root)
+        .transport(Transport::DoT)
+        .start()?;
+    let client = Client::new(&network)?;
+
+    // the authoritative name server completes the handshake and answers ...
+    let leaf_ns = nameservers
+        .iter()
+        .find(|nameserver| nameserver.zone() == &FQDN::TEST_DOMAIN)
+        .unwrap();
+    let settings = *DigSettings::default().transport(Transport::DoT);
+    let output = client.dig(settings, leaf_ns.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+
+    assert!(output.status.is_noerror());
+    assert!(output.flags.authoritative_answer);
+
+    // ... and so does the resolver
+    let settings = *DigSettings::default().recurse().transport(Transport::DoT);
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+
+    assert!(output.status.is_noerror());
+
+    let [answer] = output.answer.try_into().unwrap();
+    let a = answer.try_into_a().unwrap();
+
+    assert_eq!(needle_fqdn, a.fqdn);
+    assert_eq!(expected_ipv4_addr, a.ipv4_addr);
+
+    Ok(())
+}
+
+#[test]
+fn verifies_certificate_of_resolver() -> Result<()> {
+    if !SUBJECT.supports_transport(Role::Resolver, Transport::DoT) {
+        // the resolver does not support DoT
+        return Ok(());
+    }
+
+    let network = Network::new()?;
+    let ns = NameServer::new(&PEER, FQDN::ROOT, &network)?.start()?;
+    let resolver = Resolver::new(&network, ns.root_hint())
+        .transport(Transport::DoT)
+        .start()?;
+    let other_resolver = Resolver::new(&network, ns.root_hint())
+        .transport(Transport::DoT)
+        .start()?;
+    let client = Client::new(&network)?;
+
+    let settings = *DigSettings::default().recurse();
+    let output = client.dig_tls(
+        settings,
+        resolver.ipv4_addr(),
+        &resolver.tls_certificate()?,
+        RecordType::SOA,
+        &FQDN::ROOT,
+    )?;
+    assert!(output.status.is_noerror());
+
+    // the certificate of another resolver is not trusted to vouch for this one
+    let result = client.dig_tls(
+        settings,
+        resolver.ipv4_addr(),
+        &other_resolver.tls_certificate()?,
+        RecordType::SOA,
+        &FQDN::ROOT,
+    );
+    assert!(result.is_err(), "{result:?}");
+
+    Ok(())
+}
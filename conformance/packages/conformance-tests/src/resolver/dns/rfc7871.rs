@@ -0,0 +1,90 @@
+//! Client Subnet in DNS Queries
+
+use std::net::{IpAddr, Ipv4Addr};
+
+use dns_test::client::{Client, ClientSubnet, DigSettings};
+use dns_test::name_server::{Graph, NameServer, Sign};
+use dns_test::record::{A, RecordType};
+use dns_test::tshark::{Capture, Direction};
+use dns_test::{FQDN, Network, Resolver, Result, SUBJECT};
+
+#[test]
+fn forwards_client_subnet_when_enabled() -> Result<()> {
+    if !SUBJECT.supports_edns_client_subnet() {
+        // the resolver does not forward the EDNS Client Subnet option
+        return Ok(());
+    }
+
+    let subnets = client_subnets_sent_upstream(true)?;
+
+    assert!(!subnets.is_empty());
+    for subnet in subnets {
+        assert_eq!(IpAddr::from(Ipv4Addr::new(10, 0, 0, 0)), subnet.address);
+        assert!(subnet.source_prefix_length <= 24);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn does_not_forward_client_subnet_by_default() -> Result<()> {
+    let subnets = client_subnets_sent_upstream(false)?;
+
+    assert!(
+        subnets
+            .iter()
+            .all(|subnet| subnet.address != IpAddr::from(Ipv4Addr::new(10, 0, 0, 0))),
+        "{subnets:?}"
+    );
+
+    Ok(())
+}
+
+/// Sends a query with the client subnet 10.0.0.0/24 to the resolver and returns the client
+/// subnets of the queries the resolver sent to the leaf name server
+fn client_subnets_sent_upstream(edns_client_subnet: bool) -> Result<Vec<ClientSubnet>> {
+    let needle_fqdn = FQDN::TEST_DOMAIN.push_label("example");
+    let network = Network::new()?;
+
+    let mut leaf_ns = NameServer::new(&dns_test::PEER, FQDN::TEST_DOMAIN, &network)?;
+    leaf_ns.add(A {
+        fqdn: needle_fqdn.clone(),
+        ttl: 3600,
+        ipv4_addr: Ipv4Addr::new(1, 2, 3, 4),
+    });
+    let leaf_ns_addr = leaf_ns.ipv4_addr();
+
+    let Graph {
+        nameservers: _nameservers,
+        root,
+        ..
+    } = Graph::build(leaf_ns, Sign::No)?;
+
+    let mut resolver = Resolver::new(&network, root);
+    if edns_client_subnet {
+        resolver.edns_client_subnet();
+    }
+    let resolver = resolver.start()?;
+    let client = Client::new(&network)?;
+
+    let mut tshark = resolver.eavesdrop()?;
+    let settings = *DigSettings::default()
+        .recurse()
+        .subnet(Ipv4Addr::new(10, 0, 0, 0), 24);
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+    assert!(output.status.is_noerror());
+
+    tshark.wait_for_capture()?;
+    let captures = tshark.terminate()?;
+
+    let mut subnets = vec![];
+    for Capture { message, direction } in &captures {
+        if let Direction::Outgoing { destination } = direction {
+            if *destination == leaf_ns_addr {
+                subnets.extend(message.client_subnet());
+            }
+        }
+    }
+
+    Ok(subnets)
+}
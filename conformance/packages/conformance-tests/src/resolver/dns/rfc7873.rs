@@ -0,0 +1,155 @@
+//! Domain Name System (DNS) Cookies
+
+use std::net::Ipv4Addr;
+
+use dns_test::client::{Client, DigSettings, DigStatus};
+use dns_test::name_server::{Graph, NameServer, Running, Sign};
+use dns_test::record::{A, RecordType};
+use dns_test::{Cookies, FQDN, Network, Resolver, Result, Role, SUBJECT};
+
+const CLIENT_COOKIE: &str = "0102030405060708";
+
+#[test]
+fn echoes_the_client_cookie_and_accepts_its_own_server_cookie() -> Result<()> {
+    if !SUBJECT.supports_cookies(Role::Resolver) {
+        // the resolver has no cookie settings
+        return Ok(());
+    }
+
+    let (network, _nameservers, resolver) = setup(Cookies {
+        answer: true,
+        require: false,
+        secret: Some("0123456789abcdef0123456789abcdef".to_string()),
+    })?;
+    let client = Client::new(&network)?;
+
+    let settings = *DigSettings::default().recurse().cookie(CLIENT_COOKIE);
+    let output = client.dig(
+        settings,
+        resolver.ipv4_addr(),
+        RecordType::A,
+        &needle_fqdn(),
+    )?;
+    assert!(output.status.is_noerror());
+
+    let cookie = output.cookie.expect("no COOKIE option in the response");
+    let server_cookie = cookie
+        .strip_prefix(CLIENT_COOKIE)
+        .expect("the client cookie was not echoed");
+    // 8 to 32 bytes, in hexadecimal
+    assert!((16..=64).contains(&server_cookie.len()), "{cookie}");
+
+    let settings = *DigSettings::default().recurse().cookie(&cookie);
+    let output = client.dig(
+        settings,
+        resolver.ipv4_addr(),
+        RecordType::A,
+        &needle_fqdn(),
+    )?;
+    assert!(output.status.is_noerror());
+
+    let cookie = output.cookie.expect("no COOKIE option in the response");
+    let new_server_cookie = cookie
+        .strip_prefix(CLIENT_COOKIE)
+        .expect("the client cookie was not echoed");
+    assert_eq!(server_cookie.len(), new_server_cookie.len());
+
+    Ok(())
+}
+
+#[test]
+fn mismatched_server_cookie_is_answered_with_badcookie() -> Result<()> {
+    if !SUBJECT.supports_required_cookies() {
+        // the resolver cannot require server cookies
+        return Ok(());
+    }
+
+    let (network, _nameservers, resolver) = setup(Cookies {
+        answer: true,
+        require: true,
+        secret: None,
+    })?;
+    let client = Client::new(&network)?;
+
+    // a server cookie that this server did not issue
+    let mismatched_cookie = format!("{CLIENT_COOKIE}{}", "00".repeat(16));
+
+    let settings = *DigSettings::default()
+        .recurse()
+        .cookie(&mismatched_cookie)
+        .nobadcookie();
+    let output = client.dig(
+        settings,
+        resolver.ipv4_addr(),
+        RecordType::A,
+        &needle_fqdn(),
+    )?;
+    assert_eq!(DigStatus::BADCOOKIE, output.status);
+    assert!(output.answer.is_empty());
+
+    // the response carries a fresh server cookie, with which the query succeeds
+    let fresh_cookie = output.cookie.expect("no COOKIE option in the response");
+    assert!(fresh_cookie.starts_with(CLIENT_COOKIE));
+    assert_ne!(mismatched_cookie, fresh_cookie);
+
+    let settings = *DigSettings::default()
+        .recurse()
+        .cookie(&fresh_cookie)
+        .nobadcookie();
+    let output = client.dig(
+        settings,
+        resolver.ipv4_addr(),
+        RecordType::A,
+        &needle_fqdn(),
+    )?;
+    assert!(output.status.is_noerror());
+
+    // BADCOOKIE only applies to UDP: the same query over TCP is answered
+    let settings = *DigSettings::default()
+        .recurse()
+        .cookie(&mismatched_cookie)
+        .nobadcookie()
+        .tcp();
+    let output = client.dig(
+        settings,
+        resolver.ipv4_addr(),
+        RecordType::A,
+        &needle_fqdn(),
+    )?;
+    assert!(output.status.is_noerror());
+
+    // and `dig` itself retries with the fresh server cookie
+    let settings = *DigSettings::default().recurse().cookie(&mismatched_cookie);
+    let output = client.dig(
+        settings,
+        resolver.ipv4_addr(),
+        RecordType::A,
+        &needle_fqdn(),
+    )?;
+    assert!(output.status.is_noerror());
+
+    Ok(())
+}
+
+fn needle_fqdn() -> FQDN {
+    FQDN::EXAMPLE_SUBDOMAIN
+}
+
+fn setup(cookies: Cookies) -> Result<(Network, Vec<NameServer<Running>>, Resolver)> {
+    let network = Network::new()?;
+
+    let mut leaf_ns = NameServer::new(&dns_test::PEER, FQDN::TEST_DOMAIN, &network)?;
+    leaf_ns.add(A {
+        fqdn: needle_fqdn(),
+        ttl: 3600,
+        ipv4_addr: Ipv4Addr::new(1, 2, 3, 4),
+    });
+
+    let Graph {
+        nameservers, root, ..
+    } = Graph::build(leaf_ns, Sign::No)?;
+
+    let resolver = Resolver::new(&network, root).cookies(cookies).start()?;
+
+    Ok((network, nameservers, resolver))
+}
@@ -0,0 +1,140 @@
+//! DNS Queries over HTTPS (DoH)
+
+use std::net::Ipv4Addr;
+
+use dns_test::client::{Client, DigOutput, DigSettings};
+use dns_test::name_server::{Graph, NameServer, Sign};
+use dns_test::record::{Record, RecordType};
+use dns_test::zone_file::SignSettings;
+use dns_test::{FQDN, Network, PEER, Resolver, Result, Role, SUBJECT, Transport};
+
+#[test]
+fn get_and_post_match_do53() -> Result<()> {
+    if !SUBJECT.supports_transport(Role::Resolver, Transport::DoH) {
+        // the resolver does not support DoH
+        return Ok(());
+    }
+
+    let expected_ipv4_addr = Ipv4Addr::new(1, 2, 3, 4);
+    let needle_fqdn = FQDN::EXAMPLE_SUBDOMAIN;
+
+    let network = Network::new()?;
+
+    let mut leaf_ns = NameServer::new(&PEER, FQDN::TEST_DOMAIN, &network)?;
+    leaf_ns.add(Record::a(needle_fqdn.clone(), expected_ipv4_addr));
+
+    let Graph {
+        nameservers: _nameservers,
+        root,
+        ..
+    } = Graph::build(leaf_ns, Sign::No)?;
+
+    let resolver = Resolver::new(&network, root)
+        .transport(Transport::DoH)
+        .start()?;
+    let client = Client::new(&network)?;
+
+    let settings = *DigSettings::default().recurse();
+    let do53 = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+
+    assert!(do53.status.is_noerror());
+
+    let [answer] = do53.answer.try_into().unwrap();
+    let a = answer.try_into_a().unwrap();
+
+    assert_eq!(needle_fqdn, a.fqdn);
+    assert_eq!(expected_ipv4_addr, a.ipv4_addr);
+
+    // `dig` sends the query, with the `application/dns-message` content type, as the body of a
+    // POST request ...
+    let settings = *DigSettings::default().recurse().transport(Transport::DoH);
+    let post = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+
+    assert!(post.status.is_noerror());
+
+    let [answer] = post.answer.try_into().unwrap();
+    let answer = answer.try_into_a().unwrap();
+    assert_eq!(a.fqdn, answer.fqdn);
+    assert_eq!(a.ipv4_addr, answer.ipv4_addr);
+
+    // ... or base64url-encoded in the `dns` parameter of a GET request
+    let settings = *DigSettings::default()
+        .recurse()
+        .transport(Transport::DoH)
+        .https_get();
+    let get = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+
+    assert!(get.status.is_noerror());
+
+    let [answer] = get.answer.try_into().unwrap();
+    let answer = answer.try_into_a().unwrap();
+    assert_eq!(a.fqdn, answer.fqdn);
+    assert_eq!(a.ipv4_addr, answer.ipv4_addr);
+
+    Ok(())
+}
+
+#[test]
+fn signed_answer_validates_over_do53_and_doh() -> Result<()> {
+    if !SUBJECT.supports_transport(Role::Resolver, Transport::DoH) {
+        // the resolver does not support DoH
+        return Ok(());
+    }
+
+    let needle_fqdn = FQDN::EXAMPLE_SUBDOMAIN;
+
+    let network = Network::new()?;
+
+    let mut leaf_ns = NameServer::new(&PEER, FQDN::TEST_DOMAIN, &network)?;
+    leaf_ns.add(Record::a(needle_fqdn.clone(), Ipv4Addr::new(1, 2, 3, 4)));
+
+    let Graph {
+        nameservers: _nameservers,
+        root,
+        trust_anchor,
+    } = Graph::build(
+        leaf_ns,
+        Sign::Yes {
+            settings: SignSettings::default(),
+        },
+    )?;
+
+    let resolver = Resolver::new(&network, root)
+        .trust_anchor(&trust_anchor.unwrap())
+        .transport(Transport::DoH)
+        .start()?;
+    let client = Client::new(&network)?;
+
+    // the TTLs decrease between queries, the records and signatures do not
+    let answer = |output: &DigOutput| {
+        output
+            .answer
+            .iter()
+            .map(|record| match record {
+                Record::A(a) => a.ipv4_addr.to_string(),
+                Record::RRSIG(rrsig) => rrsig.signature.clone(),
+                _ => panic!("unexpected record: {record}"),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let settings = *DigSettings::default().recurse().dnssec().authentic_data();
+    let do53 = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+
+    assert!(do53.status.is_noerror());
+    assert!(do53.flags.authenticated_data);
+    assert_eq!(2, answer(&do53).len(), "{:?}", do53.answer);
+
+    let settings = *DigSettings::default()
+        .recurse()
+        .dnssec()
+        .authentic_data()
+        .transport(Transport::DoH);
+    let doh = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+
+    assert!(doh.status.is_noerror());
+    assert!(doh.flags.authenticated_data);
+    assert_eq!(answer(&do53), answer(&doh));
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+//! Serving Stale Data to Improve DNS Resiliency
+
+use std::net::Ipv4Addr;
+use std::thread;
+use std::time::Duration;
+
+use dns_test::client::{Client, DigSettings};
+use dns_test::record::{A, RecordType};
+use dns_test::topology::Topology;
+use dns_test::{FQDN, Network, Result, SUBJECT, ServeStale};
+
+#[test]
+fn answers_from_stale_cache_when_name_servers_are_down() -> Result<()> {
+    if !SUBJECT.supports_serve_stale() {
+        // the resolver does not serve stale data
+        return Ok(());
+    }
+
+    let expected_ipv4_addr = Ipv4Addr::new(1, 2, 3, 4);
+    let needle_fqdn = FQDN::EXAMPLE_SUBDOMAIN;
+    let ttl = 2;
+
+    let network = Network::new()?;
+
+    let deployment = Topology::unsigned(&dns_test::PEER, &network)
+        .with_leaf_zone(FQDN::TEST_DOMAIN, |zone| {
+            zone.add(A {
+                fqdn: needle_fqdn.clone(),
+                ttl,
+                ipv4_addr: expected_ipv4_addr,
+            });
+        })
+        .build()?;
+
+    let resolver = deployment
+        .resolver()
+        .serve_stale(ServeStale {
+            ttl: 3600,
+            client_timeout: 1000,
+        })
+        .start()?;
+    let client = Client::new(&network)?;
+
+    let settings = *DigSettings::default().recurse();
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+    assert!(output.status.is_noerror());
+
+    // removing the containers makes the name servers unreachable
+    drop(deployment);
+    thread::sleep(Duration::from_secs(u64::from(ttl) + 1));
+
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+    assert!(output.status.is_noerror());
+
+    let [answer] = output.answer.try_into().unwrap();
+    let a = answer.try_into_a().unwrap();
+
+    assert_eq!(needle_fqdn, a.fqdn);
+    assert_eq!(expected_ipv4_addr, a.ipv4_addr);
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+//! DNS Query Name Minimisation to Improve Privacy
+
+use std::net::Ipv4Addr;
+
+use dns_test::client::{Client, DigSettings};
+use dns_test::name_server::{Graph, NameServer, Sign};
+use dns_test::record::{Record, RecordType};
+use dns_test::tshark::{Capture, Direction};
+use dns_test::{FQDN, Network, QnameMinimization, Resolver, Result, SUBJECT};
+
+#[test]
+fn strict_minimization_hides_qname_from_ancestors() -> Result<()> {
+    hides_qname_from_ancestors(QnameMinimization::Strict)
+}
+
+#[test]
+fn relaxed_minimization_hides_qname_from_ancestors() -> Result<()> {
+    hides_qname_from_ancestors(QnameMinimization::Relaxed)
+}
+
+fn hides_qname_from_ancestors(mode: QnameMinimization) -> Result<()> {
+    if !SUBJECT.supports_qname_minimization(mode) {
+        // the resolver does not support this QNAME minimization mode
+        return Ok(());
+    }
+
+    let needle_fqdn = needle_fqdn();
+    let queries = queries_to_ancestors(Some(mode))?;
+    // e.g. `testing.` with type NS, or `_.testing.` with type A, rather than the full QNAME
+    assert!(!queries.is_empty());
+    assert!(!queries.contains(&needle_fqdn), "{queries:#?}");
+
+    Ok(())
+}
+
+#[test]
+fn without_minimization_ancestors_see_qname() -> Result<()> {
+    if !SUBJECT.supports_qname_minimization(QnameMinimization::Off) {
+        // the resolver cannot disable QNAME minimization
+        return Ok(());
+    }
+
+    let needle_fqdn = needle_fqdn();
+    let queries = queries_to_ancestors(Some(QnameMinimization::Off))?;
+    assert!(queries.contains(&needle_fqdn), "{queries:#?}");
+
+    Ok(())
+}
+
+fn needle_fqdn() -> FQDN {
+    FQDN::TEST_DOMAIN.push_label("b").push_label("a")
+}
+
+/// resolves `needle_fqdn()` and returns the names the resolver queried the name servers of the
+/// root and `testing.` zones for
+fn queries_to_ancestors(mode: Option<QnameMinimization>) -> Result<Vec<FQDN>> {
+    let needle_fqdn = needle_fqdn();
+
+    let network = Network::new()?;
+
+    let mut leaf_ns = NameServer::new(&dns_test::PEER, FQDN::TEST_DOMAIN, &network)?;
+    leaf_ns.add(Record::a(needle_fqdn.clone(), Ipv4Addr::new(1, 2, 3, 4)));
+
+    let Graph {
+        nameservers, root, ..
+    } = Graph::build(leaf_ns, Sign::No)?;
+    let ancestors = nameservers
+        .iter()
+        .filter(|nameserver| nameserver.zone() != &FQDN::TEST_DOMAIN)
+        .map(|nameserver| nameserver.ipv4_addr())
+        .collect::<Vec<_>>();
+
+    let mut settings = Resolver::new(&network, root);
+    if let Some(mode) = mode {
+        settings.qname_minimization(mode);
+    }
+    let resolver = settings.start()?;
+    let client = Client::new(&network)?;
+
+    let mut tshark = resolver.eavesdrop()?;
+    let settings = *DigSettings::default().recurse();
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+    assert!(output.status.is_noerror());
+    tshark.wait_for_capture()?;
+    let captures = tshark.terminate()?;
+
+    let mut qnames = vec![];
+    for Capture { message, direction } in &captures {
+        let Direction::Outgoing { destination } = direction else {
+            continue;
+        };
+
+        if !ancestors.contains(destination) {
+            continue;
+        }
+
+        // keys look like "a.b.hickory-dns.testing: type A, class IN"; the names omit the
+        // trailing `.`, the root zone is "<Root>", and the case may be randomized
+        if let Some(queries) = message.as_value()["Queries"].as_object() {
+            for query in queries.keys() {
+                let (name, _) = query.split_once(": ").unwrap();
+                let qname = if name == "<Root>" {
+                    FQDN::ROOT
+                } else {
+                    FQDN(format!("{}.", name.to_ascii_lowercase()))?
+                };
+                qnames.push(qname);
+            }
+        }
+    }
+
+    Ok(qnames)
+}
@@ -0,0 +1,64 @@
+//! Service binding records (SVCB and HTTPS)
+
+use std::collections::BTreeMap;
+use std::net::Ipv4Addr;
+
+use dns_test::{
+    FQDN, Network, PEER, Resolver, Result,
+    client::{Client, DigSettings},
+    name_server::{Graph, NameServer, Sign},
+    record::{HTTPS, RecordType, SvcParamKey, SvcParamValue},
+};
+
+#[test]
+fn https_record_params() -> Result<()> {
+    let network = Network::new()?;
+
+    let params = BTreeMap::from([
+        (
+            SvcParamKey::Alpn,
+            SvcParamValue::Alpn(vec!["h2".to_string(), "h3".to_string()]),
+        ),
+        (
+            SvcParamKey::Ipv4Hint,
+            SvcParamValue::Ipv4Hint(vec![Ipv4Addr::new(192, 0, 2, 1)]),
+        ),
+    ]);
+
+    let mut leaf_ns = NameServer::new(&PEER, FQDN::TEST_DOMAIN, &network)?;
+    leaf_ns.add(HTTPS {
+        zone: FQDN::TEST_DOMAIN,
+        ttl: 86400,
+        priority: 1,
+        target: FQDN::ROOT,
+        params: params.clone(),
+    });
+
+    let Graph {
+        nameservers: _nameservers,
+        root,
+        ..
+    } = Graph::build(leaf_ns, Sign::No)?;
+
+    let resolver = Resolver::new(&network, root).start()?;
+    let client = Client::new(&network)?;
+
+    let settings = *DigSettings::default().recurse();
+    let output = client.dig(
+        settings,
+        resolver.ipv4_addr(),
+        RecordType::HTTPS,
+        &FQDN::TEST_DOMAIN,
+    )?;
+
+    assert!(output.status.is_noerror(), "{:?}", output.status);
+
+    let [answer] = output.answer.try_into().unwrap();
+    let https = answer.try_into_https().unwrap();
+    assert_eq!(FQDN::TEST_DOMAIN, https.zone);
+    assert_eq!(1, https.priority);
+    assert_eq!(FQDN::ROOT, https.target);
+    assert_eq!(params, https.params);
+
+    Ok(())
+}
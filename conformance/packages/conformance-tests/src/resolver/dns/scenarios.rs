@@ -1,13 +1,89 @@
-use std::net::Ipv4Addr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::thread;
+use std::time::Duration;
 
-use dns_test::client::{Client, DigSettings};
+use dns_test::client::{Client, DigSettings, DigStatus, ResolvConfOptions};
 use dns_test::name_server::{Graph, NameServer, Sign};
-use dns_test::record::{Record, RecordType};
+use dns_test::record::{A, CAA, Record, RecordType, SRV};
 use dns_test::tshark::{Capture, Direction};
-use dns_test::{FQDN, Network, Resolver, Result};
+use dns_test::{FQDN, Network, Resolver, Result, TransportRestriction};
+use ipnet::Ipv6Net;
 
 mod bad_referral;
+mod cname;
+mod dynamic_update;
 mod packet_loss;
+mod reload;
+mod secondary;
+
+#[test]
+fn libc_resolves_through_subject() -> Result<()> {
+    let expected_ipv4_addr = Ipv4Addr::new(1, 2, 3, 4);
+    let needle_fqdn = FQDN::EXAMPLE_SUBDOMAIN;
+
+    let network = Network::new()?;
+
+    let mut leaf_ns = NameServer::new(&dns_test::PEER, FQDN::TEST_DOMAIN, &network)?;
+    leaf_ns.add(Record::a(needle_fqdn.clone(), expected_ipv4_addr));
+
+    let Graph {
+        nameservers: _nameservers,
+        root,
+        ..
+    } = Graph::build(leaf_ns, Sign::No)?;
+
+    let resolver = Resolver::new(&network, root).start()?;
+    let client = Client::new(&network)?;
+    client.use_resolver(resolver.ipv4_addr(), *ResolvConfOptions::default().edns0())?;
+
+    let addrs = client.getaddrinfo(&needle_fqdn)?;
+    assert_eq!(vec![IpAddr::from(expected_ipv4_addr)], addrs);
+
+    Ok(())
+}
+
+// the first resolver only answers from local data so libc has to move on to the subject
+#[test]
+fn libc_fails_over_when_recursion_is_refused() -> Result<()> {
+    let expected_ipv4_addr = Ipv4Addr::new(1, 2, 3, 4);
+    let needle_fqdn = FQDN::EXAMPLE_SUBDOMAIN;
+
+    let network = Network::new()?;
+
+    let mut leaf_ns = NameServer::new(&dns_test::PEER, FQDN::TEST_DOMAIN, &network)?;
+    leaf_ns.add(Record::a(needle_fqdn.clone(), expected_ipv4_addr));
+
+    let Graph {
+        nameservers: _nameservers,
+        root,
+        ..
+    } = Graph::build(leaf_ns, Sign::No)?;
+
+    let cache_only = Resolver::new(&network, root.clone())
+        .recursion_disabled()
+        .start_with_subject(&dns_test::PEER)?;
+    let resolver = Resolver::new(&network, root).start()?;
+    let client = Client::new(&network)?;
+
+    let settings = *DigSettings::default().recurse();
+    let output = client.dig(
+        settings,
+        cache_only.ipv4_addr(),
+        RecordType::A,
+        &needle_fqdn,
+    )?;
+    assert_eq!(DigStatus::REFUSED, output.status);
+
+    client.use_resolvers(
+        &[cache_only.ipv4_addr().into(), resolver.ipv4_addr().into()],
+        *ResolvConfOptions::default().edns0(),
+    )?;
+
+    let addrs = client.getaddrinfo(&needle_fqdn)?;
+    assert_eq!(vec![IpAddr::from(expected_ipv4_addr)], addrs);
+
+    Ok(())
+}
 
 #[test]
 fn can_resolve() -> Result<()> {
@@ -44,6 +120,173 @@ fn can_resolve() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn can_resolve_caa() -> Result<()> {
+    let network = Network::new()?;
+    let zone = FQDN("example.com.")?;
+
+    let mut leaf_ns = NameServer::new(&dns_test::PEER, zone.clone(), &network)?;
+    leaf_ns.add(CAA {
+        zone: zone.clone(),
+        ttl: 86400,
+        flags: 0,
+        tag: "issue".to_string(),
+        value: "letsencrypt.org".to_string(),
+    });
+
+    let Graph {
+        nameservers: _nameservers,
+        root,
+        ..
+    } = Graph::build(leaf_ns, Sign::No)?;
+
+    let resolver = Resolver::new(&network, root).start()?;
+    let client = Client::new(&network)?;
+
+    let settings = *DigSettings::default().recurse();
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::CAA, &zone)?;
+
+    assert!(output.status.is_noerror());
+
+    let [answer] = output.answer.try_into().unwrap();
+    let caa = answer.try_into_caa().unwrap();
+
+    assert_eq!(zone, caa.zone);
+    assert_eq!(0, caa.flags);
+    assert_eq!("issue", caa.tag);
+    assert_eq!("letsencrypt.org", caa.value);
+
+    Ok(())
+}
+
+#[test]
+fn can_resolve_srv() -> Result<()> {
+    let network = Network::new()?;
+    let zone = FQDN("example.com.")?;
+    let service = zone.push_label("_tcp").push_label("_ldap");
+
+    let mut leaf_ns = NameServer::new(&dns_test::PEER, zone.clone(), &network)?;
+    for (priority, target) in [(20, "ldap2.example.com."), (10, "ldap1.example.com.")] {
+        leaf_ns.add(SRV {
+            fqdn: service.clone(),
+            ttl: 86400,
+            priority,
+            weight: 0,
+            port: 389,
+            target: FQDN(target)?,
+        });
+    }
+
+    let Graph {
+        nameservers: _nameservers,
+        root,
+        ..
+    } = Graph::build(leaf_ns, Sign::No)?;
+
+    let resolver = Resolver::new(&network, root).start()?;
+    let client = Client::new(&network)?;
+
+    let settings = *DigSettings::default().recurse();
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::SRV, &service)?;
+
+    assert!(output.status.is_noerror());
+
+    // the order of the records within the RRset is not significant
+    let mut srvs = output
+        .answer
+        .into_iter()
+        .map(|record| record.try_into_srv().unwrap())
+        .collect::<Vec<_>>();
+    srvs.sort_by_key(|srv| (srv.priority, srv.weight, srv.port));
+
+    let [first, second] = srvs.try_into().unwrap();
+    assert_eq!(service, first.fqdn);
+    assert_eq!(10, first.priority);
+    assert_eq!(FQDN("ldap1.example.com.")?, first.target);
+    assert_eq!(20, second.priority);
+    assert_eq!(FQDN("ldap2.example.com.")?, second.target);
+
+    Ok(())
+}
+
+#[test]
+fn can_resolve_ptr() -> Result<()> {
+    let network = Network::new()?;
+    let host_addr = Ipv4Addr::new(192, 0, 2, 1);
+    let host = FQDN::TEST_DOMAIN.push_label("host");
+
+    let mut forward_ns = NameServer::new(&dns_test::PEER, FQDN::TEST_DOMAIN, &network)?;
+    forward_ns.add(Record::a(host.clone(), host_addr));
+
+    let reverse_zone = FQDN("2.0.192.in-addr.arpa.")?;
+    let mut reverse_ns = NameServer::new(&dns_test::PEER, reverse_zone.clone(), &network)?;
+    for ptr in forward_ns.zone_file().ptr_records(&reverse_zone) {
+        reverse_ns.add(ptr);
+    }
+
+    let Graph {
+        nameservers: _nameservers,
+        root,
+        ..
+    } = Graph::build(reverse_ns, Sign::No)?;
+
+    let resolver = Resolver::new(&network, root).start()?;
+    let client = Client::new(&network)?;
+
+    // equivalent to `dig -x 192.0.2.1`
+    let settings = *DigSettings::default().recurse();
+    let output = client.dig(
+        settings,
+        resolver.ipv4_addr(),
+        RecordType::PTR,
+        &FQDN::reverse(host_addr),
+    )?;
+
+    assert!(output.status.is_noerror());
+
+    let [answer] = output.answer.try_into().unwrap();
+    let ptr = answer.try_into_ptr().unwrap();
+
+    assert_eq!(FQDN::reverse(host_addr), ptr.fqdn);
+    assert_eq!(host, ptr.target);
+
+    Ok(())
+}
+
+// the resolver answers the second query from its cache, without querying the name servers
+#[test]
+fn cached_answer_is_not_slower_than_the_first_one() -> Result<()> {
+    let needle_fqdn = FQDN::EXAMPLE_SUBDOMAIN;
+
+    let network = Network::new()?;
+
+    let mut leaf_ns = NameServer::new(&dns_test::PEER, FQDN::TEST_DOMAIN, &network)?;
+    leaf_ns.add(Record::a(needle_fqdn.clone(), Ipv4Addr::new(1, 2, 3, 4)));
+
+    let Graph {
+        nameservers: _nameservers,
+        root,
+        ..
+    } = Graph::build(leaf_ns, Sign::No)?;
+
+    let resolver = Resolver::new(&network, root).start()?;
+    let client = Client::new(&network)?;
+
+    let settings = *DigSettings::default().recurse();
+    let mut query_times = vec![];
+    for _ in 0..2 {
+        let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+        assert!(output.status.is_noerror());
+        assert_eq!(Some(IpAddr::from(resolver.ipv4_addr())), output.server);
+        query_times.push(output.query_time.expect("dig reports the query time"));
+    }
+
+    let [cold, cached] = query_times.try_into().unwrap();
+    assert!(cached <= cold, "cached: {cached:?}, cold: {cold:?}");
+
+    Ok(())
+}
+
 #[test]
 fn nxdomain() -> Result<()> {
     let needle_fqdn = FQDN::TEST_DOMAIN.push_label("unicorn");
@@ -129,3 +372,176 @@ fn recursion_desired_flag() -> Result<()> {
 
     Ok(())
 }
+
+// tshark only captures UDP traffic; the client's own query to the resolver shows it was capturing
+#[test]
+fn tcp_only_resolver_sends_no_udp_queries() -> Result<()> {
+    if !dns_test::SUBJECT.supports_transport_restriction(TransportRestriction::TcpOnly) {
+        return Ok(());
+    }
+
+    let expected_ipv4_addr = Ipv4Addr::new(1, 2, 3, 4);
+    let needle_fqdn = FQDN::EXAMPLE_SUBDOMAIN;
+
+    let network = Network::new()?;
+
+    let mut leaf_ns = NameServer::new(&dns_test::PEER, FQDN::TEST_DOMAIN, &network)?;
+    leaf_ns.add(Record::a(needle_fqdn.clone(), expected_ipv4_addr));
+
+    let Graph {
+        nameservers, root, ..
+    } = Graph::build(leaf_ns, Sign::No)?;
+
+    let resolver = Resolver::new(&network, root)
+        .transport_restriction(TransportRestriction::TcpOnly)
+        .start()?;
+    let client = Client::new(&network)?;
+
+    let mut tshark = resolver.eavesdrop()?;
+
+    let settings = *DigSettings::default().recurse();
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+
+    assert!(output.status.is_noerror());
+    let [answer] = output.answer.try_into().unwrap();
+    assert_eq!(expected_ipv4_addr, answer.try_into_a().unwrap().ipv4_addr);
+
+    tshark.wait_for_capture()?;
+    let captures = tshark.terminate()?;
+
+    assert!(
+        captures
+            .iter()
+            .any(|capture| capture.direction.peer_addr() == client.ipv4_addr()),
+        "{captures:#?}"
+    );
+    for Capture { direction, .. } in &captures {
+        assert!(
+            nameservers
+                .iter()
+                .all(|ns| direction.peer_addr() != ns.ipv4_addr()),
+            "{captures:#?}"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn refetches_expired_records() -> Result<()> {
+    let short_fqdn = FQDN::TEST_DOMAIN.push_label("short");
+    let long_fqdn = FQDN::TEST_DOMAIN.push_label("long");
+    let short_ttl = 1;
+
+    let network = Network::new()?;
+
+    let mut leaf_ns = NameServer::new(&dns_test::PEER, FQDN::TEST_DOMAIN, &network)?;
+    leaf_ns.add(A {
+        fqdn: short_fqdn.clone(),
+        ttl: short_ttl,
+        ipv4_addr: Ipv4Addr::new(1, 2, 3, 4),
+    });
+    leaf_ns.add(A {
+        fqdn: long_fqdn.clone(),
+        ttl: 300,
+        ipv4_addr: Ipv4Addr::new(5, 6, 7, 8),
+    });
+
+    let Graph {
+        nameservers, root, ..
+    } = Graph::build(leaf_ns, Sign::No)?;
+    let leaf_ns_addr = nameservers
+        .iter()
+        .find(|nameserver| nameserver.zone() == &FQDN::TEST_DOMAIN)
+        .unwrap()
+        .ipv4_addr();
+
+    let resolver = Resolver::new(&network, root).start()?;
+    let client = Client::new(&network)?;
+
+    let settings = *DigSettings::default().recurse();
+    for fqdn in [&short_fqdn, &long_fqdn] {
+        let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, fqdn)?;
+        assert!(output.status.is_noerror());
+    }
+
+    thread::sleep(Duration::from_secs(u64::from(short_ttl) + 1));
+
+    let mut tshark = resolver.eavesdrop()?;
+    for fqdn in [&short_fqdn, &long_fqdn] {
+        let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, fqdn)?;
+        assert!(output.status.is_noerror());
+    }
+    tshark.wait_for_capture()?;
+    let captures = tshark.terminate()?;
+
+    // the names in the captured queries omit the trailing `.`
+    let queries_for = |fqdn: &FQDN| {
+        let name = fqdn.as_str().trim_end_matches('.');
+        captures
+            .iter()
+            .filter(|capture| {
+                matches!(capture.direction, Direction::Outgoing { destination } if destination == leaf_ns_addr)
+            })
+            .filter(|capture| {
+                capture.message.as_value()["Queries"]
+                    .as_object()
+                    .is_some_and(|queries| {
+                        queries
+                            .keys()
+                            .any(|query| query.starts_with(&format!("{name}: ")))
+                    })
+            })
+            .count()
+    };
+
+    assert_ne!(0, queries_for(&short_fqdn), "{captures:#?}");
+    assert_eq!(0, queries_for(&long_fqdn), "{captures:#?}");
+
+    Ok(())
+}
+
+#[test]
+fn dns64_synthesizes_aaaa_for_a_only_name() -> Result<()> {
+    if !dns_test::SUBJECT.supports_dns64() {
+        return Ok(());
+    }
+
+    let ipv4_addr = Ipv4Addr::new(1, 2, 3, 4);
+    let needle_fqdn = FQDN::EXAMPLE_SUBDOMAIN;
+    let prefix: Ipv6Net = "64:ff9b::/96".parse()?;
+
+    let network = Network::new()?;
+
+    let mut leaf_ns = NameServer::new(&dns_test::PEER, FQDN::TEST_DOMAIN, &network)?;
+    leaf_ns.add(Record::a(needle_fqdn.clone(), ipv4_addr));
+
+    let Graph {
+        nameservers: _nameservers,
+        root,
+        ..
+    } = Graph::build(leaf_ns, Sign::No)?;
+
+    let resolver = Resolver::new(&network, root).dns64_prefix(prefix).start()?;
+    let client = Client::new(&network)?;
+
+    let settings = *DigSettings::default().recurse();
+    let output = client.dig(
+        settings,
+        resolver.ipv4_addr(),
+        RecordType::AAAA,
+        &needle_fqdn,
+    )?;
+
+    assert!(output.status.is_noerror());
+    let [answer] = output.answer.try_into().unwrap();
+    let synthesized = answer.try_into_aaaa().unwrap().ipv6_addr;
+    assert!(prefix.contains(&synthesized), "{synthesized}");
+    // a /96 prefix embeds the IPv4 address in the last 32 bits (RFC6052)
+    assert_eq!(
+        Ipv6Addr::from(u128::from(prefix.network()) | u128::from(u32::from(ipv4_addr))),
+        synthesized
+    );
+
+    Ok(())
+}
@@ -2,6 +2,7 @@
 //! a private IP address where no server is running
 
 use std::net::Ipv4Addr;
+use std::time::Duration;
 
 use dns_test::client::{Client, DigOutput, DigSettings};
 use dns_test::name_server::NameServer;
@@ -105,7 +106,9 @@ fn fixture(label: &str, addr: Ipv4Addr) -> Result<(DigOutput, String)> {
     let resolver = resolver.start()?;
 
     let client = Client::new(&network)?;
-    let settings = *DigSettings::default().recurse().timeout(7);
+    let settings = *DigSettings::default()
+        .recurse()
+        .timeout(Duration::from_secs(7));
     let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
 
     Ok((output, resolver.logs().unwrap()))
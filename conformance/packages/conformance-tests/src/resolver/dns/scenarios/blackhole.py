@@ -0,0 +1,18 @@
+#!/usr/bin/env python3
+# This server ignores every query it receives, in order to simulate a server
+# whose traffic is dropped by a firewall.
+from dnslib import DNSError, DNSRecord
+from dnslib.server import BaseResolver, DNSHandler, DNSServer
+
+
+class Resolver(BaseResolver):
+    def resolve(self, request: DNSRecord, _handler: DNSHandler) -> DNSRecord:
+        # This will be caught by the try-except block in DNSHandler.handle(),
+        # which results in no response being sent.
+        raise DNSError("Ignoring query")
+
+
+if __name__ == "__main__":
+    resolver = Resolver()
+    server = DNSServer(resolver, address="0.0.0.0", port=53)
+    server.start()
@@ -0,0 +1,141 @@
+//! resolution of names that are aliases (CNAME records) of other names
+
+use std::net::Ipv4Addr;
+use std::time::Duration;
+
+use dns_test::client::{Client, DigSettings};
+use dns_test::name_server::{Graph, NameServer, Sign};
+use dns_test::record::{Record, RecordType};
+use dns_test::{FQDN, Network, Resolver, Result};
+
+#[test]
+fn follows_chain() -> Result<()> {
+    let expected_ipv4_addr = Ipv4Addr::new(1, 2, 3, 4);
+    let www = FQDN::TEST_DOMAIN.push_label("www");
+    let cdn = FQDN::TEST_DOMAIN.push_label("cdn");
+    let edge = FQDN::TEST_DOMAIN.push_label("edge");
+
+    let network = Network::new()?;
+
+    let mut leaf_ns = NameServer::new(&dns_test::PEER, FQDN::TEST_DOMAIN, &network)?;
+    leaf_ns
+        .cname(www.clone(), cdn.clone())
+        .cname(cdn.clone(), edge.clone())
+        .add(Record::a(edge.clone(), expected_ipv4_addr));
+
+    let Graph {
+        nameservers: _nameservers,
+        root,
+        ..
+    } = Graph::build(leaf_ns, Sign::No)?;
+
+    let resolver = Resolver::new(&network, root).start()?;
+    let client = Client::new(&network)?;
+
+    let settings = *DigSettings::default().recurse();
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &www)?;
+
+    assert!(output.status.is_noerror());
+
+    let [first, second, third] = output.answer.try_into().unwrap();
+    let first = first.try_into_cname().unwrap();
+    assert_eq!(www, first.fqdn);
+    assert_eq!(cdn, first.target);
+
+    let second = second.try_into_cname().unwrap();
+    assert_eq!(cdn, second.fqdn);
+    assert_eq!(edge, second.target);
+
+    let a = third.try_into_a().unwrap();
+    assert_eq!(edge, a.fqdn);
+    assert_eq!(expected_ipv4_addr, a.ipv4_addr);
+
+    Ok(())
+}
+
+/// the canonical name lives in a zone served by a different name server so the resolver must
+/// restart resolution from the root
+#[test]
+fn canonical_name_in_other_zone() -> Result<()> {
+    let expected_ipv4_addr = Ipv4Addr::new(1, 2, 3, 4);
+    let cdn_zone = FQDN::TEST_TLD.push_label("cdn");
+    let www = FQDN::TEST_DOMAIN.push_label("www");
+    let canonical = cdn_zone.push_label("edge");
+
+    let network = Network::new()?;
+
+    let mut root_ns = NameServer::new(&dns_test::PEER, FQDN::ROOT, &network)?;
+    let mut tld_ns = NameServer::new(&dns_test::PEER, FQDN::TEST_TLD, &network)?;
+    let mut alias_ns = NameServer::new(&dns_test::PEER, FQDN::TEST_DOMAIN, &network)?;
+    let mut cdn_ns = NameServer::new(&dns_test::PEER, cdn_zone, &network)?;
+
+    alias_ns.add(root_ns.a());
+    alias_ns.add(tld_ns.a());
+    alias_ns.add(cdn_ns.a());
+    alias_ns.cname(www.clone(), canonical.clone());
+
+    cdn_ns.add(Record::a(canonical.clone(), expected_ipv4_addr));
+
+    root_ns.referral_nameserver(&tld_ns);
+    tld_ns.referral_nameserver(&alias_ns);
+    tld_ns.referral_nameserver(&cdn_ns);
+
+    let root_hint = root_ns.root_hint();
+    let _nameservers = [
+        root_ns.start()?,
+        tld_ns.start()?,
+        alias_ns.start()?,
+        cdn_ns.start()?,
+    ];
+
+    let resolver = Resolver::new(&network, root_hint).start()?;
+    let client = Client::new(&network)?;
+
+    let settings = *DigSettings::default().recurse();
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &www)?;
+
+    assert!(output.status.is_noerror());
+
+    let [cname, a] = output.answer.try_into().unwrap();
+    let cname = cname.try_into_cname().unwrap();
+    assert_eq!(www, cname.fqdn);
+    assert_eq!(canonical, cname.target);
+
+    let a = a.try_into_a().unwrap();
+    assert_eq!(canonical, a.fqdn);
+    assert_eq!(expected_ipv4_addr, a.ipv4_addr);
+
+    Ok(())
+}
+
+#[test]
+fn loop_is_servfail() -> Result<()> {
+    let left = FQDN::TEST_DOMAIN.push_label("left");
+    let right = FQDN::TEST_DOMAIN.push_label("right");
+
+    let network = Network::new()?;
+
+    let mut leaf_ns = NameServer::new(&dns_test::PEER, FQDN::TEST_DOMAIN, &network)?;
+    leaf_ns
+        .cname(left.clone(), right.clone())
+        .cname(right.clone(), left.clone());
+
+    let Graph {
+        nameservers: _nameservers,
+        root,
+        ..
+    } = Graph::build(leaf_ns, Sign::No)?;
+
+    let resolver = Resolver::new(&network, root).start()?;
+    let client = Client::new(&network)?;
+
+    // a resolver that keeps following the loop makes `dig` time out instead
+    let settings = *DigSettings::default()
+        .recurse()
+        .timeout(Duration::from_secs(7));
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &left)?;
+
+    assert!(dbg!(output).status.is_servfail());
+
+    Ok(())
+}
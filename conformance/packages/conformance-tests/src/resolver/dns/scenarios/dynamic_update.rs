@@ -0,0 +1,83 @@
+//! resolution of names added to, and deleted from, a zone with dynamic updates (RFC2136)
+
+use std::net::Ipv4Addr;
+use std::thread;
+use std::time::Duration;
+
+use dns_test::client::{Client, DigSettings, Update};
+use dns_test::name_server::{Graph, NameServer, Sign};
+use dns_test::record::{A, Record, RecordType};
+use dns_test::{FQDN, Network, PEER, Resolver, Result, TsigAlgorithm, TsigKey};
+
+#[test]
+fn resolves_updated_records() -> Result<()> {
+    if !PEER.supports_dynamic_update() {
+        // the name server does not support dynamic updates
+        return Ok(());
+    }
+
+    let expected_ipv4_addr = Ipv4Addr::new(1, 2, 3, 4);
+    let needle_fqdn = FQDN::EXAMPLE_SUBDOMAIN;
+    let ttl = 2;
+    let tsig_key = TsigKey {
+        name: "update-key".to_string(),
+        algorithm: TsigAlgorithm::HmacSha256,
+        secret: "9CXmNn9WKDdQuj4KjqZRDoh8Mf8G/p9dgn5MQ6AVWH0=".to_string(),
+    };
+
+    let network = Network::new()?;
+
+    let leaf_ns = NameServer::builder(PEER.clone(), FQDN::TEST_DOMAIN, network.clone())
+        .tsig_key(tsig_key.clone())
+        .allow_update()
+        .build()?;
+    let leaf_ns_addr = leaf_ns.ipv4_addr();
+
+    let Graph {
+        nameservers: _nameservers,
+        root,
+        ..
+    } = Graph::build(leaf_ns, Sign::No)?;
+
+    let resolver = Resolver::new(&network, root).start()?;
+    let client = Client::new(&network)?;
+
+    let record = Record::A(A {
+        fqdn: needle_fqdn.clone(),
+        ttl,
+        ipv4_addr: expected_ipv4_addr,
+    });
+    client.nsupdate(
+        leaf_ns_addr,
+        &FQDN::TEST_DOMAIN,
+        &[Update::Add(record)],
+        Some(&tsig_key),
+    )?;
+
+    let settings = *DigSettings::default().recurse();
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+    assert!(output.status.is_noerror());
+
+    let [answer] = output.answer.try_into().unwrap();
+    let a = answer.try_into_a().unwrap();
+    assert_eq!(needle_fqdn, a.fqdn);
+    assert_eq!(expected_ipv4_addr, a.ipv4_addr);
+
+    client.nsupdate(
+        leaf_ns_addr,
+        &FQDN::TEST_DOMAIN,
+        &[Update::Delete {
+            fqdn: needle_fqdn.clone(),
+            record_type: RecordType::A,
+        }],
+        Some(&tsig_key),
+    )?;
+
+    // let the cached record expire
+    thread::sleep(Duration::from_secs(u64::from(ttl) + 1));
+
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+    assert!(output.status.is_nxdomain(), "{output:#?}");
+
+    Ok(())
+}
@@ -1,10 +1,10 @@
 //! Test how resolvers respond to packet loss.
 
-use std::{fs, net::Ipv4Addr};
+use std::{fs, net::Ipv4Addr, time::Duration};
 
 use dns_test::{
     FQDN, Implementation, Network, PEER, Resolver, Result,
-    client::{Client, DigSettings, DigStatus},
+    client::{Client, DigSettings, DigStatus, DnsClient, NativeClient},
     name_server::NameServer,
     record::RecordType,
 };
@@ -25,7 +25,9 @@ fn packet_loss_udp() -> Result<()> {
     let root_hint = root_ns.root_hint();
     let resolver = Resolver::new(&network, root_hint).start()?;
     let client = Client::new(resolver.network())?;
-    let dig_settings = *DigSettings::default().recurse().timeout(10);
+    let dig_settings = *DigSettings::default()
+        .recurse()
+        .timeout(Duration::from_secs(10));
 
     let _root_ns = root_ns.start()?;
     let _leaf_ns = leaf_ns.start()?;
@@ -48,3 +50,30 @@ fn packet_loss_udp() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn unanswered_query_times_out() -> Result<()> {
+    let network = Network::new()?;
+    let ns = NameServer::new(&Implementation::Dnslib, FQDN::TEST_TLD, &network)?;
+    let script = fs::read_to_string("src/resolver/dns/scenarios/blackhole.py")?;
+    ns.cp("/script.py", &script)?;
+    let ns = ns.start()?;
+
+    let client = Client::new(&network)?;
+    let settings = *DigSettings::default()
+        .timeout(Duration::from_secs(1))
+        .tries(2);
+
+    let clients: [&dyn DnsClient; 2] = [&client, &NativeClient];
+    for client in clients {
+        let outcome = client.query_outcome(
+            settings,
+            ns.ipv4_addr().into(),
+            RecordType::A,
+            &FQDN::TEST_TLD,
+        )?;
+        assert!(outcome.is_timeout(), "{outcome:?}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+//! resolution of names added to the zone of a running name server
+
+use std::net::Ipv4Addr;
+use std::thread;
+use std::time::Duration;
+
+use dns_test::client::{Client, DigSettings};
+use dns_test::name_server::{Graph, NameServer, Running, Sign};
+use dns_test::record::{Record, RecordType};
+use dns_test::{FQDN, Network, PEER, Resolver, Result};
+
+#[test]
+fn resolves_records_added_before_a_reload() -> Result<()> {
+    if !PEER.supports_reload() {
+        // the name server cannot reload its zone files
+        return Ok(());
+    }
+
+    let expected_ipv4_addr = Ipv4Addr::new(1, 2, 3, 4);
+    let needle_fqdn = FQDN::EXAMPLE_SUBDOMAIN;
+
+    let network = Network::new()?;
+
+    let leaf_ns = NameServer::new(&PEER, FQDN::TEST_DOMAIN, &network)?;
+
+    let Graph {
+        mut nameservers,
+        root,
+        ..
+    } = Graph::build(leaf_ns, Sign::No)?;
+
+    let resolver = Resolver::new(&network, root).start()?;
+    let client = Client::new(&network)?;
+
+    // the resolver caches the delegation to the leaf zone
+    let settings = *DigSettings::default().recurse();
+    let output = client.dig(
+        settings,
+        resolver.ipv4_addr(),
+        RecordType::SOA,
+        &FQDN::TEST_DOMAIN,
+    )?;
+    assert!(output.status.is_noerror());
+
+    // the leaf-most name server goes first
+    let leaf_ns = &mut nameservers[0];
+    assert_eq!(&FQDN::TEST_DOMAIN, leaf_ns.zone());
+    leaf_ns
+        .zone_file_mut()
+        .add(Record::a(needle_fqdn.clone(), expected_ipv4_addr));
+    leaf_ns.reload()?;
+
+    wait_for_serial(&client, leaf_ns)?;
+
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+    assert!(output.status.is_noerror(), "{output:#?}");
+    let [answer] = output.answer.try_into().unwrap();
+    let a = answer.try_into_a().unwrap();
+    assert_eq!(needle_fqdn, a.fqdn);
+    assert_eq!(expected_ipv4_addr, a.ipv4_addr);
+
+    Ok(())
+}
+
+/// Polls `ns` until it serves the current version of its zone; querying the resolver before that
+/// would make it cache the absence of the new record
+fn wait_for_serial(client: &Client, ns: &NameServer<Running>) -> Result<()> {
+    let serial = ns.zone_file().soa.settings.serial;
+    for _ in 0..10 {
+        let output = client.dig(
+            DigSettings::default(),
+            ns.ipv4_addr(),
+            RecordType::SOA,
+            ns.zone(),
+        )?;
+        let served = output
+            .answer
+            .into_iter()
+            .find_map(|record| record.try_into_soa().ok());
+        if served.is_some_and(|soa| soa.settings.serial == serial) {
+            return Ok(());
+        }
+
+        thread::sleep(Duration::from_millis(500));
+    }
+
+    Err(format!("{} did not load serial {serial}", ns.fqdn()).into())
+}
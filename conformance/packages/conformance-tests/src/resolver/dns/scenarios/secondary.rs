@@ -0,0 +1,66 @@
+//! resolution of names in a zone served by a primary name server and its secondary
+
+use std::net::Ipv4Addr;
+use std::time::Duration;
+
+use dns_test::client::{Client, DigSettings};
+use dns_test::name_server::{NameServer, Secondary};
+use dns_test::record::{Record, RecordType};
+use dns_test::{FQDN, Network, PEER, Resolver, Result};
+
+/// the resolver keeps resolving names in the zone when its primary name server goes away
+#[test]
+fn resolves_via_secondary_when_primary_is_down() -> Result<()> {
+    if !PEER.supports_secondaries() {
+        // the name server does not support secondaries
+        return Ok(());
+    }
+
+    let expected_ipv4_addr = Ipv4Addr::new(1, 2, 3, 4);
+    let needle_fqdn = FQDN::EXAMPLE_SUBDOMAIN;
+
+    let network = Network::new()?;
+
+    let mut root_ns = NameServer::new(&PEER, FQDN::ROOT, &network)?;
+    let mut tld_ns = NameServer::new(&PEER, FQDN::TEST_TLD, &network)?;
+    let mut primary_ns = NameServer::new(&PEER, FQDN::TEST_DOMAIN, &network)?;
+    let secondary_ns = Secondary::new(&PEER, &mut primary_ns)?;
+
+    primary_ns.add(root_ns.a());
+    primary_ns.add(tld_ns.a());
+    primary_ns.add(Record::a(needle_fqdn.clone(), expected_ipv4_addr));
+
+    root_ns.referral_nameserver(&tld_ns);
+    tld_ns
+        .referral_nameserver(&primary_ns)
+        .referral_secondary(&secondary_ns);
+
+    let serial = primary_ns.zone_file().soa.settings.serial;
+    let root_hint = root_ns.root_hint();
+    let _nameservers = [root_ns.start()?, tld_ns.start()?];
+    let primary_ns = primary_ns.start()?;
+    let secondary_ns = secondary_ns.start()?;
+    secondary_ns.wait_for_transfer(serial)?;
+
+    let resolver = Resolver::new(&network, root_hint).start()?;
+    let client = Client::new(&network)?;
+
+    // removing the container makes the primary unreachable
+    drop(primary_ns);
+
+    // the resolver may first time out waiting for the primary
+    let settings = *DigSettings::default()
+        .recurse()
+        .timeout(Duration::from_secs(10));
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+
+    assert!(output.status.is_noerror());
+
+    let [answer] = output.answer.try_into().unwrap();
+    let a = answer.try_into_a().unwrap();
+
+    assert_eq!(needle_fqdn, a.fqdn);
+    assert_eq!(expected_ipv4_addr, a.ipv4_addr);
+
+    Ok(())
+}
@@ -5,6 +5,7 @@ use dns_test::{
     FQDN, Network, Resolver, Result, TrustAnchor,
     name_server::{Graph, NameServer, Running, Sign},
     record::Record,
+    topology::Topology,
     zone_file::SignSettings,
 };
 
@@ -63,19 +64,15 @@ pub fn minimally_secure(
 
     let network = Network::new()?;
 
-    let mut leaf_ns = NameServer::new(&dns_test::PEER, FQDN::TEST_DOMAIN, &network)?;
-    leaf_ns.add(Record::a(leaf_fqdn.clone(), leaf_ipv4_addr));
+    let deployment = Topology::signed(&dns_test::PEER, &network)
+        .sign_settings(settings)
+        .with_leaf_zone(FQDN::TEST_DOMAIN, |zone| {
+            zone.add(Record::a(leaf_fqdn, leaf_ipv4_addr));
+        })
+        .build()?;
 
-    let Graph {
-        nameservers,
-        root,
-        trust_anchor,
-    } = Graph::build(leaf_ns, Sign::Yes { settings })?;
-
-    let trust_anchor = trust_anchor.unwrap();
-    let resolver = Resolver::new(&network, root)
-        .trust_anchor(&trust_anchor)
-        .start()?;
+    let resolver = deployment.resolver().start()?;
+    let trust_anchor = deployment.trust_anchor.unwrap();
 
-    Ok((resolver, nameservers, trust_anchor))
+    Ok((resolver, deployment.nameservers, trust_anchor))
 }
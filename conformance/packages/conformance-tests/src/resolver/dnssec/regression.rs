@@ -164,7 +164,7 @@ fn can_validate_ns_query_case_randomization() -> Result<()> {
 #[test]
 fn single_node_dns_graph_with_bind_as_peer() -> Result<()> {
     let network = Network::new()?;
-    let peer = Implementation::Bind;
+    let peer = Implementation::bind();
     let nameserver = NameServer::new(&peer, FQDN::ROOT, &network)?
         .sign(SignSettings::default())?
         .start()?;
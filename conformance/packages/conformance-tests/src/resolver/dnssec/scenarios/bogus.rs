@@ -1,15 +1,103 @@
 mod no_rrsig_dnskey;
 
+use std::net::Ipv4Addr;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use dns_test::{
-    FQDN, Network, PEER, Resolver, Result, TrustAnchor,
+    FQDN, Network, PEER, Resolver, Result, SUBJECT, TrustAnchor, Verbosity,
     client::{Client, DigOutput, DigSettings, ExtendedDnsError},
+    logs::LogEvent,
     name_server::{Graph, NameServer, Sign},
     record::{DNSKEY, DNSKEYRData, DS, RRSIG, Record, RecordType},
+    topology::Topology,
     zone_file::{SignSettings, Signer},
 };
 
+use crate::resolver::dnssec::fixtures;
+
+#[test]
+fn hickory_logs_reason_of_validation_failure() -> Result<()> {
+    if !SUBJECT.is_hickory() {
+        // only the logs of hickory are checked
+        return Ok(());
+    }
+
+    let needle_fqdn = FQDN::EXAMPLE_SUBDOMAIN;
+    let (resolver, graph) =
+        fixtures::bad_signature_in_leaf_nameserver(&needle_fqdn, Ipv4Addr::new(1, 2, 3, 4))?;
+
+    // hickory only logs validation results at the debug level
+    let resolver = Resolver::new(resolver.network(), graph.root.clone())
+        .trust_anchor(graph.trust_anchor.as_ref().unwrap())
+        .verbosity(Verbosity::Debug)
+        .start()?;
+
+    let client = Client::new(resolver.network())?;
+    let settings = *DigSettings::default().recurse().authentic_data();
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+    assert!(output.status.is_servfail());
+
+    let events = resolver.log_events()?;
+    let reason = events
+        .iter()
+        .find_map(|event| match event {
+            LogEvent::ValidationFailed {
+                fqdn,
+                record_type: RecordType::A,
+                reason,
+            } if *fqdn == needle_fqdn => Some(reason),
+            _ => None,
+        })
+        .unwrap_or_else(|| panic!("no validation failure was logged: {events:#?}"));
+
+    // the last byte of the signature was flipped
+    assert!(
+        reason.starts_with("dnskey and rrset failed to verify"),
+        "{reason}"
+    );
+    assert!(
+        events
+            .iter()
+            .any(|event| matches!(event, LogEvent::ServFail { .. })),
+        "{events:#?}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn delv_confirms_bad_signature_is_bogus() -> Result<()> {
+    let needle_fqdn = FQDN::EXAMPLE_SUBDOMAIN;
+    let (resolver, graph) =
+        fixtures::bad_signature_in_leaf_nameserver(&needle_fqdn, Ipv4Addr::new(1, 2, 3, 4))?;
+    let trust_anchor = graph.trust_anchor.as_ref().unwrap();
+
+    let client = Client::new(resolver.network())?;
+    let settings = *DigSettings::default().recurse().authentic_data();
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+    assert!(output.status.is_servfail());
+
+    // `delv` sets the CD bit so it gets to validate the records itself
+    let output = client.delv(
+        resolver.ipv4_addr(),
+        RecordType::A,
+        &needle_fqdn,
+        trust_anchor,
+    )?;
+    assert!(output.is_bogus(), "{output:?}");
+
+    // the rest of the chain is fine
+    let output = client.delv(
+        resolver.ipv4_addr(),
+        RecordType::SOA,
+        &FQDN::TEST_DOMAIN,
+        trust_anchor,
+    )?;
+    assert!(output.is_fully_validated(), "{output:?}");
+
+    Ok(())
+}
+
 #[test]
 fn ds_unassigned_key_algo() -> Result<()> {
     let output =
@@ -179,48 +267,17 @@ fn no_rrsig_ksk() -> Result<()> {
 
 fn malformed_ds_fixture(leaf_zone: &FQDN, mutate: impl FnOnce(&mut DS)) -> Result<DigOutput> {
     let network = Network::new()?;
-    let sign_settings = SignSettings::default();
-
-    let peer = &dns_test::PEER;
-    let mut root_ns = NameServer::new(peer, FQDN::ROOT, &network)?;
-    let mut tld_ns = NameServer::new(peer, FQDN::TEST_TLD, &network)?;
-    let mut nameservers_ns = NameServer::new(peer, FQDN::TEST_DOMAIN, &network)?;
-    let leaf_ns = NameServer::new(peer, leaf_zone.clone(), &network)?;
-
-    root_ns.referral_nameserver(&tld_ns);
-    tld_ns.referral_nameserver(&nameservers_ns);
-    tld_ns.referral_nameserver(&leaf_ns);
-
-    nameservers_ns.add(root_ns.a());
-    nameservers_ns.add(tld_ns.a());
-
-    let nameservers_ns = nameservers_ns.sign(sign_settings.clone())?;
-    let leaf_ns = leaf_ns.sign(sign_settings.clone())?;
-
-    tld_ns.add(nameservers_ns.ds().ksk.clone());
-    let mut ds = leaf_ns.ds().ksk.clone();
-    mutate(&mut ds);
-    tld_ns.add(ds);
 
-    let tld_ns = tld_ns.sign(sign_settings.clone())?;
-    root_ns.add(tld_ns.ds().ksk.clone());
-
-    let mut trust_anchor = TrustAnchor::empty();
-    let root_ns = root_ns.sign(sign_settings)?;
-    trust_anchor.add(root_ns.key_signing_key().clone());
-    trust_anchor.add(root_ns.zone_signing_key().clone());
-
-    let root_hint = root_ns.root_hint();
-    let _root_ns = root_ns.start()?;
-    let _tld_ns = tld_ns.start()?;
-    let _nameservers_ns = nameservers_ns.start()?;
-    let _leaf_ns = leaf_ns.start()?;
+    let deployment = Topology::signed(&dns_test::PEER, &network)
+        .with_leaf_zone(leaf_zone.clone(), |_| {})
+        .broken_delegation(leaf_zone.clone(), mutate)
+        .build()?;
 
-    let mut resolver = Resolver::new(&network, root_hint);
+    let mut resolver = deployment.resolver();
     if dns_test::SUBJECT.is_unbound() {
         resolver.extended_dns_errors();
     }
-    let resolver = resolver.trust_anchor(&trust_anchor).start()?;
+    let resolver = resolver.start()?;
 
     let client = Client::new(&network)?;
     let settings = *DigSettings::default().recurse().authentic_data();
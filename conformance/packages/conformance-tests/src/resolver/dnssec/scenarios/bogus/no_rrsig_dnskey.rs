@@ -81,7 +81,7 @@ fn query_other_record() -> Result<()> {
     let leaf_zone = FQDN::TEST_TLD.push_label("no-rrsig-dnskey");
 
     // other implementations fail the PRE-CONDITION below
-    let peer = Implementation::Bind;
+    let peer = Implementation::bind();
     let mut leaf_ns = NameServer::new(&peer, leaf_zone.clone(), &network)?;
     leaf_ns.add(Record::a(leaf_zone.clone(), Ipv4Addr::new(1, 2, 3, 4)));
     let leaf_ns_addr = leaf_ns.ipv4_addr();
@@ -1,5 +1,6 @@
 use std::net::Ipv4Addr;
 
+use base64::prelude::*;
 use dns_test::client::{Client, DigSettings, ExtendedDnsError};
 use dns_test::name_server::{Graph, NameServer, Sign};
 use dns_test::record::{Record, RecordType};
@@ -118,6 +119,32 @@ fn dnssec_bogus() -> Result<()> {
     )
 }
 
+#[ignore]
+#[test]
+fn bad_signature() -> Result<()> {
+    fixture(
+        ExtendedDnsError::DnssecBogus,
+        |needle_fqdn, zone, records| {
+            if zone == &FQDN::TEST_DOMAIN {
+                // flip the last byte of the signature that covers the needle record
+                let mut modified_count = 0;
+                for record in records {
+                    if let Record::RRSIG(rrsig) = record {
+                        if rrsig.type_covered == RecordType::A && rrsig.fqdn == *needle_fqdn {
+                            let mut signature = BASE64_STANDARD.decode(&rrsig.signature).unwrap();
+                            let last = signature.last_mut().expect("empty signature");
+                            *last = !*last;
+                            rrsig.signature = BASE64_STANDARD.encode(&signature);
+                            modified_count += 1;
+                        }
+                    }
+                }
+                assert_eq!(1, modified_count, "sanity check");
+            }
+        },
+    )
+}
+
 // Sets up a minimal, DNSSEC-enabled DNS graph where the leaf zone contains a "needle" A record
 // that we'll search for
 //
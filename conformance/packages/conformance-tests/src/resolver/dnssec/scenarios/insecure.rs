@@ -3,7 +3,7 @@ use std::net::Ipv4Addr;
 use dns_test::client::{Client, DigOutput, DigSettings};
 use dns_test::name_server::NameServer;
 use dns_test::record::{A, Record, RecordType};
-use dns_test::zone_file::{Nsec, SignSettings};
+use dns_test::zone_file::{DenialOfExistence, SignSettings};
 use dns_test::{FQDN, Network, Resolver, Result, TrustAnchor};
 
 mod deprecated_algorithm;
@@ -15,7 +15,8 @@ mod deprecated_algorithm;
 // the security status of the whole zone is "Insecure", not "Bogus"
 #[test]
 fn unsigned_zone_nsec3() -> Result<()> {
-    unsigned_zone_fixture(Nsec::_3 {
+    unsigned_zone_fixture(DenialOfExistence::Nsec3 {
+        iterations: 1,
         opt_out: false,
         salt: None,
     })
@@ -23,10 +24,10 @@ fn unsigned_zone_nsec3() -> Result<()> {
 
 #[test]
 fn unsigned_zone_nsec() -> Result<()> {
-    unsigned_zone_fixture(Nsec::_1)
+    unsigned_zone_fixture(DenialOfExistence::Nsec)
 }
 
-fn unsigned_zone_fixture(nsec: Nsec) -> Result<()> {
+fn unsigned_zone_fixture(nsec: DenialOfExistence) -> Result<()> {
     let expected_ipv4_addr = Ipv4Addr::new(1, 2, 3, 4);
     let unsigned_zone = FQDN::TEST_TLD.push_label("unsigned");
     let needle_fqdn = unsigned_zone.push_label("example");
@@ -94,8 +95,11 @@ fn unsigned_zone_fixture(nsec: Nsec) -> Result<()> {
 
 #[test]
 fn no_ds_record_nsec1() -> Result<()> {
-    let (output, _logs) =
-        no_ds_record_fixture(SignSettings::default().nsec(Nsec::_1), false, false)?;
+    let (output, _logs) = no_ds_record_fixture(
+        SignSettings::default().nsec(DenialOfExistence::Nsec),
+        false,
+        false,
+    )?;
 
     dbg!(&output);
 
@@ -108,7 +112,8 @@ fn no_ds_record_nsec1() -> Result<()> {
 #[test]
 fn no_ds_record_nsec3() -> Result<()> {
     let (output, _logs) = no_ds_record_fixture(
-        SignSettings::default().nsec(Nsec::_3 {
+        SignSettings::default().nsec(DenialOfExistence::Nsec3 {
+            iterations: 1,
             salt: None,
             opt_out: false,
         }),
@@ -127,7 +132,8 @@ fn no_ds_record_nsec3() -> Result<()> {
 #[test]
 fn no_ds_record_nsec3_case_randomization() -> Result<()> {
     let (output, _logs) = no_ds_record_fixture(
-        SignSettings::default().nsec(Nsec::_3 {
+        SignSettings::default().nsec(DenialOfExistence::Nsec3 {
+            iterations: 1,
             salt: None,
             opt_out: false,
         }),
@@ -1,14 +1,19 @@
 use std::net::Ipv4Addr;
+use std::thread;
+use std::time::Duration;
 
 use dns_test::client::{Client, DigSettings};
-use dns_test::name_server::NameServer;
-use dns_test::record::{Record, RecordType};
+use dns_test::name_server::{Glue, Graph, NameServer, Running, Sign};
+use dns_test::record::{Record, RecordType, TLSA};
+use dns_test::topology::Topology;
 use dns_test::tshark::Capture;
-use dns_test::zone_file::{Nsec, SignSettings};
+use dns_test::zone_file::{DenialOfExistence, SignSettings, Signer};
 use dns_test::{FQDN, Network, Resolver, Result, TrustAnchor};
 
 use crate::resolver::dnssec::fixtures;
 
+mod algorithm;
+
 // no DS records are involved; this is a single-link chain of trust
 #[test]
 fn can_validate_without_delegation() -> Result<()> {
@@ -42,6 +47,94 @@ fn can_validate_without_delegation() -> Result<()> {
     Ok(())
 }
 
+// the DNSKEY RRset does not fit in the UDP payload size of the name server so the resolver has to
+// retry the query over TCP
+#[test]
+fn can_validate_truncated_dnskey_response() -> Result<()> {
+    let network = Network::new()?;
+    let mut ns = NameServer::builder(dns_test::PEER.clone(), FQDN::ROOT, network.clone())
+        .udp_payload_size(512)
+        .build()?;
+    ns.add(ns.a());
+    // two 2048-bit RSA keys plus their signature do not fit in 512 bytes
+    let ns = ns.sign(SignSettings::rsasha256())?;
+    let trust_anchor = ns.trust_anchor();
+    let ns = ns.start()?;
+
+    let resolver = Resolver::new(&network, ns.root_hint())
+        .trust_anchor(&trust_anchor)
+        .start()?;
+    let mut tshark = resolver.eavesdrop()?;
+
+    let client = Client::new(&network)?;
+    let settings = *DigSettings::default().recurse().authentic_data();
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::SOA, &FQDN::ROOT)?;
+
+    assert!(output.status.is_noerror());
+    assert!(output.flags.authenticated_data);
+
+    tshark.wait_for_capture()?;
+    let captures = tshark.terminate()?;
+
+    // only UDP traffic is captured
+    let truncated = captures.iter().any(|Capture { message, direction }| {
+        direction
+            .try_into_incoming()
+            .is_ok_and(|source| source == ns.ipv4_addr())
+            && message.is_tc_flag_set()
+    });
+    assert!(truncated, "the name server did not truncate any response");
+
+    Ok(())
+}
+
+// the root zone is signed with two KSKs, as it is in the middle of a KSK rollover, but only the
+// second KSK is trusted
+#[test]
+fn can_validate_with_additional_ksk_as_trust_anchor() -> Result<()> {
+    can_validate_with_two_ksks(false)
+}
+
+#[test]
+#[ignore = "hickory does not support trust anchors in DS form"]
+fn can_validate_with_ds_as_trust_anchor() -> Result<()> {
+    can_validate_with_two_ksks(true)
+}
+
+fn can_validate_with_two_ksks(anchor_ds: bool) -> Result<()> {
+    let network = Network::new()?;
+    let mut ns = NameServer::new(&dns_test::PEER, FQDN::ROOT, &network)?;
+    ns.add(ns.a());
+
+    let settings = SignSettings::default();
+    let signer = Signer::new(ns.container(), settings.clone())?;
+    let mut keys = signer.generate_keys(&FQDN::ROOT)?;
+    signer.add_ksk(&FQDN::ROOT, &mut keys)?;
+    let ns = ns.sign_with_keys(settings, &keys)?;
+
+    let mut trust_anchor = TrustAnchor::empty();
+    if anchor_ds {
+        trust_anchor.add_ds(ns.ds().additional_ksks[0].clone());
+    } else {
+        let ttl = ns.signed_zone_file().soa.ttl;
+        trust_anchor.add(keys.additional_ksks[0].public.clone().with_ttl(ttl));
+    }
+
+    let ns = ns.start()?;
+    let resolver = Resolver::new(&network, ns.root_hint())
+        .trust_anchor(&trust_anchor)
+        .start()?;
+
+    let client = Client::new(&network)?;
+    let settings = *DigSettings::default().recurse().authentic_data();
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::SOA, &FQDN::ROOT)?;
+
+    assert!(output.status.is_noerror());
+    assert!(output.flags.authenticated_data);
+
+    Ok(())
+}
+
 #[test]
 fn can_validate_with_delegation() -> Result<()> {
     let expected_ipv4_addr = Ipv4Addr::new(1, 2, 3, 4);
@@ -72,6 +165,176 @@ fn can_validate_with_delegation() -> Result<()> {
     Ok(())
 }
 
+// the name servers only publish AAAA glue, and an AAAA root hint, so the resolver must reach every
+// zone in the chain of trust over IPv6
+#[test]
+fn can_validate_with_ipv6_only_delegations() -> Result<()> {
+    let expected_ipv4_addr = Ipv4Addr::new(1, 2, 3, 4);
+    let needle_fqdn = FQDN::EXAMPLE_SUBDOMAIN;
+
+    let network = Network::with_ipv6()?;
+    let mut leaf_ns =
+        NameServer::builder(dns_test::PEER.clone(), FQDN::TEST_DOMAIN, network.clone())
+            .glue(Glue::Ipv6)
+            .build()?;
+    leaf_ns.add(Record::a(needle_fqdn.clone(), expected_ipv4_addr));
+
+    let Graph {
+        nameservers: _nameservers,
+        root,
+        trust_anchor,
+    } = Graph::build(
+        leaf_ns,
+        Sign::Yes {
+            settings: SignSettings::default(),
+        },
+    )?;
+    assert!(root.ipv4_addr.is_none());
+
+    let resolver = Resolver::new(&network, root)
+        .trust_anchor(&trust_anchor.unwrap())
+        .start()?;
+
+    let client = Client::new(&network)?;
+    let settings = *DigSettings::default().recurse().authentic_data();
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+
+    assert!(output.status.is_noerror());
+    assert!(output.flags.authenticated_data);
+
+    let [a] = output.answer.try_into().unwrap();
+    let a = a.try_into_a().unwrap();
+
+    assert_eq!(needle_fqdn, a.fqdn);
+    assert_eq!(expected_ipv4_addr, a.ipv4_addr);
+
+    Ok(())
+}
+
+// DANE lookup of a TLSA record whose certificate association data is long enough to be split
+// across several columns by dig
+#[test]
+fn can_validate_tlsa() -> Result<()> {
+    let network = Network::new()?;
+    let tlsa_fqdn = FQDN::TEST_DOMAIN.push_label("_tcp").push_label("_443");
+    let cert_data = "0E2E4A8CE0A91DD8A2DE4B4A9D2C27EF087DDE0CFA0DB4E5CE7E98C20E7F5F4E7B70DA99B6602A1325B4DA7E6E1911BE27953C3A96D0A8F281F68A9E6DDD061E";
+
+    let deployment = Topology::signed(&dns_test::PEER, &network)
+        .with_leaf_zone(FQDN::TEST_DOMAIN, |zone| {
+            zone.add(TLSA {
+                fqdn: tlsa_fqdn.clone(),
+                ttl: 3600,
+                cert_usage: 3,
+                selector: 1,
+                matching_type: 2,
+                cert_data: cert_data.to_string(),
+            });
+        })
+        .build()?;
+
+    let resolver = deployment.resolver().start()?;
+
+    let client = Client::new(&network)?;
+    let settings = *DigSettings::default().recurse().authentic_data();
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::TLSA, &tlsa_fqdn)?;
+
+    assert!(output.status.is_noerror());
+    assert!(output.flags.authenticated_data);
+
+    let [tlsa] = output.answer.try_into().unwrap();
+    let tlsa = tlsa.try_into_tlsa().unwrap();
+
+    assert_eq!(tlsa_fqdn, tlsa.fqdn);
+    assert_eq!(
+        (3, 1, 2),
+        (tlsa.cert_usage, tlsa.selector, tlsa.matching_type)
+    );
+    assert_eq!(cert_data, tlsa.cert_data);
+
+    Ok(())
+}
+
+// the resolver starts without a trust anchor, so it does not validate, and gets one through a
+// reload of its configuration; its process keeps running in between
+#[test]
+fn validates_once_a_reload_turns_dnssec_on() -> Result<()> {
+    if !dns_test::SUBJECT.supports_config_reload() {
+        return Ok(());
+    }
+
+    let expected_ipv4_addr = Ipv4Addr::new(1, 2, 3, 4);
+    let before_fqdn = FQDN::TEST_DOMAIN.push_label("before");
+    let after_fqdn = FQDN::TEST_DOMAIN.push_label("after");
+
+    let network = Network::new()?;
+    let deployment = Topology::signed(&dns_test::PEER, &network)
+        .with_leaf_zone(FQDN::TEST_DOMAIN, |zone| {
+            zone.add(Record::a(before_fqdn.clone(), expected_ipv4_addr));
+            zone.add(Record::a(after_fqdn.clone(), expected_ipv4_addr));
+        })
+        .build()?;
+
+    let mut resolver = Resolver::new(&network, deployment.root.clone()).start()?;
+
+    let client = Client::new(&network)?;
+    let settings = *DigSettings::default().recurse().authentic_data();
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &before_fqdn)?;
+    assert!(output.status.is_noerror());
+    assert!(!output.flags.authenticated_data);
+
+    resolver.reload(&deployment.resolver())?;
+
+    // a name that was not resolved before the reload, so the answer does not come from the cache
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &after_fqdn)?;
+    assert!(output.status.is_noerror());
+    assert!(output.flags.authenticated_data);
+
+    Ok(())
+}
+
+#[test]
+fn can_validate_wildcard_nsec() -> Result<()> {
+    can_validate_wildcard(DenialOfExistence::Nsec)
+}
+
+#[test]
+fn can_validate_wildcard_nsec3() -> Result<()> {
+    can_validate_wildcard(DenialOfExistence::default())
+}
+
+/// the answer is synthesized from a wildcard record; validating it requires the NSEC(3) records
+/// that prove that there is no closer match
+fn can_validate_wildcard(nsec: DenialOfExistence) -> Result<()> {
+    let expected_ipv4_addr = Ipv4Addr::new(1, 2, 3, 4);
+    let wildcard = FQDN::wildcard_of(&FQDN::TEST_DOMAIN);
+    let needle_fqdn = FQDN::TEST_DOMAIN.push_label("unlisted");
+
+    let network = Network::new()?;
+    let deployment = Topology::signed(&dns_test::PEER, &network)
+        .sign_settings(SignSettings::default().nsec(nsec))
+        .with_leaf_zone(FQDN::TEST_DOMAIN, |zone| {
+            zone.add(Record::a(wildcard, expected_ipv4_addr));
+        })
+        .build()?;
+
+    let resolver = deployment.resolver().start()?;
+
+    let client = Client::new(&network)?;
+    let settings = *DigSettings::default().recurse().authentic_data();
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+
+    assert!(output.status.is_noerror());
+    assert!(output.flags.authenticated_data);
+
+    let [a] = output.answer.try_into().unwrap();
+    let a = a.try_into_a().unwrap();
+
+    assert_eq!(needle_fqdn, a.fqdn);
+    assert_eq!(expected_ipv4_addr, a.ipv4_addr);
+
+    Ok(())
+}
+
 // the inclusion of RRSIGs records in the answer should not change the outcome of validation
 // if the chain of trust was valid then the RRSIGs, which are part of the chain, must also be secure
 #[test]
@@ -266,7 +529,7 @@ fn nxdomain_nsec() -> Result<()> {
     let (resolver, _nameservers, _trust_anchor) = fixtures::minimally_secure(
         needle_fqdn.clone(),
         expected_ipv4_addr,
-        SignSettings::default().nsec(Nsec::_1),
+        SignSettings::default().nsec(DenialOfExistence::Nsec),
     )?;
 
     let resolver_addr = resolver.ipv4_addr();
@@ -315,3 +578,87 @@ fn nxdomain_nsec3() -> Result<()> {
 
     Ok(())
 }
+
+// pre-publish ZSK rollover (RFC 6781, section 4.1.1.1): the new ZSK's DNSKEY is published first
+// and, once the cached DNSKEY RRset has expired, the zone is re-signed with the new ZSK
+#[test]
+fn validates_across_zsk_rollover() -> Result<()> {
+    let network = Network::new()?;
+    let needle_fqdn = FQDN::EXAMPLE_SUBDOMAIN;
+    let other_fqdn = FQDN::TEST_DOMAIN.push_label("other");
+
+    let mut leaf_ns = NameServer::new(&dns_test::PEER, FQDN::TEST_DOMAIN, &network)?;
+    leaf_ns.add(Record::a(needle_fqdn.clone(), Ipv4Addr::new(1, 2, 3, 4)));
+    leaf_ns.add(Record::a(other_fqdn.clone(), Ipv4Addr::new(5, 6, 7, 8)));
+
+    let Graph {
+        mut nameservers,
+        root,
+        trust_anchor,
+    } = Graph::build(
+        leaf_ns,
+        Sign::Yes {
+            settings: SignSettings::default(),
+        },
+    )?;
+    let leaf_ns = &mut nameservers[0];
+    assert_eq!(&FQDN::TEST_DOMAIN, leaf_ns.zone());
+
+    // stands in for the DNSKEY TTL that the zone operator waits out between the two steps
+    let max_cache_ttl = 1;
+    let resolver = Resolver::new(&network, root)
+        .trust_anchor(&trust_anchor.unwrap())
+        .max_cache_ttl(max_cache_ttl)
+        .start()?;
+
+    let client = Client::new(&network)?;
+    let settings = *DigSettings::default().recurse().dnssec().authentic_data();
+
+    let old_zsk = leaf_ns.zone_signing_key_tag().expect("zone is signed");
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+    assert!(output.status.is_noerror());
+    assert!(output.flags.authenticated_data);
+    assert_eq!(vec![old_zsk], a_signers(output.answer));
+
+    let new_zsk = leaf_ns.add_zsk()?;
+    thread::sleep(Duration::from_secs(u64::from(max_cache_ttl) + 1));
+    leaf_ns.resign_with(new_zsk)?;
+    wait_until_signed_with(&client, leaf_ns, &other_fqdn, new_zsk)?;
+
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &other_fqdn)?;
+    assert!(!output.status.is_servfail());
+    assert!(output.status.is_noerror());
+    assert!(output.flags.authenticated_data);
+    assert_eq!(vec![new_zsk], a_signers(output.answer));
+
+    Ok(())
+}
+
+/// Key tags of the RRSIGs covering A records
+fn a_signers(answer: Vec<Record>) -> Vec<u16> {
+    answer
+        .into_iter()
+        .filter_map(|record| record.try_into_rrsig().ok())
+        .filter(|rrsig| rrsig.type_covered == RecordType::A)
+        .map(|rrsig| rrsig.key_tag)
+        .collect()
+}
+
+/// Waits until `ns` has reloaded its zone file and serves an A record signed by `key_tag`
+fn wait_until_signed_with(
+    client: &Client,
+    ns: &NameServer<Running>,
+    fqdn: &FQDN,
+    key_tag: u16,
+) -> Result<()> {
+    let settings = *DigSettings::default().dnssec();
+    for _ in 0..10 {
+        let output = client.dig(settings, ns.ipv4_addr(), RecordType::A, fqdn)?;
+        if a_signers(output.answer) == [key_tag] {
+            return Ok(());
+        }
+        thread::sleep(Duration::from_millis(500));
+    }
+
+    Err(format!("{fqdn} is not signed with the ZSK {key_tag}").into())
+}
@@ -0,0 +1,69 @@
+use std::net::Ipv4Addr;
+
+use dns_test::client::{Client, DigSettings};
+use dns_test::record::RecordType;
+use dns_test::zone_file::{Algorithm, SignSettings};
+use dns_test::{FQDN, Result};
+
+use crate::resolver::dnssec::fixtures;
+
+#[test]
+fn rsasha256() -> Result<()> {
+    can_validate(Algorithm::RSASHA256)
+}
+
+#[test]
+fn rsasha512() -> Result<()> {
+    can_validate(Algorithm::RSASHA512)
+}
+
+#[test]
+fn ecdsap256sha256() -> Result<()> {
+    can_validate(Algorithm::ECDSAP256SHA256)
+}
+
+#[test]
+fn ecdsap384sha384() -> Result<()> {
+    can_validate(Algorithm::ECDSAP384SHA384)
+}
+
+#[test]
+fn ed25519() -> Result<()> {
+    can_validate(Algorithm::ED25519)
+}
+
+#[test]
+fn ed448() -> Result<()> {
+    can_validate(Algorithm::ED448)
+}
+
+/// signs the root, TLD and leaf zones with `algorithm`
+fn can_validate(algorithm: Algorithm) -> Result<()> {
+    let capabilities = dns_test::SUBJECT.capabilities();
+    if !capabilities.dnssec_algorithms.contains(&algorithm) {
+        // the resolver does not support this algorithm
+        return Ok(());
+    }
+
+    let expected_ipv4_addr = Ipv4Addr::new(1, 2, 3, 4);
+    let needle_fqdn = FQDN::EXAMPLE_SUBDOMAIN;
+
+    let (resolver, _nameservers, _trust_anchor) = fixtures::minimally_secure(
+        needle_fqdn.clone(),
+        expected_ipv4_addr,
+        SignSettings::default().algorithm(algorithm),
+    )?;
+
+    let client = Client::new(resolver.network())?;
+    let settings = *DigSettings::default().recurse().authentic_data();
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+
+    assert!(output.status.is_noerror());
+    assert!(output.flags.authenticated_data);
+
+    let [a] = output.answer.try_into().unwrap();
+    let a = a.try_into_a().unwrap();
+    assert_eq!(expected_ipv4_addr, a.ipv4_addr);
+
+    Ok(())
+}
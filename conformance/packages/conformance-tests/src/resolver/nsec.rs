@@ -7,12 +7,13 @@ use dns_test::{
     client::{Client, DigSettings},
     name_server::{Graph, NameServer, Sign},
     record::{Record, RecordType},
-    zone_file::{Nsec, SignSettings},
+    zone_file::{DenialOfExistence, SignSettings},
 };
 
 #[test]
 fn zone_exist_domain_does_not_nsec3() -> Result<()> {
-    zone_exist_domain_does_not(Nsec::_3 {
+    zone_exist_domain_does_not(DenialOfExistence::Nsec3 {
+        iterations: 1,
         opt_out: false,
         salt: None,
     })
@@ -20,12 +21,22 @@ fn zone_exist_domain_does_not_nsec3() -> Result<()> {
 
 #[test]
 fn zone_exist_domain_does_not_nsec() -> Result<()> {
-    zone_exist_domain_does_not(Nsec::_1)
+    zone_exist_domain_does_not(DenialOfExistence::Nsec)
+}
+
+#[test]
+fn zone_exist_domain_does_not_nsec3_opt_out() -> Result<()> {
+    zone_exist_domain_does_not(DenialOfExistence::Nsec3 {
+        iterations: 0,
+        opt_out: true,
+        salt: None,
+    })
 }
 
 #[test]
 fn zone_does_not_exist_nsec3() -> Result<()> {
-    zone_does_not_exist(Nsec::_3 {
+    zone_does_not_exist(DenialOfExistence::Nsec3 {
+        iterations: 1,
         opt_out: false,
         salt: None,
     })
@@ -33,12 +44,22 @@ fn zone_does_not_exist_nsec3() -> Result<()> {
 
 #[test]
 fn zone_does_not_exist_nsec() -> Result<()> {
-    zone_does_not_exist(Nsec::_1)
+    zone_does_not_exist(DenialOfExistence::Nsec)
+}
+
+#[test]
+fn zone_does_not_exist_nsec3_opt_out() -> Result<()> {
+    zone_does_not_exist(DenialOfExistence::Nsec3 {
+        iterations: 0,
+        opt_out: true,
+        salt: None,
+    })
 }
 
 #[test]
 fn domain_exists_record_type_does_not_nsec3() -> Result<()> {
-    domain_exists_record_type_does_not(Nsec::_3 {
+    domain_exists_record_type_does_not(DenialOfExistence::Nsec3 {
+        iterations: 1,
         opt_out: false,
         salt: None,
     })
@@ -46,10 +67,77 @@ fn domain_exists_record_type_does_not_nsec3() -> Result<()> {
 
 #[test]
 fn domain_exists_record_type_does_not_nsec() -> Result<()> {
-    domain_exists_record_type_does_not(Nsec::_1)
+    domain_exists_record_type_does_not(DenialOfExistence::Nsec)
+}
+
+#[test]
+fn domain_exists_record_type_does_not_nsec3_opt_out() -> Result<()> {
+    domain_exists_record_type_does_not(DenialOfExistence::Nsec3 {
+        iterations: 0,
+        opt_out: true,
+        salt: None,
+    })
+}
+
+#[test]
+fn nsec3_parameters_are_used_in_denial_of_existence() -> Result<()> {
+    nsec3_parameters(5, Some("CAFEBABE"), "CAFEBABE")
+}
+
+#[test]
+fn nsec3_empty_salt_and_zero_iterations_are_used_in_denial_of_existence() -> Result<()> {
+    // RFC9276 recommends no salt and no additional iterations
+    nsec3_parameters(0, Some(""), "-")
+}
+
+/// `expected_salt` is the salt in presentation format, where `-` denotes the empty salt
+fn nsec3_parameters(iterations: u16, salt: Option<&str>, expected_salt: &str) -> Result<()> {
+    let leaf_zone = FQDN::TEST_TLD.push_label("exists");
+    let needle_fqdn = leaf_zone.push_label("unicorn");
+
+    let network = Network::new()?;
+    let leaf_ns = NameServer::new(&dns_test::PEER, leaf_zone.clone(), &network)?;
+
+    let settings = SignSettings::default().nsec(DenialOfExistence::Nsec3 {
+        iterations,
+        opt_out: false,
+        salt: salt.map(String::from),
+    });
+    let Graph {
+        nameservers: _nameservers,
+        root,
+        trust_anchor,
+    } = Graph::build(leaf_ns, Sign::Yes { settings })?;
+
+    let trust_anchor = trust_anchor.unwrap();
+    let resolver = Resolver::new(&network, root)
+        .trust_anchor(&trust_anchor)
+        .start()?;
+
+    let client = Client::new(&network)?;
+    let settings = *DigSettings::default().recurse().authentic_data().dnssec();
+    let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+
+    dbg!(&output);
+
+    assert!(output.status.is_nxdomain());
+    assert!(output.flags.authenticated_data);
+
+    let nsec3s = output
+        .authority
+        .into_iter()
+        .filter_map(|record| record.try_into_nsec3().ok())
+        .collect::<Vec<_>>();
+    assert!(!nsec3s.is_empty());
+    for nsec3 in nsec3s {
+        assert_eq!(iterations, nsec3.iterations);
+        assert_eq!(expected_salt, nsec3.salt);
+    }
+
+    Ok(())
 }
 
-fn zone_exist_domain_does_not(nsec: Nsec) -> Result<()> {
+fn zone_exist_domain_does_not(nsec: DenialOfExistence) -> Result<()> {
     let leaf_zone = FQDN::TEST_TLD.push_label("exists");
     let needle_fqdn = leaf_zone.push_label("unicorn");
 
@@ -86,7 +174,7 @@ fn zone_exist_domain_does_not(nsec: Nsec) -> Result<()> {
     Ok(())
 }
 
-fn zone_does_not_exist(nsec: Nsec) -> Result<()> {
+fn zone_does_not_exist(nsec: DenialOfExistence) -> Result<()> {
     let parent_zone = FQDN::TEST_DOMAIN;
     let leaf_zone = parent_zone.push_label("does-not-exist");
     let needle_fqdn = leaf_zone.push_label("unicorn");
@@ -124,7 +212,7 @@ fn zone_does_not_exist(nsec: Nsec) -> Result<()> {
     Ok(())
 }
 
-fn domain_exists_record_type_does_not(nsec: Nsec) -> Result<()> {
+fn domain_exists_record_type_does_not(nsec: DenialOfExistence) -> Result<()> {
     let leaf_zone = FQDN::TEST_TLD.push_label("exists");
     let needle_fqdn = leaf_zone.push_label("example");
 
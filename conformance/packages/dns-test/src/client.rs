@@ -1,23 +1,154 @@
+use core::array;
+use core::fmt::{self, Write as _};
 use core::str::FromStr;
 use std::collections::BTreeSet;
-use std::net::Ipv4Addr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
 
-use crate::container::{Container, Image, Network};
+use crate::container::{Container, Image, Network, Output};
 use crate::record::{Record, RecordType};
 use crate::trust_anchor::TrustAnchor;
-use crate::{Error, FQDN, Result};
+use crate::zone_file::ZoneFile;
+use crate::{Error, FQDN, Result, Transport, TsigError, TsigKey};
+
+mod drill;
+mod kdig;
+mod native;
+
+pub use native::{Message, NativeClient, Opt};
+
+/// Sends a query and reports the response the way `dig` does
+///
+/// Implemented by the `dig`-based [`Client`] and by the [`NativeClient`], so that a test can run
+/// against either and cross-check them
+pub trait DnsClient {
+    /// Sends the query and reports whether it was answered or timed out; failing to send the
+    /// query, or to parse its response, is an error
+    fn query_outcome(
+        &self,
+        settings: DigSettings,
+        server: IpAddr,
+        record_type: RecordType,
+        fqdn: &FQDN,
+    ) -> Result<Outcome>;
+
+    /// Like [`Self::query_outcome`] but a query that times out is an error
+    fn query(
+        &self,
+        settings: DigSettings,
+        server: IpAddr,
+        record_type: RecordType,
+        fqdn: &FQDN,
+    ) -> Result<DigOutput> {
+        self.query_outcome(settings, server, record_type, fqdn)?
+            .answer()
+    }
+}
+
+/// Whether a query sent with [`Client::dig_outcome`] or [`DnsClient::query_outcome`] was answered
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum Outcome {
+    Answered(DigOutput),
+    /// No response arrived within the timeout, after all the tries of [`DigSettings::tries`]
+    Timeout,
+}
+
+impl Outcome {
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, Self::Timeout)
+    }
+
+    /// The response; an error if the query timed out
+    pub fn answer(self) -> Result<DigOutput> {
+        match self {
+            Self::Answered(output) => Ok(output),
+            Self::Timeout => Err("the query timed out".into()),
+        }
+    }
+}
+
+/// Command line tool a [`Client`] sends its queries with
+///
+/// The tools parse and print DNS messages independently of each other, so sending the same query
+/// with two of them cross-checks the tools as well as the server. Only [`Client::dig`] and its
+/// variants use the selected tool; the other methods of [`Client`] always use the BIND tools
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ClientImplementation {
+    /// `dig`
+    #[default]
+    BindTools,
+    /// `kdig`; it does not support TSIG, `+opcode`, `+header-only` nor `+ednsflags`
+    KnotUtils,
+    /// `drill`; it supports plain DNS (Do53) and the header flags, DO bit and UDP payload size
+    /// settings only
+    Ldns,
+}
+
+impl ClientImplementation {
+    pub const ALL: [Self; 3] = [Self::BindTools, Self::KnotUtils, Self::Ldns];
+
+    fn tool(&self) -> &'static dyn QueryTool {
+        match self {
+            Self::BindTools => &Dig,
+            Self::KnotUtils => &kdig::Kdig,
+            Self::Ldns => &drill::Drill,
+        }
+    }
+}
+
+impl fmt::Display for ClientImplementation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::BindTools => "bind-tools",
+            Self::KnotUtils => "knot-utils",
+            Self::Ldns => "ldns",
+        })
+    }
+}
+
+impl FromStr for ClientImplementation {
+    type Err = Error;
+
+    /// Parses the `Display` form, or the name of the tool, e.g. `kdig`, ignoring case
+    fn from_str(input: &str) -> Result<Self> {
+        let implementation = match input.to_ascii_lowercase().as_str() {
+            "bind-tools" | "dig" => Self::BindTools,
+            "knot-utils" | "kdig" => Self::KnotUtils,
+            "ldns" | "drill" => Self::Ldns,
+            _ => return Err(format!("unknown client implementation: {input}").into()),
+        };
+
+        Ok(implementation)
+    }
+}
 
 pub struct Client {
     inner: Container,
+    implementation: ClientImplementation,
 }
 
 impl Client {
+    /// A client that uses the tool selected by the `DNS_TEST_CLIENT` environment variable
     pub fn new(network: &Network) -> Result<Self> {
+        Self::with_implementation(network, *crate::CLIENT)
+    }
+
+    /// A client that uses `implementation`
+    pub fn with_implementation(
+        network: &Network,
+        implementation: ClientImplementation,
+    ) -> Result<Self> {
         Ok(Self {
             inner: Container::run(&Image::Client, network)?,
+            implementation,
         })
     }
 
+    pub fn implementation(&self) -> ClientImplementation {
+        self.implementation
+    }
+
     pub fn container_id(&self) -> &str {
         self.inner.id()
     }
@@ -30,90 +161,609 @@ impl Client {
         self.inner.ipv4_addr()
     }
 
+    pub fn ipv6_addr(&self) -> Option<Ipv6Addr> {
+        self.inner.ipv6_addr()
+    }
+
+    /// Points the stub resolver of the client's container, i.e. the one libc uses, at `resolver`
+    /// by rewriting `/etc/resolv.conf`
+    pub fn use_resolver(
+        &self,
+        resolver: impl Into<IpAddr>,
+        options: ResolvConfOptions,
+    ) -> Result<()> {
+        self.use_resolvers(&[resolver.into()], options)
+    }
+
+    /// Like [`Self::use_resolver`] but with several resolvers, which the stub resolver queries in
+    /// order; it moves on to the next one when a resolver does not answer or answers SERVFAIL,
+    /// NOTIMP or REFUSED
+    pub fn use_resolvers(&self, resolvers: &[IpAddr], options: ResolvConfOptions) -> Result<()> {
+        const STAGED_PATH: &str = "/tmp/resolv.conf";
+
+        // docker bind-mounts `/etc/resolv.conf` so it cannot be replaced, only overwritten
+        self.inner.cp(STAGED_PATH, &options.format(resolvers))?;
+        self.inner
+            .status_ok(&["cp", STAGED_PATH, "/etc/resolv.conf"])
+    }
+
+    /// Resolves `fqdn` with libc's `getaddrinfo`, through the resolver set with
+    /// [`Self::use_resolver`], and returns the addresses in the order `getaddrinfo` sorted them
+    ///
+    /// Returns an error if the name does not resolve
+    pub fn getaddrinfo(&self, fqdn: &FQDN) -> Result<Vec<IpAddr>> {
+        // `getent ahosts` prints one line per address and socket type
+        let output = self.inner.stdout(&["getent", "ahosts", fqdn.as_str()])?;
+
+        let mut addrs = vec![];
+        for line in output.lines() {
+            let Some(addr) = line.split_whitespace().next() else {
+                continue;
+            };
+            let addr = addr.parse()?;
+            if !addrs.contains(&addr) {
+                addrs.push(addr);
+            }
+        }
+
+        Ok(addrs)
+    }
+
+    /// Checks, with `ldns-verify-zone`, that the ZONEMD record (RFC8976) of `zone_file` matches
+    /// its contents; the RRSIGs of a signed zone are verified as well
+    ///
+    /// Fails, with the reason `ldns-verify-zone` gives, if the zone has no ZONEMD record or if
+    /// its digest does not match
+    pub fn verify_zonemd(&self, zone_file: &ZoneFile) -> Result<()> {
+        const ZONE_FILE_PATH: &str = "/tmp/zonemd.zone";
+
+        self.inner.cp(ZONE_FILE_PATH, &zone_file.to_string())?;
+        // -Z = require a valid ZONEMD record; also lets an unsigned zone be verified
+        self.inner
+            .status_ok_with_output(&["ldns-verify-zone", "-Z", ZONE_FILE_PATH])
+    }
+
+    /// Resolves `fqdn` and `record_type` through `server` and validates the answer on the client
+    /// side, with `delv`, up to `trust_anchor`
+    ///
+    /// Returns an error if `delv` did not get an answer at all, e.g. because `server` timed out
     pub fn delv(
         &self,
         server: Ipv4Addr,
         record_type: RecordType,
         fqdn: &FQDN,
         trust_anchor: &TrustAnchor,
-    ) -> Result<String> {
+    ) -> Result<DelvOutput> {
         const TRUST_ANCHOR_PATH: &str = "/etc/bind.keys";
 
-        assert!(
-            !trust_anchor.is_empty(),
-            "`delv` cannot be used with an empty trust anchor"
-        );
+        let anchored_zone = trust_anchor
+            .keys()
+            .iter()
+            .map(|key| &key.zone)
+            .chain(trust_anchor.dses().iter().map(|ds| &ds.zone))
+            .next()
+            .expect("`delv` cannot be used with an empty trust anchor");
 
         self.inner.cp(TRUST_ANCHOR_PATH, &trust_anchor.delv())?;
 
-        self.inner.stdout(&[
+        let Output { stdout, stderr, .. } = self.inner.output(&[
             "delv",
             &format!("@{server}"),
             "-a",
             TRUST_ANCHOR_PATH,
+            &format!("+root={anchored_zone}"),
             fqdn.as_str(),
             record_type.as_name().as_ref(),
-        ])
+        ])?;
+
+        // the outcome goes to stdout while the reason of a failure goes to stderr
+        format!("{stderr}\n{stdout}").parse()
+    }
+
+    /// Requests a full transfer (AXFR) of `zone`, signing the request with `tsig_key` if provided
+    ///
+    /// The transfer may span several messages; the records of all of them are returned, starting
+    /// and ending with the SOA record of `zone`
+    ///
+    /// Returns an error if the server refuses the transfer, the request or the response fails
+    /// TSIG verification or the transfer is incomplete
+    pub fn axfr(
+        &self,
+        server: Ipv4Addr,
+        zone: &FQDN,
+        tsig_key: Option<&TsigKey>,
+    ) -> Result<Vec<Record>> {
+        self.transfer(server, zone, "AXFR", tsig_key)
+    }
+
+    /// Requests an incremental transfer (IXFR, RFC1995) of the changes made to `zone` since
+    /// `serial`, signing the request with `tsig_key` if provided
+    ///
+    /// The records are returned as sent: the current SOA record of `zone`, then, for each change,
+    /// the old SOA record followed by the deleted records and the new SOA record followed by the
+    /// added records, and the current SOA record again. The server may instead fall back to a full
+    /// transfer, as returned by [`Client::axfr`], or answer with only the current SOA record when
+    /// `serial` is up to date
+    ///
+    /// Returns an error under the same conditions as [`Client::axfr`]
+    pub fn ixfr(
+        &self,
+        server: Ipv4Addr,
+        zone: &FQDN,
+        serial: u32,
+        tsig_key: Option<&TsigKey>,
+    ) -> Result<Vec<Record>> {
+        self.transfer(server, zone, &format!("IXFR={serial}"), tsig_key)
+    }
+
+    fn transfer(
+        &self,
+        server: Ipv4Addr,
+        zone: &FQDN,
+        query_type: &str,
+        tsig_key: Option<&TsigKey>,
+    ) -> Result<Vec<Record>> {
+        let server_arg = format!("@{server}");
+        let key_arg = tsig_key.map(TsigKey::dig_arg);
+
+        let mut command_and_args = vec!["dig", server_arg.as_str()];
+        if let Some(key_arg) = key_arg.as_ref() {
+            command_and_args.extend_from_slice(&["-y", key_arg]);
+        }
+        command_and_args.extend_from_slice(&[query_type, zone.as_str()]);
+
+        let output = self.inner.stdout(&command_and_args)?;
+        if output.contains("; Transfer failed.") {
+            let tsig_error = output
+                .split_whitespace()
+                .find_map(|word| word.parse::<TsigError>().ok());
+            if let Some(tsig_error) = tsig_error {
+                return Err(format!(
+                    "transfer of {zone} failed TSIG verification ({tsig_error}):\n{output}"
+                )
+                .into());
+            }
+
+            return Err(format!("transfer of {zone} failed:\n{output}").into());
+        }
+
+        let records = output
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with(';'))
+            // the TSIG record that signs the response is not part of the zone
+            .filter(|line| line.split_whitespace().nth(3) != Some("TSIG"))
+            .map(str::parse)
+            .collect::<Result<Vec<Record>>>()?;
+
+        let is_zone_soa =
+            |record: Option<&Record>| matches!(record, Some(Record::SOA(soa)) if soa.zone == *zone);
+        // a lone SOA record is how IXFR reports that the requested serial is up to date
+        let is_up_to_date = query_type != "AXFR" && records.len() == 1;
+        if (records.len() < 2 && !is_up_to_date)
+            || !is_zone_soa(records.first())
+            || !is_zone_soa(records.last())
+        {
+            return Err(format!(
+                "transfer of {zone} does not start and end with its SOA record:\n{output}"
+            )
+            .into());
+        }
+
+        Ok(records)
+    }
+
+    /// Sends `updates` of `zone` to `server` as a single dynamic update (RFC2136), signing it
+    /// with `tsig_key` if provided
+    ///
+    /// Returns an error if the server rejects the update, e.g. with REFUSED or NOTAUTH
+    pub fn nsupdate(
+        &self,
+        server: Ipv4Addr,
+        zone: &FQDN,
+        updates: &[Update],
+        tsig_key: Option<&TsigKey>,
+    ) -> Result<()> {
+        const SCRIPT_PATH: &str = "/tmp/nsupdate.txt";
+
+        let mut script = format!("server {server}\nzone {zone}\n");
+        for update in updates {
+            match update {
+                Update::Add(record) => writeln!(script, "update add {record}"),
+                Update::Delete { fqdn, record_type } => {
+                    writeln!(script, "update delete {fqdn} {}", record_type.as_name())
+                }
+            }
+            .unwrap();
+        }
+        script.push_str("send\n");
+        self.inner.cp(SCRIPT_PATH, &script)?;
+
+        let key_arg = tsig_key.map(TsigKey::dig_arg);
+        let mut command_and_args = vec!["nsupdate"];
+        if let Some(key_arg) = key_arg.as_ref() {
+            command_and_args.extend_from_slice(&["-y", key_arg]);
+        }
+        command_and_args.push(SCRIPT_PATH);
+
+        let Output { status, stderr, .. } = self.inner.output(&command_and_args)?;
+        if !status.success() {
+            return Err(format!("update of {zone} failed:\n{stderr}").into());
+        }
+
+        Ok(())
+    }
+
+    /// Like [`Self::dig`] but sends the query from the host, rather than from this client's
+    /// container, and returns the response as received; see [`NativeClient`]
+    pub fn query_raw(
+        &self,
+        settings: DigSettings,
+        server: impl Into<IpAddr>,
+        record_type: RecordType,
+        fqdn: &FQDN,
+    ) -> Result<Message> {
+        NativeClient.query_raw(settings, server, record_type, fqdn)
+    }
+
+    /// Like [`Self::query_raw`] but sends `count` identical queries back to back over UDP; see
+    /// [`NativeClient::query_burst`]
+    pub fn query_burst(
+        &self,
+        settings: DigSettings,
+        server: impl Into<IpAddr>,
+        record_type: RecordType,
+        fqdn: &FQDN,
+        count: usize,
+    ) -> Result<Vec<Option<Message>>> {
+        NativeClient.query_burst(settings, server, record_type, fqdn, count)
     }
 
     pub fn dig(
         &self,
         settings: DigSettings,
-        server: Ipv4Addr,
+        server: impl Into<IpAddr>,
+        record_type: RecordType,
+        fqdn: &FQDN,
+    ) -> Result<DigOutput> {
+        self.dig_outcome(settings, server, record_type, fqdn)?
+            .answer()
+    }
+
+    /// Like [`Self::dig`] but a query that times out is reported as [`Outcome::Timeout`] rather
+    /// than as an error
+    ///
+    /// Only `dig` and `kdig` report timeouts; with [`ClientImplementation::Ldns`], a query that
+    /// times out is an error
+    pub fn dig_outcome(
+        &self,
+        settings: DigSettings,
+        server: impl Into<IpAddr>,
+        record_type: RecordType,
+        fqdn: &FQDN,
+    ) -> Result<Outcome> {
+        self.dig_inner(settings, None, None, server.into(), record_type, fqdn)
+    }
+
+    /// Like [`Self::dig`] but sends the query over DNS over TLS and verifies the certificate the
+    /// server presents against `ca_cert`, in PEM format, e.g. [`Resolver::tls_certificate`]
+    ///
+    /// The server is connected to by IP address, which the certificates generated by the
+    /// framework list as a subject alternative name. A certificate that does not verify makes
+    /// the query fail
+    ///
+    /// [`Resolver::tls_certificate`]: crate::Resolver::tls_certificate
+    pub fn dig_tls(
+        &self,
+        mut settings: DigSettings,
+        server: impl Into<IpAddr>,
+        ca_cert: &str,
         record_type: RecordType,
         fqdn: &FQDN,
     ) -> Result<DigOutput> {
-        let timeoutflag = &settings.timeoutflag();
-        let ednsflag = settings.ednsflag();
-        let opcodeflag = settings.opcodeflag();
+        const CA_CERT_PATH: &str = "/tmp/ca.pem";
+
+        self.inner.cp(CA_CERT_PATH, ca_cert)?;
+        settings.transport(Transport::DoT);
+        self.dig_inner(
+            settings,
+            None,
+            Some(CA_CERT_PATH),
+            server.into(),
+            record_type,
+            fqdn,
+        )?
+        .answer()
+    }
+
+    /// Like [`Self::dig`] but signs the query with `tsig_key`
+    ///
+    /// A server that cannot verify the query answers with status NOTAUTH; the reason is found in
+    /// [`DigOutput::tsig_error`]
+    pub fn dig_signed(
+        &self,
+        settings: DigSettings,
+        tsig_key: &TsigKey,
+        server: impl Into<IpAddr>,
+        record_type: RecordType,
+        fqdn: &FQDN,
+    ) -> Result<DigOutput> {
+        self.dig_inner(
+            settings,
+            Some(tsig_key),
+            None,
+            server.into(),
+            record_type,
+            fqdn,
+        )?
+        .answer()
+    }
+
+    fn dig_inner(
+        &self,
+        settings: DigSettings,
+        tsig_key: Option<&TsigKey>,
+        tls_ca_file: Option<&str>,
+        server: IpAddr,
+        record_type: RecordType,
+        fqdn: &FQDN,
+    ) -> Result<Outcome> {
+        let query = Query {
+            settings: &settings,
+            tsig_key,
+            tls_ca_file,
+            server,
+            record_type: &record_type,
+            fqdn,
+        };
+        let tool = self.implementation.tool();
+        let command_and_args = tool.command(&query)?;
+        let command_and_args = command_and_args
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+
+        let output = self.inner.output(&command_and_args)?;
+
+        if output.status.success() {
+            tool.parse(&output.stdout).map(Outcome::Answered)
+        } else if tool.timed_out(&output) {
+            Ok(Outcome::Timeout)
+        } else {
+            let Output { stdout, stderr, .. } = output;
+            eprintln!("STDOUT:\n{stdout}\nSTDERR:\n{stderr}");
+
+            Err(format!("[{}] `{command_and_args:?}` failed", self.inner.name()).into())
+        }
+    }
+}
+
+/// The query [`Client::dig`] and its variants send
+struct Query<'a> {
+    settings: &'a DigSettings,
+    tsig_key: Option<&'a TsigKey>,
+    /// CA certificate, in the client's container, that the DNS over TLS certificate of the
+    /// server is verified against
+    tls_ca_file: Option<&'a str>,
+    server: IpAddr,
+    record_type: &'a RecordType,
+    fqdn: &'a FQDN,
+}
+
+/// A command line tool that sends a [`Query`] and prints the response
+trait QueryTool {
+    /// Command, and its arguments, that sends `query`; fails if the tool cannot honor one of the
+    /// settings of the query
+    fn command(&self, query: &Query) -> Result<Vec<String>>;
+
+    /// Parses what the tool printed into the same [`DigOutput`] for every tool
+    fn parse(&self, output: &str) -> Result<DigOutput>;
+
+    /// Whether the tool failed because the query timed out, as opposed to any other failure
+    fn timed_out(&self, _output: &Output) -> bool {
+        false
+    }
+}
+
+/// `dig`, from the BIND tools
+struct Dig;
+
+impl QueryTool for Dig {
+    fn command(&self, query: &Query) -> Result<Vec<String>> {
+        let Query {
+            settings,
+            tsig_key,
+            tls_ca_file,
+            server,
+            record_type,
+            fqdn,
+        } = query;
 
         let mut command_and_args = vec![
-            "dig",
-            settings.rdflag(),
-            settings.do_bit(),
-            settings.adflag(),
-            settings.cdflag(),
-            timeoutflag.as_str(),
-            ednsflag.as_str(),
-            settings.zflag(),
-            opcodeflag.as_str(),
-            settings.header_only_flag(),
-            settings.tcpflag(),
+            "dig".to_string(),
+            settings.rdflag().into(),
+            settings.do_bit().into(),
+            settings.adflag().into(),
+            settings.cdflag().into(),
+            settings.timeoutflag(),
+            settings.ednsflag(),
+            settings.zflag().into(),
+            settings.opcodeflag(),
+            settings.header_only_flag().into(),
+            settings.tcpflag().into(),
+            settings.transportflag().into(),
             settings.cookieflag(),
-            settings.ednsnegflag(),
-            settings.ignoreflag(),
-            settings.nsidflag(),
-            settings.expireflag(),
+            settings.badcookieflag().into(),
+            settings.ednsnegflag().into(),
+            settings.ignoreflag().into(),
+            settings.nsidflag().into(),
+            settings.expireflag().into(),
         ];
 
-        let edns_option_flag = settings.ednsoptionflag();
-        if let Some(edns_option_flag) = edns_option_flag.as_ref() {
-            command_and_args.push(edns_option_flag.as_str());
+        command_and_args.extend(settings.triesflag());
+        command_and_args.extend(settings.ednsoptionflag());
+        command_and_args.extend(settings.extra_edns_flags());
+        command_and_args.extend(settings.bufsizeflag());
+        command_and_args.extend(settings.subnetflag());
+        if let Some(tsig_key) = tsig_key {
+            command_and_args.extend(["-y".into(), tsig_key.dig_arg()]);
         }
-        let edns_flags = settings.extra_edns_flags();
-        if let Some(edns_flags) = edns_flags.as_ref() {
-            command_and_args.push(edns_flags.as_str());
+        if let Some(port) = settings.portflag() {
+            command_and_args.extend(["-p".into(), port]);
         }
-        let bufsize_flag = settings.bufsizeflag();
-        if let Some(bufsize_flag) = bufsize_flag.as_ref() {
-            command_and_args.push(bufsize_flag);
-        }
-        if let Some(subnetflag) = settings.subnetflag() {
-            command_and_args.push(subnetflag);
+        if let Some(path) = tls_ca_file {
+            command_and_args.push(format!("+tls-ca={path}"));
         }
 
-        let server_arg = format!("@{server}");
-        let record_type_name = record_type.as_name();
-        command_and_args.extend_from_slice(&[
-            server_arg.as_str(),
-            record_type_name.as_ref(),
-            fqdn.as_str(),
+        command_and_args.extend([
+            format!("@{server}"),
+            record_type.as_name().into_owned(),
+            fqdn.as_str().to_string(),
         ]);
 
-        let output = self.inner.stdout(&command_and_args)?;
+        Ok(command_and_args)
+    }
 
+    fn parse(&self, output: &str) -> Result<DigOutput> {
         output.parse()
     }
+
+    /// `dig` exits with status 9 when no server replied
+    fn timed_out(&self, output: &Output) -> bool {
+        output.status.code() == Some(9)
+    }
+}
+
+impl DnsClient for Client {
+    fn query_outcome(
+        &self,
+        settings: DigSettings,
+        server: IpAddr,
+        record_type: RecordType,
+        fqdn: &FQDN,
+    ) -> Result<Outcome> {
+        self.dig_outcome(settings, server, record_type, fqdn)
+    }
+}
+
+/// Outcome of the validation `delv` performed
+#[derive(Debug)]
+pub enum DelvOutput {
+    /// The answer validated up to the trust anchor; a negative response has no answer records
+    FullyValidated(Vec<Record>),
+    /// The answer is provably unsigned; a negative response has no answer records
+    Insecure(Vec<Record>),
+    /// Validation failed, for the reason given by `delv`
+    Bogus(String),
+}
+
+impl DelvOutput {
+    pub fn is_fully_validated(&self) -> bool {
+        matches!(self, Self::FullyValidated(_))
+    }
+
+    pub fn is_bogus(&self) -> bool {
+        matches!(self, Self::Bogus(_))
+    }
+}
+
+impl FromStr for DelvOutput {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        const VALIDATED: &str = "fully validated";
+        const UNSIGNED: &str = "unsigned answer";
+        const FAILED_PREFIX: &str = "resolution failed: ";
+        const VALIDATING_PREFIX: &str = "validating ";
+
+        // `delv` comments its status lines with one or two semicolons
+        let uncomment = |line: &str| line.trim_start_matches(';').trim().to_string();
+
+        let mut validating_error = None;
+        let mut lines = input.lines();
+        while let Some(line) = lines.next() {
+            let status = uncomment(line);
+
+            // e.g. `; fully validated` or `; negative response, fully validated`
+            let is_validated = status.ends_with(VALIDATED);
+            if is_validated || status.ends_with(UNSIGNED) {
+                // negative responses are printed as comments, which are skipped
+                let answer = lines
+                    .by_ref()
+                    .filter(|line| !line.is_empty() && !line.starts_with(';'))
+                    .map(str::parse)
+                    .collect::<Result<Vec<Record>>>()?;
+
+                return Ok(if is_validated {
+                    Self::FullyValidated(answer)
+                } else {
+                    Self::Insecure(answer)
+                });
+            // e.g. `;; validating example.hickory-dns.testing/A: no valid signature found`
+            } else if let Some(validating) = status.strip_prefix(VALIDATING_PREFIX) {
+                if let Some((_query, reason)) = validating.split_once(": ") {
+                    validating_error.get_or_insert_with(|| reason.to_string());
+                }
+            // e.g. `;; resolution failed: SERVFAIL`
+            } else if let Some(reason) = status.strip_prefix(FAILED_PREFIX) {
+                if reason == "timed out" {
+                    return Err(format!("`delv` got no answer:\n{input}").into());
+                }
+
+                return Ok(Self::Bogus(
+                    validating_error.unwrap_or_else(|| reason.to_string()),
+                ));
+            }
+        }
+
+        Err(format!("could not parse `delv` output:\n{input}").into())
+    }
+}
+
+/// A change to a zone, sent to its name server in a dynamic update (RFC2136)
+#[derive(Clone, Debug)]
+pub enum Update {
+    /// Adds `Record` to the zone
+    Add(Record),
+    /// Deletes all the records of `record_type` owned by `fqdn`
+    Delete { fqdn: FQDN, record_type: RecordType },
+}
+
+/// Options of the stub resolver of a client container; see [`Client::use_resolver`]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ResolvConfOptions {
+    edns0: bool,
+    trust_ad: bool,
+}
+
+impl ResolvConfOptions {
+    /// Sends queries with an OPT record, `options edns0`
+    pub fn edns0(&mut self) -> &mut Self {
+        self.edns0 = true;
+        self
+    }
+
+    /// Sets the AD bit in queries and keeps it in responses, `options trust-ad`; otherwise glibc
+    /// clears it
+    pub fn trust_ad(&mut self) -> &mut Self {
+        self.trust_ad = true;
+        self
+    }
+
+    fn format(&self, nameservers: &[IpAddr]) -> String {
+        let mut options = vec![];
+        if self.edns0 {
+            options.push("edns0");
+        }
+        if self.trust_ad {
+            options.push("trust-ad");
+        }
+
+        minijinja::render!(
+            include_str!("templates/resolv.conf.jinja"),
+            nameservers => nameservers.iter().map(IpAddr::to_string).collect::<Vec<_>>(),
+            options => options,
+        )
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -122,7 +772,9 @@ pub struct DigSettings {
     cdflag: bool,
     dnssec: bool,
     recurse: bool,
-    timeout: Option<u8>,
+    timeout: Option<Duration>,
+    /// Number of times a UDP query is sent; `None` keeps the default of each client
+    tries: Option<u8>,
     /// EDNS version.
     ///
     /// `None` indicates EDNS should not be used, while Some indicates EDNS should be used, with
@@ -133,6 +785,9 @@ pub struct DigSettings {
     header_only: bool,
     tcp: bool,
     cookie: bool,
+    /// Value of the COOKIE option; `None` lets `dig` pick a client cookie
+    cookie_value: Option<Cookie>,
+    badcookie: bool,
     ednsneg: bool,
     extra_edns_option: Option<u16>,
     extra_edns_flags: Option<u16>,
@@ -140,7 +795,10 @@ pub struct DigSettings {
     bufsize: Option<u16>,
     nsid: bool,
     expire: bool,
-    subnet_zero: bool,
+    subnet: Option<Subnet>,
+    transport: Transport,
+    https_get: bool,
+    port: Option<u16>,
 }
 
 impl Default for DigSettings {
@@ -151,12 +809,15 @@ impl Default for DigSettings {
             dnssec: false,
             recurse: false,
             timeout: None,
+            tries: None,
             edns: Some(0),
             zflag: false,
             opcode: 0,
             header_only: false,
             tcp: false,
             cookie: true,
+            cookie_value: None,
+            badcookie: true,
             ednsneg: true,
             extra_edns_option: None,
             extra_edns_flags: None,
@@ -164,7 +825,10 @@ impl Default for DigSettings {
             bufsize: None,
             nsid: false,
             expire: false,
-            subnet_zero: false,
+            subnet: None,
+            transport: Transport::Do53,
+            https_get: false,
+            port: None,
         }
     }
 }
@@ -222,19 +886,34 @@ impl DigSettings {
         }
     }
 
-    /// Sets the timeout for the query, specified in seconds
-    pub fn timeout(&mut self, timeout: u8) -> &mut Self {
+    /// Sets how long each try of the query waits for a response
+    ///
+    /// `dig` and `kdig` only take whole seconds so the timeout is rounded up, to at least one
+    /// second
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
         self.timeout = Some(timeout);
         self
     }
 
     fn timeoutflag(&self) -> String {
         match self.timeout {
-            Some(timeout) => format!("+timeout={timeout}"),
+            Some(timeout) => format!("+timeout={}", timeout_seconds(timeout)),
             None => "+timeout=5".into(),
         }
     }
 
+    /// Sets how many times a UDP query is sent before giving up, including the first one
+    ///
+    /// By default, `dig`, `kdig` and the native client try 3 times
+    pub fn tries(&mut self, tries: u8) -> &mut Self {
+        self.tries = Some(tries);
+        self
+    }
+
+    fn triesflag(&self) -> Option<String> {
+        self.tries.map(|tries| format!("+tries={tries}"))
+    }
+
     /// Sets the EDNS version in the query, or disables EDNS
     pub fn edns(&mut self, version: Option<u8>) -> &mut Self {
         self.edns = version;
@@ -298,19 +977,94 @@ impl DigSettings {
         }
     }
 
+    /// Sends the query over `transport`, e.g. DNS over TLS
+    ///
+    /// The certificate the server presents is not verified. DNS over HTTPS queries are POST
+    /// requests unless [`Self::https_get`] is also set
+    pub fn transport(&mut self, transport: Transport) -> &mut Self {
+        self.transport = transport;
+        self
+    }
+
+    fn transportflag(&self) -> &'static str {
+        match self.transport {
+            Transport::Do53 => "+notls",
+            Transport::DoT => "+tls",
+            Transport::DoH if self.https_get => "+https-get",
+            Transport::DoH => "+https",
+        }
+    }
+
+    /// Sends the query to `port` rather than to the standard port of the transport
+    pub fn port(&mut self, port: u16) -> &mut Self {
+        self.port = Some(port);
+        self
+    }
+
+    fn portflag(&self) -> Option<String> {
+        Some(self.port?.to_string())
+    }
+
+    /// Sends DNS over HTTPS queries as GET, rather than POST, requests
+    pub fn https_get(&mut self) -> &mut Self {
+        self.https_get = true;
+        self
+    }
+
     /// Do not send a COOKIE EDNS option.
     pub fn nocookie(&mut self) -> &mut Self {
         self.cookie = false;
         self
     }
 
-    fn cookieflag(&self) -> &'static str {
+    /// Sends `cookie`, in hexadecimal, as the COOKIE EDNS option: a client cookie of 8 bytes,
+    /// optionally followed by the server cookie of an earlier response, e.g.
+    /// [`DigOutput::cookie`]
+    ///
+    /// # Panics
+    ///
+    /// If `cookie` is not the hexadecimal form of 8 to 40 bytes
+    pub fn cookie(&mut self, cookie: &str) -> &mut Self {
+        let decoded = hex::decode(cookie).expect("cookie is not hexadecimal");
+        assert!(
+            (Cookie::CLIENT_LEN..=Cookie::MAX_LEN).contains(&decoded.len()),
+            "a cookie is 8 to 40 bytes long"
+        );
+
+        let mut bytes = [0; Cookie::MAX_LEN];
+        bytes[..decoded.len()].copy_from_slice(&decoded);
+        self.cookie = true;
+        self.cookie_value = Some(Cookie {
+            bytes,
+            len: decoded.len(),
+        });
+        self
+    }
+
+    fn cookieflag(&self) -> String {
         // Only use "+cookie" when EDNS is enabled (the default). Otherwise, "+cookie" overrides
         // "+noedns".
         if self.edns.is_some() && self.cookie {
-            "+cookie"
+            match &self.cookie_value {
+                Some(cookie) => format!("+cookie={}", hex::encode(cookie.as_bytes())),
+                None => "+cookie".into(),
+            }
         } else {
-            "+nocookie"
+            "+nocookie".into()
+        }
+    }
+
+    /// Do not retry, with the server cookie of the response, queries answered with BADCOOKIE;
+    /// the native client never retries
+    pub fn nobadcookie(&mut self) -> &mut Self {
+        self.badcookie = false;
+        self
+    }
+
+    fn badcookieflag(&self) -> &'static str {
+        match self.badcookie {
+            true => "+badcookie",
+            false => "+nobadcookie",
         }
     }
 
@@ -399,25 +1153,61 @@ impl DigSettings {
         }
     }
 
+    /// Send the EDNS client subnet option, with the subnet 0.0.0.0/0.
     pub fn subnet_zero(&mut self) -> &mut Self {
-        self.subnet_zero = true;
+        self.subnet = Some(Subnet::Zero);
         self
     }
 
-    /// Send the EDNS client subnet option, with the subnet 0.0.0.0/0.
-    fn subnetflag(&self) -> Option<&'static str> {
-        match self.subnet_zero {
-            true => Some("+subnet=0"),
-            false => None,
+    /// Send the EDNS Client Subnet option (RFC7871) with `address` as the client's subnet, e.g.
+    /// 10.0.0.0 with a `source_prefix_length` of 24
+    pub fn subnet(&mut self, address: impl Into<IpAddr>, source_prefix_length: u8) -> &mut Self {
+        self.subnet = Some(Subnet::Prefix(address.into(), source_prefix_length));
+        self
+    }
+
+    fn subnetflag(&self) -> Option<String> {
+        match self.subnet? {
+            Subnet::Zero => Some("+subnet=0".to_string()),
+            Subnet::Prefix(address, source_prefix_length) => {
+                Some(format!("+subnet={address}/{source_prefix_length}"))
+            }
         }
     }
 }
 
+/// Client cookie followed by the server cookie, if any, of the COOKIE option (RFC7873); kept
+/// inline so that [`DigSettings`] stays `Copy`
+#[derive(Clone, Copy)]
+struct Cookie {
+    bytes: [u8; Self::MAX_LEN],
+    len: usize,
+}
+
+impl Cookie {
+    const CLIENT_LEN: usize = 8;
+    /// A client cookie and the longest server cookie, of 32 bytes
+    const MAX_LEN: usize = 40;
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Subnet {
+    /// tells the resolver to not use the client's address
+    Zero,
+    Prefix(IpAddr, u8),
+}
+
 #[derive(Debug)]
 pub struct DigOutput {
     pub ede: BTreeSet<ExtendedDnsError>,
     pub flags: DigFlags,
     pub status: DigStatus,
+    /// in the order `dig` printed them, e.g. a chain of CNAME records followed by the record
+    /// the last CNAME points to
     pub answer: Vec<Record>,
     pub authority: Vec<Record>,
     pub additional: Vec<Record>,
@@ -428,6 +1218,21 @@ pub struct DigOutput {
     pub opcode: String,
     pub edns_version: Option<u8>,
     pub dnssec_ok_flag: bool,
+    /// Error in the TSIG record of the response, if the server could not verify the query
+    pub tsig_error: Option<TsigError>,
+    /// EDNS Client Subnet option (RFC7871) in the OPT record of the response
+    pub client_subnet: Option<ClientSubnet>,
+    /// COOKIE option (RFC7873) of the response, in hexadecimal: the client cookie followed by
+    /// the server cookie, if any
+    pub cookie: Option<String>,
+    /// Protocol the response, the last one when `dig` retried over TCP, arrived over; `None` if
+    /// `dig` did not report it or used a transport other than plain DNS, e.g. DNS over TLS
+    pub protocol: Option<Protocol>,
+    /// How long the server took to answer, as measured by the client; `None` if the client did
+    /// not report it
+    pub query_time: Option<Duration>,
+    /// Address of the server that sent the response; `None` if the client did not report it
+    pub server: Option<IpAddr>,
 }
 
 impl FromStr for DigOutput {
@@ -441,9 +1246,14 @@ impl FromStr for DigOutput {
         const OPT_PREFIX: &str = "; OPT=";
         const OPT_HEADER: &str = ";; OPT PSEUDOSECTION:";
         const EDNS_PREFIX: &str = "; EDNS: version: ";
+        const CLIENT_SUBNET_PREFIX: &str = "; CLIENT-SUBNET: ";
+        const COOKIE_PREFIX: &str = "; COOKIE: ";
         const ANSWER_HEADER: &str = ";; ANSWER SECTION:";
         const AUTHORITY_HEADER: &str = ";; AUTHORITY SECTION:";
         const ADDITIONAL_HEADER: &str = ";; ADDITIONAL SECTION:";
+        const TSIG_HEADER: &str = ";; TSIG PSEUDOSECTION:";
+        const SERVER_PREFIX: &str = ";; SERVER: ";
+        const QUERY_TIME_PREFIX: &str = ";; Query time: ";
 
         fn not_found(prefix: &str) -> String {
             format!("`{prefix}` line was not found")
@@ -470,6 +1280,12 @@ impl FromStr for DigOutput {
         let mut edns_version = None;
         let mut edns_must_be_zero = false;
         let mut dnssec_ok_flag = false;
+        let mut tsig_error = None;
+        let mut client_subnet = None;
+        let mut cookie = None;
+        let mut protocol = None;
+        let mut query_time = None;
+        let mut server = None;
 
         let mut lines = input.lines();
         while let Some(line) = lines.next() {
@@ -540,6 +1356,23 @@ impl FromStr for DigOutput {
                 if line.contains("flags: do") {
                     dnssec_ok_flag = true;
                 }
+            } else if let Some(unprefixed) = line.strip_prefix(CLIENT_SUBNET_PREFIX) {
+                if client_subnet.is_some() {
+                    return Err(more_than_once(CLIENT_SUBNET_PREFIX).into());
+                }
+
+                client_subnet = Some(unprefixed.parse()?);
+            } else if let Some(unprefixed) = line.strip_prefix(COOKIE_PREFIX) {
+                // e.g. `0102030405060708010000006734b7e2a4d1c8d2b8ba93e2 (good)`
+                if cookie.is_some() {
+                    return Err(more_than_once(COOKIE_PREFIX).into());
+                }
+
+                let value = unprefixed
+                    .split_once(' ')
+                    .map(|(value, _verdict)| value)
+                    .unwrap_or(unprefixed);
+                cookie = Some(value.to_string());
             } else if let Some(unprefixed) = line.strip_prefix(OPT_PREFIX) {
                 let Some((option_str, value)) = unprefixed.split_once(": ") else {
                     return Err("could not parse option".into());
@@ -552,46 +1385,37 @@ impl FromStr for DigOutput {
                     return Err(more_than_once(ANSWER_HEADER).into());
                 }
 
-                let mut records = vec![];
-                for line in lines.by_ref() {
-                    if line.is_empty() {
-                        break;
-                    }
-
-                    records.push(line.parse()?);
-                }
-
-                answer = Some(records);
+                answer = Some(records(lines.by_ref())?);
             } else if line.starts_with(AUTHORITY_HEADER) {
                 if authority.is_some() {
                     return Err(more_than_once(AUTHORITY_HEADER).into());
                 }
 
-                let mut records = vec![];
-                for line in lines.by_ref() {
-                    if line.is_empty() {
-                        break;
-                    }
-
-                    records.push(line.parse()?);
-                }
-
-                authority = Some(records);
+                authority = Some(records(lines.by_ref())?);
             } else if line.starts_with(ADDITIONAL_HEADER) {
                 if additional.is_some() {
                     return Err(more_than_once(ADDITIONAL_HEADER).into());
                 }
 
-                let mut records = vec![];
-                for line in lines.by_ref() {
-                    if line.is_empty() {
-                        break;
-                    }
-
-                    records.push(line.parse()?);
+                additional = Some(records(lines.by_ref())?);
+            } else if line.starts_with(TSIG_HEADER) {
+                // e.g. `transfer-key. 0 ANY TSIG hmac-sha256. 1718000000 300 0 4242 BADSIG 0`
+                if let Some(record) = lines.next() {
+                    tsig_error = record
+                        .split_whitespace()
+                        .find_map(|column| column.parse().ok());
                 }
-
-                additional = Some(records);
+            } else if let Some(unprefixed) = line.strip_prefix(SERVER_PREFIX) {
+                // e.g. `172.18.0.3#53(172.18.0.3) (TCP)`
+                protocol = unprefixed
+                    .rsplit_once(' ')
+                    .and_then(|(_, protocol)| protocol.parse().ok());
+                server = unprefixed
+                    .split_once('#')
+                    .and_then(|(address, _)| address.parse().ok());
+            } else if let Some(unprefixed) = line.strip_prefix(QUERY_TIME_PREFIX) {
+                // e.g. `4 msec`
+                query_time = parse_query_time(unprefixed);
             }
         }
 
@@ -609,6 +1433,122 @@ impl FromStr for DigOutput {
             opcode: opcode.ok_or_else(|| not_found(OPCODE_PREFIX))?,
             edns_version,
             dnssec_ok_flag,
+            tsig_error,
+            client_subnet,
+            cookie,
+            protocol,
+            query_time,
+            server,
+        })
+    }
+}
+
+/// Whole seconds `dig` and `kdig` wait for, at least one, for a timeout of `timeout`
+fn timeout_seconds(timeout: Duration) -> u64 {
+    let seconds = timeout.as_secs() + u64::from(timeout.subsec_nanos() != 0);
+    seconds.max(1)
+}
+
+/// Parses a query time such as `4 msec` or `0.4 ms`; the spelling of the unit varies across
+/// clients and their versions
+fn parse_query_time(input: &str) -> Option<Duration> {
+    let mut columns = input.split_whitespace();
+    let value = columns.next()?.parse::<f64>().ok()?;
+    let seconds = match columns.next()? {
+        "msec" | "ms" => value / 1_000.,
+        "usec" | "us" => value / 1_000_000.,
+        "sec" | "s" => value,
+        _ => return None,
+    };
+
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// Parses the records of a section, up to the empty line that ends it
+fn records<'a>(lines: impl Iterator<Item = &'a str>) -> Result<Vec<Record>> {
+    let mut records = vec![];
+    for line in lines {
+        if line.is_empty() {
+            break;
+        }
+
+        records.push(line.parse()?);
+    }
+
+    Ok(records)
+}
+
+/// Protocol a DNS message is sent over
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Protocol {
+    Udp,
+    Tcp,
+}
+
+impl FromStr for Protocol {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let protocol = match input {
+            "(UDP)" => Self::Udp,
+            "(TCP)" => Self::Tcp,
+            _ => return Err(format!("unknown protocol: {input}").into()),
+        };
+
+        Ok(protocol)
+    }
+}
+
+/// EDNS Client Subnet option (RFC7871)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClientSubnet {
+    /// Only the first `source_prefix_length` bits are significant
+    pub address: IpAddr,
+    pub source_prefix_length: u8,
+    /// Number of leading bits of `address` the answer is valid for; 0 in queries
+    pub scope_prefix_length: u8,
+}
+
+impl ClientSubnet {
+    /// The address family: 1 for IPv4, 2 for IPv6
+    pub fn family(&self) -> u16 {
+        match self.address {
+            IpAddr::V4(_) => 1,
+            IpAddr::V6(_) => 2,
+        }
+    }
+}
+
+impl FromStr for ClientSubnet {
+    type Err = Error;
+
+    /// Parses `dig`'s `address/source/scope` format, e.g. `10.0.0.0/24/0`
+    fn from_str(input: &str) -> Result<Self> {
+        let mut parts = input.trim().split('/');
+        let [
+            Some(address),
+            Some(source_prefix_length),
+            scope_prefix_length,
+            None,
+        ] = array::from_fn(|_| parts.next())
+        else {
+            return Err(format!("could not parse client subnet: {input}").into());
+        };
+
+        // `dig` prints the address of the option sent by `+subnet=0` as `0`
+        let address = if address == "0" {
+            IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+        } else {
+            address.parse()?
+        };
+
+        Ok(Self {
+            address,
+            source_prefix_length: source_prefix_length.parse()?,
+            scope_prefix_length: scope_prefix_length
+                .map(str::parse)
+                .transpose()?
+                .unwrap_or(0),
         })
     }
 }
@@ -694,8 +1634,10 @@ impl FromStr for DigFlags {
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum DigStatus {
+    BADCOOKIE,
     BADVERS,
     NOERROR,
+    NOTAUTH,
     NOTIMP,
     NXDOMAIN,
     REFUSED,
@@ -724,8 +1666,10 @@ impl FromStr for DigStatus {
 
     fn from_str(input: &str) -> Result<Self> {
         let status = match input {
+            "BADCOOKIE" => Self::BADCOOKIE,
             "BADVERS" => Self::BADVERS,
             "NOERROR" => Self::NOERROR,
+            "NOTAUTH" => Self::NOTAUTH,
             "NOTIMP" => Self::NOTIMP,
             "NXDOMAIN" => Self::NXDOMAIN,
             "REFUSED" => Self::REFUSED,
@@ -741,6 +1685,59 @@ impl FromStr for DigStatus {
 mod tests {
     use super::*;
 
+    #[test]
+    fn resolv_conf() {
+        let nameserver = IpAddr::from([172, 18, 0, 2]);
+        assert_eq!(
+            "nameserver 172.18.0.2\n",
+            ResolvConfOptions::default().format(&[nameserver])
+        );
+
+        let resolv_conf = ResolvConfOptions::default()
+            .edns0()
+            .trust_ad()
+            .format(&[nameserver]);
+        assert_eq!(
+            "nameserver 172.18.0.2\noptions edns0 trust-ad\n",
+            resolv_conf
+        );
+
+        let fallback = IpAddr::from([172, 18, 0, 3]);
+        assert_eq!(
+            "nameserver 172.18.0.2\nnameserver 172.18.0.3\n",
+            ResolvConfOptions::default().format(&[nameserver, fallback])
+        );
+    }
+
+    #[test]
+    fn timeout_and_tries() {
+        let settings = DigSettings::default();
+        assert_eq!("+timeout=5", settings.timeoutflag());
+        assert_eq!(None, settings.triesflag());
+
+        let settings = *DigSettings::default()
+            .timeout(Duration::from_millis(1500))
+            .tries(1);
+        assert_eq!("+timeout=2", settings.timeoutflag());
+        assert_eq!(Some("+tries=1"), settings.triesflag().as_deref());
+
+        let settings = *DigSettings::default().timeout(Duration::ZERO);
+        assert_eq!("+timeout=1", settings.timeoutflag());
+    }
+
+    #[test]
+    fn client_implementation() -> Result<()> {
+        for implementation in ClientImplementation::ALL {
+            assert_eq!(implementation, implementation.to_string().parse()?);
+        }
+
+        assert_eq!(ClientImplementation::KnotUtils, "KDIG".parse()?);
+        assert_eq!(ClientImplementation::Ldns, "drill".parse()?);
+        assert!("nslookup".parse::<ClientImplementation>().is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn dig_nxdomain() -> Result<()> {
         // $ dig nonexistent.domain.
@@ -813,6 +1810,57 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn wildcard_answer() -> Result<()> {
+        // $ dig +dnssec A foo.hickory-dns.testing.
+        // with a `*.hickory-dns.testing.` record in the zone and the signatures shortened
+        let input = "
+; <<>> DiG 9.18.28-1~deb12u2-Debian <<>> +recurse +dnssec +adflag +nocdflag @172.19.0.5 A foo.hickory-dns.testing.
+; (1 server found)
+;; global options: +cmd
+;; Got answer:
+;; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 20833
+;; flags: qr rd ra ad; QUERY: 1, ANSWER: 2, AUTHORITY: 2, ADDITIONAL: 1
+
+;; OPT PSEUDOSECTION:
+; EDNS: version: 0, flags: do; udp: 1232
+;; QUESTION SECTION:
+;foo.hickory-dns.testing.	IN	A
+
+;; ANSWER SECTION:
+foo.hickory-dns.testing. 86400	IN	A	1.2.3.4
+foo.hickory-dns.testing. 86400	IN	RRSIG	A 13 2 86400 20240425134148 20240328134148 29065 hickory-dns.testing. 9wM0XbNSjNUjZD3mV6a0UUTnMy9tkzqUlw8NYUsQdHoW2sgU5SunthKX 4VmWvTHddy01q39oa8Nmwlo1vMsiCA==
+
+;; AUTHORITY SECTION:
+primary0.hickory-dns.testing. 86400 IN	NSEC	hickory-dns.testing. A RRSIG NSEC
+primary0.hickory-dns.testing. 86400 IN	RRSIG	NSEC 13 3 86400 20240425134148 20240328134148 29065 hickory-dns.testing. dNmXrEU0tr2P2g6Hxoi2hFNyHByW99tkx4X3cKT2TfikH9Kte0OswG4l LF82fZBd/6UdxwYXF/3z5uXvaYd2MQ==
+
+;; Query time: 4 msec
+;; SERVER: 172.19.0.5#53(172.19.0.5) (UDP)
+;; WHEN: Thu Mar 28 13:41:48 UTC 2024
+;; MSG SIZE  rcvd: 349
+";
+
+        let output: DigOutput = input.parse()?;
+
+        assert!(output.status.is_noerror());
+        assert!(output.flags.authenticated_data);
+
+        let [a, rrsig] = output.answer.try_into().unwrap();
+        let a = a.try_into_a().unwrap();
+        let rrsig = rrsig.try_into_rrsig().unwrap();
+        // the owner name is the queried name, not the wildcard ...
+        assert_eq!(FQDN("foo.hickory-dns.testing.")?, a.fqdn);
+        // ... but the signature reveals that the answer was synthesized from `*.hickory-dns.testing.`
+        assert_eq!(2, rrsig.labels);
+        assert!(usize::from(rrsig.labels) < a.fqdn.num_labels());
+
+        let [nsec, _rrsig] = output.authority.try_into().unwrap();
+        assert!(matches!(nsec, Record::NSEC(_)));
+
+        Ok(())
+    }
+
     #[test]
     fn additional_section() -> Result<()> {
         // $ dig @a.root-servers.net. +norecurse NS .
@@ -1068,4 +2116,236 @@ primary0.hickory-dns.testing. 86400 IN	RRSIG	A 8 3 86400 20250107210902 20241210
 
         Ok(())
     }
+
+    #[test]
+    fn tsig_error() -> Result<()> {
+        // $ dig -y hmac-sha256:transfer-key:<wrong secret> SOA .
+        let input = "
+; <<>> DiG 9.18.28-1~deb12u2-Debian <<>> -y hmac-sha256:transfer-key:MTIzNDU2Nzg5MDEyMzQ1Njc4OTAxMjM0NTY3ODkwMTI= @172.18.0.2 SOA .
+;; global options: +cmd
+;; Couldn't verify signature: tsig indicates error
+;; Got answer:
+;; ->>HEADER<<- opcode: QUERY, status: NOTAUTH, id: 17361
+;; flags: qr; QUERY: 1, ANSWER: 0, AUTHORITY: 0, ADDITIONAL: 1
+
+;; QUESTION SECTION:
+;.				IN	SOA
+
+;; TSIG PSEUDOSECTION:
+transfer-key.		0	ANY	TSIG	hmac-sha256. 1718000000 300 0  17361 BADSIG 0
+
+;; Query time: 0 msec
+;; SERVER: 172.18.0.2#53(172.18.0.2) (UDP)
+;; WHEN: Mon Jun 10 06:13:20 UTC 2024
+;; MSG SIZE  rcvd: 72
+";
+
+        let output: DigOutput = input.parse()?;
+
+        assert_eq!(DigStatus::NOTAUTH, output.status);
+        assert_eq!(Some(TsigError::BADSIG), output.tsig_error);
+
+        Ok(())
+    }
+
+    #[test]
+    fn client_subnet() -> Result<()> {
+        // $ dig +subnet=10.0.0.0/24 A example.com.
+        let input = "
+; <<>> DiG 9.18.33-1~deb12u2-Debian <<>> +subnet=10.0.0.0/24 A example.com.
+;; global options: +cmd
+;; Got answer:
+;; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 5050
+;; flags: qr rd ra; QUERY: 1, ANSWER: 1, AUTHORITY: 0, ADDITIONAL: 1
+
+;; OPT PSEUDOSECTION:
+; EDNS: version: 0, flags:; udp: 1232
+; CLIENT-SUBNET: 10.0.0.0/24/24
+;; QUESTION SECTION:
+;example.com.			IN	A
+
+;; ANSWER SECTION:
+example.com.		300	IN	A	93.184.215.14
+
+;; Query time: 12 msec
+;; SERVER: 172.18.0.3#53(172.18.0.3) (UDP)
+;; WHEN: Tue Mar 04 10:00:00 UTC 2025
+;; MSG SIZE  rcvd: 67
+";
+
+        let output: DigOutput = input.parse()?;
+
+        assert_eq!(
+            Some(ClientSubnet {
+                address: Ipv4Addr::new(10, 0, 0, 0).into(),
+                source_prefix_length: 24,
+                scope_prefix_length: 24,
+            }),
+            output.client_subnet
+        );
+        assert!(output.options.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn cookie() -> Result<()> {
+        // $ dig +cookie=0102030405060708 +nobadcookie A example.com.
+        let input = "
+; <<>> DiG 9.18.33-1~deb12u2-Debian <<>> +cookie=0102030405060708 +nobadcookie A example.com.
+;; global options: +cmd
+;; Got answer:
+;; ->>HEADER<<- opcode: QUERY, status: BADCOOKIE, id: 21337
+;; flags: qr rd ra; QUERY: 1, ANSWER: 0, AUTHORITY: 0, ADDITIONAL: 1
+
+;; OPT PSEUDOSECTION:
+; EDNS: version: 0, flags:; udp: 1232
+; COOKIE: 0102030405060708010000006734b7e2a4d1c8d2b8ba93e2 (good)
+;; QUESTION SECTION:
+;example.com.			IN	A
+
+;; Query time: 0 msec
+;; SERVER: 172.18.0.3#53(172.18.0.3) (UDP)
+;; WHEN: Tue Mar 04 10:00:00 UTC 2025
+;; MSG SIZE  rcvd: 68
+";
+
+        let output: DigOutput = input.parse()?;
+
+        assert_eq!(DigStatus::BADCOOKIE, output.status);
+        assert_eq!(
+            Some("0102030405060708010000006734b7e2a4d1c8d2b8ba93e2"),
+            output.cookie.as_deref()
+        );
+        assert!(output.options.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn protocol() -> Result<()> {
+        // $ dig +dnssec +bufsize=4096 DNSKEY .
+        let input = "
+; <<>> DiG 9.18.33-1~deb12u2-Debian <<>> +dnssec +bufsize=4096 DNSKEY .
+;; global options: +cmd
+;; Truncated, retrying in TCP mode.
+;; Got answer:
+;; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 27119
+;; flags: qr aa; QUERY: 1, ANSWER: 0, AUTHORITY: 0, ADDITIONAL: 1
+
+;; OPT PSEUDOSECTION:
+; EDNS: version: 0, flags: do; udp: 1232
+;; QUESTION SECTION:
+;.				IN	DNSKEY
+
+;; Query time: 4 msec
+;; SERVER: 172.18.0.2#53(172.18.0.2) (TCP)
+;; WHEN: Tue Mar 04 10:00:00 UTC 2025
+;; MSG SIZE  rcvd: 28
+";
+
+        let output: DigOutput = input.parse()?;
+        assert_eq!(Some(Protocol::Tcp), output.protocol);
+
+        let output: DigOutput = input.replace("(TCP)", "(UDP)").parse()?;
+        assert_eq!(Some(Protocol::Udp), output.protocol);
+
+        let output: DigOutput = input.replace("(TCP)", "(TLS)").parse()?;
+        assert_eq!(None, output.protocol);
+
+        Ok(())
+    }
+
+    #[test]
+    fn query_time_and_server() -> Result<()> {
+        // $ dig @fd00::2 A example.hickory-dns.testing.
+        let input = "
+; <<>> DiG 9.18.33-1~deb12u2-Debian <<>> @fd00::2 A example.hickory-dns.testing.
+;; global options: +cmd
+;; Got answer:
+;; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 27119
+;; flags: qr aa rd; QUERY: 1, ANSWER: 0, AUTHORITY: 0, ADDITIONAL: 1
+
+;; QUESTION SECTION:
+;example.hickory-dns.testing.	IN	A
+
+;; Query time: 12 msec
+;; SERVER: fd00::2#53(fd00::2) (UDP)
+;; WHEN: Tue Mar 04 10:00:00 UTC 2025
+;; MSG SIZE  rcvd: 56
+";
+
+        let output: DigOutput = input.parse()?;
+        assert_eq!(Some(Duration::from_millis(12)), output.query_time);
+        assert_eq!(Some("fd00::2".parse::<IpAddr>()?), output.server);
+
+        let output: DigOutput = input.replace("msec", "ms").parse()?;
+        assert_eq!(Some(Duration::from_millis(12)), output.query_time);
+
+        let output: DigOutput = input
+            .lines()
+            .filter(|line| !line.starts_with(";; Query time:") && !line.starts_with(";; SERVER:"))
+            .collect::<Vec<_>>()
+            .join("\n")
+            .parse()?;
+        assert_eq!(None, output.query_time);
+        assert_eq!(None, output.server);
+
+        Ok(())
+    }
+
+    #[test]
+    fn delv_fully_validated() -> Result<()> {
+        // $ delv @172.18.0.5 -a /etc/bind.keys +root=. example.hickory-dns.testing. A
+        let input = "
+; fully validated
+example.hickory-dns.testing. 86400 IN	A	1.2.3.4
+example.hickory-dns.testing. 86400 IN	RRSIG	A 8 3 86400 20240306132701 20240205132701 2151 hickory-dns.testing. uaaML7Cl6Y7pBD1yc/uq1scf9RLTzhTqzZfsPIRIvP+WtVI+1RpAlrQl 2M2jUNgqXTPZhl6KnhTRV7CclpBsDQ==
+";
+
+        let DelvOutput::FullyValidated(answer) = input.parse()? else {
+            panic!("expected a fully validated answer");
+        };
+
+        let [a, rrsig] = answer.try_into().unwrap();
+        assert_eq!(Ipv4Addr::new(1, 2, 3, 4), a.try_into_a().unwrap().ipv4_addr);
+        assert_eq!(RecordType::A, rrsig.try_into_rrsig().unwrap().type_covered);
+
+        Ok(())
+    }
+
+    #[test]
+    fn delv_negative_response() -> Result<()> {
+        let input = "
+; negative response, unsigned answer
+; nonexistent.hickory-dns.testing. 86400 IN \\-A ;-$NXDOMAIN
+; hickory-dns.testing. SOA primary0.hickory-dns.testing. admin0.nameservers.com. 2024010101 1800 900 604800 86400
+";
+
+        let DelvOutput::Insecure(answer) = input.parse()? else {
+            panic!("expected an insecure answer");
+        };
+        assert!(answer.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn delv_bogus() -> Result<()> {
+        let input = "
+;; validating example.hickory-dns.testing/A: no valid signature found
+;; no valid RRSIG resolving 'example.hickory-dns.testing/A/IN': 172.18.0.5#53
+;; resolution failed: SERVFAIL
+";
+
+        let DelvOutput::Bogus(reason) = input.parse()? else {
+            panic!("expected a bogus answer");
+        };
+        assert_eq!("no valid signature found", reason);
+
+        let input = ";; resolution failed: timed out\n";
+        assert!(input.parse::<DelvOutput>().is_err());
+
+        Ok(())
+    }
 }
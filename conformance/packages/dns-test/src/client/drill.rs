@@ -0,0 +1,289 @@
+//! `drill`, from the ldns utilities
+
+use super::{DigOutput, Query, QueryTool, parse_query_time, records};
+use crate::{Result, Transport};
+
+/// What `dig` advertises when `+bufsize` is not used
+const DEFAULT_UDP_PAYLOAD_SIZE: u16 = 1232;
+
+pub(super) struct Drill;
+
+impl QueryTool for Drill {
+    fn command(&self, query: &Query) -> Result<Vec<String>> {
+        let Query {
+            settings,
+            tsig_key,
+            tls_ca_file: _,
+            server,
+            record_type,
+            fqdn,
+        } = query;
+
+        let unsupported = if tsig_key.is_some() {
+            Some("TSIG")
+        } else if settings.transport != Transport::Do53 {
+            Some("transports other than plain DNS (Do53)")
+        } else if settings.timeout.is_some() || settings.tries.is_some() {
+            Some("setting the timeout or the number of tries")
+        } else if settings.edns.is_some_and(|version| version != 0) {
+            Some("EDNS versions other than 0")
+        } else if settings.edns.is_none() && settings.dnssec {
+            Some("the DO bit without EDNS")
+        } else if settings.zflag || settings.opcode != 0 || settings.header_only {
+            Some("the Z bit, opcodes other than QUERY or header-only queries")
+        } else if settings.cookie_value.is_some() {
+            Some("DNS cookies")
+        } else if settings.extra_edns_option.is_some() || settings.extra_edns_flags.is_some() {
+            Some("extra EDNS options or flags")
+        } else if settings.nsid || settings.expire || settings.subnet.is_some() {
+            Some("the NSID, EXPIRE or CLIENT-SUBNET options")
+        } else {
+            None
+        };
+        if let Some(unsupported) = unsupported {
+            return Err(format!("the drill client does not support {unsupported}").into());
+        }
+
+        // a capital mnemonic sets the header bit; a lowercase one clears it
+        let mut command_and_args = vec![
+            "drill".to_string(),
+            "-o".into(),
+            if settings.recurse { "RD" } else { "rd" }.into(),
+            "-o".into(),
+            if settings.adflag { "AD" } else { "ad" }.into(),
+            "-o".into(),
+            if settings.cdflag { "CD" } else { "cd" }.into(),
+        ];
+        if settings.dnssec {
+            command_and_args.push("-D".into());
+        }
+        if settings.edns.is_some() {
+            let bufsize = settings.bufsize.unwrap_or(DEFAULT_UDP_PAYLOAD_SIZE);
+            command_and_args.extend(["-b".into(), bufsize.to_string()]);
+        }
+        if settings.tcp {
+            command_and_args.push("-t".into());
+        } else if !settings.ignore_truncation {
+            // retry truncated responses, like `dig`; over TCP once the payload size is 4096
+            command_and_args.push("-a".into());
+        }
+        if let Some(port) = settings.port {
+            command_and_args.extend(["-p".into(), port.to_string()]);
+        }
+
+        command_and_args.extend([
+            format!("@{server}"),
+            record_type.as_name().into_owned(),
+            fqdn.as_str().to_string(),
+        ]);
+
+        Ok(command_and_args)
+    }
+
+    /// `drill` does not print the EDNS options of the response, nor which protocol it arrived
+    /// over, so [`DigOutput::ede`], [`DigOutput::options`], [`DigOutput::cookie`],
+    /// [`DigOutput::client_subnet`] and [`DigOutput::protocol`] are always empty
+    fn parse(&self, input: &str) -> Result<DigOutput> {
+        const HEADER_PREFIX: &str = ";; ->>HEADER<<- opcode: ";
+        const RCODE_PREFIX: &str = "rcode: ";
+        const FLAGS_PREFIX: &str = ";; flags: ";
+        const EDNS_PREFIX: &str = ";; EDNS: version ";
+        const ANSWER_HEADER: &str = ";; ANSWER SECTION:";
+        const AUTHORITY_HEADER: &str = ";; AUTHORITY SECTION:";
+        const ADDITIONAL_HEADER: &str = ";; ADDITIONAL SECTION:";
+        const QUERY_TIME_PREFIX: &str = ";; Query time: ";
+        const SERVER_PREFIX: &str = ";; SERVER: ";
+
+        /// `drill` appends comments to some records, e.g. `;{id = 2151 (zsk), size = 2048b}` to
+        /// DNSKEY records
+        fn strip_comment(line: &str) -> &str {
+            line.split_once(";{")
+                .map_or(line, |(record, _comment)| record.trim_end())
+        }
+
+        let mut opcode = None;
+        let mut status = None;
+        let mut flags = None;
+        let mut opt = false;
+        let mut edns_version = None;
+        let mut dnssec_ok_flag = false;
+        let mut answer = vec![];
+        let mut authority = vec![];
+        let mut additional = vec![];
+        let mut query_time = None;
+        let mut server = None;
+
+        let mut lines = input.lines();
+        while let Some(line) = lines.next() {
+            if let Some(unprefixed) = line.strip_prefix(HEADER_PREFIX) {
+                // e.g. `QUERY, rcode: NOERROR, id: 4242`
+                let mut fields = unprefixed.split(", ");
+                opcode = fields.next().map(str::to_string);
+                status = fields
+                    .find_map(|field| field.strip_prefix(RCODE_PREFIX))
+                    .map(str::parse)
+                    .transpose()?;
+            } else if let Some(unprefixed) = line.strip_prefix(FLAGS_PREFIX) {
+                // e.g. `qr aa rd ; QUERY: 1, ANSWER: 1, AUTHORITY: 0, ADDITIONAL: 0`
+                let (flags_text, _rest) = unprefixed
+                    .split_once(';')
+                    .ok_or("`;; flags:` line is missing a semicolon (;)")?;
+                flags = Some(flags_text.parse()?);
+            } else if let Some(unprefixed) = line.strip_prefix(EDNS_PREFIX) {
+                // e.g. `0; flags: do ; udp: 1232`
+                opt = true;
+                let mut fields = unprefixed.split(';').map(str::trim);
+                edns_version = fields.next().map(str::parse).transpose()?;
+                dnssec_ok_flag = fields.any(|field| {
+                    field
+                        .strip_prefix("flags:")
+                        .is_some_and(|flags| flags.split_whitespace().any(|flag| flag == "do"))
+                });
+            } else if line.starts_with(ANSWER_HEADER) {
+                answer = records(lines.by_ref().map(strip_comment))?;
+            } else if line.starts_with(AUTHORITY_HEADER) {
+                authority = records(lines.by_ref().map(strip_comment))?;
+            } else if line.starts_with(ADDITIONAL_HEADER) {
+                additional = records(lines.by_ref().map(strip_comment))?;
+            } else if let Some(unprefixed) = line.strip_prefix(QUERY_TIME_PREFIX) {
+                // e.g. `0 msec`
+                query_time = parse_query_time(unprefixed);
+            } else if let Some(unprefixed) = line.strip_prefix(SERVER_PREFIX) {
+                // e.g. `172.18.0.2`
+                server = unprefixed.trim().parse().ok();
+            }
+        }
+
+        Ok(DigOutput {
+            ede: Default::default(),
+            flags: flags.ok_or("`;; flags:` line was not found")?,
+            status: status.ok_or("`rcode:` was not found")?,
+            answer,
+            authority,
+            additional,
+            opt,
+            options: vec![],
+            must_be_zero: false,
+            edns_must_be_zero: false,
+            opcode: opcode.ok_or("`;; ->>HEADER<<-` line was not found")?,
+            edns_version,
+            dnssec_ok_flag,
+            tsig_error: None,
+            client_subnet: None,
+            cookie: None,
+            protocol: None,
+            query_time,
+            server,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::time::Duration;
+
+    use super::*;
+    use crate::FQDN;
+    use crate::client::{DigFlags, DigSettings, DigStatus};
+    use crate::record::{Record, RecordType};
+
+    #[test]
+    fn command() -> Result<()> {
+        let settings = *DigSettings::default().recurse().dnssec().tcp();
+        let query = Query {
+            settings: &settings,
+            tsig_key: None,
+            tls_ca_file: None,
+            server: Ipv4Addr::new(172, 18, 0, 2).into(),
+            record_type: &RecordType::DNSKEY,
+            fqdn: &FQDN::ROOT,
+        };
+
+        assert_eq!(
+            [
+                "drill",
+                "-o",
+                "RD",
+                "-o",
+                "ad",
+                "-o",
+                "cd",
+                "-D",
+                "-b",
+                "1232",
+                "-t",
+                "@172.18.0.2",
+                "DNSKEY",
+                "."
+            ],
+            *Drill.command(&query)?
+        );
+
+        let settings = *DigSettings::default().nsid();
+        let query = Query {
+            settings: &settings,
+            ..query
+        };
+        let error = Drill.command(&query).unwrap_err();
+        assert!(error.to_string().contains("NSID"), "{error}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse() -> Result<()> {
+        // $ drill -D -o rd @172.18.0.2 DNSKEY .
+        let input = "
+;; ->>HEADER<<- opcode: QUERY, rcode: NOERROR, id: 21924
+;; flags: qr aa ; QUERY: 1, ANSWER: 1, AUTHORITY: 0, ADDITIONAL: 0
+;; QUESTION SECTION:
+;; .	IN	DNSKEY
+
+;; ANSWER SECTION:
+.	86400	IN	DNSKEY	256 3 8 AwEAAa5Ef0vCRdzk42lQBM/QCnBC2giWaMVv/KY0Ag5lnH3lfWEM70j0+WZ8LNtSppElzm2KtWHGgz+seqznzAT5kvUgGeRsfGTKWTsC2zZKjp4sqKR8SXTEdDffYJWfJFHzQKIa5Y8UuSYFwOtk3k8kAqXLBVlL5P6ZyOZ4XG8epKVB ;{id = 2151 (zsk), size = 1024b}
+
+;; AUTHORITY SECTION:
+
+;; ADDITIONAL SECTION:
+
+;; Query time: 0 msec
+;; EDNS: version 0; flags: do ; udp: 1232
+;; SERVER: 172.18.0.2
+;; WHEN: Tue Mar  4 10:00:00 2025
+;; MSG SIZE  rcvd: 171
+";
+
+        let output = Drill.parse(input)?;
+
+        assert_eq!(DigStatus::NOERROR, output.status);
+        assert_eq!("QUERY", output.opcode);
+        assert_eq!(
+            DigFlags {
+                qr: true,
+                authoritative_answer: true,
+                ..DigFlags::default()
+            },
+            output.flags
+        );
+        assert!(output.opt);
+        assert_eq!(Some(0), output.edns_version);
+        assert!(output.dnssec_ok_flag);
+        assert_eq!(Some(Duration::ZERO), output.query_time);
+        assert_eq!(
+            Some(IpAddr::from(Ipv4Addr::new(172, 18, 0, 2))),
+            output.server
+        );
+        assert!(output.authority.is_empty());
+        assert!(output.additional.is_empty());
+
+        let [record] = output.answer.try_into().unwrap();
+        let Record::DNSKEY(dnskey) = record else {
+            panic!("expected a DNSKEY record");
+        };
+        assert_eq!(256, dnskey.rdata.flags);
+        assert!(dnskey.rdata.public_key.ends_with("epKVB"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,318 @@
+//! `kdig`, from the Knot DNS utilities
+
+use std::collections::BTreeSet;
+
+use super::{DigFlags, DigOutput, Query, QueryTool, Subnet, parse_query_time, records};
+use crate::Result;
+use crate::container::Output;
+
+pub(super) struct Kdig;
+
+impl QueryTool for Kdig {
+    fn command(&self, query: &Query) -> Result<Vec<String>> {
+        let Query {
+            settings,
+            tsig_key,
+            tls_ca_file,
+            server,
+            record_type,
+            fqdn,
+        } = query;
+
+        if tsig_key.is_some() {
+            return Err("the kdig client does not support TSIG".into());
+        }
+        if settings.opcode != 0 {
+            return Err("the kdig client only sends QUERY messages".into());
+        }
+        if settings.header_only {
+            return Err("the kdig client does not support header-only queries".into());
+        }
+        if settings.extra_edns_flags.is_some() {
+            return Err("the kdig client does not support setting EDNS flags".into());
+        }
+        if settings.edns.is_some_and(|version| version != 0) && settings.ednsneg {
+            return Err("the kdig client does not negotiate the EDNS version".into());
+        }
+
+        let mut command_and_args = vec![
+            "kdig".to_string(),
+            // DNSKEY and RRSIG records are printed without their key or signature otherwise
+            "+crypto".into(),
+            settings.rdflag().into(),
+            settings.do_bit().into(),
+            settings.adflag().into(),
+            settings.cdflag().into(),
+            settings.timeoutflag(),
+            settings.ednsflag(),
+            settings.zflag().into(),
+            settings.tcpflag().into(),
+            settings.transportflag().into(),
+            settings.cookieflag(),
+            settings.badcookieflag().into(),
+            settings.ignoreflag().into(),
+            settings.nsidflag().into(),
+            settings.expireflag().into(),
+        ];
+
+        // `kdig` counts the retries, not the tries
+        command_and_args.extend(
+            settings
+                .tries
+                .map(|tries| format!("+retry={}", tries.saturating_sub(1))),
+        );
+        command_and_args.extend(settings.ednsoptionflag());
+        command_and_args.extend(settings.bufsizeflag());
+        command_and_args.extend(settings.subnet.map(|subnet| match subnet {
+            Subnet::Zero => "+subnet=0.0.0.0/0".to_string(),
+            Subnet::Prefix(address, source_prefix_length) => {
+                format!("+subnet={address}/{source_prefix_length}")
+            }
+        }));
+        if let Some(port) = settings.portflag() {
+            command_and_args.extend(["-p".into(), port]);
+        }
+        if let Some(path) = tls_ca_file {
+            command_and_args.push(format!("+tls-ca={path}"));
+        }
+
+        command_and_args.extend([
+            format!("@{server}"),
+            record_type.as_name().into_owned(),
+            fqdn.as_str().to_string(),
+        ]);
+
+        Ok(command_and_args)
+    }
+
+    /// Unlike `dig`, `kdig` prints neither the unknown EDNS options nor the must-be-zero EDNS
+    /// flags, so [`DigOutput::options`] is always empty and [`DigOutput::edns_must_be_zero`]
+    /// always false
+    fn parse(&self, input: &str) -> Result<DigOutput> {
+        const HEADER_PREFIX: &str = ";; ->>HEADER<<- opcode: ";
+        const STATUS_PREFIX: &str = "status: ";
+        const FLAGS_PREFIX: &str = ";; Flags: ";
+        const EDNS_HEADER: &str = ";; EDNS PSEUDOSECTION:";
+        const EDNS_PREFIX: &str = ";; Version: ";
+        const EDE_PREFIX: &str = ";; EDE: ";
+        const CLIENT_SUBNET_PREFIX: &str = ";; CLIENT-SUBNET: ";
+        const COOKIE_PREFIX: &str = ";; COOKIE: ";
+        const ANSWER_HEADER: &str = ";; ANSWER SECTION:";
+        const AUTHORITY_HEADER: &str = ";; AUTHORITY SECTION:";
+        const ADDITIONAL_HEADER: &str = ";; ADDITIONAL SECTION:";
+        const FROM_PREFIX: &str = ";; From ";
+
+        let mut opcode = None;
+        let mut status = None;
+        let mut flags = None;
+        let mut must_be_zero = false;
+        let mut opt = false;
+        let mut edns_version = None;
+        let mut dnssec_ok_flag = false;
+        let mut ede = BTreeSet::new();
+        let mut client_subnet = None;
+        let mut cookie = None;
+        let mut answer = vec![];
+        let mut authority = vec![];
+        let mut additional = vec![];
+        let mut protocol = None;
+        let mut query_time = None;
+        let mut server = None;
+
+        let mut lines = input.lines();
+        while let Some(line) = lines.next() {
+            if let Some(unprefixed) = line.strip_prefix(HEADER_PREFIX) {
+                // e.g. `QUERY; status: NOERROR; id: 4242`
+                let mut fields = unprefixed.split("; ");
+                opcode = fields.next().map(str::to_string);
+                status = fields
+                    .find_map(|field| field.strip_prefix(STATUS_PREFIX))
+                    .map(str::parse)
+                    .transpose()?;
+            } else if let Some(unprefixed) = line.strip_prefix(FLAGS_PREFIX) {
+                // e.g. `qr aa rd; QUERY: 1; ANSWER: 1; AUTHORITY: 0; ADDITIONAL: 1`
+                let (flags_text, _rest) = unprefixed
+                    .split_once(';')
+                    .ok_or("`;; Flags:` line is missing a semicolon (;)")?;
+
+                // `dig` reports the Z bit separately, as MBZ
+                let mut known_flags = String::new();
+                for flag in flags_text.split_whitespace() {
+                    if flag == "z" {
+                        must_be_zero = true;
+                    } else {
+                        known_flags.push_str(flag);
+                        known_flags.push(' ');
+                    }
+                }
+                flags = Some(known_flags.parse::<DigFlags>()?);
+            } else if line.starts_with(EDNS_HEADER) {
+                opt = true;
+            } else if let Some(unprefixed) = line.strip_prefix(EDNS_PREFIX) {
+                // e.g. `0; flags: do; UDP size: 1232 B; ext-rcode: NOERROR`
+                let mut fields = unprefixed.split(';').map(str::trim);
+                edns_version = fields.next().map(str::parse).transpose()?;
+                dnssec_ok_flag = fields.any(|field| {
+                    field
+                        .strip_prefix("flags:")
+                        .is_some_and(|flags| flags.split_whitespace().any(|flag| flag == "do"))
+                });
+            } else if let Some(unprefixed) = line.strip_prefix(EDE_PREFIX) {
+                // e.g. `6 (DNSSEC Bogus)`
+                let code = unprefixed.split_whitespace().next().unwrap_or_default();
+                ede.insert(code.parse()?);
+            } else if let Some(unprefixed) = line.strip_prefix(CLIENT_SUBNET_PREFIX) {
+                client_subnet = Some(unprefixed.parse()?);
+            } else if let Some(unprefixed) = line.strip_prefix(COOKIE_PREFIX) {
+                cookie = unprefixed.split_whitespace().next().map(str::to_string);
+            } else if line.starts_with(ANSWER_HEADER) {
+                answer = records(lines.by_ref())?;
+            } else if line.starts_with(AUTHORITY_HEADER) {
+                authority = records(lines.by_ref())?;
+            } else if line.starts_with(ADDITIONAL_HEADER) {
+                additional = records(lines.by_ref())?;
+            } else if let Some(unprefixed) = line.strip_prefix(FROM_PREFIX) {
+                // e.g. `172.18.0.3@53(UDP) in 0.4 ms`
+                let (from, time) = unprefixed.split_once(" in ").unwrap_or((unprefixed, ""));
+                protocol = from.find('(').and_then(|start| from[start..].parse().ok());
+                server = from
+                    .split_once('@')
+                    .and_then(|(address, _)| address.parse().ok());
+                query_time = parse_query_time(time);
+            }
+        }
+
+        Ok(DigOutput {
+            ede,
+            flags: flags.ok_or("`;; Flags:` line was not found")?,
+            status: status.ok_or("`status:` was not found")?,
+            answer,
+            authority,
+            additional,
+            opt,
+            options: vec![],
+            must_be_zero,
+            edns_must_be_zero: false,
+            opcode: opcode.ok_or("`;; ->>HEADER<<-` line was not found")?,
+            edns_version,
+            dnssec_ok_flag,
+            tsig_error: None,
+            client_subnet,
+            cookie,
+            protocol,
+            query_time,
+            server,
+        })
+    }
+    /// `kdig` warns about each try that timed out, e.g. `;; WARNING: response timeout for
+    /// 172.18.0.2@53(UDP)`, before it gives up
+    fn timed_out(&self, output: &Output) -> bool {
+        output.stderr.contains("timeout for")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::time::Duration;
+
+    use super::*;
+    use crate::FQDN;
+    use crate::client::{DigSettings, DigStatus, ExtendedDnsError, Protocol};
+    use crate::record::RecordType;
+
+    #[test]
+    fn command() -> Result<()> {
+        let settings = *DigSettings::default().recurse().subnet_zero();
+        let query = Query {
+            settings: &settings,
+            tsig_key: None,
+            tls_ca_file: None,
+            server: Ipv4Addr::new(172, 18, 0, 2).into(),
+            record_type: &RecordType::A,
+            fqdn: &FQDN::TEST_DOMAIN,
+        };
+
+        let command = Kdig.command(&query)?;
+        assert_eq!("kdig", command[0]);
+        assert!(command.contains(&"+recurse".to_string()));
+        assert!(command.contains(&"+subnet=0.0.0.0/0".to_string()));
+        assert_eq!(
+            ["@172.18.0.2", "A", "hickory-dns.testing."],
+            command[command.len() - 3..]
+        );
+
+        let settings = *DigSettings::default().tries(1);
+        let command = Kdig.command(&Query {
+            settings: &settings,
+            ..query
+        })?;
+        assert!(command.contains(&"+retry=0".to_string()));
+
+        let settings = *DigSettings::default().opcode(2);
+        let query = Query {
+            settings: &settings,
+            ..query
+        };
+        assert!(Kdig.command(&query).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse() -> Result<()> {
+        // $ kdig +dnssec +cookie @172.18.0.3 A example.hickory-dns.testing.
+        let input = "
+;; ->>HEADER<<- opcode: QUERY; status: NOERROR; id: 40291
+;; Flags: qr rd ra; QUERY: 1; ANSWER: 1; AUTHORITY: 0; ADDITIONAL: 1
+
+;; EDNS PSEUDOSECTION:
+;; Version: 0; flags: do; UDP size: 1232 B; ext-rcode: NOERROR
+;; COOKIE: 0102030405060708010000006734b7e2a4d1c8d2b8ba93e2
+;; EDE: 6 (DNSSEC Bogus)
+
+;; QUESTION SECTION:
+;; example.hickory-dns.testing.	IN	A
+
+;; ANSWER SECTION:
+example.hickory-dns.testing.	86400	IN	A	1.2.3.4
+
+;; Received 103 B
+;; Time 2025-03-04 10:00:00 UTC
+;; From 172.18.0.3@53(UDP) in 0.4 ms
+";
+
+        let output = Kdig.parse(input)?;
+
+        assert_eq!(DigStatus::NOERROR, output.status);
+        assert_eq!("QUERY", output.opcode);
+        assert_eq!(
+            DigFlags {
+                qr: true,
+                recursion_desired: true,
+                recursion_available: true,
+                ..DigFlags::default()
+            },
+            output.flags
+        );
+        assert!(output.opt);
+        assert_eq!(Some(0), output.edns_version);
+        assert!(output.dnssec_ok_flag);
+        assert_eq!(
+            Some("0102030405060708010000006734b7e2a4d1c8d2b8ba93e2"),
+            output.cookie.as_deref()
+        );
+        assert_eq!(BTreeSet::from([ExtendedDnsError::DnssecBogus]), output.ede);
+        assert_eq!(Some(Protocol::Udp), output.protocol);
+        assert_eq!(Some(Duration::from_micros(400)), output.query_time);
+        assert_eq!(
+            Some(IpAddr::from(Ipv4Addr::new(172, 18, 0, 3))),
+            output.server
+        );
+
+        let [a] = output.answer.try_into().unwrap();
+        assert_eq!(Ipv4Addr::new(1, 2, 3, 4), a.try_into_a().unwrap().ipv4_addr);
+
+        Ok(())
+    }
+}
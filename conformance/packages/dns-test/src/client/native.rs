@@ -0,0 +1,1372 @@
+//! A client that sends queries from the host, encoding and decoding DNS messages itself
+
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, BTreeSet};
+use std::hash::BuildHasher;
+use std::io::{self, ErrorKind, Read as _, Write as _};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream, UdpSocket};
+use std::sync::atomic::{self, AtomicU16};
+use std::time::{Duration, Instant};
+
+use base64::prelude::*;
+
+use super::{
+    ClientSubnet, DigFlags, DigOutput, DigSettings, DigStatus, DnsClient, Outcome, Protocol, Subnet,
+};
+use crate::record::{
+    A, AAAA, CAA, CDNSKEY, CDS, CNAME, DNSKEY, DNSKEYRData, DS, HTTPS, NS, NSEC, NSEC3, NSEC3PARAM,
+    PTR, RRSIG, Record, RecordType, SOA, SRV, SVCB, SoaSettings, SvcParamKey, SvcParamValue, TLSA,
+    TXT, UnknownRdata, ZONEMD,
+};
+use crate::{Error, FQDN, Result, Transport, TsigError};
+
+/// What `dig` advertises when `+bufsize` is not used
+const DEFAULT_UDP_PAYLOAD_SIZE: u16 = 1232;
+/// What `dig` waits for when `+timeout` is not used
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+/// What `dig` tries when `+tries` is not used
+const DEFAULT_TRIES: u8 = 3;
+/// Sent in the COOKIE option of every query, unless `DigSettings::nocookie` or
+/// `DigSettings::cookie` is used
+const CLIENT_COOKIE: [u8; 8] = *b"dns-test";
+
+const CLASS_IN: u16 = 1;
+const TYPE_OPT: u16 = 41;
+const TYPE_TSIG: u16 = 250;
+
+const OPTION_NSID: u16 = 3;
+const OPTION_CLIENT_SUBNET: u16 = 8;
+const OPTION_EXPIRE: u16 = 9;
+const OPTION_COOKIE: u16 = 10;
+const OPTION_EDE: u16 = 15;
+/// Options `dig` prints under their own name, rather than as `; OPT=`, and that are therefore
+/// left out of [`DigOutput::options`]
+const NAMED_OPTIONS: [u16; 15] = [3, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18];
+
+/// Sends queries straight from the host to the address of a container
+///
+/// Unlike [`super::Client`], which runs `dig` in a container and parses its text output, this
+/// client decodes the response itself and can report what `dig` hides, like the message ID, the
+/// extended RCODE or the RDATA of record types this crate does not know. It is backed by its own
+/// encoder and decoder, rather than by the hickory-dns libraries, so that it can be used to
+/// cross-check the implementation under test
+///
+/// Only plain DNS over UDP and TCP is supported; DNS over TLS and DNS over HTTPS are not
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NativeClient;
+
+impl NativeClient {
+    /// Sends a query for `fqdn` and `record_type` to `server`, configured with the same `settings`
+    /// `dig` takes, and returns the response as received
+    ///
+    /// Like `dig`, the query is retried over TCP when the UDP response is truncated, unless
+    /// [`DigSettings::ignore`] is set. EDNS version negotiation is not performed. A query that
+    /// times out is an error
+    pub fn query_raw(
+        &self,
+        settings: DigSettings,
+        server: impl Into<IpAddr>,
+        record_type: RecordType,
+        fqdn: &FQDN,
+    ) -> Result<Message> {
+        let server = server.into();
+        self.exchange(settings, server, record_type, fqdn)?
+            .ok_or_else(|| format!("no response from {server}: the query timed out").into())
+    }
+
+    /// Unlike [`Self::query_raw`], returns `None` when the query times out
+    fn exchange(
+        &self,
+        settings: DigSettings,
+        server: IpAddr,
+        record_type: RecordType,
+        fqdn: &FQDN,
+    ) -> Result<Option<Message>> {
+        if settings.transport != Transport::Do53 {
+            return Err(format!(
+                "the native client does not support {:?}; only plain DNS (Do53)",
+                settings.transport
+            )
+            .into());
+        }
+
+        let port = settings.port.unwrap_or(Transport::Do53.port());
+        let server = SocketAddr::new(server, port);
+        let timeout = settings.timeout.unwrap_or(DEFAULT_TIMEOUT);
+        let id = query_id();
+        let query = encode_query(&settings, id, &record_type, fqdn);
+
+        if !settings.tcp {
+            let tries = settings.tries.unwrap_or(DEFAULT_TRIES);
+            let Some(response) = send_udp(server, &query, id, timeout, tries)? else {
+                return Ok(None);
+            };
+            let message = Message::decode(&response, Protocol::Udp)?;
+            if !message.flags.truncation || settings.ignore_truncation {
+                return Ok(Some(message));
+            }
+        }
+
+        let Some(response) = send_tcp(server, &query, timeout)? else {
+            return Ok(None);
+        };
+        let message = Message::decode(&response, Protocol::Tcp)?;
+        if message.id != id {
+            return Err(format!("response ID {} does not match query ID {id}", message.id).into());
+        }
+
+        Ok(Some(message))
+    }
+
+    /// Sends `count` identical queries to `server` back to back, from a single UDP socket, and
+    /// returns, in the order the queries were sent, the responses that arrived
+    ///
+    /// A query that is left unanswered for the timeout of `settings`, after the last one was
+    /// sent, gets `None`. Unlike [`Self::query_raw`], truncated responses are never retried over
+    /// TCP
+    pub fn query_burst(
+        &self,
+        settings: DigSettings,
+        server: impl Into<IpAddr>,
+        record_type: RecordType,
+        fqdn: &FQDN,
+        count: usize,
+    ) -> Result<Vec<Option<Message>>> {
+        if settings.transport != Transport::Do53 || settings.tcp {
+            return Err("the native client only sends bursts of queries over UDP".into());
+        }
+
+        let port = settings.port.unwrap_or(Transport::Do53.port());
+        let server = SocketAddr::new(server.into(), port);
+        let timeout = settings.timeout.unwrap_or(DEFAULT_TIMEOUT);
+
+        let mut ids = Vec::with_capacity(count);
+        while ids.len() < count {
+            let id = query_id();
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        let queries = ids
+            .iter()
+            .map(|id| encode_query(&settings, *id, &record_type, fqdn))
+            .collect::<Vec<_>>();
+
+        send_udp_burst(server, &queries, &ids, timeout)?
+            .into_iter()
+            .map(|response| {
+                response
+                    .map(|response| Message::decode(&response, Protocol::Udp))
+                    .transpose()
+            })
+            .collect()
+    }
+}
+
+impl DnsClient for NativeClient {
+    fn query_outcome(
+        &self,
+        settings: DigSettings,
+        server: IpAddr,
+        record_type: RecordType,
+        fqdn: &FQDN,
+    ) -> Result<Outcome> {
+        let start = Instant::now();
+        let Some(message) = self.exchange(settings, server, record_type, fqdn)? else {
+            return Ok(Outcome::Timeout);
+        };
+        let query_time = start.elapsed();
+
+        let mut output = DigOutput::try_from(message)?;
+        output.query_time = Some(query_time);
+        output.server = Some(server);
+        Ok(Outcome::Answered(output))
+    }
+}
+
+/// A DNS message, as decoded by the [`NativeClient`]
+#[derive(Debug)]
+pub struct Message {
+    pub id: u16,
+    /// e.g. 0 for QUERY
+    pub opcode: u8,
+    pub flags: DigFlags,
+    /// The Z bit of the header, which must be zero
+    pub z_flag: bool,
+    /// The extended RCODE: the lower 4 bits come from the header and the upper 8 bits from the
+    /// OPT record, if any
+    pub rcode: u16,
+    pub question: Vec<(FQDN, RecordType)>,
+    /// Records of types that [`Record`] does not model, e.g. MX, are returned as
+    /// [`Record::Unknown`] with their RDATA as sent, i.e. names in it may be compressed
+    pub answer: Vec<Record>,
+    pub authority: Vec<Record>,
+    /// Without the OPT record, which is decoded into [`Self::opt`]
+    pub additional: Vec<Record>,
+    pub opt: Option<Opt>,
+    /// Protocol the message arrived over
+    pub protocol: Protocol,
+}
+
+/// The OPT pseudo-record of a message (RFC6891)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Opt {
+    pub udp_payload_size: u16,
+    pub version: u8,
+    pub dnssec_ok: bool,
+    /// The flags other than DO, which must be zero
+    pub must_be_zero: u16,
+    /// The code and data of each option, in the order they were sent
+    pub options: Vec<(u16, Vec<u8>)>,
+}
+
+impl Opt {
+    /// The data of the first option with `code`
+    pub fn option(&self, code: u16) -> Option<&[u8]> {
+        self.options
+            .iter()
+            .find_map(|(option_code, data)| (*option_code == code).then_some(data.as_slice()))
+    }
+}
+
+impl Message {
+    /// Decodes a message received over `protocol`
+    pub fn decode(message: &[u8], protocol: Protocol) -> Result<Self> {
+        let mut reader = Reader::new(message);
+
+        let id = reader.u16()?;
+        let flags = reader.u16()?;
+        let counts: [u16; 4] = [reader.u16()?, reader.u16()?, reader.u16()?, reader.u16()?];
+        let [
+            question_count,
+            answer_count,
+            authority_count,
+            additional_count,
+        ] = counts;
+
+        let mut question = vec![];
+        for _ in 0..question_count {
+            let fqdn = reader.name()?;
+            let record_type = RecordType::from(reader.u16()?);
+            let _class = reader.u16()?;
+            question.push((fqdn, record_type));
+        }
+
+        let mut sections = [vec![], vec![], vec![]];
+        let mut opt = None;
+        for (section, count) in
+            sections
+                .iter_mut()
+                .zip([answer_count, authority_count, additional_count])
+        {
+            for _ in 0..count {
+                match reader.record()? {
+                    Decoded::Record(record) => section.push(record),
+                    Decoded::Opt(decoded) => {
+                        if opt.replace(decoded).is_some() {
+                            return Err("message has more than one OPT record".into());
+                        }
+                    }
+                }
+            }
+        }
+
+        if reader.position != message.len() {
+            return Err(format!(
+                "{} trailing bytes after the last record",
+                message.len() - reader.position
+            )
+            .into());
+        }
+
+        let [answer, authority, additional] = sections;
+        let extended_rcode = opt.as_ref().map_or(0, |opt| opt.extended_rcode);
+        Ok(Self {
+            id,
+            opcode: ((flags >> 11) & 0xf) as u8,
+            flags: DigFlags {
+                qr: flags & 0x8000 != 0,
+                authoritative_answer: flags & 0x0400 != 0,
+                truncation: flags & 0x0200 != 0,
+                recursion_desired: flags & 0x0100 != 0,
+                recursion_available: flags & 0x0080 != 0,
+                authenticated_data: flags & 0x0020 != 0,
+                checking_disabled: flags & 0x0010 != 0,
+            },
+            z_flag: flags & 0x0040 != 0,
+            rcode: (u16::from(extended_rcode) << 4) | (flags & 0xf),
+            question,
+            answer,
+            authority,
+            additional,
+            opt: opt.map(|opt| opt.opt),
+            protocol,
+        })
+    }
+}
+
+impl TryFrom<Message> for DigOutput {
+    type Error = Error;
+
+    /// Reports `message` the way `dig` does
+    fn try_from(message: Message) -> Result<Self> {
+        let status = match message.rcode {
+            0 => DigStatus::NOERROR,
+            2 => DigStatus::SERVFAIL,
+            3 => DigStatus::NXDOMAIN,
+            4 => DigStatus::NOTIMP,
+            5 => DigStatus::REFUSED,
+            9 => DigStatus::NOTAUTH,
+            16 => DigStatus::BADVERS,
+            23 => DigStatus::BADCOOKIE,
+            rcode => return Err(format!("unknown status: {rcode}").into()),
+        };
+
+        let opcode = match message.opcode {
+            0 => "QUERY".to_string(),
+            1 => "IQUERY".to_string(),
+            2 => "STATUS".to_string(),
+            4 => "NOTIFY".to_string(),
+            5 => "UPDATE".to_string(),
+            opcode => format!("RESERVED{opcode}"),
+        };
+
+        let mut ede = BTreeSet::new();
+        let mut options = vec![];
+        let mut client_subnet = None;
+        let mut cookie = None;
+        if let Some(opt) = &message.opt {
+            for (code, data) in &opt.options {
+                match *code {
+                    OPTION_EDE => {
+                        let info_code = data.get(..2).ok_or("EDE option is too short")?;
+                        let info_code = u16::from_be_bytes([info_code[0], info_code[1]]);
+                        ede.insert(info_code.to_string().parse()?);
+                    }
+                    OPTION_CLIENT_SUBNET => client_subnet = Some(decode_client_subnet(data)?),
+                    OPTION_COOKIE => cookie = Some(hex::encode(data)),
+                    code if NAMED_OPTIONS.contains(&code) => {}
+                    code => options.push((code, hex::encode_upper(data))),
+                }
+            }
+        }
+
+        let tsig_error = message.additional.iter().find_map(|record| match record {
+            Record::Unknown(UnknownRdata { r#type, rdata, .. }) if *r#type == TYPE_TSIG => {
+                tsig_error(rdata)
+            }
+            _ => None,
+        });
+
+        let additional = message
+            .additional
+            .into_iter()
+            .filter(
+                |record| !matches!(record, Record::Unknown(unknown) if unknown.r#type == TYPE_TSIG),
+            )
+            .collect();
+
+        Ok(Self {
+            ede,
+            flags: message.flags,
+            status,
+            answer: message.answer,
+            authority: message.authority,
+            additional,
+            opt: message.opt.is_some(),
+            options,
+            must_be_zero: message.z_flag,
+            edns_must_be_zero: message
+                .opt
+                .as_ref()
+                .is_some_and(|opt| opt.must_be_zero != 0),
+            opcode,
+            edns_version: message.opt.as_ref().map(|opt| opt.version),
+            dnssec_ok_flag: message.opt.as_ref().is_some_and(|opt| opt.dnssec_ok),
+            tsig_error,
+            client_subnet,
+            cookie,
+            protocol: Some(message.protocol),
+            // filled in by `NativeClient::query`, which sent the query
+            query_time: None,
+            server: None,
+        })
+    }
+}
+
+/// IDs of consecutive queries differ, even across tests that run in parallel
+fn query_id() -> u16 {
+    static COUNT: AtomicU16 = AtomicU16::new(0);
+
+    let count = COUNT.fetch_add(1, atomic::Ordering::Relaxed);
+    RandomState::new().hash_one(count) as u16
+}
+
+fn encode_query(settings: &DigSettings, id: u16, record_type: &RecordType, fqdn: &FQDN) -> Vec<u8> {
+    let mut flags = u16::from(settings.opcode & 0xf) << 11;
+    if settings.recurse {
+        flags |= 0x0100;
+    }
+    if settings.zflag {
+        flags |= 0x0040;
+    }
+    if settings.adflag {
+        flags |= 0x0020;
+    }
+    if settings.cdflag {
+        flags |= 0x0010;
+    }
+
+    let question_count = u16::from(!settings.header_only);
+    let additional_count = u16::from(settings.edns.is_some());
+
+    let mut query = vec![];
+    for field in [id, flags, question_count, 0, 0, additional_count] {
+        query.extend_from_slice(&field.to_be_bytes());
+    }
+
+    if !settings.header_only {
+        encode_name(&mut query, fqdn);
+        query.extend_from_slice(&record_type.code().to_be_bytes());
+        query.extend_from_slice(&CLASS_IN.to_be_bytes());
+    }
+
+    if let Some(version) = settings.edns {
+        let mut options = vec![];
+        let mut push_option = |code: u16, data: &[u8]| {
+            options.extend_from_slice(&code.to_be_bytes());
+            options.extend_from_slice(&(data.len() as u16).to_be_bytes());
+            options.extend_from_slice(data);
+        };
+
+        if settings.nsid {
+            push_option(OPTION_NSID, &[]);
+        }
+        if let Some(subnet) = settings.subnet {
+            let (address, source_prefix_length) = match subnet {
+                Subnet::Zero => (IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0),
+                Subnet::Prefix(address, source_prefix_length) => (address, source_prefix_length),
+            };
+            let (family, octets) = match address {
+                IpAddr::V4(address) => (1u16, address.octets().to_vec()),
+                IpAddr::V6(address) => (2u16, address.octets().to_vec()),
+            };
+            // only the octets that hold the prefix are sent
+            let significant = usize::from(source_prefix_length).div_ceil(8);
+
+            let mut data = family.to_be_bytes().to_vec();
+            data.extend_from_slice(&[source_prefix_length, 0]);
+            data.extend_from_slice(&octets[..significant.min(octets.len())]);
+            push_option(OPTION_CLIENT_SUBNET, &data);
+        }
+        if settings.expire {
+            push_option(OPTION_EXPIRE, &[]);
+        }
+        if settings.cookie {
+            match &settings.cookie_value {
+                Some(cookie) => push_option(OPTION_COOKIE, cookie.as_bytes()),
+                None => push_option(OPTION_COOKIE, &CLIENT_COOKIE),
+            }
+        }
+        if let Some(code) = settings.extra_edns_option {
+            push_option(code, &[]);
+        }
+
+        let mut edns_flags = settings.extra_edns_flags.unwrap_or(0);
+        if settings.dnssec {
+            edns_flags |= 0x8000;
+        }
+
+        query.push(0); // root name
+        query.extend_from_slice(&TYPE_OPT.to_be_bytes());
+        query.extend_from_slice(
+            &settings
+                .bufsize
+                .unwrap_or(DEFAULT_UDP_PAYLOAD_SIZE)
+                .to_be_bytes(),
+        );
+        query.extend_from_slice(&[0, version]); // extended RCODE and version
+        query.extend_from_slice(&edns_flags.to_be_bytes());
+        query.extend_from_slice(&(options.len() as u16).to_be_bytes());
+        query.extend_from_slice(&options);
+    }
+
+    query
+}
+
+fn encode_name(buffer: &mut Vec<u8>, fqdn: &FQDN) {
+    for label in fqdn.as_str().split('.').filter(|label| !label.is_empty()) {
+        buffer.push(label.len() as u8);
+        buffer.extend_from_slice(label.as_bytes());
+    }
+    buffer.push(0);
+}
+
+/// Sends `query` up to `tries` times; `None` if none of them was answered within `timeout`
+fn send_udp(
+    server: SocketAddr,
+    query: &[u8],
+    id: u16,
+    timeout: Duration,
+    tries: u8,
+) -> Result<Option<Vec<u8>>> {
+    let local = match server {
+        SocketAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+        SocketAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+    };
+    let socket = UdpSocket::bind(local)?;
+    socket.set_read_timeout(Some(timeout))?;
+    socket.connect(server)?;
+
+    let mut buffer = vec![0; usize::from(u16::MAX)];
+    for _ in 0..tries {
+        socket.send(query)?;
+
+        loop {
+            let len = match socket.recv(&mut buffer) {
+                Ok(len) => len,
+                Err(e) if is_timeout(&e) => break,
+                Err(e) => return Err(format!("no response from {server} over UDP: {e}").into()),
+            };
+
+            // ignore stray responses to earlier queries
+            if buffer[..len].starts_with(&id.to_be_bytes()) {
+                buffer.truncate(len);
+                return Ok(Some(buffer));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+fn is_timeout(error: &io::Error) -> bool {
+    matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+}
+
+fn send_udp_burst(
+    server: SocketAddr,
+    queries: &[Vec<u8>],
+    ids: &[u16],
+    timeout: Duration,
+) -> Result<Vec<Option<Vec<u8>>>> {
+    let local = match server {
+        SocketAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+        SocketAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+    };
+    let socket = UdpSocket::bind(local)?;
+    socket.connect(server)?;
+    for query in queries {
+        socket.send(query)?;
+    }
+
+    let mut responses = vec![None; queries.len()];
+    let mut unanswered = queries.len();
+    let deadline = Instant::now() + timeout;
+    let mut buffer = vec![0; usize::from(u16::MAX)];
+    while unanswered > 0 {
+        let Some(remaining) = deadline.checked_duration_since(Instant::now()) else {
+            break;
+        };
+        if remaining.is_zero() {
+            break;
+        }
+        socket.set_read_timeout(Some(remaining))?;
+
+        let len = match socket.recv(&mut buffer) {
+            Ok(len) => len,
+            Err(e) if is_timeout(&e) => break,
+            Err(e) => return Err(format!("could not receive from {server} over UDP: {e}").into()),
+        };
+
+        let Some(id) = buffer[..len]
+            .first_chunk()
+            .map(|id| u16::from_be_bytes(*id))
+        else {
+            continue;
+        };
+        // ignore stray responses and duplicates
+        if let Some(index) = ids.iter().position(|query_id| *query_id == id) {
+            if responses[index].is_none() {
+                responses[index] = Some(buffer[..len].to_vec());
+                unanswered -= 1;
+            }
+        }
+    }
+
+    Ok(responses)
+}
+
+/// `None` if the connection could not be established, or the response did not arrive, within
+/// `timeout`
+fn send_tcp(server: SocketAddr, query: &[u8], timeout: Duration) -> Result<Option<Vec<u8>>> {
+    let mut stream = match TcpStream::connect_timeout(&server, timeout) {
+        Ok(stream) => stream,
+        Err(e) if is_timeout(&e) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    stream.set_read_timeout(Some(timeout))?;
+
+    let mut framed = (query.len() as u16).to_be_bytes().to_vec();
+    framed.extend_from_slice(query);
+    stream.write_all(&framed)?;
+
+    let mut len = [0; 2];
+    match stream.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(e) if is_timeout(&e) => return Ok(None),
+        Err(e) => return Err(format!("no response from {server} over TCP: {e}").into()),
+    }
+    let mut response = vec![0; usize::from(u16::from_be_bytes(len))];
+    stream.read_exact(&mut response)?;
+
+    Ok(Some(response))
+}
+
+/// Decodes the data of a CLIENT-SUBNET option (RFC7871)
+fn decode_client_subnet(data: &[u8]) -> Result<ClientSubnet> {
+    let [
+        family_hi,
+        family_lo,
+        source_prefix_length,
+        scope_prefix_length,
+        address @ ..,
+    ] = data
+    else {
+        return Err("CLIENT-SUBNET option is too short".into());
+    };
+
+    let address = match u16::from_be_bytes([*family_hi, *family_lo]) {
+        1 => {
+            let mut octets = [0; 4];
+            octets
+                .get_mut(..address.len())
+                .ok_or("CLIENT-SUBNET address is too long")?
+                .copy_from_slice(address);
+            IpAddr::from(octets)
+        }
+        2 => {
+            let mut octets = [0; 16];
+            octets
+                .get_mut(..address.len())
+                .ok_or("CLIENT-SUBNET address is too long")?
+                .copy_from_slice(address);
+            IpAddr::from(octets)
+        }
+        family => return Err(format!("unknown CLIENT-SUBNET family: {family}").into()),
+    };
+
+    Ok(ClientSubnet {
+        address,
+        source_prefix_length: *source_prefix_length,
+        scope_prefix_length: *scope_prefix_length,
+    })
+}
+
+/// Reads the error field of the RDATA of a TSIG record (RFC8945)
+fn tsig_error(rdata: &[u8]) -> Option<TsigError> {
+    let mut reader = Reader::new(rdata);
+    reader.name().ok()?; // algorithm
+    reader.bytes(6 + 2).ok()?; // time signed and fudge
+    let mac_size = reader.u16().ok()?;
+    reader.bytes(usize::from(mac_size)).ok()?;
+    reader.u16().ok()?; // original ID
+
+    match reader.u16().ok()? {
+        16 => Some(TsigError::BADSIG),
+        17 => Some(TsigError::BADKEY),
+        18 => Some(TsigError::BADTIME),
+        22 => Some(TsigError::BADTRUNC),
+        _ => None,
+    }
+}
+
+enum Decoded {
+    Record(Record),
+    Opt(DecodedOpt),
+}
+
+struct DecodedOpt {
+    extended_rcode: u8,
+    opt: Opt,
+}
+
+struct Reader<'a> {
+    message: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(message: &'a [u8]) -> Self {
+        Self {
+            message,
+            position: 0,
+        }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .message
+            .get(self.position..self.position + len)
+            .ok_or("message is truncated")?;
+        self.position += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Reads a name, following compression pointers
+    fn name(&mut self) -> Result<FQDN> {
+        let mut name = String::new();
+        let mut position = self.position;
+        let mut end = None;
+
+        // each pointer must point backwards, so this bounds the number of jumps
+        let mut jumps = 0;
+        loop {
+            let len = *self.message.get(position).ok_or("message is truncated")?;
+            match len {
+                0 => {
+                    position += 1;
+                    break;
+                }
+                len if len & 0xc0 == 0xc0 => {
+                    let low = *self
+                        .message
+                        .get(position + 1)
+                        .ok_or("message is truncated")?;
+                    let target = usize::from(u16::from_be_bytes([len & 0x3f, low]));
+                    if target >= position || jumps > self.message.len() {
+                        return Err("invalid compression pointer".into());
+                    }
+                    end.get_or_insert(position + 2);
+                    position = target;
+                    jumps += 1;
+                }
+                len if len & 0xc0 == 0 => {
+                    let start = position + 1;
+                    let label = self
+                        .message
+                        .get(start..start + usize::from(len))
+                        .ok_or("message is truncated")?;
+                    name.push_str(&String::from_utf8_lossy(label));
+                    name.push('.');
+                    position = start + usize::from(len);
+                }
+                _ => return Err(format!("unsupported label type: {len:#x}").into()),
+            }
+        }
+
+        self.position = end.unwrap_or(position);
+        if name.is_empty() {
+            Ok(FQDN::ROOT)
+        } else {
+            name.parse()
+        }
+    }
+
+    /// Reads a string preceded by its length
+    fn character_string(&mut self) -> Result<String> {
+        let len = self.u8()?;
+        let bytes = self.bytes(usize::from(len))?;
+        Ok(String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    fn record(&mut self) -> Result<Decoded> {
+        let fqdn = self.name()?;
+        let r#type = self.u16()?;
+        let class = self.u16()?;
+        let ttl = self.u32()?;
+        let rdata_len = usize::from(self.u16()?);
+        let end = self.position + rdata_len;
+        if end > self.message.len() {
+            return Err("message is truncated".into());
+        }
+
+        if r#type == TYPE_OPT {
+            let mut options = vec![];
+            while self.position < end {
+                let code = self.u16()?;
+                let len = self.u16()?;
+                options.push((code, self.bytes(usize::from(len))?.to_vec()));
+            }
+
+            let [extended_rcode, version, flags @ ..] = ttl.to_be_bytes();
+            let flags = u16::from_be_bytes(flags);
+            return Ok(Decoded::Opt(DecodedOpt {
+                extended_rcode,
+                opt: Opt {
+                    udp_payload_size: class,
+                    version,
+                    dnssec_ok: flags & 0x8000 != 0,
+                    must_be_zero: flags & 0x7fff,
+                    options,
+                },
+            }));
+        }
+
+        let record_type = RecordType::from(r#type);
+        if class != CLASS_IN && r#type != TYPE_TSIG {
+            return Err(
+                format!("{fqdn} {record_type} record has unsupported class {class}").into(),
+            );
+        }
+
+        let record = self.rdata(fqdn, ttl, record_type.clone(), end)?;
+        if self.position != end {
+            return Err(format!("RDATA of {record_type} record has an inconsistent length").into());
+        }
+
+        Ok(Decoded::Record(record))
+    }
+
+    fn rdata(
+        &mut self,
+        fqdn: FQDN,
+        ttl: u32,
+        record_type: RecordType,
+        end: usize,
+    ) -> Result<Record> {
+        let record = match record_type {
+            RecordType::A => Record::A(A {
+                fqdn,
+                ttl,
+                ipv4_addr: Ipv4Addr::from(self.u32()?),
+            }),
+            RecordType::AAAA => Record::AAAA(AAAA {
+                fqdn,
+                ttl,
+                ipv6_addr: Ipv6Addr::from(<[u8; 16]>::try_from(self.bytes(16)?).unwrap()),
+            }),
+            RecordType::CAA => {
+                let flags = self.u8()?;
+                let tag = self.character_string()?;
+                let value = self.bytes(end - self.position)?;
+                Record::CAA(CAA {
+                    zone: fqdn,
+                    ttl,
+                    flags,
+                    tag,
+                    value: String::from_utf8_lossy(value).into_owned(),
+                })
+            }
+            RecordType::CDNSKEY => Record::CDNSKEY(CDNSKEY {
+                zone: fqdn,
+                ttl,
+                rdata: self.dnskey(end)?,
+            }),
+            RecordType::CDS => {
+                let DS {
+                    zone,
+                    ttl,
+                    key_tag,
+                    algorithm,
+                    digest_type,
+                    digest,
+                } = self.ds(fqdn, ttl, end)?;
+                Record::CDS(CDS {
+                    zone,
+                    ttl,
+                    key_tag,
+                    algorithm,
+                    digest_type,
+                    digest,
+                })
+            }
+            RecordType::CNAME => Record::CNAME(CNAME {
+                fqdn,
+                ttl,
+                target: self.name()?,
+            }),
+            RecordType::DNSKEY => Record::DNSKEY(DNSKEY {
+                zone: fqdn,
+                ttl,
+                rdata: self.dnskey(end)?,
+            }),
+            RecordType::DS => Record::DS(self.ds(fqdn, ttl, end)?),
+            RecordType::HTTPS => {
+                let SVCB {
+                    zone,
+                    ttl,
+                    priority,
+                    target,
+                    params,
+                } = self.svcb(fqdn, ttl, end)?;
+                Record::HTTPS(HTTPS {
+                    zone,
+                    ttl,
+                    priority,
+                    target,
+                    params,
+                })
+            }
+            RecordType::NS => Record::NS(NS {
+                zone: fqdn,
+                ttl,
+                nameserver: self.name()?,
+            }),
+            RecordType::NSEC => Record::NSEC(NSEC {
+                fqdn,
+                ttl,
+                next_domain: self.name()?,
+                record_types: self.type_bitmaps(end)?,
+            }),
+            RecordType::NSEC3 => {
+                let hash_alg = self.u8()?;
+                let flags = self.u8()?;
+                let iterations = self.u16()?;
+                let salt_len = self.u8()?;
+                let salt = self.bytes(usize::from(salt_len))?;
+                let hash_len = self.u8()?;
+                let next_hashed_owner_name = base32hex(self.bytes(usize::from(hash_len))?);
+                Record::NSEC3(NSEC3 {
+                    fqdn,
+                    ttl,
+                    hash_alg,
+                    flags,
+                    iterations,
+                    salt: presentation_salt(salt),
+                    next_hashed_owner_name,
+                    record_types: self.type_bitmaps(end)?,
+                })
+            }
+            RecordType::NSEC3PARAM => {
+                let hash_alg = self.u8()?;
+                let flags = self.u8()?;
+                let iterations = self.u16()?;
+                let salt_len = self.u8()?;
+                self.bytes(usize::from(salt_len))?;
+                Record::NSEC3PARAM(NSEC3PARAM {
+                    zone: fqdn,
+                    ttl,
+                    hash_alg,
+                    flags,
+                    iterations,
+                })
+            }
+            RecordType::PTR => Record::PTR(PTR {
+                fqdn,
+                ttl,
+                target: self.name()?,
+            }),
+            RecordType::RRSIG => Record::RRSIG(RRSIG {
+                fqdn,
+                ttl,
+                type_covered: RecordType::from(self.u16()?),
+                algorithm: self.u8()?,
+                labels: self.u8()?,
+                original_ttl: self.u32()?,
+                signature_expiration: presentation_timestamp(self.u32()?),
+                signature_inception: presentation_timestamp(self.u32()?),
+                key_tag: self.u16()?,
+                signer_name: self.name()?,
+                signature: BASE64_STANDARD.encode(self.bytes(end - self.position)?),
+            }),
+            RecordType::SOA => Record::SOA(SOA {
+                zone: fqdn,
+                ttl,
+                nameserver: self.name()?,
+                admin: self.name()?,
+                settings: SoaSettings {
+                    serial: self.u32()?,
+                    refresh: self.u32()?,
+                    retry: self.u32()?,
+                    expire: self.u32()?,
+                    minimum: self.u32()?,
+                },
+            }),
+            RecordType::SRV => Record::SRV(SRV {
+                fqdn,
+                ttl,
+                priority: self.u16()?,
+                weight: self.u16()?,
+                port: self.u16()?,
+                target: self.name()?,
+            }),
+            RecordType::SVCB => Record::SVCB(self.svcb(fqdn, ttl, end)?),
+            RecordType::TLSA => Record::TLSA(TLSA {
+                fqdn,
+                ttl,
+                cert_usage: self.u8()?,
+                selector: self.u8()?,
+                matching_type: self.u8()?,
+                cert_data: hex::encode_upper(self.bytes(end - self.position)?),
+            }),
+            RecordType::TXT => {
+                let mut character_strings = vec![];
+                while self.position < end {
+                    character_strings.push(self.character_string()?);
+                }
+                Record::TXT(TXT {
+                    zone: fqdn,
+                    ttl,
+                    character_strings,
+                })
+            }
+            RecordType::ZONEMD => Record::ZONEMD(ZONEMD {
+                zone: fqdn,
+                ttl,
+                serial: self.u32()?,
+                scheme: self.u8()?,
+                hash_algorithm: self.u8()?,
+                digest: hex::encode_upper(self.bytes(end - self.position)?),
+            }),
+            RecordType::MX | RecordType::Unknown(_) => Record::Unknown(UnknownRdata {
+                zone: fqdn,
+                ttl,
+                r#type: record_type.code(),
+                rdata: self.bytes(end - self.position)?.to_vec(),
+            }),
+        };
+
+        Ok(record)
+    }
+
+    fn dnskey(&mut self, end: usize) -> Result<DNSKEYRData> {
+        Ok(DNSKEYRData {
+            flags: self.u16()?,
+            protocol: self.u8()?,
+            algorithm: self.u8()?,
+            public_key: BASE64_STANDARD.encode(self.bytes(end - self.position)?),
+        })
+    }
+
+    fn ds(&mut self, zone: FQDN, ttl: u32, end: usize) -> Result<DS> {
+        Ok(DS {
+            zone,
+            ttl,
+            key_tag: self.u16()?,
+            algorithm: self.u8()?,
+            digest_type: self.u8()?,
+            digest: hex::encode_upper(self.bytes(end - self.position)?),
+        })
+    }
+
+    fn svcb(&mut self, zone: FQDN, ttl: u32, end: usize) -> Result<SVCB> {
+        let priority = self.u16()?;
+        let target = self.name()?;
+
+        let mut params = BTreeMap::new();
+        while self.position < end {
+            let key = svc_param_key(self.u16()?);
+            let len = self.u16()?;
+            let mut value = Reader::new(self.bytes(usize::from(len))?);
+
+            let value = match key {
+                SvcParamKey::Mandatory => {
+                    let mut keys = vec![];
+                    while value.position < value.message.len() {
+                        keys.push(svc_param_key(value.u16()?));
+                    }
+                    SvcParamValue::Keys(keys)
+                }
+                SvcParamKey::Alpn => {
+                    let mut protocols = vec![];
+                    while value.position < value.message.len() {
+                        protocols.push(value.character_string()?);
+                    }
+                    SvcParamValue::Alpn(protocols)
+                }
+                SvcParamKey::NoDefaultAlpn => SvcParamValue::Empty,
+                SvcParamKey::Port => SvcParamValue::Port(value.u16()?),
+                SvcParamKey::Ipv4Hint => SvcParamValue::Ipv4Hint(
+                    value
+                        .message
+                        .chunks_exact(4)
+                        .map(|octets| Ipv4Addr::new(octets[0], octets[1], octets[2], octets[3]))
+                        .collect(),
+                ),
+                SvcParamKey::Ipv6Hint => SvcParamValue::Ipv6Hint(
+                    value
+                        .message
+                        .chunks_exact(16)
+                        .map(|octets| Ipv6Addr::from(<[u8; 16]>::try_from(octets).unwrap()))
+                        .collect(),
+                ),
+                SvcParamKey::Ech => SvcParamValue::Opaque(BASE64_STANDARD.encode(value.message)),
+                SvcParamKey::Unknown(_) => {
+                    SvcParamValue::Opaque(String::from_utf8_lossy(value.message).into_owned())
+                }
+            };
+
+            if params.insert(key, value).is_some() {
+                return Err(format!("duplicate SvcParam key: {key}").into());
+            }
+        }
+
+        Ok(SVCB {
+            zone,
+            ttl,
+            priority,
+            target,
+            params,
+        })
+    }
+
+    /// Reads the type bitmaps of NSEC and NSEC3 records (RFC4034, section 4.1.2)
+    fn type_bitmaps(&mut self, end: usize) -> Result<Vec<RecordType>> {
+        let mut record_types = vec![];
+        while self.position < end {
+            let window = u16::from(self.u8()?);
+            let len = self.u8()?;
+            for (index, byte) in self.bytes(usize::from(len))?.iter().enumerate() {
+                for bit in 0..8 {
+                    if byte & (0x80 >> bit) != 0 {
+                        let code = window * 256 + index as u16 * 8 + bit;
+                        record_types.push(RecordType::from(code));
+                    }
+                }
+            }
+        }
+
+        Ok(record_types)
+    }
+}
+
+fn svc_param_key(number: u16) -> SvcParamKey {
+    match number {
+        0 => SvcParamKey::Mandatory,
+        1 => SvcParamKey::Alpn,
+        2 => SvcParamKey::NoDefaultAlpn,
+        3 => SvcParamKey::Port,
+        4 => SvcParamKey::Ipv4Hint,
+        5 => SvcParamKey::Ech,
+        6 => SvcParamKey::Ipv6Hint,
+        _ => SvcParamKey::Unknown(number),
+    }
+}
+
+/// `dig` prints an empty salt as `-`
+fn presentation_salt(salt: &[u8]) -> String {
+    if salt.is_empty() {
+        "-".to_string()
+    } else {
+        hex::encode_upper(salt)
+    }
+}
+
+/// Base 32 encoding with the extended hex alphabet (RFC4648), without padding, in uppercase
+fn base32hex(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHIJKLMNOPQRSTUV";
+
+    let mut encoded = String::new();
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for byte in bytes {
+        buffer = (buffer << 8) | u16::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(char::from(ALPHABET[usize::from((buffer >> bits) & 0x1f)]));
+        }
+    }
+    if bits > 0 {
+        encoded.push(char::from(
+            ALPHABET[usize::from((buffer << (5 - bits)) & 0x1f)],
+        ));
+    }
+
+    encoded
+}
+
+/// Converts a UNIX timestamp into the `YYYYMMDDHHmmSS` format of RRSIG records, as a number
+fn presentation_timestamp(timestamp: u32) -> u64 {
+    let days = i64::from(timestamp / 86_400);
+    let seconds_of_day = u64::from(timestamp % 86_400);
+
+    // civil calendar date from the number of days since 1970-01-01, as per
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    let date = (year * 10_000 + month * 100 + day) as u64;
+    let time =
+        seconds_of_day / 3_600 * 10_000 + seconds_of_day % 3_600 / 60 * 100 + seconds_of_day % 60;
+    date * 1_000_000 + time
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query() -> Result<()> {
+        let settings = *DigSettings::default()
+            .recurse()
+            .dnssec()
+            .nocookie()
+            .subnet(Ipv4Addr::new(10, 1, 2, 0), 24);
+        let query = encode_query(&settings, 0x1234, &RecordType::A, &FQDN::TEST_DOMAIN);
+
+        let expected = [
+            &[0x12, 0x34, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 1][..],
+            b"\x0bhickory-dns\x07testing\x00\x00\x01\x00\x01",
+            // OPT: root, type, UDP payload size, extended RCODE, version, DO, RDATA length
+            &[0, 0, 41, 0x04, 0xd0, 0, 0, 0x80, 0x00, 0, 11],
+            // CLIENT-SUBNET: code, length, family, source and scope prefix length, address
+            &[0, 8, 0, 7, 0, 1, 24, 0, 10, 1, 2],
+        ]
+        .concat();
+        assert_eq!(expected, query);
+
+        Ok(())
+    }
+
+    #[test]
+    fn response() -> Result<()> {
+        let mut response = vec![
+            0x12, 0x34, // ID
+            0x85, 0xa0, // QR, AA, RD, RA, AD
+            0, 1, 0, 3, 0, 0, 0, 1,
+        ];
+        // question; offset 12
+        response.extend_from_slice(b"\x07example\x0bhickory-dns\x07testing\x00\x00\x01\x00\x01");
+        // A record, compressed owner pointing at the question
+        response.extend_from_slice(&[0xc0, 12, 0, 1, 0, 1, 0, 0, 0x0e, 0x10, 0, 4, 192, 0, 2, 1]);
+        // RRSIG covering A, signed by hickory-dns.testing. (offset 20)
+        response.extend_from_slice(&[0xc0, 12, 0, 46, 0, 1, 0, 0, 0x0e, 0x10, 0, 24]);
+        response.extend_from_slice(&[0, 1, 8, 3, 0, 0, 0x0e, 0x10]);
+        // 2024-03-06 13:27:01 UTC and 2024-02-05 13:27:01 UTC
+        response.extend_from_slice(&1_709_731_621u32.to_be_bytes());
+        response.extend_from_slice(&1_707_139_621u32.to_be_bytes());
+        response.extend_from_slice(&[0x30, 0x39, 0xc0, 20, 0xde, 0xad, 0xbe, 0xef]);
+        // record of unknown type 65280
+        response.extend_from_slice(&[0xc0, 12, 0xff, 0x00, 0, 1, 0, 0, 0, 0, 0, 2, 0xab, 0xcd]);
+        // OPT: upper bits of the RCODE 1, DO set, EDE 6 (DNSSEC Bogus)
+        response.extend_from_slice(&[0, 0, 41, 0x04, 0xd0, 1, 0, 0x80, 0, 0, 6, 0, 15, 0, 2, 0, 6]);
+
+        let message = Message::decode(&response, Protocol::Udp)?;
+
+        assert_eq!(0x1234, message.id);
+        assert_eq!(0, message.opcode);
+        assert!(message.flags.qr);
+        assert!(message.flags.authoritative_answer);
+        assert!(message.flags.authenticated_data);
+        assert!(!message.flags.truncation);
+        assert_eq!(
+            vec![(FQDN::EXAMPLE_SUBDOMAIN, RecordType::A)],
+            message.question
+        );
+        // BADVERS, which does not fit in the header alone
+        assert_eq!(16, message.rcode);
+
+        let [a, rrsig, unknown] = message.answer.try_into().unwrap();
+        let a = a.try_into_a().unwrap();
+        assert_eq!(FQDN::EXAMPLE_SUBDOMAIN, a.fqdn);
+        assert_eq!(Ipv4Addr::new(192, 0, 2, 1), a.ipv4_addr);
+
+        let rrsig = rrsig.try_into_rrsig().unwrap();
+        assert_eq!(RecordType::A, rrsig.type_covered);
+        assert_eq!(20240306132701, rrsig.signature_expiration);
+        assert_eq!(20240205132701, rrsig.signature_inception);
+        assert_eq!(12345, rrsig.key_tag);
+        assert_eq!(FQDN::TEST_DOMAIN, rrsig.signer_name);
+        assert_eq!("3q2+7w==", rrsig.signature);
+
+        let Record::Unknown(unknown) = unknown else {
+            panic!("expected a record of unknown type");
+        };
+        assert_eq!(65280, unknown.r#type);
+        assert_eq!(vec![0xab, 0xcd], unknown.rdata);
+
+        let opt = message.opt.expect("OPT record");
+        assert!(opt.dnssec_ok);
+        assert_eq!(1232, opt.udp_payload_size);
+        assert_eq!(Some(&[0, 6][..]), opt.option(OPTION_EDE));
+
+        Ok(())
+    }
+
+    #[test]
+    fn udp_burst() -> Result<()> {
+        let server = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let server_addr = server.local_addr()?;
+        let ids = [1, 2, 3, 4];
+        let queries = ids.map(|id: u16| id.to_be_bytes().to_vec());
+
+        // answers every other query, twice, plus a stray message, in reverse order
+        let answering = std::thread::spawn(move || -> std::io::Result<()> {
+            let mut buffer = [0; 512];
+            let mut received = vec![];
+            for _ in ids {
+                let (len, client) = server.recv_from(&mut buffer)?;
+                received.push((buffer[..len].to_vec(), client));
+            }
+            for (query, client) in received.iter().rev().step_by(2) {
+                server.send_to(query, client)?;
+                server.send_to(query, client)?;
+            }
+            server.send_to(&[0xff, 0xff], received[0].1)?;
+            Ok(())
+        });
+
+        let responses = send_udp_burst(server_addr, &queries, &ids, Duration::from_secs(1))?;
+        answering.join().unwrap()?;
+
+        assert_eq!(
+            vec![None, Some(vec![0, 2]), None, Some(vec![0, 4])],
+            responses
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn udp_tries() -> Result<()> {
+        let server = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let server_addr = server.local_addr()?;
+        let query = 7u16.to_be_bytes();
+
+        // drops the first try and answers the second one
+        let answering = std::thread::spawn(move || -> std::io::Result<()> {
+            let mut buffer = [0; 512];
+            server.recv_from(&mut buffer)?;
+            let (len, client) = server.recv_from(&mut buffer)?;
+            server.send_to(&buffer[..len], client)?;
+            Ok(())
+        });
+
+        let timeout = Duration::from_millis(200);
+        let response = send_udp(server_addr, &query, 7, timeout, 2)?;
+        answering.join().unwrap()?;
+        assert_eq!(Some(query.to_vec()), response);
+
+        let silent = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let response = send_udp(silent.local_addr()?, &query, 7, timeout, 1)?;
+        assert_eq!(None, response);
+
+        Ok(())
+    }
+
+    #[test]
+    fn base32hex_encoding() {
+        assert_eq!("", base32hex(b""));
+        assert_eq!("CO", base32hex(b"f"));
+        assert_eq!("CPNMU", base32hex(b"foo"));
+        assert_eq!("CPNMUOJ1E8", base32hex(b"foobar"));
+    }
+}
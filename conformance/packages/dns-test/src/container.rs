@@ -1,13 +1,17 @@
 mod network;
 
 use core::{fmt, str};
+use std::collections::BTreeMap;
 use std::ffi::OsStr;
-use std::net::Ipv4Addr;
+use std::io::{BufRead as _, BufReader, Write as _};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::{Path, PathBuf};
 use std::process::{self, ChildStderr, ChildStdout, ExitStatus};
 use std::process::{Command, Stdio};
 use std::sync::atomic::AtomicUsize;
-use std::sync::{Arc, Once, atomic};
-use std::{env, fs};
+use std::sync::{Arc, Mutex, Once, atomic, mpsc};
+use std::time::Duration;
+use std::{env, fs, thread};
 
 use tempfile::{NamedTempFile, TempDir};
 
@@ -23,14 +27,20 @@ const PACKAGE_NAME: &str = env!("CARGO_PKG_NAME");
 
 #[derive(Clone)]
 pub enum Image {
-    Bind,
+    Bind {
+        version: Option<String>,
+    },
     Dnslib,
     Client,
     Hickory {
         repo: Repository<'static>,
         dnssec_feature: HickoryDnssecFeature,
     },
-    Unbound,
+    Knot,
+    PowerDns,
+    Unbound {
+        version: Option<String>,
+    },
     EdeDotCom,
 }
 
@@ -44,56 +54,230 @@ impl Image {
 
     fn dockerfile(&self) -> &'static str {
         match self {
-            Self::Bind => include_str!("docker/bind.Dockerfile"),
+            Self::Bind { .. } => include_str!("docker/bind.Dockerfile"),
             Self::Dnslib => include_str!("docker/dnslib.Dockerfile"),
             Self::Client => include_str!("docker/client.Dockerfile"),
             Self::Hickory { .. } => include_str!("docker/hickory.Dockerfile"),
-            Self::Unbound => include_str!("docker/unbound.Dockerfile"),
+            Self::Knot => include_str!("docker/knot.Dockerfile"),
+            Self::PowerDns => include_str!("docker/powerdns.Dockerfile"),
+            Self::Unbound { .. } => include_str!("docker/unbound.Dockerfile"),
             Self::EdeDotCom => include_str!("docker/ede-dot-com/Dockerfile"),
         }
     }
 
-    fn once(&self) -> &'static Once {
+    fn build_args(&self) -> Vec<String> {
         match self {
-            Self::Bind => {
-                static BIND_ONCE: Once = Once::new();
-                &BIND_ONCE
+            Self::Hickory {
+                repo,
+                dnssec_feature,
+            } => {
+                let mut args = vec![format!("--build-arg=DNSSEC_FEATURE={dnssec_feature}")];
+                if !repo.features().is_empty() {
+                    let features = repo.features().join(",");
+                    args.push(format!("--build-arg=HICKORY_FEATURES={features}"));
+                }
+                if !repo.uses_default_features() {
+                    args.push("--build-arg=HICKORY_BUILD_FLAGS=--no-default-features".to_string());
+                }
+                args
             }
+            Self::Bind {
+                version: Some(version),
+            } => vec![format!("--build-arg=BIND_VERSION={version}")],
+            Self::Unbound {
+                version: Some(version),
+            } => vec![format!("--build-arg=UNBOUND_VERSION={version}")],
+            _ => vec![],
+        }
+    }
 
-            Self::Dnslib => {
-                static DNSLIB_ONCE: Once = Once::new();
-                &DNSLIB_ONCE
-            }
+    /// Digest of everything that goes into building the image: the Dockerfile, the build
+    /// arguments, the files copied into the build context and, for hickory, the commit (or the
+    /// contents of the working tree) being built
+    fn cache_key(&self) -> Result<String> {
+        let mut inputs = String::new();
+        inputs.push_str(self.dockerfile());
+        for arg in self.build_args() {
+            inputs.push('\0');
+            inputs.push_str(&arg);
+        }
 
-            Self::Client => {
-                static CLIENT_ONCE: Once = Once::new();
-                &CLIENT_ONCE
+        match self {
+            Self::Hickory { repo, .. } => {
+                inputs.push('\0');
+                inputs.push_str(repo.as_str());
+                inputs.push('\0');
+                inputs.push_str(&source_id(repo)?);
+            }
+            Self::EdeDotCom => {
+                inputs.push_str(include_str!("docker/ede-dot-com/configure_child.sh"));
+                inputs.push_str(include_str!("docker/ede-dot-com/configure_parent.sh"));
             }
+            _ => {}
+        }
+
+        hash_object(None, &["--stdin"], &inputs)
+    }
 
-            Self::Hickory { .. } => {
-                static HICKORY_ONCE: Once = Once::new();
-                &HICKORY_ONCE
+    /// Builds the image unless it was already built, or found in the local cache of images, by
+    /// this process
+    fn ensure_built(&self) {
+        if !skip_docker_build() {
+            self.once().call_once(|| self.build());
+        }
+    }
+
+    /// Builds several images at once
+    ///
+    /// Identical images, e.g. two `Image::bind()`, are built only once. Calling this before
+    /// setting up a network that mixes implementations avoids building their images one after
+    /// the other as the containers get started. Set `DNS_TEST_SERIAL_DOCKER_BUILD` to build the
+    /// images one at a time, e.g. when the docker daemon serializes builds anyway
+    pub fn build_all(images: impl IntoIterator<Item = Image>) {
+        let mut distinct = BTreeMap::new();
+        for image in images {
+            distinct.entry(image.to_string()).or_insert(image);
+        }
+
+        if serial_docker_build() {
+            for image in distinct.values() {
+                image.ensure_built();
             }
+        } else {
+            thread::scope(|scope| {
+                for image in distinct.values() {
+                    scope.spawn(|| image.ensure_built());
+                }
+            });
+        }
+    }
+
+    fn build(&self) {
+        let image_tag = self.tag();
+        let cached_image_tag = self
+            .cached_tag()
+            .expect("failed to compute the image's cache key");
+        if !rebuild_docker_images() && image_exists(&cached_image_tag) {
+            let mut tag = Command::new("docker");
+            tag.args(["tag", &cached_image_tag, &image_tag]);
+            exec_or_panic(&mut tag, false);
+            return;
+        }
 
-            Self::Unbound => {
-                static UNBOUND_ONCE: Once = Once::new();
-                &UNBOUND_ONCE
+        let dockerfile = self.dockerfile();
+        let docker_build_dir = TempDir::new().expect("failed to create temporary directory");
+        let docker_build_dir = docker_build_dir.path();
+        fs::write(docker_build_dir.join("Dockerfile"), dockerfile)
+            .expect("failed to write Dockerfile");
+
+        let mut command = Command::new("docker");
+        command
+            .args(["build", "--load", "-t"])
+            .arg(&image_tag)
+            .arg("-t")
+            .arg(&cached_image_tag)
+            .arg(docker_build_dir);
+        // Use BuildKit instead of the legacy builder. We need to choose this in order to
+        // pass the `--load` flag above. Depending on which BuildKit build driver is in use,
+        // the `--load` flag may be necessary, in order to load the resulting image as a
+        // local Docker image.
+        command.env("DOCKER_BUILDKIT", "1");
+
+        command.args(self.build_args());
+
+        if docker_build_gha_cache() {
+            let scope = match self {
+                // all revisions share a cache
+                Image::Hickory { dnssec_feature, .. } => {
+                    format!("hickory-{dnssec_feature}")
+                }
+                _ => self.to_string(),
+            };
+
+            command.arg(format!("--cache-from=type=gha,scope=${scope}"));
+            if let Image::Hickory { .. } = self {
+                command.arg(format!(
+                    "--cache-to=type=gha,scope=${scope},mode=max,ignore-error=true"
+                ));
+            } else {
+                command.arg(format!(
+                    "--cache-to=type=gha,scope=${scope},ignore-error=true"
+                ));
             }
+        }
 
-            Self::EdeDotCom => {
-                static EDE_ONCE: Once = Once::new();
-                &EDE_ONCE
+        if let Image::Hickory { repo, .. } = self {
+            let src_dir = docker_build_dir.join("src").display().to_string();
+            let mut cp_r = Command::new("git");
+            if repo.is_worktree() {
+                copy_worktree(repo.as_str(), &docker_build_dir.join("src"))
+                    .expect("failed to copy the working tree");
+            } else if let Some(rev) = repo.rev().filter(|_| repo.is_ref()) {
+                cp_r.args(["clone", "--depth", "1", "--branch", rev, repo.as_str()])
+                    .arg(&src_dir);
+                exec_or_panic(&mut cp_r, false);
+            } else if let Some(rev) = repo.rev() {
+                // `rev` may not be part of a shallow clone
+                cp_r.args(["clone", "--no-checkout", repo.as_str(), &src_dir]);
+                exec_or_panic(&mut cp_r, false);
+
+                let mut checkout = Command::new("git");
+                checkout.args(["-C", &src_dir, "checkout", "--detach", rev]);
+                exec_or_panic(&mut checkout, false);
+            } else {
+                cp_r.args(["clone", "--depth", "1", repo.as_str(), &src_dir]);
+                exec_or_panic(&mut cp_r, false);
             }
         }
+
+        if let Image::EdeDotCom = self {
+            fs::write(
+                docker_build_dir.join("configure_child.sh"),
+                include_str!("docker/ede-dot-com/configure_child.sh"),
+            )
+            .expect("could not copy configure_child.sh");
+            fs::write(
+                docker_build_dir.join("configure_parent.sh"),
+                include_str!("docker/ede-dot-com/configure_parent.sh"),
+            )
+            .expect("could not copy configure_parent.sh");
+        }
+
+        fs::write(docker_build_dir.join(".dockerignore"), "src/.git")
+            .expect("could not create .dockerignore file");
+
+        exec_or_panic(&mut command, verbose_docker_build());
+    }
+
+    fn tag(&self) -> String {
+        format!("{PACKAGE_NAME}-{self}")
+    }
+
+    /// The tag, plus the digest of the build inputs, under which the image is cached
+    fn cached_tag(&self) -> Result<String> {
+        Ok(format!("{}:{}", self.tag(), self.cache_key()?))
+    }
+
+    fn once(&self) -> &'static Once {
+        // versions, revisions and features are part of the image tag so each tag needs to be
+        // built separately
+        static ONCES: Mutex<BTreeMap<String, &'static Once>> = Mutex::new(BTreeMap::new());
+
+        let mut onces = ONCES.lock().unwrap();
+        onces
+            .entry(self.to_string())
+            .or_insert_with(|| Box::leak(Box::new(Once::new())))
     }
 }
 
 impl From<Implementation> for Image {
     fn from(implementation: Implementation) -> Self {
         match implementation {
-            Implementation::Bind => Self::Bind,
+            Implementation::Bind { version } => Self::Bind { version },
             Implementation::Dnslib => Self::Dnslib,
-            Implementation::Unbound => Self::Unbound,
+            Implementation::Knot => Self::Knot,
+            Implementation::PowerDns => Self::PowerDns,
+            Implementation::Unbound { version } => Self::Unbound { version },
             Implementation::Hickory {
                 repo,
                 dnssec_feature,
@@ -110,114 +294,87 @@ impl fmt::Display for Image {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Client => f.write_str("client"),
-            Self::Bind => f.write_str("bind"),
+            Self::Bind { version } => {
+                f.write_str("bind")?;
+                if let Some(version) = version {
+                    write!(f, "-{}", tag_safe(version))?;
+                }
+                Ok(())
+            }
             Self::Dnslib => f.write_str("dnslib"),
-            Self::Hickory { dnssec_feature, .. } => write!(f, "hickory-{dnssec_feature}"),
-            Self::Unbound => f.write_str("unbound"),
+            Self::Hickory {
+                repo,
+                dnssec_feature,
+            } => {
+                write!(f, "hickory-{dnssec_feature}")?;
+                if let Some(rev) = repo.rev() {
+                    write!(f, "-{}", tag_safe(rev))?;
+                } else if repo.is_worktree() {
+                    f.write_str("-worktree")?;
+                }
+                if !repo.uses_default_features() {
+                    f.write_str("-no-default-features")?;
+                }
+                for feature in repo.features() {
+                    write!(f, "-{}", tag_safe(feature))?;
+                }
+                Ok(())
+            }
+            Self::Knot => f.write_str("knot"),
+            Self::PowerDns => f.write_str("powerdns"),
+            Self::Unbound { version } => {
+                f.write_str("unbound")?;
+                if let Some(version) = version {
+                    write!(f, "-{}", tag_safe(version))?;
+                }
+                Ok(())
+            }
             Self::EdeDotCom => f.write_str("ede-dot-com"),
         }
     }
 }
 
+/// image tags and container names only allow a subset of ASCII
+fn tag_safe(s: &str) -> String {
+    s.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
 impl Container {
     /// Starts the container in a "parked" state
+    ///
+    /// When `DNS_TEST_ARTIFACTS` is set, the contents of `/tmp`, where the servers write their
+    /// logs, are copied to `$DNS_TEST_ARTIFACTS/<test name>/<container name>` when the container
+    /// is dropped
     pub fn run(image: &Image, network: &Network) -> Result<Self> {
-        let image_tag = format!("{PACKAGE_NAME}-{image}");
-
-        if !skip_docker_build() {
-            image.once().call_once(|| {
-                let dockerfile = image.dockerfile();
-                let docker_build_dir =
-                    TempDir::new().expect("failed to create temporary directory");
-                let docker_build_dir = docker_build_dir.path();
-                fs::write(docker_build_dir.join("Dockerfile"), dockerfile)
-                    .expect("failed to write Dockerfile");
-
-                let mut command = Command::new("docker");
-                command
-                    .args(["build", "--load", "-t"])
-                    .arg(&image_tag)
-                    .arg(docker_build_dir);
-                // Use BuildKit instead of the legacy builder. We need to choose this in order to
-                // pass the `--load` flag above. Depending on which BuildKit build driver is in use,
-                // the `--load` flag may be necessary, in order to load the resulting image as a
-                // local Docker image.
-                command.env("DOCKER_BUILDKIT", "1");
-
-                if let Image::Hickory { dnssec_feature, .. } = image {
-                    command.arg(format!("--build-arg=DNSSEC_FEATURE={dnssec_feature}"));
-                };
-
-                if docker_build_gha_cache() {
-                    let scope = match image {
-                        Image::Bind => "bind",
-                        Image::Dnslib => "dnslib",
-                        Image::Client => "client",
-                        Image::Hickory {
-                            dnssec_feature: HickoryDnssecFeature::AwsLcRs,
-                            ..
-                        } => "hickory-dnssec-aws-lc-rs",
-                        Image::Hickory {
-                            dnssec_feature: HickoryDnssecFeature::Ring,
-                            ..
-                        } => "hickory-dnssec-ring",
-                        Image::Unbound => "unbound",
-                        Image::EdeDotCom => "ede-dot-com",
-                    };
-
-                    command.arg(format!("--cache-from=type=gha,scope=${scope}"));
-                    if let Image::Hickory { .. } = image {
-                        command.arg(format!(
-                            "--cache-to=type=gha,scope=${scope},mode=max,ignore-error=true"
-                        ));
-                    } else {
-                        command.arg(format!(
-                            "--cache-to=type=gha,scope=${scope},ignore-error=true"
-                        ));
-                    }
-                }
-
-                if let Image::Hickory { repo, .. } = image {
-                    let mut cp_r = Command::new("git");
-                    cp_r.args([
-                        "clone",
-                        "--depth",
-                        "1",
-                        repo.as_str(),
-                        &docker_build_dir.join("src").display().to_string(),
-                    ]);
-
-                    exec_or_panic(&mut cp_r, false);
-                }
-
-                if let Image::EdeDotCom = image {
-                    fs::write(
-                        docker_build_dir.join("configure_child.sh"),
-                        include_str!("docker/ede-dot-com/configure_child.sh"),
-                    )
-                    .expect("could not copy configure_child.sh");
-                    fs::write(
-                        docker_build_dir.join("configure_parent.sh"),
-                        include_str!("docker/ede-dot-com/configure_parent.sh"),
-                    )
-                    .expect("could not copy configure_parent.sh");
-                }
+        Self::run_inner(image, network, None)
+    }
 
-                fs::write(docker_build_dir.join(".dockerignore"), "src/.git")
-                    .expect("could not create .dockerignore file");
-
-                exec_or_panic(&mut command, verbose_docker_build());
-            });
-        }
+    fn run_inner(image: &Image, network: &Network, artifacts_dir: Option<PathBuf>) -> Result<Self> {
+        image.ensure_built();
 
         let mut command = Command::new("docker");
         let pid = process::id();
         let count = container_count();
         let name = format!("{PACKAGE_NAME}-{image}-{pid}-{count}");
+        let artifacts_dir = artifacts_dir
+            .or_else(artifacts_root)
+            .map(|root| root.join(test_name()).join(&name));
+        command.arg("run");
+        // a container that stopped on its own, e.g. because it was OOM-killed, must stick around
+        // until its files are copied; `Inner::drop` removes it regardless
+        if artifacts_dir.is_none() {
+            command.arg("--rm");
+        }
         command
             .args([
-                "run",
-                "--rm",
                 "--detach",
                 "--cap-add=NET_RAW",
                 "--cap-add=NET_ADMIN",
@@ -226,19 +383,27 @@ impl Container {
                 "--name",
                 &name,
             ])
-            .arg(image_tag)
+            .arg(image.tag())
             .args(["sleep", "infinity"]);
 
         let output: Output = checked_output(&mut command)?.try_into()?;
         let id = output.stdout;
 
         let ipv4_addr = get_ipv4_addr(&id)?;
+        let ipv6_addr = if network.ipv6_netmask().is_some() {
+            get_ipv6_addr(&id)?
+        } else {
+            None
+        };
 
         let inner = Inner {
             id,
             name,
             ipv4_addr,
+            ipv6_addr,
             network: network.clone(),
+            connected_networks: Mutex::new(vec![]),
+            artifacts_dir,
         };
         Ok(Self {
             inner: Arc::new(inner),
@@ -314,6 +479,26 @@ impl Container {
         }
     }
 
+    /// Similar to `Self::status_ok` but, when `command_and_args` fails, the error includes what
+    /// it printed, e.g. the reason a control command gives for rejecting a configuration
+    pub fn status_ok_with_output(&self, command_and_args: &[&str]) -> Result<()> {
+        let Output {
+            status,
+            stderr,
+            stdout,
+        } = self.output(command_and_args)?;
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "[{}] `{command_and_args:?}` failed: {status}\nSTDOUT:\n{stdout}\nSTDERR:\n{stderr}",
+                self.inner.name
+            )
+            .into())
+        }
+    }
+
     pub fn spawn(&self, cmd: &[impl AsRef<OsStr>]) -> Result<Child> {
         let mut command = Command::new("docker");
         command.stdout(Stdio::piped()).stderr(Stdio::piped());
@@ -327,14 +512,127 @@ impl Container {
         })
     }
 
+    /// Sends `signal` to the process whose PID is in the file at `pidfile` inside the container
+    pub fn kill(&self, pidfile: &str, signal: Signal) -> Result<()> {
+        let kill = format!("kill -s {} $(cat {pidfile})", signal.as_str());
+        self.status_ok(&["sh", "-c", &kill])
+    }
+
+    /// Streams the lines of the files at `paths_in_container`, from their first line, as they
+    /// are written
+    ///
+    /// The files do not need to exist yet; lines of different files are interleaved in the order
+    /// they were written
+    pub fn tail_logs(&self, paths_in_container: &[&str]) -> Result<LogStream> {
+        let mut command = vec!["tail", "-q", "-F", "-n", "+1"];
+        command.extend(paths_in_container);
+
+        let mut child = self.spawn(&command)?;
+        let stdout = child.stdout()?;
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(LogStream {
+            _child: child,
+            lines: receiver,
+        })
+    }
+
     pub fn ipv4_addr(&self) -> Ipv4Addr {
         self.inner.ipv4_addr
     }
 
+    /// Returns the IPv6 address of the container; `None` unless its network has IPv6 enabled
+    pub fn ipv6_addr(&self) -> Option<Ipv6Addr> {
+        self.inner.ipv6_addr
+    }
+
+    /// Attaches the running container to `network` as well; it keeps the address it has in the
+    /// network it was started in, which is the one [`Self::ipv4_addr`] returns
+    pub fn connect(&self, network: &Network) -> Result<()> {
+        let mut connected_networks = self.inner.connected_networks.lock().unwrap();
+        let is_attached = network.name() == self.inner.network.name()
+            || connected_networks
+                .iter()
+                .any(|(connected, _)| connected.name() == network.name());
+        if is_attached {
+            return Err(format!(
+                "[{}] is already attached to network {}",
+                self.inner.name,
+                network.name()
+            )
+            .into());
+        }
+
+        let mut command = Command::new("docker");
+        command
+            .args(["network", "connect", network.name()])
+            .arg(&self.inner.id);
+        checked_output(&mut command)?;
+
+        let ipv4_addr = get_ipv4_addr_in(&self.inner.id, network)?;
+        connected_networks.push((network.clone(), ipv4_addr));
+
+        Ok(())
+    }
+
+    /// Returns the IPv4 address of the container in `network`; `None` if it is not attached to it
+    pub fn ipv4_addr_in(&self, network: &Network) -> Option<Ipv4Addr> {
+        if network.name() == self.inner.network.name() {
+            return Some(self.inner.ipv4_addr);
+        }
+
+        self.inner
+            .connected_networks
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(connected, _)| connected.name() == network.name())
+            .map(|(_, ipv4_addr)| *ipv4_addr)
+    }
+
     pub fn id(&self) -> &str {
         &self.inner.id
     }
 
+    /// Removes the images tagged with a cache key so that they are rebuilt the next time they
+    /// are needed
+    ///
+    /// This only affects images that have not been used yet by the current process
+    pub fn purge_cache() -> Result<()> {
+        let mut ls = Command::new("docker");
+        ls.args([
+            "image",
+            "ls",
+            "--filter",
+            &format!("reference={PACKAGE_NAME}-*"),
+            "--format",
+            "{{.Repository}}:{{.Tag}}",
+        ]);
+        let output = checked_output(&mut ls)?;
+
+        let cached_images = str::from_utf8(&output.stdout)?
+            .lines()
+            .filter(|image| !image.ends_with(":latest") && !image.ends_with(":<none>"))
+            .collect::<Vec<_>>();
+        if cached_images.is_empty() {
+            return Ok(());
+        }
+
+        let mut rmi = Command::new("docker");
+        rmi.arg("rmi").args(cached_images);
+        checked_output(&mut rmi)?;
+
+        Ok(())
+    }
+
     pub(crate) fn network(&self) -> &Network {
         &self.inner.network
     }
@@ -348,14 +646,145 @@ fn verbose_docker_build() -> bool {
     env::var("DNS_TEST_VERBOSE_DOCKER_BUILD").as_deref().is_ok()
 }
 
+fn serial_docker_build() -> bool {
+    env::var("DNS_TEST_SERIAL_DOCKER_BUILD").is_ok()
+}
+
 fn skip_docker_build() -> bool {
     env::var("DNS_TEST_SKIP_DOCKER_BUILD").is_ok()
 }
 
+fn rebuild_docker_images() -> bool {
+    env::var("DNS_TEST_REBUILD_DOCKER_IMAGES").is_ok()
+}
+
+fn artifacts_root() -> Option<PathBuf> {
+    env::var_os("DNS_TEST_ARTIFACTS").map(PathBuf::from)
+}
+
+/// The test harness names the thread that runs a test after the test
+fn test_name() -> String {
+    thread::current()
+        .name()
+        .unwrap_or("unnamed")
+        .replace("::", "-")
+}
+
+fn image_exists(image_tag: &str) -> bool {
+    Command::new("docker")
+        .args(["image", "inspect", image_tag])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// the commit that will be built or, for a working tree, a digest of its contents
+fn source_id(repo: &Repository<'_>) -> Result<String> {
+    if repo.is_worktree() {
+        let files = worktree_files(repo.as_str())?;
+        let blobs = hash_object(Some(repo.as_str()), &["--stdin-paths"], &files.join("\n"))?;
+        return Ok(format!("{}\0{blobs}", files.join("\0")));
+    }
+
+    let rev = repo.rev().unwrap_or("HEAD");
+    let mut ls_remote = Command::new("git");
+    ls_remote.args(["ls-remote", repo.as_str(), rev]);
+    let output = checked_output(&mut ls_remote)?;
+
+    // `ls-remote` only knows about refs; anything else is expected to be a commit hash
+    let commit = str::from_utf8(&output.stdout)?
+        .split_whitespace()
+        .next()
+        .unwrap_or(rev);
+    Ok(commit.to_string())
+}
+
+/// runs `git hash-object` with `input` as its standard input
+fn hash_object(repo: Option<&str>, args: &[&str], input: &str) -> Result<String> {
+    let mut command = Command::new("git");
+    if let Some(repo) = repo {
+        command.args(["-C", repo]);
+    }
+    let mut child = command
+        .arg("hash-object")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(input.as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "`git hash-object` failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    Ok(str::from_utf8(&output.stdout)?.trim().to_string())
+}
+
 fn docker_build_gha_cache() -> bool {
     env::var("DNS_TEST_DOCKER_CACHE_GHA").is_ok()
 }
 
+/// copies the files in the working tree at `repo`, minus the ones ignored by git, into `dest`
+fn copy_worktree(repo: &str, dest: &Path) -> Result<()> {
+    for file in worktree_files(repo)? {
+        let src = Path::new(repo).join(&file);
+        let dest = dest.join(&file);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(&src, &dest)?;
+    }
+
+    Ok(())
+}
+
+/// lists the files in the working tree at `repo`, minus the ones ignored by git
+fn worktree_files(repo: &str) -> Result<Vec<String>> {
+    let mut ls_files = Command::new("git");
+    ls_files.args([
+        "-C",
+        repo,
+        "ls-files",
+        "-z",
+        "--cached",
+        "--others",
+        "--exclude-standard",
+    ]);
+    let output = checked_output(&mut ls_files)?;
+
+    let mut files = vec![];
+    for file in str::from_utf8(&output.stdout)?.split_terminator('\0') {
+        let file = file.trim_end_matches('/');
+        let path = Path::new(repo).join(file);
+        if path.is_dir() {
+            // a submodule, or a nested repository, whose files git does not list; its `.git` is
+            // usually a file that points into the `.git` directory of `repo`. Submodules that
+            // were not checked out are empty
+            if path.join(".git").exists() {
+                let nested = worktree_files(&path.display().to_string())?;
+                files.extend(nested.into_iter().map(|nested| format!("{file}/{nested}")));
+            }
+        } else if path.exists() {
+            // tracked files that were deleted but whose deletion has not been committed are left
+            // out
+            files.push(file.to_string());
+        }
+    }
+
+    Ok(files)
+}
+
 fn exec_or_panic(command: &mut Command, verbose: bool) {
     if verbose {
         let status = command.status().unwrap();
@@ -380,9 +809,14 @@ fn container_count() -> usize {
 struct Inner {
     name: String,
     id: String,
-    // TODO probably also want the IPv6 address
     ipv4_addr: Ipv4Addr,
+    ipv6_addr: Option<Ipv6Addr>,
     network: Network,
+    /// Networks joined with [`Container::connect`], and the address in each; dropped after the
+    /// container is removed so that the networks can be removed as well
+    connected_networks: Mutex<Vec<(Network, Ipv4Addr)>>,
+    /// Where the contents of `/tmp` are copied to on drop; see [`Container::run`]
+    artifacts_dir: Option<PathBuf>,
 }
 
 /// NOTE unlike `std::process::Child`, the drop implementation of this type will `kill` the
@@ -441,6 +875,47 @@ impl Drop for Child {
     }
 }
 
+/// How [`Container::kill`] stops a process
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Signal {
+    /// SIGTERM: the process may shut down cleanly
+    Term,
+    /// SIGKILL: the process is terminated right away
+    Kill,
+}
+
+impl Signal {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Term => "TERM",
+            Self::Kill => "KILL",
+        }
+    }
+}
+
+/// Lines written to log files while the container runs; see [`Container::tail_logs`]
+///
+/// Iterating blocks until the next line is written; the iterator ends if the container stops
+pub struct LogStream {
+    _child: Child,
+    lines: mpsc::Receiver<String>,
+}
+
+impl LogStream {
+    /// Waits up to `timeout` for the next line
+    pub fn next_timeout(&mut self, timeout: Duration) -> Option<String> {
+        self.lines.recv_timeout(timeout).ok()
+    }
+}
+
+impl Iterator for LogStream {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        self.lines.recv().ok()
+    }
+}
+
 #[derive(Debug)]
 pub struct Output {
     pub status: ExitStatus,
@@ -499,9 +974,79 @@ fn get_ipv4_addr(container_id: &str) -> Result<Ipv4Addr> {
     Ok(ipv4_addr.parse()?)
 }
 
+fn get_ipv4_addr_in(container_id: &str, network: &Network) -> Result<Ipv4Addr> {
+    let mut command = Command::new("docker");
+    command
+        .args([
+            "inspect",
+            "-f",
+            &format!(
+                "{{{{(index .NetworkSettings.Networks \"{}\").IPAddress}}}}",
+                network.name()
+            ),
+        ])
+        .arg(container_id);
+
+    let output = command.output()?;
+    if !output.status.success() {
+        return Err(format!("`{command:?}` failed").into());
+    }
+
+    let ipv4_addr = str::from_utf8(&output.stdout)?.trim().to_string();
+
+    Ok(ipv4_addr.parse()?)
+}
+
+fn get_ipv6_addr(container_id: &str) -> Result<Option<Ipv6Addr>> {
+    let mut command = Command::new("docker");
+    command
+        .args([
+            "inspect",
+            "-f",
+            "{{range.NetworkSettings.Networks}}{{.GlobalIPv6Address}}{{end}}",
+        ])
+        .arg(container_id);
+
+    let output = command.output()?;
+    if !output.status.success() {
+        return Err(format!("`{command:?}` failed").into());
+    }
+
+    let ipv6_addr = str::from_utf8(&output.stdout)?.trim();
+    if ipv6_addr.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(ipv6_addr.parse()?))
+}
+
 // this ensures the container gets deleted and does not linger after the test runner process ends
+impl Inner {
+    fn save_artifacts(&self, dir: &Path) -> Result<()> {
+        fs::create_dir_all(dir)?;
+
+        // `docker cp` also works on a container that is no longer running
+        let mut command = Command::new("docker");
+        command
+            .arg("cp")
+            .arg(format!("{}:/tmp/.", self.id))
+            .arg(dir);
+        checked_output(&mut command)?;
+
+        Ok(())
+    }
+}
+
 impl Drop for Inner {
     fn drop(&mut self) {
+        if let Some(dir) = &self.artifacts_dir {
+            // the test harness only shows this output when the test fails
+            match self.save_artifacts(dir) {
+                Ok(()) => eprintln!("[{}] artifacts saved to {}", self.name, dir.display()),
+                Err(e) => eprintln!("[{}] could not save artifacts: {e}", self.name),
+            }
+        }
+
         // running this to completion would block the current thread for several seconds so just
         // fire and forget
         let _ = Command::new("docker")
@@ -527,6 +1072,19 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn build_all_works() -> Result<()> {
+        Image::build_all([Image::Client, Image::Client]);
+
+        let network = Network::new()?;
+        let container = Container::run(&Image::Client, &network)?;
+
+        let output = container.output(&["true"])?;
+        assert!(output.status.success());
+
+        Ok(())
+    }
+
     #[test]
     fn ipv4_addr_works() -> Result<()> {
         let network = Network::new()?;
@@ -556,4 +1114,270 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn status_ok_with_output_reports_what_the_command_printed() -> Result<()> {
+        let network = Network::new()?;
+        let container = Container::run(&Image::Client, &network)?;
+
+        container.status_ok_with_output(&["true"])?;
+
+        let error = container
+            .status_ok_with_output(&["sh", "-c", "echo bad config >&2; exit 1"])
+            .unwrap_err();
+        assert!(error.to_string().contains("bad config"), "{error}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn tail_logs_streams_lines_as_they_are_written() -> Result<()> {
+        let network = Network::new()?;
+        let container = Container::run(&Image::Client, &network)?;
+
+        let mut logs = container.tail_logs(&["/tmp/first.log", "/tmp/second.log"])?;
+        container.status_ok(&["sh", "-c", "echo hello > /tmp/first.log"])?;
+
+        let timeout = Duration::from_secs(5);
+        assert_eq!(Some("hello"), logs.next_timeout(timeout).as_deref());
+
+        container.status_ok(&["sh", "-c", "echo world >> /tmp/second.log"])?;
+        assert_eq!(Some("world"), logs.next_timeout(timeout).as_deref());
+
+        Ok(())
+    }
+
+    #[test]
+    fn artifacts_are_saved_after_the_container_stopped() -> Result<()> {
+        let artifacts_root = TempDir::new()?;
+        let network = Network::new()?;
+        let container =
+            Container::run_inner(&Image::Client, &network, Some(artifacts_root.path().into()))?;
+        container.status_ok(&["sh", "-c", "echo hello > /tmp/client.stderr"])?;
+
+        let mut command = Command::new("docker");
+        command.args(["kill", container.id()]);
+        checked_output(&mut command)?;
+
+        let dir = container.inner.artifacts_dir.clone().unwrap();
+        drop(container);
+
+        assert!(dir.starts_with(artifacts_root.path()));
+        assert_eq!("hello\n", fs::read_to_string(dir.join("client.stderr"))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn image_tag_includes_rev() {
+        let image = Image::Hickory {
+            repo: Repository::with_rev(crate::repo_root(), "release/0.24"),
+            dnssec_feature: HickoryDnssecFeature::Ring,
+        };
+
+        assert_eq!("hickory-dnssec-ring-release-0-24", image.to_string());
+    }
+
+    #[test]
+    fn image_tag_includes_branch_and_tag() {
+        let image = Image::Hickory {
+            repo: Repository::with_branch(crate::repo_root(), "main"),
+            dnssec_feature: HickoryDnssecFeature::AwsLcRs,
+        };
+        assert_eq!("hickory-dnssec-aws-lc-rs-main", image.to_string());
+
+        let image = Image::Hickory {
+            repo: Repository::with_tag(crate::repo_root(), "v0.25.0"),
+            dnssec_feature: HickoryDnssecFeature::AwsLcRs,
+        };
+        assert_eq!("hickory-dnssec-aws-lc-rs-v0-25-0", image.to_string());
+    }
+
+    #[test]
+    fn validate_reachable() {
+        assert!(Repository(crate::repo_root()).validate_reachable().is_ok());
+
+        let error = Repository::with_branch(crate::repo_root(), "does-not-exist")
+            .validate_reachable()
+            .unwrap_err();
+        assert!(error.to_string().contains("does-not-exist was not found"));
+
+        let repo = Repository("file:///does/not/exist.git");
+        assert!(repo.validate_reachable().is_err());
+    }
+
+    #[test]
+    fn image_tag_includes_features() {
+        let image = Image::Hickory {
+            repo: Repository(crate::repo_root())
+                .with_features(["https-aws-lc-rs"])
+                .no_default_features(),
+            dnssec_feature: HickoryDnssecFeature::AwsLcRs,
+        };
+        assert_eq!(
+            "hickory-dnssec-aws-lc-rs-no-default-features-https-aws-lc-rs",
+            image.to_string()
+        );
+        assert_eq!(
+            [
+                "--build-arg=DNSSEC_FEATURE=dnssec-aws-lc-rs",
+                "--build-arg=HICKORY_FEATURES=https-aws-lc-rs",
+                "--build-arg=HICKORY_BUILD_FLAGS=--no-default-features",
+            ],
+            &image.build_args()[..]
+        );
+    }
+
+    #[test]
+    fn image_tag_includes_version() {
+        let implementation = Implementation::Bind {
+            version: Some("9.18.24".to_string()),
+        };
+        assert_eq!("bind@9.18.24", implementation.to_string());
+
+        let image = Image::from(implementation);
+        assert_eq!("bind-9-18-24", image.to_string());
+        assert_eq!("bind", Image::from(Implementation::bind()).to_string());
+    }
+
+    #[test]
+    fn run_tags_image_with_cache_key() -> Result<()> {
+        let network = Network::new()?;
+        Container::run(&Image::Client, &network)?;
+
+        let mut ls = Command::new("docker");
+        ls.args(["image", "ls", "--format", "{{.Repository}}:{{.Tag}}"]);
+        let output = checked_output(&mut ls)?;
+        let cached_image_tag = format!("{PACKAGE_NAME}-client:{}", Image::Client.cache_key()?);
+        assert!(
+            str::from_utf8(&output.stdout)?
+                .lines()
+                .any(|image| image == cached_image_tag)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn cache_key_depends_on_version() -> Result<()> {
+        let default = Image::from(Implementation::unbound()).cache_key()?;
+        let pinned = Image::from(Implementation::Unbound {
+            version: Some("1.19.0".to_string()),
+        })
+        .cache_key()?;
+
+        assert_ne!(default, pinned);
+        assert_eq!(default, Image::from(Implementation::unbound()).cache_key()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn cache_key_depends_on_features() -> Result<()> {
+        let image = |repo: Repository<'static>| Image::Hickory {
+            repo,
+            dnssec_feature: HickoryDnssecFeature::AwsLcRs,
+        };
+        let root = || Repository(crate::repo_root());
+
+        let default = image(root()).cache_key()?;
+        let with_features = image(root().with_features(["recursor"])).cache_key()?;
+        let no_default_features = image(root().no_default_features()).cache_key()?;
+
+        assert_ne!(default, with_features);
+        assert_ne!(default, no_default_features);
+        assert_eq!(default, image(root()).cache_key()?);
+
+        Ok(())
+    }
+
+    fn git(repo: &Path, args: &[&str]) -> Result<()> {
+        let mut command = Command::new("git");
+        command
+            .arg("-C")
+            .arg(repo)
+            .args([
+                "-c",
+                "user.name=dns-test",
+                "-c",
+                "user.email=dns-test@example.com",
+            ])
+            // allows `submodule add` of a local repository
+            .args(["-c", "protocol.file.allow=always"])
+            .args(args);
+        checked_output(&mut command).map(drop)
+    }
+
+    /// Creates a repository with one committed `file`
+    fn commit_repo() -> Result<TempDir> {
+        let repo = TempDir::new()?;
+        git(repo.path(), &["init", "--quiet"])?;
+        fs::write(repo.path().join("file"), "committed")?;
+        git(repo.path(), &["add", "."])?;
+        git(repo.path(), &["commit", "--quiet", "-m", "initial commit"])?;
+        Ok(repo)
+    }
+
+    #[test]
+    fn copy_worktree_includes_uncommitted_changes() -> Result<()> {
+        let repo = commit_repo()?;
+        fs::write(repo.path().join(".gitignore"), "target/\n")?;
+        fs::write(repo.path().join("file"), "new")?;
+        fs::create_dir(repo.path().join("untracked"))?;
+        fs::write(repo.path().join("untracked/file"), "untracked")?;
+        fs::create_dir(repo.path().join("target"))?;
+        fs::write(repo.path().join("target/ignored"), "ignored")?;
+
+        let dest = TempDir::new()?;
+        copy_worktree(&repo.path().display().to_string(), dest.path())?;
+
+        assert_eq!("new", fs::read_to_string(dest.path().join("file"))?);
+        assert_eq!(
+            "untracked",
+            fs::read_to_string(dest.path().join("untracked/file"))?
+        );
+        assert!(!dest.path().join("target").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn copy_worktree_includes_submodules() -> Result<()> {
+        let submodule = commit_repo()?;
+        let repo = commit_repo()?;
+        let url = submodule.path().display().to_string();
+        git(repo.path(), &["submodule", "add", "--quiet", &url, "sub"])?;
+        git(repo.path(), &["commit", "--quiet", "-m", "add submodule"])?;
+        assert!(repo.path().join("sub/.git").is_file());
+        fs::write(repo.path().join("sub/file"), "changed")?;
+
+        let dest = TempDir::new()?;
+        copy_worktree(&repo.path().display().to_string(), dest.path())?;
+
+        assert_eq!("committed", fs::read_to_string(dest.path().join("file"))?);
+        assert_eq!("changed", fs::read_to_string(dest.path().join("sub/file"))?);
+        assert!(!dest.path().join("sub/.git").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn worktree_change_changes_cached_tag() -> Result<()> {
+        let repo = commit_repo()?;
+        let image = || Image::Hickory {
+            repo: Repository::local_worktree(repo.path().display().to_string()),
+            dnssec_feature: HickoryDnssecFeature::AwsLcRs,
+        };
+
+        let before = image().cached_tag()?;
+        assert_eq!(before, image().cached_tag()?);
+
+        fs::write(repo.path().join("file"), "uncommitted")?;
+        let after = image().cached_tag()?;
+        assert_ne!(before, after);
+        // the change is not committed so the tag without the digest stays the same
+        assert_eq!(image().tag(), before.split_once(':').unwrap().0);
+
+        Ok(())
+    }
 }
@@ -22,6 +22,11 @@ impl Network {
     pub fn netmask(&self) -> &str {
         &self.0.config.subnet
     }
+
+    /// Returns the IPv6 subnet mask, if the network has IPv6 enabled
+    pub fn ipv6_netmask(&self) -> Option<&str> {
+        self.0.config.ipv6_subnet.as_deref()
+    }
 }
 
 struct NetworkInner {
@@ -33,17 +38,44 @@ impl Network {
     pub fn new() -> Result<Self> {
         let pid = process::id();
         let network_name = env!("CARGO_PKG_NAME");
-        Ok(Self(Arc::new(NetworkInner::new(pid, network_name, true)?)))
+        let inner = NetworkInner::new(pid, network_name, true, false)?;
+        Ok(Self(Arc::new(inner)))
     }
 
     pub fn with_internet_access() -> Result<Self> {
         let pid = process::id();
         let network_name = env!("CARGO_PKG_NAME");
-        Ok(Self(Arc::new(NetworkInner::new(pid, network_name, false)?)))
+        let inner = NetworkInner::new(pid, network_name, false, false)?;
+        Ok(Self(Arc::new(inner)))
+    }
+
+    /// Creates a dual-stack network; Docker picks its IPv6 subnet
+    pub fn with_ipv6() -> Result<Self> {
+        let pid = process::id();
+        let network_name = env!("CARGO_PKG_NAME");
+        let inner = NetworkInner::new(pid, network_name, true, true)?;
+        Ok(Self(Arc::new(inner)))
+    }
+
+    /// Like [`Self::new`] but `label` becomes part of the name of the network, e.g. to tell apart
+    /// the segments of a test that uses several networks
+    ///
+    /// Containers in different networks cannot reach each other unless one of them is also
+    /// connected to the other network; see [`Container::connect`]
+    ///
+    /// [`Container::connect`]: crate::container::Container::connect
+    pub fn with_label(label: &str) -> Result<Self> {
+        let pid = process::id();
+        let network_name = format!("{}-{label}", env!("CARGO_PKG_NAME"));
+        let inner = NetworkInner::new(pid, &network_name, true, false)?;
+        Ok(Self(Arc::new(inner)))
     }
 }
 
 /// This ensure the Docker network is deleted after the test runner process ends.
+///
+/// Every container attached to the network, including those connected to it after they started,
+/// holds a handle to it so the network is only removed once it has no containers left
 impl Drop for NetworkInner {
     fn drop(&mut self) {
         let _ = Command::new("docker")
@@ -55,7 +87,7 @@ impl Drop for NetworkInner {
 }
 
 impl NetworkInner {
-    pub fn new(pid: u32, network_name: &str, internal: bool) -> Result<Self> {
+    pub fn new(pid: u32, network_name: &str, internal: bool, ipv6: bool) -> Result<Self> {
         static CRITICAL_SECTION: Mutex<()> = Mutex::new(());
 
         let count = network_count();
@@ -66,6 +98,9 @@ impl NetworkInner {
         if internal {
             command.arg("--internal");
         }
+        if ipv6 {
+            command.arg("--ipv6");
+        }
         command.arg("--attachable").arg(&network_name);
 
         // create network
@@ -97,6 +132,8 @@ impl NetworkInner {
 pub struct NetworkConfig {
     /// The CIDR subnet mask, e.g. "172.21.0.0/16"
     subnet: String,
+    /// The IPv6 CIDR subnet mask, e.g. "fd12:3456:789a::/64"
+    ipv6_subnet: Option<String>,
 }
 
 /// Return network config
@@ -107,7 +144,7 @@ fn get_network_config(network_name: &str) -> Result<NetworkConfig> {
             "network",
             "inspect",
             "-f",
-            "{{range .IPAM.Config}}{{.Subnet}}{{end}}",
+            "{{range .IPAM.Config}}{{.Subnet}} {{end}}",
         ])
         .arg(network_name);
 
@@ -116,8 +153,21 @@ fn get_network_config(network_name: &str) -> Result<NetworkConfig> {
         return Err(format!("{command:?} failed").into());
     }
 
-    let subnet = std::str::from_utf8(&output.stdout)?.trim().to_string();
-    Ok(NetworkConfig { subnet })
+    let mut subnet = None;
+    let mut ipv6_subnet = None;
+    for cidr in std::str::from_utf8(&output.stdout)?.split_whitespace() {
+        if cidr.contains(':') {
+            ipv6_subnet = Some(cidr.to_string());
+        } else {
+            subnet = Some(cidr.to_string());
+        }
+    }
+
+    let subnet = subnet.ok_or_else(|| format!("network {network_name} has no IPv4 subnet"))?;
+    Ok(NetworkConfig {
+        subnet,
+        ipv6_subnet,
+    })
 }
 
 fn network_count() -> usize {
@@ -168,4 +218,23 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn remove_connected_network_works() -> Result<()> {
+        let network = Network::new()?;
+        let other_network = Network::with_label("other")?;
+        let other_network_name = other_network.name().to_string();
+        assert!(other_network_name.contains("-other-"));
+
+        let container = Container::run(&Image::Client, &network)?;
+        container.connect(&other_network)?;
+
+        drop(other_network);
+        assert!(exists_network(&other_network_name));
+
+        drop(container);
+        assert!(!exists_network(&other_network_name));
+
+        Ok(())
+    }
 }
@@ -2,8 +2,9 @@ use std::net::Ipv4Addr;
 
 use crate::{
     Implementation, Network, Resolver, Result, TrustAnchor,
-    container::{Child, Container},
-    implementation::{Config, Role},
+    container::{Child, Container, LogStream},
+    implementation::{Config, ExtraConfig, Role, Verbosity},
+    logs::{self, LogEvent},
     record::DNSKEY,
     tshark::Tshark,
 };
@@ -15,12 +16,28 @@ pub struct Forwarder {
 }
 
 impl Forwarder {
+    /// Creates a forwarder that forwards all queries to `resolver`
     #[allow(clippy::new_ret_no_self)]
-    pub fn new<'a>(network: &Network, resolver: &'a Resolver) -> ForwarderSettings<'a> {
+    pub fn new(network: &Network, resolver: &Resolver) -> ForwarderSettings {
+        Self::with_upstreams(network, [resolver.ipv4_addr()])
+    }
+
+    /// Creates a forwarder that forwards all queries to the `upstreams` resolvers
+    ///
+    /// The upstream resolvers are listed in the configuration file in the order given
+    pub fn with_upstreams(
+        network: &Network,
+        upstreams: impl IntoIterator<Item = Ipv4Addr>,
+    ) -> ForwarderSettings {
         ForwarderSettings {
             network: network.clone(),
-            resolver,
+            upstreams: upstreams.into_iter().collect(),
             trust_anchor: TrustAnchor::empty(),
+            verbosity: Verbosity::default(),
+            forward_first: false,
+            query_logging: false,
+            netmasks: vec![],
+            extra_configs: vec![],
         }
     }
 
@@ -44,6 +61,13 @@ impl Forwarder {
         self.container.ipv4_addr()
     }
 
+    /// Returns the events parsed from the logs collected so far
+    ///
+    /// See [`logs::parse`] for the events each implementation logs
+    pub fn log_events(&self) -> Result<Vec<LogEvent>> {
+        Ok(logs::parse(&self.implementation, &self.logs()?))
+    }
+
     /// Returns the logs collected so far
     pub fn logs(&self) -> Result<String> {
         if self.implementation.is_hickory() {
@@ -53,6 +77,14 @@ impl Forwarder {
         }
     }
 
+    /// Streams the lines the server writes to its stdout and stderr, starting from the first one
+    pub fn logs_stream(&self) -> Result<LogStream> {
+        self.container.tail_logs(&[
+            &self.implementation.stdout_logfile(Role::Forwarder),
+            &self.implementation.stderr_logfile(Role::Forwarder),
+        ])
+    }
+
     fn stdout(&self) -> Result<String> {
         self.container
             .stdout(&["cat", &self.implementation.stdout_logfile(Role::Forwarder)])
@@ -64,13 +96,18 @@ impl Forwarder {
     }
 }
 
-pub struct ForwarderSettings<'a> {
+pub struct ForwarderSettings {
     network: Network,
-    resolver: &'a Resolver,
+    upstreams: Vec<Ipv4Addr>,
     trust_anchor: TrustAnchor,
+    verbosity: Verbosity,
+    forward_first: bool,
+    query_logging: bool,
+    netmasks: Vec<String>,
+    extra_configs: Vec<ExtraConfig>,
 }
 
-impl ForwarderSettings<'_> {
+impl ForwarderSettings {
     /// Starts a DNS server in the forwarder role.
     ///
     /// The server uses the implementation chosen by the `$DNS_TEST_SUBJECT` environment variable.
@@ -80,19 +117,41 @@ impl ForwarderSettings<'_> {
 
     /// Starts a DNS server in the forwarder role.
     pub fn start_with_subject(&self, implementation: &Implementation) -> Result<Forwarder> {
+        implementation.check_role(Role::Forwarder)?;
+
+        if self.query_logging && !implementation.supports_query_logging(Role::Forwarder) {
+            return Err(
+                format!("the {implementation} forwarder does not support logging queries").into(),
+            );
+        }
+
         let image = implementation.clone().into();
         let container = Container::run(&image, &self.network)?;
 
         let use_dnssec = !self.trust_anchor.is_empty();
+        let netmasks = if self.netmasks.is_empty() {
+            vec![self.network.netmask()]
+        } else {
+            self.netmasks.iter().map(String::as_str).collect()
+        };
+        let extra_config = ExtraConfig::snippets_for(&self.extra_configs, implementation);
         let config = Config::Forwarder {
             use_dnssec,
-            resolver_ip: self.resolver.ipv4_addr(),
+            upstreams: &self.upstreams,
+            netmasks: &netmasks,
+            forward_first: self.forward_first,
+            query_logging: self.query_logging,
+            extra_config: extra_config.as_deref(),
         };
-        let config_contents = implementation.format_config(config);
+        let config_contents = implementation.format_config(config.clone());
         if let Some(conf_file_path) = implementation.conf_file_path(Role::Forwarder) {
             container.cp(conf_file_path, &config_contents)?;
         }
 
+        for (path, contents) in implementation.extra_config_files(&config) {
+            container.cp(path, &contents)?;
+        }
+
         if use_dnssec {
             let path = if implementation.is_bind() {
                 "/etc/bind/bind.keys"
@@ -109,7 +168,13 @@ impl ForwarderSettings<'_> {
             container.cp(path, &contents)?;
         }
 
-        let child = container.spawn(&implementation.cmd_args(Role::Forwarder))?;
+        let mut child = container.spawn(&implementation.cmd_args(&config, self.verbosity))?;
+        implementation.wait_until_ready(
+            &container,
+            &mut child,
+            &config,
+            implementation.readiness_timeout(),
+        )?;
 
         Ok(Forwarder {
             container,
@@ -118,17 +183,69 @@ impl ForwarderSettings<'_> {
         })
     }
 
+    /// Adds a resolver to the end of the list of upstream resolvers
+    pub fn upstream(&mut self, upstream: Ipv4Addr) -> &mut Self {
+        self.upstreams.push(upstream);
+        self
+    }
+
+    /// Resolves queries on its own, instead of failing them, when none of the upstream resolvers
+    /// answers (BIND's `forward first`)
+    ///
+    /// By default the forwarder only forwards queries (BIND's `forward only`).
+    pub fn forward_first(&mut self) -> &mut Self {
+        self.forward_first = true;
+        self
+    }
+
+    /// Logs every query the forwarder receives, e.g. to match what a client sent with what the
+    /// forwarder received
+    pub fn query_logging(&mut self) -> &mut Self {
+        self.query_logging = true;
+        self
+    }
+
+    /// Appends the snippet of `extra_config` verbatim to the main block of the server's
+    /// configuration, for directives that have no setting of their own, if the server runs the
+    /// implementation the snippet is for
+    ///
+    /// Can be called once per implementation, or several times for the same one; the syntax,
+    /// indentation included, is that of the implementation's configuration file, e.g. TOML for
+    /// hickory
+    pub fn extra_config(&mut self, extra_config: ExtraConfig) -> &mut Self {
+        self.extra_configs.push(extra_config);
+        self
+    }
+
+    /// Allows the clients in `netmask` to query the forwarder
+    ///
+    /// When no netmask is given the forwarder serves the whole network. Otherwise it refuses
+    /// queries from clients outside the given netmasks.
+    pub fn allow_netmask(&mut self, netmask: &str) -> &mut Self {
+        self.netmasks.push(netmask.to_string());
+        self
+    }
+
+    /// Overrides how much the forwarder logs.
+    pub fn verbosity(&mut self, verbosity: Verbosity) -> &mut Self {
+        self.verbosity = verbosity;
+        self
+    }
+
     /// Adds a DNSKEY record to the trust anchor
     pub fn trust_anchor_key(&mut self, key: DNSKEY) -> &mut Self {
         self.trust_anchor.add(key.clone());
         self
     }
 
-    /// Adds all the keys in the `other` trust anchor to ours
+    /// Adds all the keys, and DS records, in the `other` trust anchor to ours
     pub fn trust_anchor(&mut self, other: &TrustAnchor) -> &mut Self {
         for key in other.keys() {
             self.trust_anchor.add(key.clone());
         }
+        for ds in other.dses() {
+            self.trust_anchor.add_ds(ds.clone());
+        }
         self
     }
 }
@@ -1,6 +1,7 @@
 use core::fmt;
 use core::str::FromStr;
 use std::borrow::Cow;
+use std::net::Ipv4Addr;
 
 use crate::{Error, Result};
 
@@ -67,6 +68,36 @@ impl FQDN {
         }
     }
 
+    /// The wildcard name that covers the names below `domain` that do not exist, e.g.
+    /// `*.example.com.` for `example.com.`
+    pub fn wildcard_of(domain: &FQDN) -> FQDN {
+        domain.push_label("*")
+    }
+
+    /// Whether the leftmost label is the asterisk label
+    pub fn is_wildcard(&self) -> bool {
+        self.last_label() == "*"
+    }
+
+    /// The name used for reverse lookups of `ipv4_addr`, e.g. `1.2.0.192.in-addr.arpa.` for
+    /// `192.0.2.1`
+    pub fn reverse(ipv4_addr: Ipv4Addr) -> FQDN {
+        let [a, b, c, d] = ipv4_addr.octets();
+        FQDN {
+            inner: format!("{d}.{c}.{b}.{a}.in-addr.arpa.").into(),
+        }
+    }
+
+    /// Whether `self` is `zone` or a subdomain of it
+    pub fn is_in_zone(&self, zone: &FQDN) -> bool {
+        zone.is_root()
+            || self == zone
+            || self
+                .as_str()
+                .strip_suffix(zone.as_str())
+                .is_some_and(|prefix| prefix.ends_with('.'))
+    }
+
     pub fn into_owned(self) -> FQDN {
         let owned = match self.inner {
             Cow::Borrowed(borrowed) => borrowed.to_string(),
@@ -154,4 +185,49 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn reverse() -> Result<()> {
+        let fqdn = FQDN::reverse(Ipv4Addr::new(192, 0, 2, 1));
+        assert_eq!(FQDN("1.2.0.192.in-addr.arpa.")?, fqdn);
+        assert!(fqdn.is_in_zone(&FQDN("2.0.192.in-addr.arpa.")?));
+        assert!(fqdn.is_in_zone(&FQDN::ROOT));
+        assert!(!fqdn.is_in_zone(&FQDN("12.0.192.in-addr.arpa.")?));
+        assert!(
+            !fqdn.is_in_zone(
+                &FQDN("2.0.192.in-addr.arpa.")?
+                    .push_label("1")
+                    .push_label("x")
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn underscore_labels() -> Result<()> {
+        let fqdn = FQDN("_ldap._tcp.example.com.")?;
+        assert_eq!(4, fqdn.num_labels());
+        assert_eq!(Some(FQDN("_tcp.example.com.")?), fqdn.parent());
+        assert_eq!(
+            fqdn,
+            FQDN("example.com.")?.push_label("_tcp").push_label("_ldap")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn wildcard() -> Result<()> {
+        let wildcard = FQDN::wildcard_of(&FQDN::TEST_DOMAIN);
+        assert_eq!(FQDN("*.hickory-dns.testing.")?, wildcard);
+        assert!(wildcard.is_wildcard());
+        assert_eq!(Some(FQDN::TEST_DOMAIN), wildcard.parent());
+        assert!("*.hickory-dns.testing.".parse::<FQDN>()?.is_wildcard());
+        assert!(!FQDN::TEST_DOMAIN.is_wildcard());
+        assert!(!FQDN("a*.hickory-dns.testing.")?.is_wildcard());
+        assert!(!FQDN::ROOT.is_wildcard());
+
+        Ok(())
+    }
 }
@@ -1,14 +1,23 @@
 use core::fmt;
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::mem;
 use std::net::Ipv4Addr;
 use std::path::Path;
+use std::process::{Command, ExitStatus, Stdio};
 use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
 
+use ipnet::Ipv6Net;
+use lazy_static::lazy_static;
+use serde::Serialize;
 use url::Url;
 
-use crate::zone_file::ZoneFile;
-use crate::{Error, FQDN};
+use crate::container::{Child, Container, Image};
+use crate::transport::{TLS_CERT_PATH, TLS_KEY_PATH};
+use crate::zone_file::{Algorithm, DenialOfExistence, ZoneFile};
+use crate::{Error, FQDN, Transport, TsigKey};
 
 #[derive(Clone)]
 pub enum Config<'a> {
@@ -16,17 +25,114 @@ pub enum Config<'a> {
         origin: &'a FQDN,
         use_dnssec: bool,
         additional_zones: HashMap<FQDN, ZoneFile>,
+        /// Largest response, in bytes, sent over UDP
+        udp_payload_size: Option<u16>,
+        /// Log every query received
+        query_logging: bool,
+        /// Key that authenticates zone transfers and dynamic updates
+        tsig_key: Option<&'a TsigKey>,
+        /// Transports the name server listens on
+        transports: &'a BTreeSet<Transport>,
+        /// Port of the plain DNS (Do53) listener
+        port: u16,
+        /// Listen on IPv6 as well as on IPv4
+        listen_ipv6: bool,
+        /// Number of worker threads; `None` keeps the implementation's default
+        threads: Option<usize>,
+        /// Secondary name servers that may transfer the zone and are notified when it changes
+        secondaries: &'a [Ipv4Addr],
+        /// Accept dynamic updates (RFC2136) of the zone, signed with `tsig_key` if set
+        allow_update: bool,
+        /// Transfer the zone from this primary name server, as a secondary, instead of loading it
+        /// from a zone file; NOTIFY messages from it trigger a new transfer
+        primary: Option<Ipv4Addr>,
+        /// Catalog zone (RFC9432): a primary serves it with the additional zones as its members; a
+        /// secondary transfers the member zones it lists
+        catalog_zone: Option<&'a FQDN>,
+        /// How the zone proves non-existence, when the server signs the zone itself
+        denial_of_existence: Option<&'a DenialOfExistence>,
+        /// Networks, one per view, whose clients are served that view's version of the zone
+        /// instead of the main one; the first view that matches the client wins
+        views: &'a [&'a str],
+        /// DNS cookies (RFC7873); `None` keeps the implementation's default
+        cookies: Option<&'a Cookies>,
+        /// Response Rate Limiting; `None` turns it off
+        rrl: Option<Rrl>,
+        /// Have the server add a ZONEMD record (RFC8976) to the zone, with a digest computed with
+        /// this scheme; `None` adds none
+        zonemd: Option<ZonemdScheme>,
+        /// How the server answers queries of type ANY (RFC8482)
+        any_policy: AnyPolicy,
+        /// Lines appended verbatim to the main block of the generated configuration, e.g. the
+        /// `options` of BIND or the `server` clause of Unbound; their syntax, indentation
+        /// included, is implementation-specific
+        extra_config: Option<&'a str>,
     },
     Resolver {
         use_dnssec: bool,
-        netmask: &'a str,
+        /// Networks whose clients are allowed to query the resolver
+        netmasks: &'a [&'a str],
+        /// Networks of the name servers the resolver may query; when empty, hickory only queries
+        /// the private IPv4 ranges and other implementations query any address
+        name_server_netmasks: &'a [&'a str],
+        /// Resolve queries; when disabled the server only answers from local data
+        recursion: bool,
         /// Extended DNS error (RFC8914)
         ede: bool,
         case_randomization: bool,
+        cache_enabled: bool,
+        /// Upper bound on the TTL of cached records, in seconds
+        max_cache_ttl: Option<u32>,
+        /// Lower bound on the TTL of cached records, in seconds
+        min_cache_ttl: Option<u32>,
+        /// EDNS UDP payload size advertised in queries, and largest response sent over UDP
+        udp_payload_size: Option<u16>,
+        /// Log every query received
+        query_logging: bool,
+        /// Transports the resolver listens on
+        transports: &'a BTreeSet<Transport>,
+        /// Port of the plain DNS (Do53) listener
+        port: u16,
+        /// Number of worker threads; `None` keeps the implementation's default
+        threads: Option<usize>,
+        /// `None` keeps the implementation's default
+        qname_minimization: Option<QnameMinimization>,
+        /// Answer from expired cache entries when the name servers are unreachable (RFC8767);
+        /// `None` keeps the implementation's default, which is not to
+        serve_stale: Option<ServeStale>,
+        /// Restricts the protocol of the queries sent to name servers; `None` lets the resolver
+        /// use both UDP and TCP
+        transport_restriction: Option<TransportRestriction>,
+        /// Synthesizes AAAA records, under this prefix, for names that only have A records
+        /// (RFC6147); `None` disables DNS64
+        dns64_prefix: Option<Ipv6Net>,
+        /// Name servers, by network, that are sent the EDNS Client Subnet option (RFC7871) of
+        /// client queries; when empty the option is not forwarded
+        edns_client_subnet: &'a [&'a str],
+        /// Networks, one per view, whose queries are forwarded to that view's resolvers instead
+        /// of being resolved; the first view that matches the client wins
+        forwarding_views: &'a [(&'a str, &'a [Ipv4Addr])],
+        /// DNS cookies (RFC7873); `None` keeps the implementation's default
+        cookies: Option<&'a Cookies>,
+        /// Lines appended verbatim to the main block of the generated configuration, e.g. the
+        /// `options` of BIND or the `server` clause of Unbound; their syntax, indentation
+        /// included, is implementation-specific
+        extra_config: Option<&'a str>,
     },
     Forwarder {
-        resolver_ip: Ipv4Addr,
+        /// Resolvers the queries are forwarded to, in order of preference
+        upstreams: &'a [Ipv4Addr],
         use_dnssec: bool,
+        /// Clients in these networks may query the forwarder; other clients are refused
+        netmasks: &'a [&'a str],
+        /// Resolve queries on its own when none of the upstreams answer, instead of failing them
+        forward_first: bool,
+        /// Log every query received
+        query_logging: bool,
+        /// Lines appended verbatim to the main block of the generated configuration, e.g. the
+        /// `options` of BIND or the `server` clause of Unbound; their syntax, indentation
+        /// included, is implementation-specific
+        extra_config: Option<&'a str>,
     },
 }
 
@@ -38,38 +144,699 @@ impl Config<'_> {
             Config::Forwarder { .. } => Role::Forwarder,
         }
     }
+
+    /// Whether the server logs every query it receives
+    pub fn query_logging(&self) -> bool {
+        match self {
+            Config::NameServer { query_logging, .. }
+            | Config::Resolver { query_logging, .. }
+            | Config::Forwarder { query_logging, .. } => *query_logging,
+        }
+    }
+
+    /// Port the server answers plain DNS (Do53) queries on
+    pub fn port(&self) -> u16 {
+        match self {
+            Config::NameServer { port, .. } | Config::Resolver { port, .. } => *port,
+            Config::Forwarder { .. } => Transport::Do53.port(),
+        }
+    }
+
+    /// Number of worker threads the server runs; `None` for the implementation's default
+    pub fn threads(&self) -> Option<usize> {
+        match self {
+            Config::NameServer { threads, .. } | Config::Resolver { threads, .. } => *threads,
+            Config::Forwarder { .. } => None,
+        }
+    }
 }
 
-#[derive(Clone, Copy)]
+lazy_static! {
+    static ref DO53: BTreeSet<Transport> = BTreeSet::from([Transport::Do53]);
+}
+
+/// Generates a setter of [`ConfigBuilder`] for `field`, which appears in all the `variants`
+///
+/// `=> Some` makes the setter take the value of an optional field
+macro_rules! setters {
+    ($(
+        $(#[$doc:meta])*
+        $($variant:ident)|+ => $field:ident: $ty:ty $(=> $wrap:ident)?;
+    )*) => {
+        $(
+            $(#[$doc])*
+            pub fn $field(&mut self, $field: $ty) -> &mut Self {
+                // fields that every role has leave the last arm unreachable
+                #[allow(unreachable_patterns)]
+                match &mut self.config {
+                    $(Config::$variant { $field: slot, .. })|+ => *slot = $($wrap)?($field),
+                    config => panic!(
+                        concat!("`", stringify!($field), "` does not apply to a {} config"),
+                        config.role()
+                    ),
+                }
+                self
+            }
+        )*
+    };
+}
+
+/// Builds a [`Config`] from the defaults of its role, so that callers only spell out the fields
+/// they care about
+///
+/// Setters of fields that the role does not have panic
+#[derive(Clone)]
+pub struct ConfigBuilder<'a> {
+    config: Config<'a>,
+}
+
+impl<'a> ConfigBuilder<'a> {
+    /// A name server for the `origin` zone that listens on Do53 and does not sign its zone
+    pub fn name_server(origin: &'a FQDN) -> Self {
+        Self {
+            config: Config::NameServer {
+                origin,
+                use_dnssec: false,
+                additional_zones: HashMap::new(),
+                udp_payload_size: None,
+                tsig_key: None,
+                transports: &DO53,
+                port: Transport::Do53.port(),
+                listen_ipv6: false,
+                threads: None,
+                secondaries: &[],
+                allow_update: false,
+                primary: None,
+                catalog_zone: None,
+                denial_of_existence: None,
+                query_logging: false,
+                views: &[],
+                cookies: None,
+                rrl: None,
+                zonemd: None,
+                any_policy: AnyPolicy::Normal,
+                extra_config: None,
+            },
+        }
+    }
+
+    /// A recursive, caching resolver that listens on Do53 and does not validate responses
+    pub fn resolver() -> Self {
+        Self {
+            config: Config::Resolver {
+                use_dnssec: false,
+                netmasks: &[],
+                name_server_netmasks: &[],
+                recursion: true,
+                ede: false,
+                case_randomization: false,
+                cache_enabled: true,
+                max_cache_ttl: None,
+                min_cache_ttl: None,
+                udp_payload_size: None,
+                transports: &DO53,
+                port: Transport::Do53.port(),
+                threads: None,
+                qname_minimization: None,
+                serve_stale: None,
+                query_logging: false,
+                transport_restriction: None,
+                dns64_prefix: None,
+                edns_client_subnet: &[],
+                forwarding_views: &[],
+                cookies: None,
+                extra_config: None,
+            },
+        }
+    }
+
+    /// A forwarder to the `upstreams` that does not validate responses
+    pub fn forwarder(upstreams: &'a [Ipv4Addr]) -> Self {
+        Self {
+            config: Config::Forwarder {
+                upstreams,
+                use_dnssec: false,
+                netmasks: &[],
+                forward_first: false,
+                query_logging: false,
+                extra_config: None,
+            },
+        }
+    }
+
+    setters! {
+        NameServer | Resolver | Forwarder => use_dnssec: bool;
+        NameServer | Resolver | Forwarder => query_logging: bool;
+        NameServer | Resolver | Forwarder => extra_config: &'a str => Some;
+        Resolver | Forwarder => netmasks: &'a [&'a str];
+        Resolver => name_server_netmasks: &'a [&'a str];
+        NameServer | Resolver => udp_payload_size: u16 => Some;
+        NameServer | Resolver => transports: &'a BTreeSet<Transport>;
+        NameServer | Resolver => port: u16;
+        NameServer | Resolver => threads: usize => Some;
+        NameServer | Resolver => cookies: &'a Cookies => Some;
+        NameServer => listen_ipv6: bool;
+        NameServer => tsig_key: &'a TsigKey => Some;
+        NameServer => secondaries: &'a [Ipv4Addr];
+        NameServer => allow_update: bool;
+        NameServer => primary: Ipv4Addr => Some;
+        NameServer => catalog_zone: &'a FQDN => Some;
+        NameServer => denial_of_existence: &'a DenialOfExistence => Some;
+        NameServer => views: &'a [&'a str];
+        NameServer => rrl: Rrl => Some;
+        NameServer => zonemd: ZonemdScheme => Some;
+        NameServer => any_policy: AnyPolicy;
+        Resolver => recursion: bool;
+        Resolver => ede: bool;
+        Resolver => case_randomization: bool;
+        Resolver => cache_enabled: bool;
+        Resolver => max_cache_ttl: u32 => Some;
+        Resolver => min_cache_ttl: u32 => Some;
+        Resolver => qname_minimization: QnameMinimization => Some;
+        Resolver => serve_stale: ServeStale => Some;
+        Resolver => transport_restriction: TransportRestriction => Some;
+        Resolver => dns64_prefix: Ipv6Net => Some;
+        Resolver => edns_client_subnet: &'a [&'a str];
+        Resolver => forwarding_views: &'a [(&'a str, &'a [Ipv4Addr])];
+        Forwarder => forward_first: bool;
+    }
+
+    /// Adds a zone, other than the origin, that the name server serves
+    pub fn additional_zone(&mut self, name: FQDN, zone_file: ZoneFile) -> &mut Self {
+        match &mut self.config {
+            Config::NameServer {
+                additional_zones, ..
+            } => {
+                additional_zones.insert(name, zone_file);
+            }
+            config => panic!(
+                "`additional_zone` does not apply to a {} config",
+                config.role()
+            ),
+        }
+        self
+    }
+
+    pub fn build(&self) -> Config<'a> {
+        self.config.clone()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Role {
     NameServer,
     Resolver,
     Forwarder,
 }
 
-#[derive(Clone, Debug)]
+impl Role {
+    pub const ALL: [Self; 3] = [Self::NameServer, Self::Resolver, Self::Forwarder];
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Role::NameServer => "name server",
+            Role::Resolver => "resolver",
+            Role::Forwarder => "forwarder",
+        };
+        f.write_str(s)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Implementation {
-    Bind,
+    Bind {
+        /// The BIND release to build; `None` uses the version packaged by the distribution
+        version: Option<String>,
+    },
     Dnslib,
     Hickory {
         repo: Repository<'static>,
         dnssec_feature: HickoryDnssecFeature,
     },
-    Unbound,
+    Knot,
+    PowerDns,
+    Unbound {
+        /// The Unbound release to build; `None` uses the default version
+        version: Option<String>,
+    },
     EdeDotCom,
 }
 
+impl Default for Implementation {
+    fn default() -> Self {
+        Self::unbound()
+    }
+}
+
 impl Implementation {
+    /// Whether the resolver can validate zones signed with the ED448 algorithm
+    pub fn supports_ed448(&self) -> bool {
+        match self {
+            Implementation::Bind { .. } => true,
+            Implementation::Dnslib => false,
+            Implementation::Hickory { .. } => false,
+            Implementation::Knot => true,
+            Implementation::PowerDns => true,
+            Implementation::Unbound { .. } => true,
+            Implementation::EdeDotCom => false, // does not support running a resolver
+        }
+    }
+
+    /// Whether the resolver validates zones signed with `algorithm`; zones signed with an
+    /// algorithm it does not support are treated as insecure
+    pub fn supports_algorithm(&self, algorithm: Algorithm) -> bool {
+        if !self.supports_role(Role::Resolver) {
+            return false;
+        }
+
+        match algorithm {
+            // deprecated (RFC8624) and not implemented by any of the resolvers
+            Algorithm::DSA | Algorithm::RSAMD5 => false,
+            // not covered by the conformance tests, so not claimed
+            Algorithm::RSASHA1_NSEC3 => false,
+            Algorithm::RSASHA256
+            | Algorithm::RSASHA512
+            | Algorithm::ECDSAP256SHA256
+            | Algorithm::ECDSAP384SHA384
+            | Algorithm::ED25519 => true,
+            Algorithm::ED448 => self.supports_ed448(),
+        }
+    }
+
     pub fn supports_ede(&self) -> bool {
         match self {
-            Implementation::Bind => false,
+            Implementation::Bind { .. } => false,
             Implementation::Dnslib => true,
             Implementation::Hickory { .. } => true,
-            Implementation::Unbound => true,
+            Implementation::Knot => true,
+            Implementation::PowerDns => true,
+            Implementation::Unbound { .. } => true,
             Implementation::EdeDotCom => false, // does not support running a resolver
         }
     }
 
+    /// Whether the server, in `role`, can log every query it receives
+    pub fn supports_query_logging(&self, role: Role) -> bool {
+        match self {
+            Implementation::Bind { .. } => true,
+            // at the info level, which `Verbosity::Quiet` filters out otherwise
+            Implementation::Hickory { .. } => true,
+            // kresd and knotd only log queries through modules this image does not set up
+            Implementation::Knot => false,
+            Implementation::PowerDns => true,
+            // NSD does not log queries
+            Implementation::Unbound { .. } => role != Role::NameServer,
+            Implementation::Dnslib | Implementation::EdeDotCom => false,
+        }
+    }
+
+    /// Builds the docker images of all the `implementations`, concurrently
+    ///
+    /// Call this before setting up a network with several implementations; otherwise their
+    /// images are built one after the other as each container starts
+    pub fn build_images<'a>(implementations: impl IntoIterator<Item = &'a Implementation>) {
+        Image::build_all(implementations.into_iter().cloned().map(Image::from))
+    }
+
+    /// Whether the implementation can run a server in the given `role`
+    pub fn supports_role(&self, role: Role) -> bool {
+        match self {
+            Implementation::Bind { .. } => true,
+            // the script is expected to answer as an authoritative name server
+            Implementation::Dnslib => role == Role::NameServer,
+            Implementation::Hickory { .. } => true,
+            Implementation::Knot => true,
+            Implementation::PowerDns => true,
+            Implementation::Unbound { .. } => true,
+            Implementation::EdeDotCom => role == Role::NameServer,
+        }
+    }
+
+    /// Whether the implementation, in the given `role`, can listen on `transport`
+    pub fn supports_transport(&self, role: Role, transport: Transport) -> bool {
+        match transport {
+            Transport::Do53 => true,
+            Transport::DoT => match self {
+                Implementation::Bind { .. } => role != Role::Forwarder,
+                Implementation::Hickory { .. } => role != Role::Forwarder,
+                // as a name server, this is NSD
+                Implementation::Unbound { .. } => role != Role::Forwarder,
+                Implementation::Dnslib
+                | Implementation::Knot
+                | Implementation::PowerDns
+                | Implementation::EdeDotCom => false,
+            },
+            Transport::DoH => match self {
+                Implementation::Hickory { .. } => role != Role::Forwarder,
+                // NSD has no DNS-over-HTTPS listener
+                Implementation::Unbound { .. } => role == Role::Resolver,
+                Implementation::Bind { .. }
+                | Implementation::Dnslib
+                | Implementation::Knot
+                | Implementation::PowerDns
+                | Implementation::EdeDotCom => false,
+            },
+        }
+    }
+
+    /// Whether the resolver can be configured to use the given QNAME minimization `mode`
+    pub fn supports_qname_minimization(&self, mode: QnameMinimization) -> bool {
+        match self {
+            Implementation::Bind { .. } => true,
+            Implementation::Hickory { .. } => mode == QnameMinimization::Off,
+            // both fall back to the full QNAME when a minimized query fails
+            Implementation::Knot | Implementation::PowerDns => mode != QnameMinimization::Strict,
+            Implementation::Unbound { .. } => true,
+            Implementation::Dnslib | Implementation::EdeDotCom => false, // do not run resolvers
+        }
+    }
+
+    /// Whether the name server can be the primary, or a secondary, of a zone that is transferred
+    /// between servers
+    pub fn supports_secondaries(&self) -> bool {
+        match self {
+            Implementation::Bind { .. } => true,
+            // as a name server, this is NSD
+            Implementation::Unbound { .. } => true,
+            Implementation::Dnslib
+            | Implementation::Hickory { .. }
+            | Implementation::Knot
+            | Implementation::PowerDns
+            | Implementation::EdeDotCom => false,
+        }
+    }
+
+    /// Whether the name server can serve, and consume as a secondary, catalog zones (RFC9432)
+    pub fn supports_catalog_zones(&self) -> bool {
+        match self {
+            Implementation::Bind { .. } => true,
+            // NSD has no catalog zone support of its own
+            Implementation::Dnslib
+            | Implementation::Hickory { .. }
+            | Implementation::Knot
+            | Implementation::PowerDns
+            | Implementation::Unbound { .. }
+            | Implementation::EdeDotCom => false,
+        }
+    }
+
+    /// Whether the server can answer clients differently depending on their network, with views
+    pub fn supports_views(&self) -> bool {
+        match self {
+            Implementation::Bind { .. } => true,
+            Implementation::Dnslib
+            | Implementation::Hickory { .. }
+            | Implementation::Knot
+            | Implementation::PowerDns
+            | Implementation::Unbound { .. }
+            | Implementation::EdeDotCom => false,
+        }
+    }
+
+    /// Whether the DNS cookies (RFC7873) of the server can be configured
+    pub fn supports_cookies(&self, role: Role) -> bool {
+        match self {
+            Implementation::Bind { .. } => true,
+            // NSD, which serves the zones of the Unbound image, has no cookie settings
+            Implementation::Unbound { .. } => role == Role::Resolver,
+            Implementation::Dnslib
+            | Implementation::Hickory { .. }
+            | Implementation::Knot
+            | Implementation::PowerDns
+            | Implementation::EdeDotCom => false,
+        }
+    }
+
+    /// Whether the server can answer BADCOOKIE to queries without a valid server cookie; see
+    /// [`Cookies::require`]
+    pub fn supports_required_cookies(&self) -> bool {
+        match self {
+            Implementation::Bind { .. } => true,
+            Implementation::Dnslib
+            | Implementation::Hickory { .. }
+            | Implementation::Knot
+            | Implementation::PowerDns
+            | Implementation::Unbound { .. }
+            | Implementation::EdeDotCom => false,
+        }
+    }
+
+    /// Whether the name server can limit the rate of its responses to a client; see [`Rrl`]
+    pub fn supports_rrl(&self) -> bool {
+        match self {
+            Implementation::Bind { .. } | Implementation::Unbound { .. } => true,
+            Implementation::Dnslib
+            | Implementation::Hickory { .. }
+            | Implementation::Knot
+            | Implementation::PowerDns
+            | Implementation::EdeDotCom => false,
+        }
+    }
+
+    /// Whether the name server logs the queries it receives, which
+    /// [`NameServer::stats`](crate::name_server::NameServer::stats) counts
+    pub fn supports_query_stats(&self) -> bool {
+        match self {
+            Implementation::Bind { .. } | Implementation::Hickory { .. } => true,
+            // NSD and Knot do not log the queries they receive
+            Implementation::Dnslib
+            | Implementation::Knot
+            | Implementation::PowerDns
+            | Implementation::Unbound { .. }
+            | Implementation::EdeDotCom => false,
+        }
+    }
+
+    /// Whether the name server can add a ZONEMD record to the zones it loads; see
+    /// [`ZonemdScheme`]
+    pub fn supports_zonemd(&self) -> bool {
+        match self {
+            Implementation::Knot => true,
+            // BIND, NSD and PowerDNS serve ZONEMD records but do not compute them
+            Implementation::Bind { .. }
+            | Implementation::Dnslib
+            | Implementation::Hickory { .. }
+            | Implementation::PowerDns
+            | Implementation::Unbound { .. }
+            | Implementation::EdeDotCom => false,
+        }
+    }
+
+    /// Whether the name server can answer queries of type ANY per `policy`
+    pub fn supports_any_policy(&self, policy: AnyPolicy) -> bool {
+        match self {
+            Implementation::Bind { .. } => policy != AnyPolicy::Refuse,
+            // NSD
+            Implementation::Unbound { .. } => policy != AnyPolicy::Minimal,
+            Implementation::Dnslib
+            | Implementation::Hickory { .. }
+            | Implementation::Knot
+            | Implementation::PowerDns
+            | Implementation::EdeDotCom => policy == AnyPolicy::Normal,
+        }
+    }
+
+    /// Whether the number of worker threads of the server can be set
+    pub fn supports_threads(&self) -> bool {
+        match self {
+            Implementation::Hickory { .. } => true,
+            Implementation::Bind { .. }
+            | Implementation::Dnslib
+            | Implementation::Knot
+            | Implementation::PowerDns
+            | Implementation::Unbound { .. }
+            | Implementation::EdeDotCom => false,
+        }
+    }
+
+    /// Whether the name server accepts dynamic updates (RFC2136) of its zone
+    pub fn supports_dynamic_update(&self) -> bool {
+        match self {
+            Implementation::Bind { .. } => true,
+            // hickory only authorizes updates signed with SIG(0), not TSIG
+            Implementation::Dnslib
+            | Implementation::Hickory { .. }
+            | Implementation::Knot
+            | Implementation::PowerDns
+            | Implementation::Unbound { .. }
+            | Implementation::EdeDotCom => false,
+        }
+    }
+
+    /// Whether the name server can reload its zone files without restarting
+    pub fn supports_reload(&self) -> bool {
+        match self {
+            Implementation::Bind { .. }
+            | Implementation::Knot
+            | Implementation::PowerDns
+            | Implementation::Unbound { .. } => true,
+            // hickory has no signal nor control channel to trigger a reload
+            Implementation::Dnslib | Implementation::Hickory { .. } | Implementation::EdeDotCom => {
+                false
+            }
+        }
+    }
+
+    /// Whether the resolver can load a new configuration without restarting
+    pub fn supports_config_reload(&self) -> bool {
+        match self {
+            // `rndc reconfig` and `unbound-control reload`, respectively
+            Implementation::Bind { .. } | Implementation::Unbound { .. } => true,
+            // kresd and the PowerDNS recursor only reload parts of their configuration
+            Implementation::Knot | Implementation::PowerDns => false,
+            // hickory has no signal nor control channel to trigger a reload
+            Implementation::Hickory { .. } => false,
+            Implementation::Dnslib | Implementation::EdeDotCom => false, // do not run resolvers
+        }
+    }
+
+    /// Whether the resolver can answer with stale data (RFC8767)
+    pub fn supports_serve_stale(&self) -> bool {
+        match self {
+            Implementation::Bind { .. } => true,
+            Implementation::Hickory { .. } => false,
+            Implementation::Knot => false,
+            Implementation::PowerDns => false,
+            Implementation::Unbound { .. } => true,
+            Implementation::Dnslib | Implementation::EdeDotCom => false, // do not run resolvers
+        }
+    }
+
+    /// Whether the resolver can be restricted to querying name servers over one protocol
+    pub fn supports_transport_restriction(&self, restriction: TransportRestriction) -> bool {
+        match self {
+            // BIND always retries truncated responses over TCP
+            Implementation::Bind { .. } => restriction == TransportRestriction::TcpOnly,
+            Implementation::Hickory { .. } => false,
+            Implementation::Knot => false,
+            Implementation::PowerDns => false,
+            Implementation::Unbound { .. } => true,
+            Implementation::Dnslib | Implementation::EdeDotCom => false, // do not run resolvers
+        }
+    }
+
+    /// Whether the resolver can synthesize AAAA records from A records (DNS64, RFC6147)
+    pub fn supports_dns64(&self) -> bool {
+        match self {
+            Implementation::Bind { .. } => true,
+            Implementation::Hickory { .. } => false,
+            Implementation::Knot => false,
+            Implementation::PowerDns => false,
+            Implementation::Unbound { .. } => true,
+            Implementation::Dnslib | Implementation::EdeDotCom => false, // do not run resolvers
+        }
+    }
+
+    /// Whether the resolver can forward the EDNS Client Subnet option (RFC7871) of client
+    /// queries to the name servers
+    pub fn supports_edns_client_subnet(&self) -> bool {
+        match self {
+            // only the subscription edition of BIND supports ECS
+            Implementation::Bind { .. } => false,
+            Implementation::Hickory { .. } => false,
+            Implementation::Knot => false,
+            Implementation::PowerDns => true,
+            Implementation::Unbound { .. } => true,
+            Implementation::Dnslib | Implementation::EdeDotCom => false, // do not run resolvers
+        }
+    }
+
+    /// Everything the implementation supports, as reported by the `supports_*` methods
+    pub fn capabilities(&self) -> Capabilities {
+        let roles = Role::ALL
+            .into_iter()
+            .filter(|role| self.supports_role(*role))
+            .map(|role| {
+                let transports = Transport::ALL
+                    .into_iter()
+                    .filter(|transport| self.supports_transport(role, *transport))
+                    .collect();
+                (role, transports)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        Capabilities {
+            dnssec_algorithms: Algorithm::ALL
+                .into_iter()
+                .filter(|algorithm| self.supports_algorithm(*algorithm))
+                .collect(),
+            qname_minimization: QnameMinimization::ALL
+                .into_iter()
+                .filter(|mode| self.supports_qname_minimization(*mode))
+                .collect(),
+            cookies: roles
+                .keys()
+                .copied()
+                .filter(|role| self.supports_cookies(*role))
+                .collect(),
+            roles,
+            ede: self.supports_ede(),
+            secondaries: self.supports_secondaries(),
+            catalog_zones: self.supports_catalog_zones(),
+            views: self.supports_views(),
+            required_cookies: self.supports_required_cookies(),
+            rrl: self.supports_rrl(),
+            zonemd: self.supports_zonemd(),
+            query_stats: self.supports_query_stats(),
+            any_policies: AnyPolicy::ALL
+                .into_iter()
+                .filter(|policy| self.supports_any_policy(*policy))
+                .collect(),
+            threads: self.supports_threads(),
+            dynamic_update: self.supports_dynamic_update(),
+            reload: self.supports_reload(),
+            config_reload: self.supports_config_reload(),
+            serve_stale: self.supports_serve_stale(),
+            query_logging: Role::ALL
+                .into_iter()
+                .filter(|role| self.supports_query_logging(*role))
+                .collect(),
+            transport_restrictions: TransportRestriction::ALL
+                .into_iter()
+                .filter(|restriction| self.supports_transport_restriction(*restriction))
+                .collect(),
+            dns64: self.supports_dns64(),
+            edns_client_subnet: self.supports_edns_client_subnet(),
+        }
+    }
+
+    /// Fails with a descriptive error if the implementation, in `role`, cannot listen on one of
+    /// the `transports`
+    pub(crate) fn check_transports(
+        &self,
+        role: Role,
+        transports: &BTreeSet<Transport>,
+    ) -> Result<(), Error> {
+        for transport in transports {
+            if !self.supports_transport(role, *transport) {
+                return Err(format!("the {self} {role} does not support {transport}").into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fails with a descriptive error if the implementation cannot run a server in `role`
+    pub(crate) fn check_role(&self, role: Role) -> Result<(), Error> {
+        if self.supports_role(role) {
+            Ok(())
+        } else {
+            Err(format!("{self} does not support the {role} role").into())
+        }
+    }
+
+    /// BIND at the default version
+    pub const fn bind() -> Self {
+        Self::Bind { version: None }
+    }
+
+    /// Unbound at the default version
+    pub const fn unbound() -> Self {
+        Self::Unbound { version: None }
+    }
+
     /// Returns the latest hickory-dns local revision
     pub fn hickory() -> Self {
         Self::Hickory {
@@ -82,12 +849,12 @@ impl Implementation {
     ///
     /// This is intended for use within `e2e-tests`, not `conformance-tests`.
     pub const fn test_peer() -> Implementation {
-        Implementation::Unbound
+        Implementation::unbound()
     }
 
     #[must_use]
     pub fn is_bind(&self) -> bool {
-        matches!(self, Self::Bind)
+        matches!(self, Self::Bind { .. })
     }
 
     #[must_use]
@@ -100,50 +867,201 @@ impl Implementation {
         matches!(self, Self::Hickory { .. })
     }
 
+    #[must_use]
+    pub fn is_knot(&self) -> bool {
+        matches!(self, Self::Knot)
+    }
+
+    #[must_use]
+    pub fn is_powerdns(&self) -> bool {
+        matches!(self, Self::PowerDns)
+    }
+
     #[must_use]
     pub fn is_unbound(&self) -> bool {
-        matches!(self, Self::Unbound)
+        matches!(self, Self::Unbound { .. })
     }
 
-    pub(crate) fn format_config(&self, config: Config) -> String {
+    /// Renders the configuration file of the server for `config`, e.g. as a starting point for
+    /// [`crate::Resolver::custom_config`]
+    pub fn format_config(&self, config: Config) -> String {
         match config {
             Config::Resolver {
                 use_dnssec,
-                netmask,
+                netmasks,
+                name_server_netmasks,
+                recursion,
                 ede,
                 case_randomization,
+                cache_enabled,
+                max_cache_ttl,
+                min_cache_ttl,
+                udp_payload_size,
+                query_logging,
+                transports,
+                port,
+                // a command line argument; see `cmd_args`
+                threads: _,
+                qname_minimization,
+                serve_stale,
+                transport_restriction,
+                dns64_prefix,
+                edns_client_subnet,
+                forwarding_views,
+                cookies,
+                extra_config,
             } => match self {
-                Self::Bind => {
+                Self::Bind { .. } => {
                     assert!(!ede, "the BIND resolver does not support EDE (RFC8914)");
+                    assert!(
+                        cache_enabled,
+                        "the BIND resolver does not support disabling the cache"
+                    );
 
                     minijinja::render!(
                         include_str!("templates/named.resolver.conf.jinja"),
+                        extra_config => extra_config,
+                        port => port,
                         use_dnssec => use_dnssec,
-                        netmask => netmask,
+                        netmasks => netmasks,
+                        recursion => recursion,
+                        max_cache_ttl => max_cache_ttl,
+                        min_cache_ttl => min_cache_ttl,
+                        udp_payload_size => udp_payload_size,
+                        query_logging => query_logging,
+                        tls => tls_files(transports),
+                        dot => transports.contains(&Transport::DoT),
+                        qname_minimization => qname_minimization,
+                        serve_stale => serve_stale,
+                        transport_restriction => transport_restriction,
+                        dns64_prefix => dns64_prefix,
+                        forwarding_views => forwarding_views,
+                        cookies => cookies,
                     )
                 }
 
                 Self::Dnslib => {
+                    assert!(
+                        extra_config.is_none(),
+                        "dnslib resolvers don't have a config"
+                    );
+
                     // Dnslib resolvers don't have a config
                     "".into()
                 }
 
+                // the port is set on the command line; see `cmd_args`
                 Self::Hickory { .. } => {
                     // TODO enable EDE in Hickory when supported
+                    assert!(
+                        recursion,
+                        "the hickory-dns resolver does not support disabling recursion"
+                    );
+                    assert!(
+                        udp_payload_size.is_none(),
+                        "the hickory-dns resolver does not support setting the UDP payload size"
+                    );
+
                     minijinja::render!(
                         include_str!("templates/hickory.resolver.toml.jinja"),
+                        extra_config => extra_config,
+                        use_dnssec => use_dnssec,
+                        netmasks => netmasks,
+                        name_server_netmasks => name_server_netmasks,
+                        case_randomization => case_randomization,
+                        cache_enabled => cache_enabled,
+                        max_cache_ttl => max_cache_ttl,
+                        min_cache_ttl => min_cache_ttl,
+                        tls => tls_files(transports),
+                        dot => transports.contains(&Transport::DoT),
+                        doh => transports.contains(&Transport::DoH),
+                    )
+                }
+
+                Self::Knot => {
+                    // Knot Resolver always reports EDE (RFC8914) so `ede` needs no setting
+                    assert!(
+                        cache_enabled,
+                        "Knot Resolver does not support disabling the cache"
+                    );
+                    assert!(!query_logging, "Knot Resolver does not log queries");
+
+                    minijinja::render!(
+                        include_str!("templates/knot-resolver.conf.jinja"),
+                        extra_config => extra_config,
+                        port => port,
+                        listen_ipv6 => listens_on_ipv6(netmasks),
                         use_dnssec => use_dnssec,
+                        netmasks => netmasks,
+                        recursion => recursion,
                         case_randomization => case_randomization,
+                        max_cache_ttl => max_cache_ttl,
+                        min_cache_ttl => min_cache_ttl,
+                        udp_payload_size => udp_payload_size,
+                        qname_minimization => qname_minimization,
+                    )
+                }
+
+                Self::PowerDns => {
+                    assert!(
+                        !case_randomization,
+                        "the PowerDNS recursor does not support case randomization"
+                    );
+                    assert!(
+                        cache_enabled,
+                        "the PowerDNS recursor does not support disabling the cache"
+                    );
+                    assert!(
+                        recursion,
+                        "the PowerDNS recursor does not support disabling recursion"
+                    );
+
+                    minijinja::render!(
+                        include_str!("templates/pdns-recursor.conf.jinja"),
+                        extra_config => extra_config,
+                        port => port,
+                        listen_ipv6 => listens_on_ipv6(netmasks),
+                        use_dnssec => use_dnssec,
+                        netmasks => netmasks,
+                        ede => ede,
+                        max_cache_ttl => max_cache_ttl,
+                        min_cache_ttl => min_cache_ttl,
+                        udp_payload_size => udp_payload_size,
+                        qname_minimization => qname_minimization,
+                        edns_client_subnet => edns_client_subnet,
                     )
                 }
 
-                Self::Unbound => {
+                Self::Unbound { .. } => {
+                    assert!(
+                        !cookies.is_some_and(|cookies| cookies.require),
+                        "the Unbound resolver does not support requiring server cookies"
+                    );
+
                     minijinja::render!(
                         include_str!("templates/unbound.conf.jinja"),
+                        extra_config => extra_config,
+                        port => port,
+                        listen_ipv6 => listens_on_ipv6(netmasks),
                         use_dnssec => use_dnssec,
-                        netmask => netmask,
+                        netmasks => netmasks,
+                        recursion => recursion,
                         ede => ede,
                         case_randomization => case_randomization,
+                        cache_enabled => cache_enabled,
+                        max_cache_ttl => max_cache_ttl,
+                        min_cache_ttl => min_cache_ttl,
+                        udp_payload_size => udp_payload_size,
+                        query_logging => query_logging,
+                        tls => tls_files(transports),
+                        dot => transports.contains(&Transport::DoT),
+                        doh => transports.contains(&Transport::DoH),
+                        qname_minimization => qname_minimization,
+                        serve_stale => serve_stale,
+                        transport_restriction => transport_restriction,
+                        dns64_prefix => dns64_prefix,
+                        edns_client_subnet => edns_client_subnet,
+                        cookies => cookies,
                     )
                 }
 
@@ -157,67 +1075,303 @@ impl Implementation {
                 origin,
                 use_dnssec,
                 additional_zones,
+                udp_payload_size,
+                // BIND name servers always log queries, for `QueryStats`; hickory and PowerDNS
+                // are told so on the command line, see `cmd_args`
+                query_logging,
+                tsig_key,
+                transports,
+                port,
+                listen_ipv6,
+                // a command line argument; see `cmd_args`
+                threads: _,
+                secondaries,
+                allow_update,
+                primary,
+                catalog_zone,
+                denial_of_existence,
+                views,
+                cookies,
+                rrl,
+                zonemd,
+                any_policy,
+                extra_config,
             } => match self {
-                Self::Bind => {
+                Self::Bind { .. } => {
                     minijinja::render!(
                         include_str!("templates/named.name-server.conf.jinja"),
+                        extra_config => extra_config,
+                        port => port,
                         fqdn => origin.as_str(),
                         additional_zones => additional_zones.keys().map(|x| x.as_str()).collect::<Vec<&str>>(),
+                        udp_payload_size => udp_payload_size,
+                        tsig_key => tsig_key,
+                        tls => tls_files(transports),
+                        dot => transports.contains(&Transport::DoT),
+                        secondaries => secondaries,
+                        primary => primary,
+                        allow_update => allow_update,
+                        catalog_zone => catalog_zone.map(FQDN::as_str),
+                        views => views,
+                        cookies => cookies,
+                        rrl => rrl,
+                        minimal_any => any_policy == AnyPolicy::Minimal,
                     )
                 }
 
                 Self::Dnslib => {
+                    assert_eq!(
+                        Transport::Do53.port(),
+                        port,
+                        "dnslib name servers only listen on the standard port"
+                    );
+
+                    assert!(
+                        extra_config.is_none(),
+                        "dnslib name servers don't have a config"
+                    );
+
                     // Dnslib name servers don't have a config
                     "".into()
                 }
 
-                Self::Unbound => {
+                Self::Unbound { .. } => {
+                    assert!(!query_logging, "the NSD name server does not log queries");
+
+                    assert!(
+                        !allow_update,
+                        "the NSD name server does not support dynamic updates"
+                    );
+                    assert!(
+                        catalog_zone.is_none(),
+                        "the NSD name server does not support catalog zones"
+                    );
+                    assert!(
+                        rrl.is_none_or(|rrl| rrl.window == 1),
+                        "the NSD name server only limits responses over a 1-second window"
+                    );
+
                     minijinja::render!(
                         include_str!("templates/nsd.conf.jinja"),
+                        extra_config => extra_config,
+                        port => port,
                         fqdn => origin.as_str(),
                         additional_zones => additional_zones.keys().map(|x| x.as_str()).collect::<Vec<&str>>(),
+                        udp_payload_size => udp_payload_size,
+                        tsig_key => tsig_key,
+                        tls => tls_files(transports),
+                        dot => transports.contains(&Transport::DoT),
+                        secondaries => secondaries,
+                        primary => primary,
+                        rrl => rrl,
+                        refuse_any => any_policy == AnyPolicy::Refuse,
                     )
                 }
 
                 Self::Hickory { dnssec_feature, .. } => {
+                    assert!(
+                        udp_payload_size.is_none(),
+                        "the hickory-dns name server does not support setting the UDP payload size"
+                    );
+                    assert!(
+                        tsig_key.is_none(),
+                        "the hickory-dns name server does not support TSIG keys"
+                    );
+                    assert!(
+                        secondaries.is_empty() && primary.is_none(),
+                        "the hickory-dns name server does not support secondaries"
+                    );
+                    assert!(
+                        !allow_update,
+                        "the hickory-dns name server does not support dynamic updates"
+                    );
+                    assert!(
+                        catalog_zone.is_none(),
+                        "the hickory-dns name server does not support catalog zones"
+                    );
+
                     let use_pkcs8 = matches!(dnssec_feature, HickoryDnssecFeature::Ring);
+                    let denial_of_existence = denial_of_existence.cloned().unwrap_or_default();
+                    let nsec3 = match &denial_of_existence {
+                        DenialOfExistence::Nsec => None,
+                        DenialOfExistence::Nsec3 {
+                            iterations,
+                            opt_out,
+                            ..
+                        } => Some(minijinja::context! {
+                            iterations,
+                            opt_out,
+                            // hickory reads the salt as an array of bytes
+                            salt => hex::decode(denial_of_existence.nsec3_salt().unwrap_or_default())
+                                .expect("NSEC3 salt must be hex-encoded"),
+                        }),
+                    };
                     minijinja::render!(
                         include_str!("templates/hickory.name-server.toml.jinja"),
+                        extra_config => extra_config,
                         fqdn => origin.as_str(),
                         use_dnssec => use_dnssec,
+                        nsec3 => nsec3,
                         additional_zones => additional_zones.keys().map(|x| x.as_str()).collect::<Vec<&str>>(),
                         use_pkcs8 => use_pkcs8,
+                        tls => tls_files(transports),
+                        dot => transports.contains(&Transport::DoT),
+                        doh => transports.contains(&Transport::DoH),
+                    )
+                }
+
+                Self::Knot => {
+                    assert!(
+                        tsig_key.is_none(),
+                        "the Knot name server does not support TSIG keys"
+                    );
+                    assert!(
+                        secondaries.is_empty() && primary.is_none(),
+                        "the Knot name server does not support secondaries"
+                    );
+                    assert!(
+                        !allow_update,
+                        "the Knot name server does not support dynamic updates"
+                    );
+                    assert!(
+                        catalog_zone.is_none(),
+                        "the Knot name server does not support catalog zones"
+                    );
+
+                    minijinja::render!(
+                        include_str!("templates/knot.name-server.conf.jinja"),
+                        extra_config => extra_config,
+                        port => port,
+                        listen_ipv6 => listen_ipv6,
+                        fqdn => origin.as_str(),
+                        additional_zones => additional_zones.keys().map(|x| x.as_str()).collect::<Vec<&str>>(),
+                        udp_payload_size => udp_payload_size,
+                        zonemd_hash => zonemd.map(ZonemdScheme::hash_name),
                     )
                 }
 
-                Self::EdeDotCom => include_str!("templates/named.ede-dot-com.conf").into(),
+                Self::PowerDns => {
+                    assert!(
+                        tsig_key.is_none(),
+                        "the PowerDNS name server does not support TSIG keys"
+                    );
+                    assert!(
+                        secondaries.is_empty() && primary.is_none(),
+                        "the PowerDNS name server does not support secondaries"
+                    );
+                    assert!(
+                        !allow_update,
+                        "the PowerDNS name server does not support dynamic updates"
+                    );
+                    assert!(
+                        catalog_zone.is_none(),
+                        "the PowerDNS name server does not support catalog zones"
+                    );
+
+                    minijinja::render!(
+                        include_str!("templates/pdns.conf.jinja"),
+                        extra_config => extra_config,
+                        port => port,
+                        listen_ipv6 => listen_ipv6,
+                        use_dnssec => use_dnssec,
+                        udp_payload_size => udp_payload_size,
+                    )
+                }
+
+                Self::EdeDotCom => {
+                    assert_eq!(
+                        Transport::Do53.port(),
+                        port,
+                        "the ede-dot-com name server only listens on the standard port"
+                    );
+                    assert!(
+                        extra_config.is_none(),
+                        "the ede-dot-com name server has a fixed configuration"
+                    );
+
+                    include_str!("templates/named.ede-dot-com.conf").into()
+                }
             },
 
             Config::Forwarder {
-                resolver_ip,
+                upstreams,
                 use_dnssec,
+                netmasks,
+                forward_first,
+                query_logging,
+                extra_config,
             } => match self {
-                Self::Bind => minijinja::render!(
+                Self::Bind { .. } => minijinja::render!(
                     include_str!("templates/named.forwarder.conf.jinja"),
-                    resolver_ip => resolver_ip,
+                    extra_config => extra_config,
+                    upstreams => upstreams,
                     use_dnssec => use_dnssec,
+                    netmasks => netmasks,
+                    forward_first => forward_first,
+                    query_logging => query_logging,
                 ),
 
                 Self::Dnslib => {
+                    assert!(extra_config.is_none(), "dnslib servers don't have a config");
+
                     // Dnslib servers don't have a config
                     "".into()
                 }
 
-                Self::Hickory { .. } => minijinja::render!(
-                    include_str!("templates/hickory.forwarder.toml.jinja"),
-                    resolver_ip => resolver_ip,
-                    use_dnssec => use_dnssec,
-                ),
+                Self::Hickory { .. } => {
+                    assert!(
+                        !forward_first,
+                        "the hickory-dns forwarder does not support forward first"
+                    );
+
+                    minijinja::render!(
+                        include_str!("templates/hickory.forwarder.toml.jinja"),
+                        extra_config => extra_config,
+                        upstreams => upstreams,
+                        use_dnssec => use_dnssec,
+                        netmasks => netmasks,
+                    )
+                }
+
+                Self::Knot => {
+                    assert!(
+                        !forward_first,
+                        "the Knot Resolver forwarder does not support forward first"
+                    );
+                    assert!(!query_logging, "Knot Resolver does not log queries");
+
+                    minijinja::render!(
+                        include_str!("templates/knot-resolver.forwarder.conf.jinja"),
+                        extra_config => extra_config,
+                        upstreams => upstreams,
+                        use_dnssec => use_dnssec,
+                        netmasks => netmasks,
+                    )
+                }
 
-                Self::Unbound => minijinja::render!(
+                Self::PowerDns => {
+                    assert!(
+                        !forward_first,
+                        "the PowerDNS forwarder does not support forward first"
+                    );
+
+                    minijinja::render!(
+                        include_str!("templates/pdns-recursor.forwarder.conf.jinja"),
+                        extra_config => extra_config,
+                        upstreams => upstreams,
+                        use_dnssec => use_dnssec,
+                        netmasks => netmasks,
+                    )
+                }
+
+                Self::Unbound { .. } => minijinja::render!(
                     include_str!("templates/unbound.forwarder.conf.jinja"),
-                    resolver_ip => resolver_ip,
+                    extra_config => extra_config,
+                    upstreams => upstreams,
                     use_dnssec => use_dnssec,
+                    netmasks => netmasks,
+                    forward_first => forward_first,
+                    query_logging => query_logging,
                 ),
 
                 Self::EdeDotCom => {
@@ -228,15 +1382,73 @@ impl Implementation {
         }
     }
 
+    /// Configuration files, other than the one at `conf_file_path`, that the server needs
+    pub(crate) fn extra_config_files(&self, config: &Config) -> Vec<(&'static str, String)> {
+        match (self, config) {
+            (
+                Self::PowerDns,
+                Config::NameServer {
+                    origin,
+                    additional_zones,
+                    ..
+                },
+            ) => {
+                // the bind backend reads the list of zones from a `named.conf`-like file
+                let zones = minijinja::render!(
+                    include_str!("templates/pdns.named.conf.jinja"),
+                    fqdn => origin.as_str(),
+                    additional_zones => additional_zones.keys().map(|x| x.as_str()).collect::<Vec<&str>>(),
+                );
+                vec![("/etc/powerdns/named.conf", zones)]
+            }
+
+            (
+                Self::PowerDns,
+                Config::Resolver {
+                    use_dnssec: true, ..
+                }
+                | Config::Forwarder {
+                    use_dnssec: true, ..
+                },
+            ) => {
+                // trust anchors can only be configured from Lua
+                vec![(
+                    "/etc/powerdns/recursor.lua",
+                    include_str!("templates/pdns-recursor.lua").into(),
+                )]
+            }
+
+            // key of the control channel that `rndc reconfig` goes through
+            (Self::Bind { .. }, Config::Resolver { .. }) => {
+                vec![(
+                    "/etc/bind/rndc.key",
+                    include_str!("templates/rndc.key").into(),
+                )]
+            }
+
+            _ => vec![],
+        }
+    }
+
     pub(crate) fn conf_file_path(&self, role: Role) -> Option<&'static str> {
         match self {
-            Self::Bind => Some("/etc/bind/named.conf"),
+            Self::Bind { .. } => Some("/etc/bind/named.conf"),
 
             Self::Dnslib => None,
 
             Self::Hickory { .. } => Some("/etc/named.toml"),
 
-            Self::Unbound => match role {
+            Self::Knot => match role {
+                Role::NameServer => Some("/etc/knot/knot.conf"),
+                Role::Resolver | Role::Forwarder => Some("/etc/knot-resolver/kresd.conf"),
+            },
+
+            Self::PowerDns => match role {
+                Role::NameServer => Some("/etc/powerdns/pdns.conf"),
+                Role::Resolver | Role::Forwarder => Some("/etc/powerdns/recursor.conf"),
+            },
+
+            Self::Unbound { .. } => match role {
                 Role::NameServer => Some("/etc/nsd/nsd.conf"),
                 Role::Resolver | Role::Forwarder => Some("/etc/unbound/unbound.conf"),
             },
@@ -245,14 +1457,122 @@ impl Implementation {
         }
     }
 
-    pub(crate) fn cmd_args(&self, role: Role) -> Vec<String> {
+    /// Command that starts the server; some implementations take part of the `config`, such as
+    /// the port, on the command line rather than in their configuration file
+    pub(crate) fn cmd_args(&self, config: &Config, verbosity: Verbosity) -> Vec<String> {
+        let role = config.role();
+        let port = config.port();
         let base = match self {
-            Implementation::Bind | Implementation::EdeDotCom => "named -g -d5",
-            Implementation::Dnslib => "python3 /script.py",
-            Implementation::Hickory { .. } => "hickory-dns -d",
-            Implementation::Unbound => match role {
-                Role::NameServer => "nsd -d",
-                Role::Resolver | Role::Forwarder => "unbound -d",
+            Implementation::Bind { .. } | Implementation::EdeDotCom => {
+                let level = match verbosity {
+                    Verbosity::Quiet => 0,
+                    Verbosity::Normal => 1,
+                    Verbosity::Debug => 5,
+                    Verbosity::Trace => 10,
+                };
+                format!("named -g -d{level}")
+            }
+
+            Implementation::Dnslib => "python3 /script.py".into(),
+
+            // hickory-dns has no option to write a pidfile so the shell writes its own PID and then
+            // `exec`s into hickory-dns
+            Implementation::Hickory { .. } => {
+                let args = match verbosity {
+                    Verbosity::Quiet => "-q",
+                    Verbosity::Normal => "",
+                    Verbosity::Debug => "-d",
+                    // overrides the `hickory_=debug` directive added by `-d`
+                    Verbosity::Trace => "-d",
+                };
+                // the server logs each request at the info level
+                let env = if verbosity == Verbosity::Trace {
+                    "RUST_LOG=hickory_=trace "
+                } else if config.query_logging() {
+                    "RUST_LOG=hickory_server::server=info "
+                } else {
+                    ""
+                };
+                let port = if port == Transport::Do53.port() {
+                    String::new()
+                } else {
+                    format!(" --port {port}")
+                };
+                // the size of the tokio runtime; the configuration file has no setting for it
+                let workers = config
+                    .threads()
+                    .map(|threads| format!(" --workers {threads}"))
+                    .unwrap_or_default();
+                format!("echo $$ > /tmp/hickory.pid && {env}exec hickory-dns {args}{port}{workers}")
+            }
+
+            Implementation::Knot => {
+                let args = match verbosity {
+                    Verbosity::Quiet | Verbosity::Normal => "",
+                    Verbosity::Debug | Verbosity::Trace => "-v",
+                };
+                match role {
+                    Role::NameServer => format!("knotd {args} -c /etc/knot/knot.conf"),
+                    // kresd does not write a pidfile either
+                    Role::Resolver | Role::Forwarder => format!(
+                        "echo $$ > /tmp/kresd.pid && \
+                        exec kresd -n {args} -c /etc/knot-resolver/kresd.conf /tmp"
+                    ),
+                }
+            }
+
+            Implementation::PowerDns => {
+                let level = match verbosity {
+                    Verbosity::Quiet => 3,
+                    Verbosity::Normal => 4,
+                    Verbosity::Debug | Verbosity::Trace => 7,
+                };
+                let trace = verbosity == Verbosity::Trace;
+                match role {
+                    Role::NameServer => {
+                        let args = if trace {
+                            " --log-dns-queries=yes --log-dns-details=yes"
+                        } else if config.query_logging() {
+                            " --log-dns-queries=yes"
+                        } else {
+                            ""
+                        };
+                        format!(
+                            "pdns_server --daemon=no --guardian=no --disable-syslog \
+                            --loglevel={level}{args}"
+                        )
+                    }
+                    Role::Resolver | Role::Forwarder => {
+                        let args = if trace || config.query_logging() {
+                            " --quiet=no"
+                        } else {
+                            ""
+                        };
+                        format!(
+                            "pdns_recursor --daemon=no --disable-syslog --loglevel={level}{args}"
+                        )
+                    }
+                }
+            }
+
+            Implementation::Unbound { .. } => match role {
+                Role::NameServer => {
+                    let args = if verbosity == Verbosity::Trace {
+                        " -V 2"
+                    } else {
+                        ""
+                    };
+                    format!("nsd -d{args}")
+                }
+                Role::Resolver | Role::Forwarder => {
+                    let args = match verbosity {
+                        Verbosity::Quiet => "",
+                        Verbosity::Normal => " -v",
+                        Verbosity::Debug => " -vvvv",
+                        Verbosity::Trace => " -vvvvv",
+                    };
+                    format!("unbound -d{args}")
+                }
             },
         };
 
@@ -267,6 +1587,144 @@ impl Implementation {
         ]
     }
 
+    /// Path, inside the container, of the file where the server process writes its PID; `None`
+    /// if the server does not write one
+    pub fn pidfile(&self, role: Role) -> Option<&'static str> {
+        let path = match self {
+            Implementation::Bind { .. } | Implementation::EdeDotCom => "/tmp/named.pid",
+
+            // dnslib does not write a pidfile
+            Implementation::Dnslib => return None,
+
+            Implementation::Hickory { .. } => "/tmp/hickory.pid",
+
+            Implementation::Knot => match role {
+                Role::NameServer => "/tmp/knot.pid",
+                Role::Resolver | Role::Forwarder => "/tmp/kresd.pid",
+            },
+
+            // written to `socket-dir`
+            Implementation::PowerDns => match role {
+                Role::NameServer => "/tmp/pdns.pid",
+                Role::Resolver | Role::Forwarder => "/tmp/pdns_recursor.pid",
+            },
+
+            Implementation::Unbound { .. } => match role {
+                Role::NameServer => "/tmp/nsd.pid",
+                Role::Resolver | Role::Forwarder => "/tmp/unbound.pid",
+            },
+        };
+
+        Some(path)
+    }
+
+    /// How long the server may take to answer its first query; see
+    /// [`Implementation::wait_until_ready`]
+    ///
+    /// Packaged implementations are up within a second or two; hickory-dns gets more leeway as its
+    /// image holds a debug build, which is slower to start, e.g. when it signs its zones
+    pub fn readiness_timeout(&self) -> Duration {
+        match self {
+            Implementation::Hickory { .. } => Duration::from_secs(60),
+            Implementation::Bind { .. }
+            | Implementation::Dnslib
+            | Implementation::Knot
+            | Implementation::PowerDns
+            | Implementation::Unbound { .. }
+            | Implementation::EdeDotCom => Duration::from_secs(15),
+        }
+    }
+
+    /// Blocks until the server, the `child` process running in `container`, answers queries
+    ///
+    /// The server is probed from inside its own container: name servers are sent a SOA query for
+    /// their origin; resolvers and forwarders a non-recursive query for the NS records of the
+    /// root. Any answer, even an error, means the server is listening. Gives up after `timeout`,
+    /// or as soon as the server exits, with an error that includes the server's logs
+    pub(crate) fn wait_until_ready(
+        &self,
+        container: &Container,
+        child: &mut Child,
+        config: &Config,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        let role = config.role();
+        let logs = || container.stdout(&["cat", &self.stderr_logfile(role)]);
+        let exited = |status: ExitStatus| -> Error {
+            format!(
+                "the {self} {role} exited before it was ready: {status}; logs: {:?}",
+                logs(),
+            )
+            .into()
+        };
+
+        // dnslib scripts may only answer the queries the test is about, so only make sure the
+        // python interpreter is still running after two seconds
+        if let Implementation::Dnslib = self {
+            thread::sleep(Duration::from_secs(2));
+
+            return match child.try_wait() {
+                Ok(None) => Ok(()), // the process is still running
+                Ok(Some(status)) => Err(exited(status)),
+                Err(e) => Err(format!("unable to determine if dnslib started: {e}").into()),
+            };
+        }
+
+        let (fqdn, record_type) = match config {
+            Config::NameServer { origin, .. } => (origin.as_str(), "SOA"),
+            Config::Resolver { .. } | Config::Forwarder { .. } => (".", "NS"),
+        };
+        let server = format!("@{}", container.ipv4_addr());
+        let port = config.port().to_string();
+        // TCP, unlike UDP, fails right away when the server is not listening yet
+        let probe = match self {
+            // the image only has the tools that come with BIND
+            Implementation::EdeDotCom => vec![
+                "dig",
+                "+tcp",
+                "+norecurse",
+                "+tries=1",
+                "+time=1",
+                "-p",
+                &port,
+                &server,
+                fqdn,
+                record_type,
+            ],
+            _ => vec![
+                "drill",
+                "-t",
+                "-o",
+                "rd",
+                "-p",
+                &port,
+                &server,
+                fqdn,
+                record_type,
+            ],
+        };
+
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            if let Some(status) = child.try_wait()? {
+                return Err(exited(status));
+            }
+
+            if container.output(&probe)?.status.success() {
+                return Ok(());
+            }
+
+            thread::sleep(Duration::from_millis(100));
+        }
+
+        Err(format!(
+            "the {self} {role} did not answer queries within {} seconds; logs: {:?}",
+            timeout.as_secs(),
+            logs(),
+        )
+        .into())
+    }
+
     pub(crate) fn stdout_logfile(&self, role: Role) -> String {
         self.logfile(role, Stream::Stdout)
     }
@@ -279,13 +1737,23 @@ impl Implementation {
         let suffix = stream.as_str();
 
         let path = match self {
-            Implementation::Bind | Implementation::EdeDotCom => "/tmp/named",
+            Implementation::Bind { .. } | Implementation::EdeDotCom => "/tmp/named",
 
             Implementation::Dnslib => "/tmp/dnslib",
 
             Implementation::Hickory { .. } => "/tmp/hickory",
 
-            Implementation::Unbound => match role {
+            Implementation::Knot => match role {
+                Role::NameServer => "/tmp/knotd",
+                Role::Resolver | Role::Forwarder => "/tmp/kresd",
+            },
+
+            Implementation::PowerDns => match role {
+                Role::NameServer => "/tmp/pdns",
+                Role::Resolver | Role::Forwarder => "/tmp/pdns_recursor",
+            },
+
+            Implementation::Unbound { .. } => match role {
                 Role::NameServer => "/tmp/nsd",
                 Role::Resolver | Role::Forwarder => "/tmp/unbound",
             },
@@ -295,8 +1763,396 @@ impl Implementation {
     }
 }
 
+/// BIND and hickory-dns listen on IPv6 addresses by default; the other resolvers need to be told
+/// to, but only when their container has IPv6 connectivity
+fn listens_on_ipv6(netmasks: &[&str]) -> bool {
+    netmasks.iter().any(|netmask| netmask.contains(':'))
+}
+
+/// Certificate and private key of the DNS-over-TLS and DNS-over-HTTPS listeners, if any is
+/// enabled
+#[derive(Serialize)]
+struct TlsFiles {
+    cert: &'static str,
+    key: &'static str,
+}
+
+fn tls_files(transports: &BTreeSet<Transport>) -> Option<TlsFiles> {
+    transports
+        .iter()
+        .any(Transport::uses_tls)
+        .then_some(TlsFiles {
+            cert: TLS_CERT_PATH,
+            key: TLS_KEY_PATH,
+        })
+}
+
+impl fmt::Display for Implementation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bind { version } => {
+                f.write_str("bind")?;
+                if let Some(version) = version {
+                    write!(f, "@{version}")?;
+                }
+                Ok(())
+            }
+            Self::Dnslib => f.write_str("dnslib"),
+            Self::Hickory { dnssec_feature, .. } => write!(f, "hickory-{dnssec_feature}"),
+            Self::Knot => f.write_str("knot"),
+            Self::PowerDns => f.write_str("powerdns"),
+            Self::Unbound { version } => {
+                f.write_str("unbound")?;
+                if let Some(version) = version {
+                    write!(f, "@{version}")?;
+                }
+                Ok(())
+            }
+            Self::EdeDotCom => f.write_str("ede-dot-com"),
+        }
+    }
+}
+
+impl FromStr for Implementation {
+    type Err = Error;
+
+    /// Parses the `Display` form of an implementation, e.g. `bind@9.18.0`, ignoring the case of
+    /// its name
+    ///
+    /// hickory-dns is also accepted as `hickory` (the local checkout), `hickory:$REPO` or
+    /// `hickory $URL $DNSSEC_FEATURE [$REV]`
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let lowercase = input.to_ascii_lowercase();
+
+        if let Some(repo) = lowercase.strip_prefix("hickory:") {
+            if repo.is_empty() {
+                return Err("`hickory:` must be followed by a repository".into());
+            }
+            // keep the original case of the repository
+            let repo = &input[input.len() - repo.len()..];
+            return Ok(Self::Hickory {
+                repo: Repository(repo.to_string()),
+                dnssec_feature: HickoryDnssecFeature::AwsLcRs,
+            });
+        }
+
+        if lowercase.starts_with("hickory ") {
+            let tokens = input.split_ascii_whitespace().collect::<Vec<_>>();
+            let (url, dnssec_feature, rev) = match tokens[..] {
+                [_, url, dnssec_feature] => (url, dnssec_feature, None),
+                [_, url, dnssec_feature, rev] => (url, dnssec_feature, Some(rev)),
+                _ => {
+                    return Err("the syntax is 'hickory $URL $DNSSEC_FEATURE [$REV]', e.g. \
+                        'hickory /tmp/hickory dnssec-aws-lc-rs' or \
+                        'hickory https://github.com/owner/repo dnssec-ring v0.24.1'"
+                        .into());
+                }
+            };
+            let repo = match rev {
+                Some(rev) => Repository::with_rev(url.to_string(), rev.to_string()),
+                None => Repository(url.to_string()),
+            };
+            return Ok(Self::Hickory {
+                repo,
+                dnssec_feature: dnssec_feature.parse()?,
+            });
+        }
+
+        if let Some(dnssec_feature) = lowercase.strip_prefix("hickory-") {
+            return Ok(Self::Hickory {
+                repo: Repository(crate::repo_root()),
+                dnssec_feature: dnssec_feature.parse()?,
+            });
+        }
+
+        let (name, version) = match lowercase.split_once('@') {
+            Some((name, "")) => {
+                return Err(format!("`{name}@` must be followed by a version").into());
+            }
+            Some((name, _)) => (name, Some(input[name.len() + 1..].to_string())),
+            None => (lowercase.as_str(), None),
+        };
+
+        let implementation = match (name, version) {
+            ("bind", version) => Self::Bind { version },
+            ("unbound", version) => Self::Unbound { version },
+            ("dnslib", None) => Self::Dnslib,
+            ("hickory", None) => Self::hickory(),
+            ("knot", None) => Self::Knot,
+            ("powerdns", None) => Self::PowerDns,
+            ("ede-dot-com", None) => Self::EdeDotCom,
+            _ => return Err(format!("unknown implementation: {input}").into()),
+        };
+
+        Ok(implementation)
+    }
+}
+
+/// QNAME minimization (RFC9156): how much of the name being resolved a resolver reveals to the
+/// name servers of the ancestor zones
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QnameMinimization {
+    /// Every query carries the full QNAME
+    Off,
+    /// Minimized queries, falling back to the full QNAME when a name server answers them with an
+    /// error
+    Relaxed,
+    /// Minimized queries only
+    Strict,
+}
+
+impl QnameMinimization {
+    pub const ALL: [Self; 3] = [Self::Off, Self::Relaxed, Self::Strict];
+}
+
+impl fmt::Display for QnameMinimization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Off => "off",
+            Self::Relaxed => "relaxed",
+            Self::Strict => "strict",
+        })
+    }
+}
+
+/// Serving stale data (RFC8767) settings
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct ServeStale {
+    /// For how long, in seconds, past their expiration cached records may still be served
+    pub ttl: u32,
+    /// For how long, in milliseconds, to wait for the name servers before answering with stale
+    /// data
+    pub client_timeout: u32,
+}
+
+/// The only protocol a resolver queries name servers over
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransportRestriction {
+    /// Every query goes over TCP, as if UDP were blocked
+    TcpOnly,
+    /// Every query goes over UDP and truncated responses are not retried over TCP; Unbound also
+    /// stops accepting queries from clients over TCP
+    UdpOnly,
+}
+
+impl TransportRestriction {
+    pub const ALL: [Self; 2] = [Self::TcpOnly, Self::UdpOnly];
+}
+
+impl fmt::Display for TransportRestriction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::TcpOnly => "TCP-only",
+            Self::UdpOnly => "UDP-only",
+        })
+    }
+}
+
+/// DNS cookie (RFC7873) settings
+#[derive(Clone, Debug, Serialize)]
+pub struct Cookies {
+    /// Add a server cookie to the responses to queries that carry a client cookie
+    pub answer: bool,
+    /// Answer UDP queries that carry a client cookie but no valid server cookie with BADCOOKIE
+    /// and a fresh server cookie, so that the client retries with it or over TCP
+    pub require: bool,
+    /// Secret, as 32 hexadecimal digits, that server cookies are derived from; `None` lets the
+    /// server generate a random one on startup
+    pub secret: Option<String>,
+}
+
+/// Response Rate Limiting settings of a name server
+///
+/// Past the limit, responses to a client are alternately dropped and truncated, to make a
+/// legitimate client retry over TCP; this is the default "slip" of both BIND and NSD
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct Rrl {
+    /// Identical responses sent to a client network, per second, before responses get limited
+    pub responses_per_second: u32,
+    /// Number of seconds over which the rate of responses is averaged; NSD only supports 1
+    pub window: u32,
+}
+
+/// How a name server answers queries of type ANY
+///
+/// RFC8482 lets a server answer them with a subset of the records of the name, to make them
+/// useless for amplification attacks
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AnyPolicy {
+    /// All the records of the name
+    #[default]
+    Normal,
+    /// A single RRset of the name, over UDP; BIND's `minimal-any`
+    Minimal,
+    /// An empty, truncated, response over UDP, which makes the client retry over TCP, where the
+    /// query is answered normally; NSD's `refuse-any`
+    Refuse,
+}
+
+impl AnyPolicy {
+    pub const ALL: [Self; 3] = [Self::Normal, Self::Minimal, Self::Refuse];
+}
+
+impl fmt::Display for AnyPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Normal => "normal",
+            Self::Minimal => "minimal",
+            Self::Refuse => "refuse",
+        })
+    }
+}
+
+/// How the digest of a ZONEMD record (RFC8976) is computed
+///
+/// ZONEMD records of signed zones are added by [`SignSettings::zonemd`] instead, as the signer,
+/// not the name server, has to cover them with an RRSIG
+///
+/// [`SignSettings::zonemd`]: crate::zone_file::SignSettings::zonemd
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZonemdScheme {
+    /// The SIMPLE scheme, with a SHA-384 digest
+    SimpleSha384,
+}
+
+impl ZonemdScheme {
+    /// Scheme field of the ZONEMD record
+    pub fn scheme(self) -> u8 {
+        match self {
+            Self::SimpleSha384 => 1,
+        }
+    }
+
+    /// Hash algorithm field of the ZONEMD record
+    pub fn hash_algorithm(self) -> u8 {
+        match self {
+            Self::SimpleSha384 => 1,
+        }
+    }
+
+    fn hash_name(self) -> &'static str {
+        match self {
+            Self::SimpleSha384 => "sha384",
+        }
+    }
+}
+
+/// A configuration snippet that only applies to one implementation
+///
+/// The snippet is appended verbatim to the main block of the implementation's configuration,
+/// e.g. `options {}` for BIND, `server:` for Unbound and the top-level keys for hickory, so a test
+/// can carry the snippets of several implementations side by side. Versions and repositories are
+/// not compared: a snippet for `Implementation::bind()` also applies to other BIND releases
+#[derive(Clone, Debug)]
+pub struct ExtraConfig {
+    implementation: Implementation,
+    snippet: String,
+}
+
+impl ExtraConfig {
+    pub fn for_implementation(implementation: &Implementation, snippet: &str) -> Self {
+        Self {
+            implementation: implementation.clone(),
+            snippet: snippet.to_string(),
+        }
+    }
+
+    /// The snippets of `extra_configs` that apply to `implementation`, one after the other
+    pub(crate) fn snippets_for(
+        extra_configs: &[Self],
+        implementation: &Implementation,
+    ) -> Option<String> {
+        let snippets = extra_configs
+            .iter()
+            .filter(|extra_config| {
+                mem::discriminant(&extra_config.implementation) == mem::discriminant(implementation)
+            })
+            .map(|extra_config| extra_config.snippet.trim_end_matches('\n'))
+            .collect::<Vec<_>>();
+
+        (!snippets.is_empty()).then(|| snippets.join("\n"))
+    }
+}
+
+/// What an implementation supports; see [`Implementation::capabilities`]
+///
+/// Lets a test skip the scenarios the implementation under test cannot run, rather than
+/// hardcoding which implementation supports what
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Roles the implementation can run a server in, with the transports it listens on in each
+    pub roles: BTreeMap<Role, BTreeSet<Transport>>,
+    /// DNSSEC algorithms the resolver validates
+    pub dnssec_algorithms: Vec<Algorithm>,
+    /// QNAME minimization modes the resolver can be configured with
+    pub qname_minimization: Vec<QnameMinimization>,
+    /// Resolver answers carry Extended DNS Errors (RFC8914)
+    pub ede: bool,
+    /// The name server can be the primary, or a secondary, of a transferred zone
+    pub secondaries: bool,
+    /// The name server can serve, and consume as a secondary, catalog zones (RFC9432)
+    pub catalog_zones: bool,
+    /// The server can answer clients differently depending on their network
+    pub views: bool,
+    /// Roles in which the DNS cookies (RFC7873) of the server can be configured
+    pub cookies: BTreeSet<Role>,
+    /// The server can answer BADCOOKIE to queries without a valid server cookie
+    pub required_cookies: bool,
+    /// The name server can limit the rate of its responses; see [`Rrl`]
+    pub rrl: bool,
+    /// The name server can add a ZONEMD record to its zones; see [`ZonemdScheme`]
+    pub zonemd: bool,
+    /// How the name server can be configured to answer queries of type ANY
+    pub any_policies: Vec<AnyPolicy>,
+    /// The name server logs the queries it receives, so that they can be counted
+    pub query_stats: bool,
+    /// The number of worker threads of the server can be set
+    pub threads: bool,
+    /// The name server accepts dynamic updates (RFC2136)
+    pub dynamic_update: bool,
+    /// The name server can reload its zone files without restarting
+    pub reload: bool,
+    /// The resolver can load a new configuration without restarting
+    pub config_reload: bool,
+    /// The resolver can answer with stale data (RFC8767)
+    pub serve_stale: bool,
+    /// Roles in which the server can log every query it receives
+    pub query_logging: Vec<Role>,
+    /// Protocols the resolver can be restricted to when querying name servers
+    pub transport_restrictions: Vec<TransportRestriction>,
+    /// The resolver can synthesize AAAA records from A records (DNS64, RFC6147)
+    pub dns64: bool,
+    /// The resolver can forward the EDNS Client Subnet option (RFC7871)
+    pub edns_client_subnet: bool,
+}
+
+impl Capabilities {
+    /// Whether the implementation can run a server in `role` that listens on `transport`
+    pub fn supports(&self, role: Role, transport: Transport) -> bool {
+        self.roles
+            .get(&role)
+            .is_some_and(|transports| transports.contains(&transport))
+    }
+}
+
+/// How much a server logs
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Verbosity {
+    /// Errors only
+    Quiet,
+    /// The server's default log level
+    Normal,
+    /// Debug messages
+    #[default]
+    Debug,
+    /// Everything the server is able to log
+    Trace,
+}
+
 /// A Hickory DNS Cargo feature used to enable DNSSEC with a particular cryptography library.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum HickoryDnssecFeature {
     AwsLcRs,
     Ring,
@@ -340,15 +2196,172 @@ impl Stream {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Repository<'a> {
     inner: Cow<'a, str>,
+    checkout: Checkout<'a>,
+    /// Cargo features of `hickory-dns` to enable on top of the ones the tests need
+    features: Vec<String>,
+    no_default_features: bool,
+}
+
+/// What gets built out of a `Repository`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Checkout<'a> {
+    /// The last commit of the default branch
+    Head,
+    /// The last commit of a branch, or the commit a tag points to
+    Ref(Cow<'a, str>),
+    /// A branch, a tag or a commit hash
+    Rev(Cow<'a, str>),
+    /// The files in a local working tree, including uncommitted changes
+    WorkTree,
+}
+
+impl Repository<'static> {
+    /// Like `Repository` but builds `rev`, which can be a branch, a tag or a commit hash, instead
+    /// of the default branch
+    ///
+    /// # Panics
+    ///
+    /// this function panics if `input` is not a local `Path` that exists or a well-formed URL
+    pub fn with_rev(
+        input: impl Into<Cow<'static, str>>,
+        rev: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        Self {
+            checkout: Checkout::Rev(rev.into()),
+            ..Repository(input)
+        }
+    }
+
+    /// Like `Repository` but builds the last commit of `branch` instead of the default branch
+    ///
+    /// # Panics
+    ///
+    /// this function panics if `input` is not a local `Path` that exists or a well-formed URL
+    pub fn with_branch(
+        input: impl Into<Cow<'static, str>>,
+        branch: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        Self {
+            checkout: Checkout::Ref(branch.into()),
+            ..Repository(input)
+        }
+    }
+
+    /// Like `Repository` but builds the commit `tag` points to instead of the default branch
+    ///
+    /// # Panics
+    ///
+    /// this function panics if `input` is not a local `Path` that exists or a well-formed URL
+    pub fn with_tag(
+        input: impl Into<Cow<'static, str>>,
+        tag: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        Self {
+            checkout: Checkout::Ref(tag.into()),
+            ..Repository(input)
+        }
+    }
+
+    /// Builds the working tree at `path` as it is, uncommitted changes included
+    ///
+    /// Files ignored by git, like `target/`, are not copied into the build context
+    ///
+    /// # Panics
+    ///
+    /// this function panics if `path` is not an existing directory
+    pub fn local_worktree(path: impl Into<Cow<'static, str>>) -> Self {
+        let path = path.into();
+        assert!(
+            Path::new(&*path).is_dir(),
+            "{path} is not a local working tree"
+        );
+        Self {
+            inner: path,
+            checkout: Checkout::WorkTree,
+            features: vec![],
+            no_default_features: false,
+        }
+    }
+
+    /// Also enables `features` when building `hickory-dns`
+    ///
+    /// The `recursor` feature and the `HickoryDnssecFeature` are always enabled
+    pub fn with_features(mut self, features: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.features.extend(features.into_iter().map(Into::into));
+        self
+    }
+
+    /// Builds `hickory-dns` with `--no-default-features`
+    pub fn no_default_features(mut self) -> Self {
+        self.no_default_features = true;
+        self
+    }
 }
 
 impl Repository<'_> {
+    /// Checks that the repository can be reached and that the branch or tag to build exists
+    ///
+    /// The constructors only check that a remote repository is a well-formed URL. This runs `git
+    /// ls-remote` instead, which catches typos before any container is built. Commit hashes are
+    /// not checked because `ls-remote` only lists refs
+    pub fn validate_reachable(&self) -> Result<(), Error> {
+        let rev = match &self.checkout {
+            Checkout::Ref(rev) => rev,
+            Checkout::Head | Checkout::Rev(_) | Checkout::WorkTree => "HEAD",
+        };
+
+        let output = Command::new("git")
+            .args(["ls-remote", "--exit-code", self.as_str(), rev])
+            // fail instead of asking for credentials, which GitHub does for non-existent repositories
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| format!("could not run `git ls-remote`: {e}"))?;
+
+        if output.status.success() {
+            return Ok(());
+        }
+
+        // `--exit-code` makes `ls-remote` exit with status 2 when no ref matches
+        let reason = if output.status.code() == Some(2) {
+            format!("{rev} was not found")
+        } else {
+            String::from_utf8_lossy(&output.stderr).trim().to_string()
+        };
+        Err(format!("repository {} is not reachable: {reason}", self.as_str()).into())
+    }
+
     pub(crate) fn as_str(&self) -> &str {
         &self.inner
     }
+
+    /// The branch, tag or commit hash that gets built, if not the default branch
+    pub(crate) fn rev(&self) -> Option<&str> {
+        match &self.checkout {
+            Checkout::Ref(rev) | Checkout::Rev(rev) => Some(rev),
+            Checkout::Head | Checkout::WorkTree => None,
+        }
+    }
+
+    /// Whether `rev` names a branch or a tag, which, unlike commit hashes, can be shallow cloned
+    pub(crate) fn is_ref(&self) -> bool {
+        matches!(self.checkout, Checkout::Ref(_))
+    }
+
+    pub(crate) fn is_worktree(&self) -> bool {
+        matches!(self.checkout, Checkout::WorkTree)
+    }
+
+    pub(crate) fn features(&self) -> &[String] {
+        &self.features
+    }
+
+    pub(crate) fn uses_default_features(&self) -> bool {
+        !self.no_default_features
+    }
 }
 
 /// checks that `input` looks like a valid repository which can be either local or remote
@@ -363,11 +2376,806 @@ pub fn Repository(input: impl Into<Cow<'static, str>>) -> Repository<'static> {
         Path::new(&*input).exists() || Url::parse(&input).is_ok(),
         "{input} is not a valid repository"
     );
-    Repository { inner: input }
+    Repository {
+        inner: input,
+        checkout: Checkout::Head,
+        features: vec![],
+        no_default_features: false,
+    }
 }
 
-impl Default for Implementation {
-    fn default() -> Self {
-        Self::Unbound
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn supports_role() {
+        let implementations = [
+            Implementation::bind(),
+            Implementation::Dnslib,
+            Implementation::hickory(),
+            Implementation::Knot,
+            Implementation::PowerDns,
+            Implementation::unbound(),
+            Implementation::EdeDotCom,
+        ];
+        let roles = [Role::NameServer, Role::Resolver, Role::Forwarder];
+
+        for implementation in &implementations {
+            for role in roles {
+                if implementation.supports_role(role) {
+                    assert!(implementation.check_role(role).is_ok());
+                } else {
+                    let error = implementation.check_role(role).unwrap_err();
+                    assert_eq!(
+                        format!("{implementation} does not support the {role} role"),
+                        error.to_string()
+                    );
+                }
+            }
+        }
+
+        assert!(!Implementation::EdeDotCom.supports_role(Role::Resolver));
+        assert!(Implementation::hickory().supports_role(Role::NameServer));
+    }
+
+    #[test]
+    fn query_logging() {
+        let resolver = ConfigBuilder::resolver().query_logging(true).build();
+        let forwarder = ConfigBuilder::forwarder(&[]).query_logging(true).build();
+
+        for config in [resolver.clone(), forwarder.clone()] {
+            let unbound_conf = Implementation::unbound().format_config(config.clone());
+            assert!(unbound_conf.contains("log-queries: yes"));
+
+            let named_conf = Implementation::bind().format_config(config);
+            assert!(named_conf.contains("querylog yes;"));
+        }
+
+        let config = ConfigBuilder::resolver().build();
+        assert!(
+            !Implementation::unbound()
+                .format_config(config.clone())
+                .contains("log-queries")
+        );
+        assert!(
+            !Implementation::bind()
+                .format_config(config.clone())
+                .contains("querylog")
+        );
+
+        let cmd_args = Implementation::PowerDns.cmd_args(&resolver, Verbosity::Normal);
+        assert!(
+            cmd_args.iter().any(|arg| arg.contains("--quiet=no")),
+            "{cmd_args:?}"
+        );
+
+        for config in [&resolver, &forwarder] {
+            let cmd_args = Implementation::hickory().cmd_args(config, Verbosity::Normal);
+            assert!(
+                cmd_args
+                    .iter()
+                    .any(|arg| arg.contains("RUST_LOG=hickory_server::server=info ")),
+                "{cmd_args:?}"
+            );
+        }
+        let cmd_args = Implementation::hickory().cmd_args(&config, Verbosity::Normal);
+        assert!(
+            !cmd_args.iter().any(|arg| arg.contains("RUST_LOG")),
+            "{cmd_args:?}"
+        );
+
+        assert!(Implementation::unbound().supports_query_logging(Role::Resolver));
+        assert!(!Implementation::unbound().supports_query_logging(Role::NameServer));
+    }
+
+    #[test]
+    fn capabilities() {
+        let implementations = [
+            Implementation::bind(),
+            Implementation::Dnslib,
+            Implementation::hickory(),
+            Implementation::Knot,
+            Implementation::PowerDns,
+            Implementation::unbound(),
+            Implementation::EdeDotCom,
+        ];
+
+        for implementation in &implementations {
+            let capabilities = implementation.capabilities();
+            for role in Role::ALL {
+                assert_eq!(
+                    implementation.supports_role(role),
+                    capabilities.roles.contains_key(&role)
+                );
+                for transport in Transport::ALL {
+                    assert_eq!(
+                        implementation.supports_role(role)
+                            && implementation.supports_transport(role, transport),
+                        capabilities.supports(role, transport),
+                        "{implementation} {role} over {transport}"
+                    );
+                }
+            }
+            assert_eq!(implementation.supports_rrl(), capabilities.rrl);
+            assert!(
+                !capabilities.config_reload || capabilities.roles.contains_key(&Role::Resolver)
+            );
+        }
+
+        let unbound = Implementation::unbound().capabilities();
+        assert!(unbound.supports(Role::Resolver, Transport::DoH));
+        assert!(!unbound.supports(Role::NameServer, Transport::DoH));
+        assert_eq!(BTreeSet::from([Role::Resolver]), unbound.cookies);
+        assert!(unbound.dnssec_algorithms.contains(&Algorithm::ED448));
+        assert!(!unbound.dnssec_algorithms.contains(&Algorithm::DSA));
+
+        let ede_dot_com = Implementation::EdeDotCom.capabilities();
+        assert_eq!(
+            [Role::NameServer],
+            *ede_dot_com.roles.keys().copied().collect::<Vec<_>>()
+        );
+        assert!(ede_dot_com.dnssec_algorithms.is_empty());
+        assert!(ede_dot_com.qname_minimization.is_empty());
+
+        let hickory = Implementation::hickory().capabilities();
+        assert_eq!([QnameMinimization::Off], *hickory.qname_minimization);
+        assert!(!hickory.dnssec_algorithms.contains(&Algorithm::ED448));
+    }
+
+    #[test]
+    fn config_builder() {
+        let upstreams = [Ipv4Addr::new(172, 18, 0, 2)];
+        let config = ConfigBuilder::forwarder(&upstreams)
+            .forward_first(true)
+            .build();
+        assert_eq!(Role::Forwarder, config.role());
+        let named_conf = Implementation::bind().format_config(config);
+        assert!(named_conf.contains("forward first;"));
+
+        let result = std::panic::catch_unwind(|| {
+            ConfigBuilder::resolver().allow_update(true).build();
+        });
+        assert!(result.is_err(), "resolvers do not accept dynamic updates");
+    }
+
+    #[test]
+    fn netmasks() {
+        let netmasks = ["192.0.2.0/24", "2001:db8::/32"];
+        let resolver = ConfigBuilder::resolver().netmasks(&netmasks).build();
+
+        let hickory_conf = Implementation::hickory().format_config(resolver);
+        assert!(hickory_conf.contains(r#"allow_networks = ["192.0.2.0/24", "2001:db8::/32", ]"#));
+        assert!(
+            hickory_conf
+                .contains(r#"allow_server = ["10.0.0.0/8", "172.16.0.0/12", "192.168.0.0/16"]"#)
+        );
+
+        let resolver = ConfigBuilder::resolver()
+            .name_server_netmasks(&netmasks)
+            .build();
+        let hickory_conf = Implementation::hickory().format_config(resolver);
+        assert!(hickory_conf.contains(r#"allow_server = ["192.0.2.0/24", "2001:db8::/32", ]"#));
+    }
+
+    #[test]
+    fn port() {
+        let name_server = ConfigBuilder::name_server(&FQDN::TEST_DOMAIN)
+            .port(5300)
+            .build();
+        let named_conf = Implementation::bind().format_config(name_server.clone());
+        assert!(named_conf.contains("listen-on port 5300 { any; };"));
+        assert!(named_conf.contains("listen-on-v6 port 5300 { any; };"));
+        let nsd_conf = Implementation::unbound().format_config(name_server.clone());
+        assert!(nsd_conf.contains("port: 5300"));
+        let knot_conf = Implementation::Knot.format_config(name_server.clone());
+        assert!(knot_conf.contains("listen: 0.0.0.0@5300"));
+        let pdns_conf = Implementation::PowerDns.format_config(name_server.clone());
+        assert!(pdns_conf.contains("local-port=5300"));
+
+        let args = Implementation::hickory().cmd_args(&name_server, Verbosity::Normal);
+        assert!(args.last().unwrap().contains(" --port 5300 "));
+
+        let resolver = ConfigBuilder::resolver().port(5353).build();
+        let unbound_conf = Implementation::unbound().format_config(resolver.clone());
+        assert!(unbound_conf.contains("port: 5353"));
+        let kresd_conf = Implementation::Knot.format_config(resolver.clone());
+        assert!(kresd_conf.contains("net.listen('0.0.0.0', 5353, { kind = 'dns' })"));
+
+        // the defaults of the implementations are left alone
+        let named_conf = Implementation::bind().format_config(ConfigBuilder::resolver().build());
+        assert!(!named_conf.contains("listen-on"));
+    }
+
+    #[test]
+    fn threads() {
+        let resolver = ConfigBuilder::resolver().threads(1).build();
+        let args = Implementation::hickory().cmd_args(&resolver, Verbosity::Normal);
+        assert!(args.last().unwrap().contains(" --workers 1 "));
+
+        let name_server = ConfigBuilder::name_server(&FQDN::TEST_DOMAIN)
+            .threads(4)
+            .build();
+        let args = Implementation::hickory().cmd_args(&name_server, Verbosity::Normal);
+        assert!(args.last().unwrap().contains(" --workers 4 "));
+
+        let args = Implementation::hickory()
+            .cmd_args(&ConfigBuilder::resolver().build(), Verbosity::Normal);
+        assert!(!args.last().unwrap().contains("--workers"));
+    }
+
+    #[test]
+    fn dot_listeners() -> Result<(), Error> {
+        let transports = BTreeSet::from([Transport::Do53, Transport::DoT]);
+        let name_server = |transports| {
+            ConfigBuilder::name_server(&FQDN::TEST_DOMAIN)
+                .transports(transports)
+                .build()
+        };
+
+        let named_conf = Implementation::bind().format_config(name_server(&transports));
+        assert!(named_conf.contains("listen-on port 853 tls local-tls { any; };"));
+        assert!(named_conf.contains(&format!("cert-file \"{TLS_CERT_PATH}\";")));
+
+        let nsd_conf = Implementation::unbound().format_config(name_server(&transports));
+        assert!(nsd_conf.contains(&format!("tls-service-key: {TLS_KEY_PATH}")));
+
+        let do53 = BTreeSet::from([Transport::Do53]);
+        let hickory_conf = Implementation::hickory().format_config(name_server(&do53));
+        assert!(!hickory_conf.contains("tls_cert"));
+
+        let netmasks = ["172.16.0.0/12"];
+        let unbound_conf = Implementation::unbound().format_config(
+            ConfigBuilder::resolver()
+                .netmasks(&netmasks)
+                .transports(&transports)
+                .build(),
+        );
+        assert!(unbound_conf.contains("interface: 0.0.0.0@853"));
+
+        assert!(
+            Implementation::Knot
+                .check_transports(Role::NameServer, &do53)
+                .is_ok()
+        );
+        let error = Implementation::Knot
+            .check_transports(Role::NameServer, &transports)
+            .unwrap_err();
+        assert_eq!(
+            "the knot name server does not support DoT",
+            error.to_string()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn secondaries() {
+        let secondaries = [Ipv4Addr::new(172, 18, 0, 3)];
+        let name_server = || ConfigBuilder::name_server(&FQDN::TEST_DOMAIN);
+
+        let named_conf =
+            Implementation::bind().format_config(name_server().secondaries(&secondaries).build());
+        assert!(named_conf.contains("type primary;"));
+        assert!(named_conf.contains("allow-transfer { 172.18.0.3; };"));
+        assert!(named_conf.contains("also-notify { 172.18.0.3; };"));
+        assert!(named_conf.contains("notify-delay 0;"));
+        assert!(named_conf.contains("ixfr-from-differences yes;"));
+
+        let primary = Ipv4Addr::new(172, 18, 0, 2);
+        let named_conf =
+            Implementation::bind().format_config(name_server().primary(primary).build());
+        assert!(named_conf.contains("type secondary;"));
+        assert!(named_conf.contains("primaries { 172.18.0.2; };"));
+        assert!(!named_conf.contains("also-notify"));
+
+        let nsd_conf = Implementation::unbound()
+            .format_config(name_server().secondaries(&secondaries).build());
+        assert!(nsd_conf.contains("provide-xfr: 172.18.0.3 NOKEY"));
+        assert!(nsd_conf.contains("notify: 172.18.0.3 NOKEY"));
+
+        let nsd_conf =
+            Implementation::unbound().format_config(name_server().primary(primary).build());
+        assert!(nsd_conf.contains("allow-notify: 172.18.0.2 NOKEY"));
+        assert!(nsd_conf.contains("request-xfr: 172.18.0.2 NOKEY"));
+    }
+
+    #[test]
+    fn catalog_zone() {
+        let secondaries = [Ipv4Addr::new(172, 18, 0, 3)];
+        let catalog = FQDN("catalog.invalid.").unwrap();
+        let name_server = || {
+            let mut config = ConfigBuilder::name_server(&FQDN::TEST_DOMAIN);
+            config.catalog_zone(&catalog);
+            config
+        };
+
+        let named_conf =
+            Implementation::bind().format_config(name_server().secondaries(&secondaries).build());
+        assert!(named_conf.contains("zone \"catalog.invalid.\" IN {"));
+        assert!(named_conf.contains("file \"/etc/zones/catalog.invalid.zone\";"));
+        assert!(!named_conf.contains("catalog-zones"));
+
+        let primary = Ipv4Addr::new(172, 18, 0, 2);
+        let named_conf =
+            Implementation::bind().format_config(name_server().primary(primary).build());
+        assert!(
+            named_conf.contains(
+                "zone \"catalog.invalid.\" default-primaries { 172.18.0.2; } in-memory yes"
+            )
+        );
+    }
+
+    #[test]
+    fn views() {
+        let named_conf = Implementation::bind()
+            .format_config(ConfigBuilder::name_server(&FQDN::TEST_DOMAIN).build());
+        assert!(!named_conf.contains("view "));
+
+        let views = ["10.0.0.0/8", "192.168.0.0/24"];
+        let named_conf = Implementation::bind().format_config(
+            ConfigBuilder::name_server(&FQDN::TEST_DOMAIN)
+                .views(&views)
+                .build(),
+        );
+        assert!(named_conf.contains("view \"view0\" {\n    match-clients { 10.0.0.0/8; };"));
+        assert!(named_conf.contains("file \"/etc/zones/view1.zone\";"));
+        let default = named_conf.find("view \"default\"").unwrap();
+        assert!(named_conf.find("view \"view1\"").unwrap() < default);
+        assert!(named_conf.find("file \"/etc/zones/main.zone\";").unwrap() > default);
+
+        let forwarders = [Ipv4Addr::new(172, 18, 0, 2)];
+        let named_conf = Implementation::bind().format_config(
+            ConfigBuilder::resolver()
+                .forwarding_views(&[("10.0.0.0/8", &forwarders)])
+                .build(),
+        );
+        assert!(named_conf.contains("forwarders { 172.18.0.2; };"));
+        assert!(
+            named_conf.find("view \"view0\"").unwrap()
+                < named_conf.find("view \"default\"").unwrap()
+        );
+    }
+
+    #[test]
+    fn extra_config() {
+        let unbound_conf = Implementation::unbound().format_config(
+            ConfigBuilder::resolver()
+                .extra_config("    aggressive-nsec: yes")
+                .build(),
+        );
+        let (server, remote_control) = unbound_conf.split_once("remote-control:").unwrap();
+        assert!(server.contains("\n    aggressive-nsec: yes\n"));
+        assert!(!remote_control.contains("aggressive-nsec"));
+
+        let named_conf = Implementation::bind().format_config(
+            ConfigBuilder::name_server(&FQDN::TEST_DOMAIN)
+                .extra_config("    minimal-responses yes;")
+                .build(),
+        );
+        let (options, _zones) = named_conf.split_once("zone ").unwrap();
+        assert!(options.contains("\n    minimal-responses yes;\n"));
+
+        // top-level TOML keys must come before the first table
+        let hickory_conf = Implementation::hickory().format_config(
+            ConfigBuilder::name_server(&FQDN::TEST_DOMAIN)
+                .extra_config("tcp_request_timeout = 10")
+                .build(),
+        );
+        let (top_level, _zones) = hickory_conf.split_once("[[zones]]").unwrap();
+        assert!(top_level.contains("tcp_request_timeout = 10\n"));
+    }
+
+    #[test]
+    fn extra_config_for_implementation() {
+        let extra_configs = [
+            ExtraConfig::for_implementation(&Implementation::unbound(), "    serve-expired: yes\n"),
+            ExtraConfig::for_implementation(
+                &Implementation::bind(),
+                "    stale-answer-enable yes;",
+            ),
+            ExtraConfig::for_implementation(&Implementation::unbound(), "    prefetch: yes"),
+        ];
+
+        assert_eq!(
+            Some("    serve-expired: yes\n    prefetch: yes"),
+            ExtraConfig::snippets_for(&extra_configs, &Implementation::unbound()).as_deref()
+        );
+        // the version is not compared
+        let bind = Implementation::Bind {
+            version: Some("9.18.33".to_string()),
+        };
+        assert_eq!(
+            Some("    stale-answer-enable yes;"),
+            ExtraConfig::snippets_for(&extra_configs, &bind).as_deref()
+        );
+        assert_eq!(
+            None,
+            ExtraConfig::snippets_for(&extra_configs, &Implementation::hickory())
+        );
+    }
+
+    #[test]
+    fn denial_of_existence() {
+        let name_server = |denial_of_existence| {
+            ConfigBuilder::name_server(&FQDN::TEST_DOMAIN)
+                .use_dnssec(true)
+                .denial_of_existence(denial_of_existence)
+                .build()
+        };
+
+        let hickory_conf =
+            Implementation::hickory().format_config(name_server(&DenialOfExistence::Nsec));
+        assert!(hickory_conf.contains(r#"nx_proof_kind = "nsec""#));
+
+        let nsec3 = DenialOfExistence::Nsec3 {
+            iterations: 0,
+            opt_out: true,
+            salt: Some(String::new()),
+        };
+        let hickory_conf = Implementation::hickory().format_config(name_server(&nsec3));
+        assert!(
+            hickory_conf.contains(
+                "nx_proof_kind = { nsec3 = { iterations = 0, opt_out = true, salt = [] } }"
+            )
+        );
+
+        let nsec3 = DenialOfExistence::Nsec3 {
+            iterations: 5,
+            opt_out: false,
+            salt: Some("CAFEBABE".to_string()),
+        };
+        let hickory_conf = Implementation::hickory().format_config(name_server(&nsec3));
+        assert!(hickory_conf.contains("salt = [202, 254, 186, 190]"));
+    }
+
+    #[test]
+    fn doh_listeners() -> Result<(), Error> {
+        let transports = BTreeSet::from([Transport::Do53, Transport::DoH]);
+        let netmasks = ["172.16.0.0/12"];
+        let resolver = |transports| {
+            ConfigBuilder::resolver()
+                .netmasks(&netmasks)
+                .transports(transports)
+                .build()
+        };
+
+        let unbound_conf = Implementation::unbound().format_config(resolver(&transports));
+        assert!(unbound_conf.contains("interface: 0.0.0.0@443"));
+        assert!(unbound_conf.contains("http-endpoint: \"/dns-query\""));
+        assert!(unbound_conf.contains(&format!("tls-service-pem: {TLS_CERT_PATH}")));
+        assert!(!unbound_conf.contains("interface: 0.0.0.0@853"));
+
+        let hickory_conf = Implementation::hickory().format_config(resolver(&transports));
+        assert!(hickory_conf.contains("disable_tls = true"));
+        assert!(hickory_conf.contains("disable_https = false"));
+        assert!(hickory_conf.contains("http_endpoint = \"/dns-query\""));
+        assert!(hickory_conf.contains(&format!("private_key = \"{TLS_KEY_PATH}\"")));
+
+        let error = Implementation::bind()
+            .check_transports(Role::Resolver, &transports)
+            .unwrap_err();
+        assert_eq!("the bind resolver does not support DoH", error.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn qname_minimization() {
+        let netmasks = ["172.16.0.0/12"];
+        let resolver = || {
+            let mut config = ConfigBuilder::resolver();
+            config.netmasks(&netmasks);
+            config
+        };
+
+        let unbound_conf = Implementation::unbound().format_config(
+            resolver()
+                .qname_minimization(QnameMinimization::Strict)
+                .build(),
+        );
+        assert!(unbound_conf.contains("    qname-minimisation: yes\n"));
+        assert!(unbound_conf.contains("    qname-minimisation-strict: yes\n"));
+
+        let unbound_conf = Implementation::unbound().format_config(
+            resolver()
+                .qname_minimization(QnameMinimization::Off)
+                .build(),
+        );
+        assert!(unbound_conf.contains("    qname-minimisation: no\n"));
+        assert!(unbound_conf.contains("    qname-minimisation-strict: no\n"));
+
+        let named_conf = Implementation::bind().format_config(
+            resolver()
+                .qname_minimization(QnameMinimization::Relaxed)
+                .build(),
+        );
+        assert!(named_conf.contains("qname-minimization relaxed;"));
+
+        let named_conf = Implementation::bind().format_config(resolver().build());
+        assert!(!named_conf.contains("qname-minimization"));
+
+        assert!(Implementation::hickory().supports_qname_minimization(QnameMinimization::Off));
+        assert!(!Implementation::hickory().supports_qname_minimization(QnameMinimization::Strict));
+    }
+
+    #[test]
+    fn serve_stale() {
+        let netmasks = ["172.16.0.0/12"];
+        let resolver = || {
+            let mut config = ConfigBuilder::resolver();
+            config.netmasks(&netmasks);
+            config
+        };
+        let serve_stale = ServeStale {
+            ttl: 3600,
+            client_timeout: 1800,
+        };
+
+        let unbound_conf =
+            Implementation::unbound().format_config(resolver().serve_stale(serve_stale).build());
+        assert!(unbound_conf.contains("serve-expired: yes"));
+        assert!(unbound_conf.contains("serve-expired-ttl: 3600"));
+        assert!(unbound_conf.contains("serve-expired-client-timeout: 1800"));
+
+        let named_conf =
+            Implementation::bind().format_config(resolver().serve_stale(serve_stale).build());
+        assert!(named_conf.contains("stale-answer-enable yes;"));
+        assert!(named_conf.contains("max-stale-ttl 3600;"));
+        assert!(named_conf.contains("stale-answer-client-timeout 1800;"));
+
+        let named_conf = Implementation::bind().format_config(resolver().build());
+        assert!(!named_conf.contains("stale"));
+    }
+
+    #[test]
+    fn cookies() {
+        let origin = FQDN::TEST_DOMAIN;
+        let cookies = Cookies {
+            answer: true,
+            require: true,
+            secret: Some("0123456789abcdef0123456789abcdef".to_string()),
+        };
+
+        let named_conf = Implementation::bind().format_config(
+            ConfigBuilder::name_server(&origin)
+                .cookies(&cookies)
+                .build(),
+        );
+        assert!(named_conf.contains("answer-cookie yes;"));
+        assert!(named_conf.contains("require-server-cookie yes;"));
+        assert!(named_conf.contains(r#"cookie-secret "0123456789abcdef0123456789abcdef";"#));
+
+        let cookies = Cookies {
+            answer: false,
+            require: false,
+            secret: None,
+        };
+        let named_conf = Implementation::bind()
+            .format_config(ConfigBuilder::resolver().cookies(&cookies).build());
+        assert!(named_conf.contains("answer-cookie no;"));
+        assert!(!named_conf.contains("require-server-cookie"));
+        assert!(!named_conf.contains("cookie-secret"));
+
+        let cookies = Cookies {
+            answer: true,
+            require: false,
+            secret: Some("0123456789abcdef0123456789abcdef".to_string()),
+        };
+        let unbound_conf = Implementation::unbound()
+            .format_config(ConfigBuilder::resolver().cookies(&cookies).build());
+        assert!(unbound_conf.contains("answer-cookie: yes"));
+        assert!(unbound_conf.contains(r#"cookie-secret: "0123456789abcdef0123456789abcdef""#));
+
+        let unbound_conf =
+            Implementation::unbound().format_config(ConfigBuilder::resolver().build());
+        assert!(!unbound_conf.contains("cookie"));
+    }
+
+    #[test]
+    fn rrl() {
+        let origin = FQDN::TEST_DOMAIN;
+        let rrl = Rrl {
+            responses_per_second: 5,
+            window: 1,
+        };
+
+        let named_conf = Implementation::bind()
+            .format_config(ConfigBuilder::name_server(&origin).rrl(rrl).build());
+        assert!(named_conf.contains("responses-per-second 5;"));
+        assert!(named_conf.contains("window 1;"));
+
+        let named_conf =
+            Implementation::bind().format_config(ConfigBuilder::name_server(&origin).build());
+        assert!(!named_conf.contains("rate-limit"));
+
+        let nsd_conf = Implementation::unbound()
+            .format_config(ConfigBuilder::name_server(&origin).rrl(rrl).build());
+        assert!(nsd_conf.contains("rrl-ratelimit: 5"));
+
+        let nsd_conf =
+            Implementation::unbound().format_config(ConfigBuilder::name_server(&origin).build());
+        assert!(nsd_conf.contains("rrl-ratelimit: 0"));
+    }
+
+    #[test]
+    fn transport_restriction() {
+        let resolver = |restriction| {
+            ConfigBuilder::resolver()
+                .transport_restriction(restriction)
+                .build()
+        };
+
+        let unbound_conf =
+            Implementation::unbound().format_config(resolver(TransportRestriction::TcpOnly));
+        assert!(unbound_conf.contains("tcp-upstream: yes"));
+        assert!(!unbound_conf.contains("do-tcp: no"));
+
+        let unbound_conf =
+            Implementation::unbound().format_config(resolver(TransportRestriction::UdpOnly));
+        assert!(unbound_conf.contains("do-tcp: no"));
+        assert!(!unbound_conf.contains("tcp-upstream"));
+
+        let named_conf =
+            Implementation::bind().format_config(resolver(TransportRestriction::TcpOnly));
+        assert!(named_conf.contains("server 0.0.0.0/0 {\n    tcp-only yes;\n};"));
+        assert!(named_conf.contains("server ::/0 {\n    tcp-only yes;\n};"));
+
+        let named_conf = Implementation::bind().format_config(ConfigBuilder::resolver().build());
+        assert!(!named_conf.contains("tcp-only"));
+    }
+
+    #[test]
+    fn dns64() {
+        let prefix = "64:ff9b::/96".parse().unwrap();
+        let resolver = || ConfigBuilder::resolver().dns64_prefix(prefix).build();
+
+        let unbound_conf = Implementation::unbound().format_config(resolver());
+        assert!(unbound_conf.contains("module-config: \"dns64 validator iterator\""));
+        assert!(unbound_conf.contains("dns64-prefix: 64:ff9b::/96"));
+
+        let named_conf = Implementation::bind().format_config(resolver());
+        assert!(named_conf.contains("dns64 64:ff9b::/96 { };"));
+
+        let unbound_conf =
+            Implementation::unbound().format_config(ConfigBuilder::resolver().build());
+        assert!(!unbound_conf.contains("dns64"));
+        assert!(!unbound_conf.contains("module-config"));
+    }
+
+    #[test]
+    fn any_policy() {
+        let origin = FQDN::TEST_DOMAIN;
+        let config = |any_policy| {
+            ConfigBuilder::name_server(&origin)
+                .any_policy(any_policy)
+                .build()
+        };
+
+        let named_conf = Implementation::bind().format_config(config(AnyPolicy::Minimal));
+        assert!(named_conf.contains("minimal-any yes;"));
+        let named_conf = Implementation::bind().format_config(config(AnyPolicy::Normal));
+        assert!(!named_conf.contains("minimal-any"));
+
+        let nsd_conf = Implementation::unbound().format_config(config(AnyPolicy::Refuse));
+        assert!(nsd_conf.contains("refuse-any: yes"));
+        let nsd_conf = Implementation::unbound().format_config(config(AnyPolicy::Normal));
+        assert!(!nsd_conf.contains("refuse-any"));
+
+        assert!(Implementation::hickory().supports_any_policy(AnyPolicy::Normal));
+        assert!(!Implementation::hickory().supports_any_policy(AnyPolicy::Minimal));
+    }
+
+    #[test]
+    fn zonemd() {
+        let origin = FQDN::TEST_DOMAIN;
+        let config = || {
+            ConfigBuilder::name_server(&origin)
+                .zonemd(ZonemdScheme::SimpleSha384)
+                .build()
+        };
+
+        let knot_conf = Implementation::Knot.format_config(config());
+        assert!(knot_conf.contains("zonemd-generate: zonemd-sha384"));
+
+        let knot_conf =
+            Implementation::Knot.format_config(ConfigBuilder::name_server(&origin).build());
+        assert!(!knot_conf.contains("zonemd"));
+    }
+
+    #[test]
+    fn edns_client_subnet() {
+        let netmasks = ["172.16.0.0/12"];
+        let resolver = |edns_client_subnet| {
+            ConfigBuilder::resolver()
+                .netmasks(&netmasks)
+                .edns_client_subnet(edns_client_subnet)
+                .build()
+        };
+
+        let unbound_conf = Implementation::unbound().format_config(resolver(&netmasks));
+        assert!(unbound_conf.contains("module-config: \"subnetcache validator iterator\""));
+        assert!(unbound_conf.contains("send-client-subnet: 172.16.0.0/12"));
+
+        let unbound_conf = Implementation::unbound().format_config(resolver(&[]));
+        assert!(!unbound_conf.contains("subnet"));
+
+        let recursor_conf = Implementation::PowerDns.format_config(resolver(&netmasks));
+        assert!(recursor_conf.contains("edns-subnet-allow-list=172.16.0.0/12"));
+        assert!(recursor_conf.contains("use-incoming-edns-subnet=yes"));
+
+        let recursor_conf = Implementation::PowerDns.format_config(resolver(&[]));
+        assert!(!recursor_conf.contains("subnet"));
+    }
+
+    #[test]
+    fn parse_implementation() -> Result<(), Error> {
+        for input in [
+            "bind",
+            "bind@9.18.28",
+            "dnslib",
+            "knot",
+            "powerdns",
+            "unbound",
+            "unbound@1.22.0",
+            "ede-dot-com",
+            "hickory-dnssec-ring",
+        ] {
+            let implementation: Implementation = input.parse()?;
+            assert_eq!(input, implementation.to_string());
+        }
+
+        assert!("BIND".parse::<Implementation>()?.is_bind());
+        assert!("Hickory".parse::<Implementation>()?.is_hickory());
+
+        let Implementation::Hickory { repo, .. } =
+            "hickory:https://github.com/Owner/Repo".parse()?
+        else {
+            panic!("expected a hickory-dns implementation");
+        };
+        assert_eq!("https://github.com/Owner/Repo", repo.as_str());
+        // the example of the error message of the `hickory $URL ...` syntax
+        let input = "hickory https://github.com/owner/repo dnssec-ring v0.24.1";
+        assert!(input.parse::<Implementation>()?.is_hickory());
+
+        let error = "nsd".parse::<Implementation>().unwrap_err();
+        assert_eq!("unknown implementation: nsd", error.to_string());
+        assert!("hickory:".parse::<Implementation>().is_err());
+        assert!("knot@6.0".parse::<Implementation>().is_err());
+        let error = "bind@".parse::<Implementation>().unwrap_err();
+        assert_eq!("`bind@` must be followed by a version", error.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn implementations_dedup_in_hash_set() {
+        let hickory = |rev| Implementation::Hickory {
+            repo: Repository::with_rev(crate::repo_root(), rev),
+            dnssec_feature: HickoryDnssecFeature::AwsLcRs,
+        };
+
+        let implementations = HashSet::from([
+            Implementation::bind(),
+            Implementation::bind(),
+            Implementation::Bind {
+                version: Some("9.18.28".to_string()),
+            },
+            hickory("main"),
+            hickory("main"),
+            hickory("release/0.24"),
+        ]);
+
+        assert_eq!(4, implementations.len());
+        assert!(implementations.contains(&hickory("release/0.24")));
+        assert_ne!(
+            hickory("main"),
+            Implementation::Hickory {
+                repo: Repository::with_rev(crate::repo_root(), "main"),
+                dnssec_feature: HickoryDnssecFeature::Ring,
+            }
+        );
     }
 }
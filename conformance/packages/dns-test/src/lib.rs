@@ -3,28 +3,39 @@
 use std::io::{Read as _, Write as _};
 use std::{env, io};
 
-use client::Client;
+use client::{Client, ClientImplementation};
 use lazy_static::lazy_static;
 use name_server::{NameServer, Running};
 
 pub use crate::container::Network;
 pub use crate::forwarder::Forwarder;
 pub use crate::fqdn::FQDN;
-pub use crate::implementation::{HickoryDnssecFeature, Implementation, Repository};
+pub use crate::implementation::{
+    AnyPolicy, Capabilities, Config, ConfigBuilder, Cookies, ExtraConfig, HickoryDnssecFeature,
+    Implementation, QnameMinimization, Repository, Role, Rrl, ServeStale, TransportRestriction,
+    Verbosity, ZonemdScheme,
+};
 pub use crate::resolver::Resolver;
+pub use crate::transport::Transport;
 pub use crate::trust_anchor::TrustAnchor;
+pub use crate::tsig::{TsigAlgorithm, TsigError, TsigKey};
 
 pub mod client;
 pub mod container;
 mod forwarder;
 mod fqdn;
 mod implementation;
+pub mod logs;
 pub mod name_server;
 pub mod nsec3;
+pub mod pcap;
 pub mod record;
 mod resolver;
+pub mod topology;
+mod transport;
 mod trust_anchor;
 pub mod tshark;
+mod tsig;
 pub mod zone_file;
 
 pub type Error = Box<dyn std::error::Error>;
@@ -36,6 +47,7 @@ const DEFAULT_TTL: u32 = 24 * 60 * 60; // 1 day
 lazy_static! {
     pub static ref SUBJECT: Implementation = parse_subject();
     pub static ref PEER: Implementation = parse_peer();
+    pub static ref CLIENT: ClientImplementation = parse_client();
 }
 
 /// Helper to prevent a unit test from immediately terminating so its associated containers can be
@@ -120,32 +132,23 @@ fn parse_peer() -> Implementation {
     parse_implementation("DNS_TEST_PEER")
 }
 
+fn parse_client() -> ClientImplementation {
+    const ENV_VAR: &str = "DNS_TEST_CLIENT";
+
+    if let Ok(client) = env::var(ENV_VAR) {
+        client
+            .parse()
+            .unwrap_or_else(|e| panic!("invalid {ENV_VAR}: {e}"))
+    } else {
+        ClientImplementation::default()
+    }
+}
+
 fn parse_implementation(env_var: &str) -> Implementation {
     if let Ok(subject) = env::var(env_var) {
-        if subject == "unbound" {
-            return Implementation::Unbound;
-        }
-
-        if subject == "bind" {
-            return Implementation::Bind;
-        }
-
-        if subject.starts_with("hickory ") {
-            let tokens = subject.split_ascii_whitespace().collect::<Vec<_>>();
-            let Ok([_, url, dnssec_feature]) = <[&str; 3]>::try_from(tokens) else {
-                panic!(
-                    "the syntax of {env_var} is 'hickory $URL $DNSSEC_FEATURE', e.g. \
-                    'hickory /tmp/hickory aws-lc-rs' or \
-                    'hickory https://github.com/owner/repo ring'"
-                )
-            };
-            Implementation::Hickory {
-                repo: Repository(url.to_string()),
-                dnssec_feature: dnssec_feature.parse().unwrap(),
-            }
-        } else {
-            panic!("unknown implementation: {subject}")
-        }
+        subject
+            .parse()
+            .unwrap_or_else(|e| panic!("invalid {env_var}: {e}"))
     } else {
         Implementation::default()
     }
@@ -167,19 +170,10 @@ mod tests {
 
     use super::*;
 
-    impl PartialEq for Implementation {
-        fn eq(&self, other: &Self) -> bool {
-            match (self, other) {
-                (Self::Hickory { .. }, Self::Hickory { .. }) => true,
-                _ => core::mem::discriminant(self) == core::mem::discriminant(other),
-            }
-        }
-    }
-
     #[test]
     fn immutable_subject() {
         let before = super::SUBJECT.clone();
-        let newval = if before == Implementation::Unbound {
+        let newval = if before == Implementation::unbound() {
             "bind"
         } else {
             "unbound"
@@ -193,7 +187,7 @@ mod tests {
     #[test]
     fn immutable_peer() {
         let before = super::PEER.clone();
-        let newval = if before == Implementation::Unbound {
+        let newval = if before == Implementation::unbound() {
             "bind"
         } else {
             "unbound"
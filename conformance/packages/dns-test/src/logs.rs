@@ -0,0 +1,458 @@
+//! Structured events parsed from the logs of the servers
+
+use std::collections::HashMap;
+
+use crate::client::Protocol;
+use crate::record::RecordType;
+use crate::{FQDN, Implementation};
+
+/// Something a server reported doing in its logs
+///
+/// Which events are logged depends on the verbosity of the server; see [`parse`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum LogEvent {
+    /// The server received a query from a client
+    QueryReceived { fqdn: FQDN, record_type: RecordType },
+    /// The resolver sent a query to a name server
+    QuerySent { fqdn: FQDN, record_type: RecordType },
+    /// The resolver validated the RRset of `fqdn` and `record_type`
+    Validated { fqdn: FQDN, record_type: RecordType },
+    /// The resolver could not validate the RRset of `fqdn` and `record_type`
+    ValidationFailed {
+        fqdn: FQDN,
+        record_type: RecordType,
+        /// As worded by the implementation
+        reason: String,
+    },
+    /// The server answered a query with SERVFAIL
+    ServFail {
+        /// As worded by the implementation, when it logged one
+        reason: Option<String>,
+    },
+}
+
+/// Parses the `logs` of a resolver or forwarder that runs `implementation`
+///
+/// Lines that do not describe any of the events in [`LogEvent`] are skipped. Only the logs of
+/// hickory and unbound are parsed; other implementations produce no events
+///
+/// - hickory logs the queries it receives and sends, and the SERVFAIL answers, at the default
+///   verbosity; validation results only at `Verbosity::Debug`
+/// - unbound logs validation failures and the reason of SERVFAIL answers at every verbosity and the
+///   queries it sends at `Verbosity::Debug`; it does not log the queries it receives
+pub fn parse(implementation: &Implementation, logs: &str) -> Vec<LogEvent> {
+    match implementation {
+        Implementation::Hickory { .. } => parse_hickory(logs),
+        Implementation::Unbound { .. } => parse_unbound(logs),
+        Implementation::Bind { .. }
+        | Implementation::Dnslib
+        | Implementation::EdeDotCom
+        | Implementation::Knot
+        | Implementation::PowerDns => vec![],
+    }
+}
+
+/// Number of queries a name server received, by name, type and protocol
+///
+/// Queries over DoT and DoH are counted as TCP queries, and queries over DoQ as UDP ones
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct QueryStats {
+    counts: HashMap<(FQDN, RecordType, Protocol), usize>,
+}
+
+impl QueryStats {
+    /// Number of queries for `fqdn` and `record_type`, over any protocol
+    pub fn count(&self, fqdn: &FQDN, record_type: &RecordType) -> usize {
+        [Protocol::Udp, Protocol::Tcp]
+            .into_iter()
+            .map(|protocol| self.count_over(fqdn, record_type, protocol))
+            .sum()
+    }
+
+    /// Number of queries for `fqdn` and `record_type` received over `protocol`
+    pub fn count_over(&self, fqdn: &FQDN, record_type: &RecordType, protocol: Protocol) -> usize {
+        self.counts
+            .get(&(fqdn.clone(), record_type.clone(), protocol))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Number of queries, for any name and type
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// The queries counted in `self` that are not counted in `baseline`
+    pub(crate) fn since(mut self, baseline: &Self) -> Self {
+        for (key, count) in &baseline.counts {
+            if let Some(current) = self.counts.get_mut(key) {
+                *current = current.saturating_sub(*count);
+            }
+        }
+        self.counts.retain(|_, count| *count != 0);
+        self
+    }
+
+    fn add(&mut self, fqdn: FQDN, record_type: RecordType, protocol: Protocol) {
+        *self
+            .counts
+            .entry((fqdn, record_type, protocol))
+            .or_default() += 1;
+    }
+}
+
+/// Counts the queries in the `logs` of a name server that runs `implementation`; `None` if the
+/// implementation does not log the queries it receives
+///
+/// - BIND logs them with `querylog`, which the name server configuration turns on
+/// - hickory logs them at the default verbosity, not at `Verbosity::Quiet`
+pub(crate) fn query_stats(implementation: &Implementation, logs: &str) -> Option<QueryStats> {
+    match implementation {
+        Implementation::Bind { .. } => Some(bind_query_stats(logs)),
+        Implementation::Hickory { .. } => Some(hickory_query_stats(logs)),
+        Implementation::Dnslib
+        | Implementation::EdeDotCom
+        | Implementation::Knot
+        | Implementation::PowerDns
+        | Implementation::Unbound { .. } => None,
+    }
+}
+
+/// e.g. `client @0x7f3e2c003a68 172.18.0.5#41234 (example.hickory-dns.testing): query:
+/// example.hickory-dns.testing IN A -E(0)DC (172.18.0.3)`, where the `T` flag marks TCP queries
+fn bind_query_stats(logs: &str) -> QueryStats {
+    let mut stats = QueryStats::default();
+
+    for line in logs.lines() {
+        let Some((_, query)) = line.split_once("): query: ") else {
+            continue;
+        };
+
+        let mut columns = query.split_whitespace();
+        let (Some(fqdn), Some(_class), Some(record_type), Some(flags)) = (
+            columns.next(),
+            columns.next(),
+            columns.next(),
+            columns.next(),
+        ) else {
+            continue;
+        };
+
+        // BIND leaves the trailing dot out, except for the root
+        let fqdn = if fqdn.ends_with('.') {
+            fqdn.to_string()
+        } else {
+            format!("{fqdn}.")
+        };
+        let protocol = if flags.contains('T') {
+            Protocol::Tcp
+        } else {
+            Protocol::Udp
+        };
+        if let Some((fqdn, record_type)) = name_and_type(&fqdn, record_type) {
+            stats.add(fqdn, record_type, protocol);
+        }
+    }
+
+    stats
+}
+
+/// hickory logs a `request:` line with the protocol of each query, then a `query:` line with its
+/// name and type
+fn hickory_query_stats(logs: &str) -> QueryStats {
+    let mut stats = QueryStats::default();
+    let mut protocol = None;
+
+    for line in logs.lines() {
+        let level = line.split(':').nth(1);
+        if level != Some("INFO") {
+            continue;
+        }
+
+        // e.g. `request:4243 src:udp://172.18.0.5#41235 QUERY qflags:RD response:NoError ..`
+        if line.contains(":request:") {
+            protocol = line
+                .split_once(" src:")
+                .and_then(|(_, src)| src.split_once("://"))
+                .and_then(|(scheme, _)| match scheme {
+                    "udp" | "quic" | "h3" => Some(Protocol::Udp),
+                    "tcp" | "tls" | "https" => Some(Protocol::Tcp),
+                    _ => None,
+                });
+        // e.g. `query:example.hickory-dns.testing.:A:IN`
+        } else if let Some((_, query)) = line.split_once(":query:") {
+            let mut columns = query.split(':');
+            if let (Some(fqdn), Some(record_type), Some(protocol)) =
+                (columns.next(), columns.next(), protocol.take())
+            {
+                if let Some((fqdn, record_type)) = name_and_type(fqdn, record_type) {
+                    stats.add(fqdn, record_type, protocol);
+                }
+            }
+        }
+    }
+
+    stats
+}
+
+/// hickory-dns formats lines as `{timestamp}:{level}:{target}:{line}:{spans}:{message}`
+fn parse_hickory(logs: &str) -> Vec<LogEvent> {
+    let mut events = vec![];
+    // hickory logs why a lookup failed before it logs the response to the client
+    let mut lookup_error = None;
+
+    for line in logs.lines() {
+        let level = line.split(':').nth(1);
+
+        // e.g. `query:example.hickory-dns.testing.:A:IN`; also logged at DEBUG level, before the
+        // query is answered
+        if let Some((_, query)) = line.split_once(":query:") {
+            if level == Some("INFO") {
+                let mut columns = query.split(':');
+                if let (Some(fqdn), Some(record_type)) = (columns.next(), columns.next()) {
+                    if let Some((fqdn, record_type)) = name_and_type(fqdn, record_type) {
+                        events.push(LogEvent::QueryReceived { fqdn, record_type });
+                    }
+                }
+            }
+        // e.g. `querying hickory-dns.testing. for example.hickory-dns.testing. IN A`
+        } else if let Some((_, querying)) = line.split_once(":querying ") {
+            let query = querying.split_once(" for ").map(|(_zone, query)| query);
+            let mut columns = query.unwrap_or_default().split_whitespace();
+            if let (Some(fqdn), Some(_class), Some(record_type)) =
+                (columns.next(), columns.next(), columns.next())
+            {
+                if let Some((fqdn, record_type)) = name_and_type(fqdn, record_type) {
+                    events.push(LogEvent::QuerySent { fqdn, record_type });
+                }
+            }
+        // e.g. `failed to verify: example.hickory-dns.testing. record_type: A: rrsigs were not
+        // able to be verified: example.hickory-dns.testing., type: A`
+        } else if let Some((_, failure)) = line.split_once(":failed to verify: ") {
+            let Some((fqdn, rest)) = failure.split_once(" record_type: ") else {
+                continue;
+            };
+            let Some((record_type, reason)) = rest.split_once(": ") else {
+                continue;
+            };
+
+            if let Some((fqdn, record_type)) = name_and_type(fqdn, record_type) {
+                events.push(LogEvent::ValidationFailed {
+                    fqdn,
+                    record_type,
+                    reason: reason.to_string(),
+                });
+            }
+        // e.g. `verified: example.hickory-dns.testing. record_type: A`
+        } else if let Some((_, verified)) = line.split_once(":verified: ") {
+            if let Some((fqdn, record_type)) = verified.split_once(" record_type: ") {
+                if let Some((fqdn, record_type)) = name_and_type(fqdn, record_type) {
+                    events.push(LogEvent::Validated { fqdn, record_type });
+                }
+            }
+        // e.g. `error resolving error=ResolveError { .. }`
+        } else if let Some((_, error)) = line.split_once(":error resolving") {
+            lookup_error = error
+                .trim()
+                .strip_prefix("error=")
+                .map(|error| error.to_string());
+        // e.g. `request:1234 src:udp://172.18.0.5#41234 QUERY qflags:RD response:ServFail ..`
+        } else if line.contains(":request:") && line.contains(" response:") {
+            let reason = lookup_error.take();
+            if line.contains(" response:ServFail ") {
+                events.push(LogEvent::ServFail { reason });
+            }
+        }
+    }
+
+    events
+}
+
+/// unbound formats lines as `[{timestamp}] unbound[{pid}:{thread}] {level}: {message}`
+fn parse_unbound(logs: &str) -> Vec<LogEvent> {
+    let mut events = vec![];
+
+    for line in logs.lines() {
+        let Some((_, message)) = line.split_once("] ") else {
+            continue;
+        };
+        let Some((_, message)) = message.split_once("] ") else {
+            continue;
+        };
+
+        // e.g. `info: sending query: example.hickory-dns.testing. A IN`
+        if let Some(query) = message.strip_prefix("info: sending query: ") {
+            if let Some((fqdn, record_type)) = unbound_query(query) {
+                events.push(LogEvent::QuerySent { fqdn, record_type });
+            }
+        // e.g. `info: validation failure <example.hickory-dns.testing. A IN>: signature crypto
+        // failed from 172.18.0.4`
+        } else if let Some(failure) = message.strip_prefix("info: validation failure <") {
+            let Some((query, reason)) = failure.split_once(">: ") else {
+                continue;
+            };
+
+            if let Some((fqdn, record_type)) = unbound_query(query) {
+                events.push(LogEvent::ValidationFailed {
+                    fqdn,
+                    record_type,
+                    reason: reason.to_string(),
+                });
+            }
+        // e.g. `error: SERVFAIL <example.hickory-dns.testing. A IN>: all servers for this domain
+        // failed`
+        } else if let Some(servfail) = message.strip_prefix("error: SERVFAIL <") {
+            let reason = servfail
+                .split_once(">: ")
+                .map(|(_query, reason)| reason.to_string());
+            events.push(LogEvent::ServFail { reason });
+        }
+    }
+
+    events
+}
+
+/// Parses unbound's `{name} {type} {class}` format of queries
+fn unbound_query(query: &str) -> Option<(FQDN, RecordType)> {
+    let mut columns = query.split_whitespace();
+    let fqdn = columns.next()?;
+    let record_type = columns.next()?;
+    name_and_type(fqdn, record_type)
+}
+
+fn name_and_type(fqdn: &str, record_type: &str) -> Option<(FQDN, RecordType)> {
+    let fqdn = fqdn.parse().ok()?;
+    let record_type = record_type.trim().parse().ok()?;
+    Some((fqdn, record_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hickory() {
+        let logs = "\
+1718000000:INFO:hickory_recursor::recursor_pool:88:querying hickory-dns.testing. for example.hickory-dns.testing. IN A
+1718000000:DEBUG:hickory_proto::dnssec::dnssec_dns_handle:410:verified: hickory-dns.testing. record_type: DNSKEY
+1718000000:DEBUG:hickory_proto::dnssec::dnssec_dns_handle:418:failed to verify: example.hickory-dns.testing. record_type: A: rrsigs were not able to be verified: example.hickory-dns.testing., type: A
+1718000000:DEBUG:hickory_server::authority::catalog:841:error resolving error=ResolveError { kind: Proto(ProtoError { kind: Msg(\"bogus\") }) }
+1718000000:INFO:hickory_server::server:882:request:4242 src:udp://172.18.0.5#41234 QUERY qflags:RD,AD response:ServFail rr:0/0/1 rflags:RD,RA
+1718000000:INFO:hickory_server::server:897:query:example.hickory-dns.testing.:A:IN
+1718000000:INFO:hickory_server::server:882:request:4243 src:udp://172.18.0.5#41235 QUERY qflags:RD response:NoError rr:1/0/1 rflags:RD,RA
+";
+
+        let needle = FQDN::EXAMPLE_SUBDOMAIN;
+        assert_eq!(
+            vec![
+                LogEvent::QuerySent {
+                    fqdn: needle.clone(),
+                    record_type: RecordType::A,
+                },
+                LogEvent::Validated {
+                    fqdn: FQDN::TEST_DOMAIN,
+                    record_type: RecordType::DNSKEY,
+                },
+                LogEvent::ValidationFailed {
+                    fqdn: needle.clone(),
+                    record_type: RecordType::A,
+                    reason: "rrsigs were not able to be verified: example.hickory-dns.testing., \
+                        type: A"
+                        .to_string(),
+                },
+                LogEvent::ServFail {
+                    reason: Some(
+                        "ResolveError { kind: Proto(ProtoError { kind: Msg(\"bogus\") }) }"
+                            .to_string()
+                    ),
+                },
+                LogEvent::QueryReceived {
+                    fqdn: needle,
+                    record_type: RecordType::A,
+                },
+            ],
+            parse(&Implementation::hickory(), logs)
+        );
+    }
+
+    #[test]
+    fn bind_query_stats() {
+        let logs = "\
+14-Oct-2026 10:00:00.000 client @0x7f3e2c003a68 172.18.0.5#41234 (example.hickory-dns.testing): query: example.hickory-dns.testing IN A -E(0)DC (172.18.0.3)
+14-Oct-2026 10:00:00.100 client @0x7f3e2c003a68 172.18.0.5#41235 (example.hickory-dns.testing): query: example.hickory-dns.testing IN A -E(0)TDC (172.18.0.3)
+14-Oct-2026 10:00:00.200 client @0x7f3e2c003a68 172.18.0.5#41236 (example.hickory-dns.testing): query: example.hickory-dns.testing IN A -E(0)DC (172.18.0.3)
+14-Oct-2026 10:00:00.300 client @0x7f3e2c003a68 172.18.0.5#41237 (.): query: . IN NS -E(0)K (172.18.0.3)
+14-Oct-2026 10:00:00.400 zone hickory-dns.testing/IN: loaded serial 2024010101
+";
+
+        let stats = query_stats(&Implementation::bind(), logs).unwrap();
+        let needle = FQDN::EXAMPLE_SUBDOMAIN;
+        assert_eq!(3, stats.count(&needle, &RecordType::A));
+        assert_eq!(1, stats.count_over(&needle, &RecordType::A, Protocol::Tcp));
+        assert_eq!(1, stats.count(&FQDN::ROOT, &RecordType::NS));
+        assert_eq!(0, stats.count(&needle, &RecordType::AAAA));
+        assert_eq!(4, stats.total());
+
+        let baseline = query_stats(
+            &Implementation::bind(),
+            &logs[..logs.find("#41236").unwrap()],
+        )
+        .unwrap();
+        let since = stats.since(&baseline);
+        assert_eq!(1, since.count(&needle, &RecordType::A));
+        assert_eq!(1, since.count(&FQDN::ROOT, &RecordType::NS));
+        assert_eq!(2, since.total());
+    }
+
+    #[test]
+    fn hickory_query_stats() {
+        let logs = "\
+1718000000:INFO:hickory_server::server:882:request:4242 src:udp://172.18.0.5#41234 QUERY qflags:RD response:NoError rr:1/0/1 rflags:AA
+1718000000:INFO:hickory_server::server:897:query:example.hickory-dns.testing.:A:IN
+1718000000:INFO:hickory_server::server:882:request:4243 src:tcp://172.18.0.5#41235 QUERY qflags:RD response:NoError rr:1/0/1 rflags:AA
+1718000000:INFO:hickory_server::server:897:query:example.hickory-dns.testing.:A:IN
+";
+
+        let stats = query_stats(&Implementation::hickory(), logs).unwrap();
+        let needle = FQDN::EXAMPLE_SUBDOMAIN;
+        assert_eq!(2, stats.count(&needle, &RecordType::A));
+        assert_eq!(1, stats.count_over(&needle, &RecordType::A, Protocol::Udp));
+        assert_eq!(2, stats.total());
+
+        assert!(query_stats(&Implementation::unbound(), logs).is_none());
+    }
+
+    #[test]
+    fn unbound() {
+        let logs = "\
+[1718000000] unbound[1:0] info: resolving example.hickory-dns.testing. A IN
+[1718000000] unbound[1:0] info: sending query: example.hickory-dns.testing. A IN
+[1718000000] unbound[1:0] debug: sending to target: <hickory-dns.testing.> 172.18.0.4#53
+[1718000000] unbound[1:0] info: validation failure <example.hickory-dns.testing. A IN>: signature crypto failed from 172.18.0.4
+[1718000000] unbound[1:0] error: SERVFAIL <example.hickory-dns.testing. A IN>: validation failure <example.hickory-dns.testing. A IN>: signature crypto failed from 172.18.0.4
+";
+
+        let needle = FQDN::EXAMPLE_SUBDOMAIN;
+        assert_eq!(
+            vec![
+                LogEvent::QuerySent {
+                    fqdn: needle.clone(),
+                    record_type: RecordType::A,
+                },
+                LogEvent::ValidationFailed {
+                    fqdn: needle,
+                    record_type: RecordType::A,
+                    reason: "signature crypto failed from 172.18.0.4".to_string(),
+                },
+                LogEvent::ServFail {
+                    reason: Some(
+                        "validation failure <example.hickory-dns.testing. A IN>: signature crypto \
+                        failed from 172.18.0.4"
+                            .to_string()
+                    ),
+                },
+            ],
+            parse(&Implementation::unbound(), logs)
+        );
+    }
+}
@@ -1,13 +1,23 @@
+use core::cmp;
 use core::sync::atomic::{self, AtomicUsize};
-use std::{collections::HashMap, net::Ipv4Addr, thread, time::Duration};
-
-use crate::container::{Child, Container, Network};
-use crate::implementation::{Config, Role};
-use crate::record::{self, DS, Record, SOA, SoaSettings};
+use std::collections::{BTreeSet, HashMap};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::sync::Mutex;
+use std::{env, mem, thread, time::Duration};
+
+use crate::client::{Client, DigSettings};
+use crate::container::{Child, Container, LogStream, Network};
+use crate::implementation::{
+    AnyPolicy, Config, Cookies, ExtraConfig, Role, Rrl, Verbosity, ZonemdScheme,
+};
+use crate::logs::{self, QueryStats};
+use crate::record::{self, DS, PTR, Record, RecordType, SOA, SoaSettings, TXT};
+use crate::topology::Delegation;
+use crate::transport;
 use crate::tshark::Tshark;
-use crate::zone_file::{self, Root, SigningKeys, ZoneFile};
+use crate::zone_file::{Keypair, Root, SigningKeys, ZoneFile};
 use crate::zone_file::{SignSettings, Signer};
-use crate::{DEFAULT_TTL, FQDN, Implementation, Result, TrustAnchor};
+use crate::{DEFAULT_TTL, FQDN, Implementation, Result, Transport, TrustAnchor, TsigKey};
 
 pub struct Graph {
     pub nameservers: Vec<NameServer<Running>>,
@@ -28,11 +38,34 @@ pub enum Sign<'a> {
     },
 }
 
+/// Which addresses of a name server are published in the zone files, as glue of delegations and
+/// in root hints; resolvers can only reach the name server over the corresponding IP versions
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Glue {
+    /// A records only
+    #[default]
+    Ipv4,
+    /// AAAA records only; the network must have IPv6 enabled
+    Ipv6,
+    /// Both A and AAAA records; the network must have IPv6 enabled
+    DualStack,
+}
+
+impl Glue {
+    fn ipv4(self) -> bool {
+        matches!(self, Self::Ipv4 | Self::DualStack)
+    }
+
+    fn ipv6(self) -> bool {
+        matches!(self, Self::Ipv6 | Self::DualStack)
+    }
+}
+
 impl Graph {
     /// Builds up a minimal DNS graph from `leaf` up to a root name server and returns all the
     /// name servers in the graph
     ///
-    /// All new name servers will share the `Implementation` of `leaf`.
+    /// All new name servers will share the `Implementation` and the [`Glue`] of `leaf`.
     ///
     /// The returned name servers are sorted from leaf zone to root zone.
     ///
@@ -40,42 +73,85 @@ impl Graph {
     /// key to the parent's zone file
     ///
     /// a non-empty `TrustAnchor` is returned only when `Sign::Yes` or `Sign::AndAmend` is used
+    ///
+    /// Name servers that do not depend on each other are created, signed and started
+    /// concurrently: sibling zones are signed at the same time but a parent zone is only signed
+    /// once the DS records of its children are in. Set `DNS_TEST_SERIAL_STARTUP` to do it one
+    /// name server at a time instead, e.g. when the docker daemon rate limits container creation.
     pub fn build(leaf: NameServer<Stopped>, sign: Sign) -> Result<Self> {
-        assert_eq!(2, leaf.zone().num_labels(), "not yet implemented");
-        assert_eq!(
-            Some(FQDN::TEST_TLD),
-            leaf.zone().parent(),
-            "not yet implemented"
+        Self::build_with_parallelism(leaf, sign, HashMap::new(), startup_parallelism())
+    }
+
+    /// [`Graph::build`] where the zones in `delegations` are not securely delegated to; they
+    /// only apply to signed graphs
+    pub(crate) fn build_with_delegations(
+        leaf: NameServer<Stopped>,
+        sign: Sign,
+        delegations: HashMap<FQDN, Delegation<'_>>,
+    ) -> Result<Self> {
+        Self::build_with_parallelism(leaf, sign, delegations, startup_parallelism())
+    }
+
+    /// [`Graph::build_with_delegations`] that signs, or starts, at most `parallelism` name
+    /// servers at once
+    fn build_with_parallelism(
+        leaf: NameServer<Stopped>,
+        sign: Sign,
+        mut delegations: HashMap<FQDN, Delegation<'_>>,
+        parallelism: usize,
+    ) -> Result<Self> {
+        assert!(
+            !leaf.zone().is_root(),
+            "the leaf zone cannot be the root zone"
         );
 
         // first pass: create nameservers for parent zones
-        let mut zone = leaf.zone().clone();
         let network = leaf.container.network().clone();
         let implementation = leaf.implementation.clone();
+        let glue = leaf.glue;
+        let new_nameserver = move |zone| {
+            NameServer::builder(implementation.clone(), zone, network.clone())
+                .glue(glue)
+                .build()
+        };
 
-        let (mut nameservers_ns, leaf) = if leaf.zone() != &FQDN::TEST_DOMAIN {
-            let nameservers_ns = NameServer::new(&implementation, FQDN::TEST_DOMAIN, &network)?;
+        let (nameservers_ns, leaf) = if leaf.zone() != &FQDN::TEST_DOMAIN {
+            let nameservers_ns = new_nameserver(FQDN::TEST_DOMAIN)?;
             (nameservers_ns, Some(leaf))
         } else {
             (leaf, None)
         };
 
-        // the nameserver covering `FQDN::NAMESERVERS` needs A records about all the nameservers in the graph
-        let mut nameservers = vec![];
-        while let Some(parent) = zone.parent() {
-            let nameserver = NameServer::new(&implementation, parent.clone(), &network)?;
+        // the names of the nameservers are in `FQDN::TEST_DOMAIN` so its parent zones are needed
+        // as well when the leaf zone is elsewhere, e.g. `in-addr.arpa.`
+        let mut parents: Vec<FQDN> = vec![];
+        let leaf_zone = leaf.as_ref().map(|leaf| leaf.zone().clone());
+        for mut zone in leaf_zone.iter().cloned().chain([FQDN::TEST_DOMAIN]) {
+            while let Some(parent) = zone.parent() {
+                let exists = parents.contains(&parent)
+                    || parent == FQDN::TEST_DOMAIN
+                    || Some(&parent) == leaf_zone.as_ref();
+                if !exists {
+                    parents.push(parent.clone());
+                }
+                zone = parent;
+            }
+        }
 
-            nameservers_ns.add(nameserver.a());
+        // the nameserver covering `FQDN::TEST_DOMAIN` needs the address records of all the
+        // nameservers in the graph
+        let mut nameservers = vec![nameservers_ns];
+        nameservers.extend(leaf);
+        let parents = map_parallel(parents, parallelism, FQDN::clone, new_nameserver)?;
+        for nameserver in parents {
+            for record in nameserver.address_records() {
+                nameservers[0].add(record);
+            }
             nameservers.push(nameserver);
-
-            zone = parent;
         }
-        drop((network, implementation));
 
-        if let Some(leaf) = leaf {
-            nameservers.insert(0, leaf);
-        }
-        nameservers.insert(0, nameservers_ns);
+        // leaf-most zone first; the root zone goes last
+        nameservers.sort_by_key(|nameserver| cmp::Reverse(nameserver.zone().num_labels()));
 
         // second pass: add referrals from parent to child
         // the nameservers are sorted leaf-most zone first but siblings may be next to each other
@@ -97,10 +173,12 @@ impl Graph {
         // start name servers
         let (nameservers, trust_anchor) = match sign {
             Sign::No => (
-                nameservers
-                    .into_iter()
-                    .map(|nameserver| nameserver.start())
-                    .collect::<Result<_>>()?,
+                map_parallel(
+                    nameservers,
+                    parallelism,
+                    zone_of,
+                    NameServer::<Stopped>::start,
+                )?,
                 None,
             ),
 
@@ -112,36 +190,58 @@ impl Graph {
                     Sign::AndAmend { settings, mutate } => (settings, Some(mutate)),
                 };
 
-                let mut running = vec![];
-                let mut children_ds = vec![];
-                let mut children_num_labels = 0;
-                let len = nameservers.len();
-                for (index, mut nameserver) in nameservers.into_iter().enumerate() {
-                    if !children_ds.is_empty() {
-                        let is_parent = nameserver.zone().num_labels() + 1 == children_num_labels;
-                        if is_parent {
-                            for ds in children_ds.drain(..) {
-                                nameserver.add(ds);
+                let mut signed = vec![];
+                // (parent zone, DS) pairs; children are signed before their parents
+                let mut children_ds: Vec<(FQDN, DS)> = vec![];
+                let mut nameservers = nameservers.into_iter().peekable();
+                // the name servers are sorted leaf-most zone first so each level, e.g. all the
+                // TLDs, is contiguous and only depends on the levels signed before it
+                while let Some(num_labels) = nameservers.peek().map(|it| it.zone().num_labels()) {
+                    let mut level = vec![];
+                    while let Some(mut nameserver) =
+                        nameservers.next_if(|it| it.zone().num_labels() == num_labels)
+                    {
+                        let zone = nameserver.zone().clone();
+                        children_ds.retain(|(parent, ds)| {
+                            let is_parent = *parent == zone;
+                            if is_parent {
+                                nameserver.add(ds.clone());
                             }
-                        }
+                            !is_parent
+                        });
+                        level.push(nameserver);
                     }
 
-                    let mut nameserver = nameserver.sign(settings.clone())?;
-                    children_ds.push(nameserver.ds().ksk.clone());
-                    children_num_labels = nameserver.zone().num_labels();
-                    if let Some(mutate) = maybe_mutate {
+                    let level = map_parallel(level, parallelism, zone_of, |nameserver| {
+                        nameserver.sign(settings.clone())
+                    })?;
+                    for mut nameserver in level {
                         let zone = nameserver.zone().clone();
-                        mutate(&zone, &mut nameserver.signed_zone_file_mut().records);
+                        if let Some(parent) = zone.parent() {
+                            let mut ds = nameserver.ds().ksk.clone();
+                            match delegations.remove(&zone) {
+                                None => children_ds.push((parent, ds)),
+                                Some(Delegation::Insecure) => {}
+                                Some(Delegation::Broken(mutate)) => {
+                                    mutate(&mut ds);
+                                    children_ds.push((parent, ds));
+                                }
+                            }
+                        }
+                        if let Some(mutate) = maybe_mutate {
+                            mutate(&zone, &mut nameserver.signed_zone_file_mut().records);
+                        }
+                        signed.push(nameserver);
                     }
+                }
 
-                    if index == len - 1 {
-                        // the last nameserver covers `.`
-                        trust_anchor.add(nameserver.key_signing_key().clone());
-                        trust_anchor.add(nameserver.zone_signing_key().clone());
-                    }
+                // the last nameserver covers `.`
+                let root_ns = signed.last().unwrap();
+                trust_anchor.add(root_ns.key_signing_key().clone());
+                trust_anchor.add(root_ns.zone_signing_key().clone());
 
-                    running.push(nameserver.start()?);
-                }
+                let running =
+                    map_parallel(signed, parallelism, zone_of, NameServer::<Signed>::start)?;
 
                 (running, Some(trust_anchor))
             }
@@ -155,12 +255,87 @@ impl Graph {
     }
 }
 
+/// Most name servers that [`Graph::build`] creates, signs or starts at once; bounds the load on
+/// the docker daemon
+const MAX_PARALLEL_STARTUPS: usize = 8;
+
+fn startup_parallelism() -> usize {
+    if env::var("DNS_TEST_SERIAL_STARTUP").is_ok() {
+        1
+    } else {
+        MAX_PARALLEL_STARTUPS
+    }
+}
+
+fn zone_of<S>(nameserver: &NameServer<S>) -> FQDN {
+    nameserver.zone().clone()
+}
+
+/// Applies `f` to every item on up to `parallelism` threads and returns the results in the order
+/// of `items`
+///
+/// Errors are prefixed with the zone, per `zone`, of the item that failed; when several items
+/// fail, the error of the first one in `items` is returned
+fn map_parallel<T: Send, U: Send>(
+    items: Vec<T>,
+    parallelism: usize,
+    zone: impl Fn(&T) -> FQDN,
+    f: impl Fn(T) -> Result<U> + Sync,
+) -> Result<Vec<U>> {
+    let zones = items.iter().map(zone).collect::<Vec<_>>();
+    let queue = Mutex::new(items.into_iter().enumerate());
+    let results = Mutex::new(zones.iter().map(|_| None).collect::<Vec<_>>());
+
+    thread::scope(|scope| {
+        for _ in 0..parallelism.clamp(1, zones.len().max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let Some((index, item)) = queue.lock().unwrap().next() else {
+                        break;
+                    };
+                    // `Error` is not `Send` so it cannot leave the thread as is
+                    let result = f(item).map_err(|e| e.to_string());
+                    results.lock().unwrap()[index] = Some(result);
+                }
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .zip(&zones)
+        .map(|(result, zone)| {
+            result
+                .expect("every item was processed")
+                .map_err(|e| format!("{zone}: {e}").into())
+        })
+        .collect()
+}
+
 /// Builder for [`NameServer`].
 pub struct NameServerBuilder {
     zone: FQDN,
     nameserver_fqdn: Option<FQDN>,
     implementation: Implementation,
+    verbosity: Verbosity,
+    udp_payload_size: Option<u16>,
+    query_logging: bool,
+    tsig_key: Option<TsigKey>,
+    transports: BTreeSet<Transport>,
+    port: u16,
+    threads: Option<usize>,
+    soa_settings: SoaSettings,
+    allow_update: bool,
+    glue: Glue,
     network: Network,
+    connected_networks: Vec<Network>,
+    cookies: Option<Cookies>,
+    rrl: Option<Rrl>,
+    zonemd: Option<ZonemdScheme>,
+    any_policy: AnyPolicy,
+    extra_configs: Vec<ExtraConfig>,
 }
 
 impl NameServerBuilder {
@@ -170,38 +345,138 @@ impl NameServerBuilder {
     /// it is started.
     ///
     /// The zone file will initially contain an SOA record, an NS record pointing to this name
-    /// server, and an A record with the address of this server.
+    /// server, and the A and/or AAAA records, per [`Self::glue`], with the addresses of this
+    /// server.
     pub fn build(self) -> Result<NameServer<Stopped>> {
         let Self {
             zone,
             nameserver_fqdn,
             implementation,
+            verbosity,
+            udp_payload_size,
+            query_logging,
+            tsig_key,
+            transports,
+            port,
+            threads,
+            soa_settings,
+            allow_update,
+            glue,
             network,
+            connected_networks,
+            cookies,
+            rrl,
+            zonemd,
+            any_policy,
+            extra_configs,
         } = self;
 
+        implementation.check_role(Role::NameServer)?;
+        implementation.check_transports(Role::NameServer, &transports)?;
+        if allow_update && !implementation.supports_dynamic_update() {
+            return Err(format!(
+                "the {implementation} name server does not support dynamic updates"
+            )
+            .into());
+        }
+        if threads.is_some() && !implementation.supports_threads() {
+            return Err(format!(
+                "the {implementation} name server does not support setting the number of threads"
+            )
+            .into());
+        }
+        if cookies.is_some() && !implementation.supports_cookies(Role::NameServer) {
+            return Err(format!(
+                "the {implementation} name server does not support configuring DNS cookies"
+            )
+            .into());
+        }
+        if cookies.as_ref().is_some_and(|cookies| cookies.require)
+            && !implementation.supports_required_cookies()
+        {
+            return Err(format!(
+                "the {implementation} name server does not support requiring server cookies"
+            )
+            .into());
+        }
+        if rrl.is_some() && !implementation.supports_rrl() {
+            return Err(format!(
+                "the {implementation} name server does not support Response Rate Limiting"
+            )
+            .into());
+        }
+        if zonemd.is_some() && !implementation.supports_zonemd() {
+            return Err(format!(
+                "the {implementation} name server does not generate ZONEMD records; sign the zone \
+                 with `SignSettings::zonemd` instead"
+            )
+            .into());
+        }
+        if !implementation.supports_any_policy(any_policy) {
+            return Err(format!(
+                "the {implementation} name server does not support the {any_policy} policy for \
+                 ANY queries"
+            )
+            .into());
+        }
+        if glue.ipv6() && network.ipv6_netmask().is_none() {
+            return Err("IPv6 glue requires a network with IPv6 enabled".into());
+        }
+
+        if query_logging && !implementation.supports_query_logging(Role::NameServer) {
+            return Err(format!(
+                "the {implementation} name server does not support logging queries"
+            )
+            .into());
+        }
+
+        let extra_config = ExtraConfig::snippets_for(&extra_configs, &implementation);
+
         let ns_count = ns_count();
         let nameserver = nameserver_fqdn.unwrap_or_else(|| primary_ns(ns_count, &zone));
         let admin = admin_ns(ns_count, &zone);
 
         let image = implementation.clone().into();
         let container = Container::run(&image, &network)?;
+        for network in &connected_networks {
+            container.connect(network)?;
+        }
 
         let soa = SOA {
             zone: zone.clone(),
             ttl: DEFAULT_TTL,
             nameserver: nameserver.clone(),
             admin,
-            settings: SoaSettings::default(),
+            settings: soa_settings,
         };
         let mut zone_file = ZoneFile::new(soa);
 
         zone_file.add(Record::ns(zone, nameserver.clone()));
-        // BIND requires that `nameserver` has an A record
-        zone_file.add(Record::a(nameserver.clone(), container.ipv4_addr()));
+        // BIND requires that `nameserver` has an address record
+        for record in address_records(&container, glue, &nameserver) {
+            zone_file.add(record);
+        }
 
         Ok(NameServer {
             container,
             implementation,
+            verbosity,
+            udp_payload_size,
+            query_logging,
+            tsig_key,
+            transports,
+            port,
+            threads,
+            secondaries: vec![],
+            allow_update,
+            catalog_zone: None,
+            glue,
+            views: vec![],
+            cookies,
+            rrl,
+            zonemd,
+            any_policy,
+            extra_config,
             state: Stopped,
             zone_file,
             additional_zones: HashMap::new(),
@@ -213,11 +488,148 @@ impl NameServerBuilder {
         self.nameserver_fqdn = Some(nameserver_fqdn);
         self
     }
+
+    /// Override how much the name server logs.
+    pub fn verbosity(mut self, verbosity: Verbosity) -> Self {
+        self.verbosity = verbosity;
+        self
+    }
+
+    /// Caps the size, in bytes, of the responses the name server sends over UDP; larger
+    /// responses are truncated.
+    pub fn udp_payload_size(mut self, udp_payload_size: u16) -> Self {
+        self.udp_payload_size = Some(udp_payload_size);
+        self
+    }
+
+    /// Logs every query the name server receives, e.g. to match what a client sent with what the
+    /// server received; BIND name servers always do
+    pub fn query_logging(mut self) -> Self {
+        self.query_logging = true;
+        self
+    }
+
+    /// Makes the name server listen on `transport` as well, e.g. DNS over TLS; it always listens
+    /// for plain DNS, on port 53 unless changed with [`Self::port`]
+    pub fn transport(mut self, transport: Transport) -> Self {
+        self.transports.insert(transport);
+        self
+    }
+
+    /// Makes the name server listen for plain DNS on `port` instead of 53
+    ///
+    /// Delegations cannot point at other ports, so resolvers do not find a name server that
+    /// listens elsewhere; query it directly with [`DigSettings::port`]
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Runs the name server with `threads` worker threads instead of the implementation's default
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    /// Allows zone transfers, and dynamic updates if enabled with [`Self::allow_update`], from
+    /// clients that sign their requests with `tsig_key`; all other transfer and update requests
+    /// are refused
+    pub fn tsig_key(mut self, tsig_key: TsigKey) -> Self {
+        self.tsig_key = Some(tsig_key);
+        self
+    }
+
+    /// Overrides the serial and timers of the zone's SOA record, e.g. a small `minimum` to
+    /// shorten how long resolvers cache negative answers
+    pub fn soa_settings(mut self, soa_settings: SoaSettings) -> Self {
+        self.soa_settings = soa_settings;
+        self
+    }
+
+    /// Accepts dynamic updates (RFC2136) of the zone; only those signed with the
+    /// [`Self::tsig_key`], if one is set, or else from any client
+    pub fn allow_update(mut self) -> Self {
+        self.allow_update = true;
+        self
+    }
+
+    /// Attaches the name server to `network` as well as to the network it was created for
+    ///
+    /// The published addresses of the name server are those in the latter; use
+    /// [`NameServer::ipv4_addr_in`] to point referrals at its address in `network` instead
+    pub fn connect(mut self, network: &Network) -> Self {
+        self.connected_networks.push(network.clone());
+        self
+    }
+
+    /// Overrides which addresses of the name server are published; see [`Glue`]
+    pub fn glue(mut self, glue: Glue) -> Self {
+        self.glue = glue;
+        self
+    }
+
+    /// Configures the DNS cookies (RFC7873) of the server, instead of the implementation's
+    /// defaults
+    pub fn cookies(mut self, cookies: Cookies) -> Self {
+        self.cookies = Some(cookies);
+        self
+    }
+
+    /// Limits the rate of the responses the server sends to each client; see [`Rrl`]
+    pub fn rrl(mut self, rrl: Rrl) -> Self {
+        self.rrl = Some(rrl);
+        self
+    }
+
+    /// Makes the server add a ZONEMD record to the zone; the zone must not be signed. See
+    /// [`ZonemdScheme`]
+    pub fn zonemd(mut self, scheme: ZonemdScheme) -> Self {
+        self.zonemd = Some(scheme);
+        self
+    }
+
+    /// Sets how the server answers queries of type ANY; see [`AnyPolicy`]
+    pub fn any_policy(mut self, any_policy: AnyPolicy) -> Self {
+        self.any_policy = any_policy;
+        self
+    }
+
+    /// Appends the snippet of `extra_config` verbatim to the main block of the server's
+    /// configuration, for directives that have no setting of their own, if the server runs the
+    /// implementation the snippet is for
+    ///
+    /// Can be called once per implementation, or several times for the same one; the syntax,
+    /// indentation included, is that of the implementation's configuration file, e.g. TOML for
+    /// hickory
+    pub fn extra_config(mut self, extra_config: ExtraConfig) -> Self {
+        self.extra_configs.push(extra_config);
+        self
+    }
 }
 
 pub struct NameServer<State> {
     container: Container,
     implementation: Implementation,
+    verbosity: Verbosity,
+    udp_payload_size: Option<u16>,
+    query_logging: bool,
+    tsig_key: Option<TsigKey>,
+    transports: BTreeSet<Transport>,
+    port: u16,
+    threads: Option<usize>,
+    /// addresses of the secondaries of the zone
+    secondaries: Vec<Ipv4Addr>,
+    allow_update: bool,
+    /// catalog zone (RFC9432) whose members are the additional zones
+    catalog_zone: Option<FQDN>,
+    glue: Glue,
+    /// networks whose clients are served another version of the zone; see [`NameServer::add_view`]
+    views: Vec<(String, ZoneFile)>,
+    cookies: Option<Cookies>,
+    rrl: Option<Rrl>,
+    zonemd: Option<ZonemdScheme>,
+    any_policy: AnyPolicy,
+    extra_config: Option<String>,
     state: State,
     zone_file: ZoneFile,
     additional_zones: HashMap<FQDN, ZoneFile>,
@@ -250,22 +662,62 @@ impl NameServer<Stopped> {
             zone,
             nameserver_fqdn: None,
             implementation,
+            verbosity: Verbosity::default(),
+            udp_payload_size: None,
+            query_logging: false,
+            tsig_key: None,
+            transports: BTreeSet::from([Transport::Do53]),
+            port: Transport::Do53.port(),
+            threads: None,
+            soa_settings: SoaSettings::default(),
+            allow_update: false,
+            glue: Glue::default(),
             network,
+            connected_networks: vec![],
+            cookies: None,
+            rrl: None,
+            zonemd: None,
+            any_policy: AnyPolicy::Normal,
+            extra_configs: vec![],
         }
     }
 
+    /// Zone file BEFORE signing
+    pub fn zone_file_mut(&mut self) -> &mut ZoneFile {
+        &mut self.zone_file
+    }
+
     /// Adds a NS + A record pair to the zone file
     pub fn referral(&mut self, zone: FQDN, nameserver: FQDN, ipv4_addr: Ipv4Addr) -> &mut Self {
         self.zone_file.referral(zone, nameserver, ipv4_addr);
         self
     }
 
-    /// Adds a NS + A record pair to the zone file from another NameServer
+    /// Adds a CNAME record, from `alias` to `canonical`, to the zone file
+    pub fn cname(&mut self, alias: FQDN, canonical: FQDN) -> &mut Self {
+        self.zone_file.cname(alias, canonical);
+        self
+    }
+
+    /// Adds a NS record, and the A and/or AAAA records that go with it, to the zone file from
+    /// another NameServer
     pub fn referral_nameserver<T>(&mut self, nameserver: &NameServer<T>) -> &mut Self {
-        self.referral(
+        self.add(Record::ns(
             nameserver.zone().clone(),
             nameserver.fqdn().clone(),
-            nameserver.ipv4_addr(),
+        ));
+        for record in nameserver.address_records() {
+            self.add(record);
+        }
+        self
+    }
+
+    /// Adds a NS + A record pair, of a secondary of this name server's zone, to the zone file
+    pub fn referral_secondary<T>(&mut self, secondary: &Secondary<T>) -> &mut Self {
+        self.referral(
+            secondary.zone().clone(),
+            secondary.fqdn().clone(),
+            secondary.ipv4_addr(),
         )
     }
 
@@ -286,16 +738,79 @@ impl NameServer<Stopped> {
         self.additional_zones.insert(name, zone);
     }
 
+    /// Serves `zone_file`, instead of the name server's zone file, to the clients in the
+    /// `match_clients` network, e.g. `10.0.0.0/8`
+    ///
+    /// Views are matched in the order they were added; clients that match none of them are served
+    /// the name server's zone file. The zone file of a view is written as is: it is not signed
+    /// along with the main zone file
+    pub fn add_view(&mut self, match_clients: &str, zone_file: ZoneFile) -> Result<&mut Self> {
+        let implementation = &self.implementation;
+        if !implementation.supports_views() {
+            return Err(format!("the {implementation} name server does not support views").into());
+        }
+        if zone_file.origin() != self.zone_file.origin() {
+            return Err(format!(
+                "the zone file of a view must have origin {}",
+                self.zone_file.origin()
+            )
+            .into());
+        }
+
+        self.views.push((match_clients.to_string(), zone_file));
+        Ok(self)
+    }
+
+    /// Serves a catalog zone (RFC9432), named `catalog`, whose members are the additional zones
+    ///
+    /// Secondaries created with [`Secondary::new`] afterwards consume the catalog: they transfer
+    /// the member zones from this server without any configuration of their own for them. See
+    /// [`NameServer::publish_zone`] to add members once the server is running
+    pub fn catalog_zone(&mut self, catalog: FQDN) -> Result<&mut Self> {
+        let implementation = &self.implementation;
+        if !implementation.supports_catalog_zones() {
+            return Err(
+                format!("the {implementation} name server does not support catalog zones").into(),
+            );
+        }
+
+        self.catalog_zone = Some(catalog);
+        Ok(self)
+    }
+
     /// Freezes and signs the name server's zone file
     pub fn sign(self, settings: SignSettings) -> Result<NameServer<Signed>> {
         let Self {
             container,
             zone_file,
             implementation,
+            verbosity,
+            udp_payload_size,
+            query_logging,
+            tsig_key,
+            transports,
+            port,
+            threads,
+            secondaries,
+            allow_update,
+            catalog_zone,
+            glue,
+            views,
+            cookies,
+            rrl,
+            zonemd,
+            any_policy,
+            extra_config,
             additional_zones,
             state: _,
         } = self;
 
+        if zonemd.is_some() {
+            return Err(
+                "the ZONEMD record of a signed zone must be added by `SignSettings::zonemd`".into(),
+            );
+        }
+
         let signer = Signer::new(&container, settings)?;
         let keys = signer.generate_keys(zone_file.origin())?;
         let state = signer.sign_zone(&zone_file, &keys)?;
@@ -303,6 +818,23 @@ impl NameServer<Stopped> {
         Ok(NameServer {
             container,
             implementation,
+            verbosity,
+            udp_payload_size,
+            query_logging,
+            tsig_key,
+            transports,
+            port,
+            threads,
+            secondaries,
+            allow_update,
+            catalog_zone,
+            glue,
+            views,
+            cookies,
+            rrl,
+            zonemd,
+            any_policy,
+            extra_config,
             zone_file,
             state,
             additional_zones,
@@ -319,16 +851,56 @@ impl NameServer<Stopped> {
             container,
             zone_file,
             implementation,
+            verbosity,
+            udp_payload_size,
+            query_logging,
+            tsig_key,
+            transports,
+            port,
+            threads,
+            secondaries,
+            allow_update,
+            catalog_zone,
+            glue,
+            views,
+            cookies,
+            rrl,
+            zonemd,
+            any_policy,
+            extra_config,
             additional_zones,
             state: _,
         } = self;
 
+        if zonemd.is_some() {
+            return Err(
+                "the ZONEMD record of a signed zone must be added by `SignSettings::zonemd`".into(),
+            );
+        }
+
         let signer = Signer::new(&container, settings)?;
         let state = signer.sign_zone(&zone_file, keys)?;
 
         Ok(NameServer {
             container,
             implementation,
+            verbosity,
+            udp_payload_size,
+            query_logging,
+            tsig_key,
+            transports,
+            port,
+            threads,
+            secondaries,
+            allow_update,
+            catalog_zone,
+            glue,
+            views,
+            cookies,
+            rrl,
+            zonemd,
+            any_policy,
+            extra_config,
             zone_file,
             state,
             additional_zones,
@@ -341,14 +913,50 @@ impl NameServer<Stopped> {
             container,
             zone_file,
             implementation,
+            verbosity,
+            udp_payload_size,
+            query_logging,
+            tsig_key,
+            transports,
+            port,
+            threads,
+            secondaries,
+            allow_update,
+            catalog_zone,
+            glue,
+            views,
+            cookies,
+            rrl,
+            zonemd,
+            any_policy,
+            extra_config,
             additional_zones,
             state: _,
         } = self;
 
+        let view_netmasks = view_netmasks(&views);
         let config = Config::NameServer {
             origin: zone_file.origin(),
             use_dnssec: false,
             additional_zones: additional_zones.clone(),
+            udp_payload_size,
+            query_logging,
+            tsig_key: tsig_key.as_ref(),
+            transports: &transports,
+            port,
+            listen_ipv6: container.ipv6_addr().is_some(),
+            threads,
+            secondaries: &secondaries,
+            allow_update,
+            primary: None,
+            catalog_zone: catalog_zone.as_ref(),
+            denial_of_existence: None,
+            views: &view_netmasks,
+            cookies: cookies.as_ref(),
+            rrl,
+            zonemd,
+            any_policy,
+            extra_config: extra_config.as_deref(),
         };
 
         if let Some(conf_file_path) = implementation.conf_file_path(config.role()) {
@@ -358,6 +966,10 @@ impl NameServer<Stopped> {
             )?;
         }
 
+        for (path, contents) in implementation.extra_config_files(&config) {
+            container.cp(path, &contents)?;
+        }
+
         container.status_ok(&["mkdir", "-p", ZONES_DIR])?;
         container.cp(&zone_file_path(), &zone_file.to_string())?;
 
@@ -365,34 +977,58 @@ impl NameServer<Stopped> {
             container.cp(&format!("{ZONES_DIR}/{key}zone"), &zone_file.to_string())?;
         }
 
-        let mut child = container.spawn(&implementation.cmd_args(config.role()))?;
+        for (index, (_, zone_file)) in views.iter().enumerate() {
+            container.cp(&view_zone_file_path(index), &zone_file.to_string())?;
+        }
 
-        // For Dnslib, make sure the python interpreter is still running after two seconds
-        if let Implementation::Dnslib = implementation {
-            thread::sleep(Duration::from_secs(2));
+        if let Some(catalog_zone) = &catalog_zone {
+            let catalog = catalog_zone_file(catalog_zone, &zone_file, &additional_zones);
+            container.cp(
+                &format!("{ZONES_DIR}/{catalog_zone}zone"),
+                &catalog.to_string(),
+            )?;
+        }
 
-            match child.try_wait() {
-                Ok(None) => {} // the process is still running
-                Ok(Some(status)) => {
-                    return Err(format!(
-                        "unable to start dnslib server: {status:?}; logs: {:?}",
-                        container
-                            .stdout(&["cat", &implementation.stderr_logfile(Role::NameServer)]),
-                    )
-                    .into());
-                }
-                Err(e) => println!("unable to determine if dnslib started: {e}"),
-            }
+        if transports.iter().any(Transport::uses_tls) {
+            transport::generate_tls_cert(&container, zone_file.soa.nameserver.as_str())?;
         }
 
+        let mut child = container.spawn(&implementation.cmd_args(&config, verbosity))?;
+
+        implementation.wait_until_ready(
+            &container,
+            &mut child,
+            &config,
+            implementation.readiness_timeout(),
+        )?;
+
         Ok(NameServer {
             container,
             implementation,
+            verbosity,
+            udp_payload_size,
+            query_logging,
+            tsig_key,
+            transports,
+            port,
+            threads,
+            secondaries,
+            allow_update,
+            catalog_zone,
+            glue,
+            views,
+            cookies,
+            rrl,
+            zonemd,
+            any_policy,
+            extra_config,
             zone_file,
             additional_zones,
             state: Running {
                 _child: child,
                 trust_anchor: None,
+                keys: None,
+                stats_baseline: QueryStats::default(),
             },
         })
     }
@@ -406,6 +1042,15 @@ const ZSK_PKCS8_FILENAME: &str = "zsk.pk8";
 fn zone_file_path() -> String {
     format!("{ZONES_DIR}/{ZONE_FILENAME}")
 }
+
+/// must match the `file` of the views in `named.name-server.conf.jinja`
+fn view_zone_file_path(index: usize) -> String {
+    format!("{ZONES_DIR}/view{index}.zone")
+}
+
+fn view_netmasks(views: &[(String, ZoneFile)]) -> Vec<&str> {
+    views.iter().map(|(netmask, _)| netmask.as_str()).collect()
+}
 fn zsk_private_path() -> String {
     format!("{ZONES_DIR}/{ZSK_PRIVATE_FILENAME}")
 }
@@ -414,6 +1059,44 @@ fn zsk_pkcs8_path() -> String {
     format!("{ZONES_DIR}/{ZSK_PKCS8_FILENAME}")
 }
 
+/// Catalog zone (RFC9432, version 2) that lists `members`; it shares the SOA serial of `zone_file`
+/// so that it changes whenever the server reloads
+fn catalog_zone_file(
+    catalog: &FQDN,
+    zone_file: &ZoneFile,
+    members: &HashMap<FQDN, ZoneFile>,
+) -> ZoneFile {
+    let invalid = FQDN("invalid.").unwrap();
+    let mut catalog_file = ZoneFile::new(SOA {
+        zone: catalog.clone(),
+        ttl: 0,
+        nameserver: invalid.clone(),
+        admin: invalid.clone(),
+        settings: zone_file.soa.settings.clone(),
+    });
+    catalog_file.add(Record::ns(catalog.clone(), invalid));
+    catalog_file.add(Record::TXT(TXT {
+        zone: catalog.push_label("version"),
+        ttl: 0,
+        character_strings: vec!["2".to_string()],
+    }));
+
+    let zones = catalog.push_label("zones");
+    let mut members = members.keys().collect::<Vec<_>>();
+    members.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+    for member in members {
+        // the unique ID of a member only has to be stable, so it is derived from its name
+        let id = member.as_str().trim_end_matches('.').replace('.', "-");
+        catalog_file.add(Record::PTR(PTR {
+            fqdn: zones.push_label(&id),
+            ttl: 0,
+            target: member.clone(),
+        }));
+    }
+
+    catalog_file
+}
+
 fn ns_count() -> usize {
     thread_local! {
         static COUNT: AtomicUsize = const { AtomicUsize::new(0) };
@@ -428,14 +1111,50 @@ impl NameServer<Signed> {
             container,
             zone_file,
             implementation,
+            verbosity,
+            udp_payload_size,
+            query_logging,
+            tsig_key,
+            transports,
+            port,
+            threads,
+            secondaries,
+            allow_update,
+            catalog_zone,
+            glue,
+            views,
+            cookies,
+            rrl,
+            zonemd,
+            any_policy,
+            extra_config,
             additional_zones,
             state,
         } = self;
 
+        let view_netmasks = view_netmasks(&views);
         let config = Config::NameServer {
             origin: zone_file.origin(),
             use_dnssec: state.use_dnssec,
             additional_zones: additional_zones.clone(),
+            udp_payload_size,
+            query_logging,
+            tsig_key: tsig_key.as_ref(),
+            transports: &transports,
+            port,
+            listen_ipv6: container.ipv6_addr().is_some(),
+            threads,
+            secondaries: &secondaries,
+            allow_update,
+            primary: None,
+            catalog_zone: catalog_zone.as_ref(),
+            denial_of_existence: Some(state.settings.denial_of_existence()),
+            views: &view_netmasks,
+            cookies: cookies.as_ref(),
+            rrl,
+            zonemd,
+            any_policy,
+            extra_config: extra_config.as_deref(),
         };
 
         if let Some(conf_file_path) = implementation.conf_file_path(config.role()) {
@@ -445,6 +1164,10 @@ impl NameServer<Signed> {
             )?;
         }
 
+        for (path, contents) in implementation.extra_config_files(&config) {
+            container.cp(path, &contents)?;
+        }
+
         if implementation.is_hickory() && state.use_dnssec {
             // FIXME: Hickory does not support pre-signed zonefiles. We copy the unsigned
             // zonefile so hickory can sign the zonefile itself.
@@ -472,16 +1195,80 @@ impl NameServer<Signed> {
             container.cp(&zone_file_path(), &state.signed.to_string())?;
         }
 
-        let child = container.spawn(&implementation.cmd_args(config.role()))?;
+        for (key, zone_file) in &additional_zones {
+            container.cp(&format!("{ZONES_DIR}/{key}zone"), &zone_file.to_string())?;
+        }
+
+        for (index, (_, zone_file)) in views.iter().enumerate() {
+            container.cp(&view_zone_file_path(index), &zone_file.to_string())?;
+        }
+
+        if let Some(catalog_zone) = &catalog_zone {
+            let catalog = catalog_zone_file(catalog_zone, &zone_file, &additional_zones);
+            container.cp(
+                &format!("{ZONES_DIR}/{catalog_zone}zone"),
+                &catalog.to_string(),
+            )?;
+        }
+
+        if implementation.is_powerdns() && state.use_dnssec {
+            // the bind backend only serves the RRSIG records found in the zone file when the zone
+            // is marked as pre-signed in its DNSSEC database
+            container.status_ok(&[
+                "pdnsutil",
+                "create-bind-db",
+                "/var/lib/powerdns/bind-dnssec-db.sqlite3",
+            ])?;
+            container.status_ok(&["pdnsutil", "set-presigned", zone_file.origin().as_str()])?;
+        }
+
+        if transports.iter().any(Transport::uses_tls) {
+            transport::generate_tls_cert(&container, zone_file.soa.nameserver.as_str())?;
+        }
+
+        let mut child = container.spawn(&implementation.cmd_args(&config, verbosity))?;
+        implementation.wait_until_ready(
+            &container,
+            &mut child,
+            &config,
+            implementation.readiness_timeout(),
+        )?;
 
+        let trust_anchor = state.trust_anchor();
+        let Signed { keys, settings, .. } = state;
         Ok(NameServer {
             container,
             implementation,
+            verbosity,
+            udp_payload_size,
+            query_logging,
+            tsig_key,
+            transports,
+            port,
+            threads,
+            secondaries,
+            allow_update,
+            catalog_zone,
+            glue,
+            views,
+            cookies,
+            rrl,
+            zonemd,
+            any_policy,
+            extra_config,
             zone_file,
             additional_zones,
             state: Running {
                 _child: child,
-                trust_anchor: Some(state.trust_anchor()),
+                trust_anchor: Some(trust_anchor),
+                keys: Some(ZoneKeys {
+                    settings,
+                    ksk: keys.ksk,
+                    additional_ksks: keys.additional_ksks,
+                    zsk: keys.zsk,
+                    standby_zsks: vec![],
+                }),
+                stats_baseline: QueryStats::default(),
             },
         })
     }
@@ -521,6 +1308,13 @@ impl NameServer<Running> {
         self.state.trust_anchor.as_ref()
     }
 
+    /// Certificate presented by the DoT and DoH listeners, to pass to [`Client::dig_tls`]
+    ///
+    /// Fails if the name server does not listen on any of those transports
+    pub fn tls_certificate(&self) -> Result<String> {
+        transport::read_tls_cert(&self.container)
+    }
+
     /// Returns the logs collected so far
     pub fn logs(&self) -> Result<String> {
         if self.implementation.is_hickory() || self.implementation.is_dnslib() {
@@ -534,6 +1328,273 @@ impl NameServer<Running> {
         }
     }
 
+    /// Counts the queries the server received since it started, or since the last
+    /// [`Self::reset_stats`]
+    ///
+    /// The queries are parsed from the logs of the server; fails if the implementation does not
+    /// log them, see [`Implementation::supports_query_stats`]
+    pub fn stats(&self) -> Result<QueryStats> {
+        Ok(self.logged_stats()?.since(&self.state.stats_baseline))
+    }
+
+    /// Makes [`Self::stats`] only count the queries received from now on
+    pub fn reset_stats(&mut self) -> Result<()> {
+        self.state.stats_baseline = self.logged_stats()?;
+        Ok(())
+    }
+
+    fn logged_stats(&self) -> Result<QueryStats> {
+        logs::query_stats(&self.implementation, &self.logs()?).ok_or_else(|| {
+            format!(
+                "the {} name server does not log the queries it receives",
+                self.implementation
+            )
+            .into()
+        })
+    }
+
+    /// Streams the lines the server writes to its stdout and stderr, starting from the first one
+    pub fn logs_stream(&self) -> Result<LogStream> {
+        self.container.tail_logs(&[
+            &self.implementation.stdout_logfile(Role::NameServer),
+            &self.implementation.stderr_logfile(Role::NameServer),
+        ])
+    }
+
+    /// Key tag of the ZSK that currently signs the zone; `None` if the zone is not signed
+    pub fn zone_signing_key_tag(&self) -> Option<u16> {
+        let keys = self.state.keys.as_ref()?;
+        Some(keys.zsk.public.rdata().calculate_key_tag())
+    }
+
+    /// Generates a new ZSK and publishes its DNSKEY next to the current one; the zone remains
+    /// signed with the current ZSK
+    ///
+    /// This is the first step of a pre-publish ZSK rollover. The zone is re-signed and the server
+    /// reloaded, without restarting its container. Returns the key tag of the new ZSK
+    ///
+    /// Changes made through [`NameServer::signed_zone_file_mut`] are not preserved
+    pub fn add_zsk(&mut self) -> Result<u16> {
+        let keys = self.state.keys.as_mut().ok_or("zone is not signed")?;
+
+        let signer = Signer::new(&self.container, keys.settings.clone())?;
+        let zsk_keytags = keys
+            .standby_zsks
+            .iter()
+            .chain([&keys.zsk])
+            .map(|zsk| zsk.public.rdata().calculate_key_tag())
+            .collect::<Vec<_>>();
+        let ksk_keytags = keys
+            .additional_ksks
+            .iter()
+            .chain([&keys.ksk])
+            .map(|ksk| ksk.public.rdata().calculate_key_tag())
+            .collect::<Vec<_>>();
+        let zsk = signer.generate_zsk(self.zone_file.origin(), &ksk_keytags, &zsk_keytags)?;
+        let zsk_keytag = zsk.public.rdata().calculate_key_tag();
+
+        if let Some(trust_anchor) = &mut self.state.trust_anchor {
+            trust_anchor.add(zsk.public.clone().with_ttl(self.zone_file.soa.ttl));
+        }
+        keys.standby_zsks.push(zsk);
+
+        self.reload()?;
+
+        Ok(zsk_keytag)
+    }
+
+    /// Re-signs the zone with the published ZSK identified by `key_tag`
+    ///
+    /// This is the second step of a pre-publish ZSK rollover: the DNSKEY of the previous ZSK
+    /// remains published. The server is reloaded, without restarting its container
+    pub fn resign_with(&mut self, key_tag: u16) -> Result<()> {
+        let keys = self.state.keys.as_mut().ok_or("zone is not signed")?;
+
+        let index = keys
+            .standby_zsks
+            .iter()
+            .position(|zsk| zsk.public.rdata().calculate_key_tag() == key_tag)
+            .ok_or_else(|| format!("no published ZSK has key tag {key_tag}"))?;
+        let zsk = keys.standby_zsks.remove(index);
+        let previous = mem::replace(&mut keys.zsk, zsk);
+        keys.standby_zsks.push(previous);
+
+        self.reload()
+    }
+
+    /// Adds `record` to the zone and reloads the server, without restarting its container
+    ///
+    /// See [`NameServer::reload`]
+    pub fn publish(&mut self, record: impl Into<Record>) -> Result<()> {
+        self.zone_file.add(record);
+        self.reload()
+    }
+
+    /// Adds an additional zone, which becomes a member of the catalog zone if there is one,
+    /// and reloads the server
+    ///
+    /// See [`NameServer::reload`]
+    pub fn publish_zone(&mut self, name: FQDN, zone: ZoneFile) -> Result<()> {
+        self.additional_zones.insert(name, zone);
+        self.reload()
+    }
+
+    /// Zone file of the server; changes made through it are served after the next
+    /// [`NameServer::reload`]
+    pub fn zone_file_mut(&mut self) -> &mut ZoneFile {
+        &mut self.zone_file
+    }
+
+    /// Writes the configuration and zone files again and makes the server load them, without
+    /// restarting its container
+    ///
+    /// The SOA serial is incremented so the secondaries of the zone transfer its new version.
+    /// Signed zones are re-signed with the current keys
+    pub fn reload(&mut self) -> Result<()> {
+        let reload = self.reload_cmd()?;
+        self.zone_file.soa.settings.serial += 1;
+
+        let view_netmasks = view_netmasks(&self.views);
+        let config = Config::NameServer {
+            origin: self.zone_file.origin(),
+            use_dnssec: self.state.keys.is_some(),
+            additional_zones: self.additional_zones.clone(),
+            udp_payload_size: self.udp_payload_size,
+            query_logging: self.query_logging,
+            tsig_key: self.tsig_key.as_ref(),
+            transports: &self.transports,
+            port: self.port,
+            listen_ipv6: self.container.ipv6_addr().is_some(),
+            threads: self.threads,
+            secondaries: &self.secondaries,
+            allow_update: self.allow_update,
+            primary: None,
+            catalog_zone: self.catalog_zone.as_ref(),
+            denial_of_existence: self
+                .state
+                .keys
+                .as_ref()
+                .map(|keys| keys.settings.denial_of_existence()),
+            views: &view_netmasks,
+            cookies: self.cookies.as_ref(),
+            rrl: self.rrl,
+            zonemd: self.zonemd,
+            any_policy: self.any_policy,
+            extra_config: self.extra_config.as_deref(),
+        };
+
+        if let Some(conf_file_path) = self.implementation.conf_file_path(config.role()) {
+            self.container.cp(
+                conf_file_path,
+                &self.implementation.format_config(config.clone()),
+            )?;
+        }
+
+        for (path, contents) in self.implementation.extra_config_files(&config) {
+            self.container.cp(path, &contents)?;
+        }
+
+        for (key, zone_file) in &self.additional_zones {
+            self.container
+                .cp(&format!("{ZONES_DIR}/{key}zone"), &zone_file.to_string())?;
+        }
+
+        for (index, (_, zone_file)) in self.views.iter().enumerate() {
+            self.container
+                .cp(&view_zone_file_path(index), &zone_file.to_string())?;
+        }
+
+        if let Some(catalog_zone) = &self.catalog_zone {
+            let catalog = catalog_zone_file(catalog_zone, &self.zone_file, &self.additional_zones);
+            self.container.cp(
+                &format!("{ZONES_DIR}/{catalog_zone}zone"),
+                &catalog.to_string(),
+            )?;
+        }
+
+        let zone_file = if let Some(keys) = &self.state.keys {
+            let mut zone_file = self.zone_file.clone();
+            let ttl = zone_file.soa.ttl;
+            for zsk in &keys.standby_zsks {
+                zone_file.add(zsk.public.clone().with_ttl(ttl));
+            }
+
+            let signer = Signer::new(&self.container, keys.settings.clone())?;
+            let signing_keys = SigningKeys {
+                ksk: keys.ksk.clone(),
+                zsk: keys.zsk.clone(),
+                additional_ksks: keys.additional_ksks.clone(),
+            };
+            signer.sign_zone(&zone_file, &signing_keys)?.signed
+        } else {
+            self.zone_file.clone()
+        };
+        self.container
+            .cp(&zone_file_path(), &zone_file.to_string())?;
+
+        let reload = reload.iter().map(String::as_str).collect::<Vec<_>>();
+        self.container.status_ok_with_output(&reload)
+    }
+
+    /// Replaces the zone file of the server with `zone_file` and reloads the server, without
+    /// restarting its container
+    ///
+    /// `zone_file` must be for the zone of the server. Its SOA serial is raised above the one
+    /// currently served, if needed, so the secondaries of the zone transfer it. See
+    /// [`NameServer::reload`]
+    pub fn reload_zone(&mut self, mut zone_file: ZoneFile) -> Result<()> {
+        if zone_file.origin() != self.zone_file.origin() {
+            return Err(format!(
+                "the zone file is for {}, not for the {} zone of the server",
+                zone_file.origin(),
+                self.zone_file.origin()
+            )
+            .into());
+        }
+
+        let serial = &mut zone_file.soa.settings.serial;
+        *serial = (*serial).max(self.zone_file.soa.settings.serial);
+        self.zone_file = zone_file;
+        self.reload()
+    }
+
+    /// Command that makes the server load its configuration and zone files again
+    fn reload_cmd(&self) -> Result<Vec<String>> {
+        let implementation = &self.implementation;
+        if !implementation.supports_reload() {
+            return Err(format!(
+                "the {implementation} name server cannot reload its zone files without restarting"
+            )
+            .into());
+        }
+
+        Ok(match implementation {
+            Implementation::Bind { .. } | Implementation::Knot => {
+                let pidfile = implementation
+                    .pidfile(Role::NameServer)
+                    .ok_or_else(|| format!("the {implementation} name server has no pidfile"))?;
+                vec![
+                    "sh".into(),
+                    "-c".into(),
+                    format!("kill -HUP $(cat {pidfile})"),
+                ]
+            }
+
+            Implementation::Unbound { .. } => vec!["nsd-control".into(), "reload".into()],
+
+            Implementation::PowerDns => vec![
+                "pdns_control".into(),
+                "--socket-dir=/tmp".into(),
+                "bind-reload-now".into(),
+                self.zone().as_str().into(),
+            ],
+
+            Implementation::Hickory { .. } | Implementation::Dnslib | Implementation::EdeDotCom => {
+                unreachable!()
+            }
+        })
+    }
+
     fn stdout(&self) -> Result<String> {
         self.container
             .stdout(&["cat", &self.implementation.stdout_logfile(Role::NameServer)])
@@ -562,6 +1623,17 @@ impl<S> NameServer<S> {
         self.container.ipv4_addr()
     }
 
+    /// Returns the IPv6 address of the name server; `None` unless its network has IPv6 enabled
+    pub fn ipv6_addr(&self) -> Option<Ipv6Addr> {
+        self.container.ipv6_addr()
+    }
+
+    /// Returns the IPv4 address of the name server in `network`; `None` if it is not attached
+    /// to it
+    pub fn ipv4_addr_in(&self, network: &Network) -> Option<Ipv4Addr> {
+        self.container.ipv4_addr_in(network)
+    }
+
     /// Zone file BEFORE signing
     pub fn zone_file(&self) -> &ZoneFile {
         &self.zone_file
@@ -580,11 +1652,242 @@ impl<S> NameServer<S> {
         Record::a(self.fqdn().clone(), self.ipv4_addr())
     }
 
-    /// Returns the [`Root`] hint for this server.
-    pub fn root_hint(&self) -> Root {
-        Root::new(self.fqdn().clone(), self.ipv4_addr())
+    /// Returns the [`Record::A`] and [`Record::AAAA`] records for this server, per its [`Glue`].
+    pub fn address_records(&self) -> Vec<Record> {
+        address_records(&self.container, self.glue, self.fqdn())
     }
-}
+
+    /// Returns the [`Root`] hint for this server, with the addresses per its [`Glue`].
+    pub fn root_hint(&self) -> Root {
+        Root {
+            ipv4_addr: self.glue.ipv4().then(|| self.ipv4_addr()),
+            ipv6_addr: self.ipv6_addr().filter(|_| self.glue.ipv6()),
+            ns: self.fqdn().clone(),
+            ttl: DEFAULT_TTL,
+        }
+    }
+}
+
+fn address_records(container: &Container, glue: Glue, nameserver: &FQDN) -> Vec<Record> {
+    let mut records = vec![];
+    if glue.ipv4() {
+        records.push(Record::a(nameserver.clone(), container.ipv4_addr()));
+    }
+    if glue.ipv6() {
+        let ipv6_addr = container
+            .ipv6_addr()
+            .expect("IPv6 glue requires a network with IPv6 enabled");
+        records.push(Record::aaaa(nameserver.clone(), ipv6_addr));
+    }
+    records
+}
+
+/// A secondary name server: it has authority over the zone of its primary name server and
+/// serves the copy of the zone that it transfers (AXFR) from it
+pub struct Secondary<State> {
+    container: Container,
+    implementation: Implementation,
+    verbosity: Verbosity,
+    zone: FQDN,
+    fqdn: FQDN,
+    primary: Ipv4Addr,
+    /// catalog zone of the primary, whose members are transferred as well
+    catalog_zone: Option<FQDN>,
+    _state: State,
+}
+
+impl Secondary<Stopped> {
+    /// Spins up a secondary, using `implementation`, of the zone of `primary`
+    ///
+    /// The FQDN of the secondary will have the form `secondary{count}.nameservers.com.`. Its NS
+    /// and A records are added to the zone file of `primary`, which is configured to allow the
+    /// secondary to transfer the zone and to notify it when the zone changes
+    ///
+    /// Start `primary` before the secondary so the latter can transfer the zone right away
+    pub fn new(implementation: &Implementation, primary: &mut NameServer<Stopped>) -> Result<Self> {
+        for implementation in [implementation, &primary.implementation] {
+            if !implementation.supports_secondaries() {
+                return Err(format!(
+                    "the {implementation} name server does not support secondaries"
+                )
+                .into());
+            }
+        }
+
+        let image = implementation.clone().into();
+        let container = Container::run(&image, primary.container.network())?;
+
+        let zone = primary.zone().clone();
+        let fqdn = secondary_ns(ns_count(), &zone);
+        primary.add(Record::ns(zone.clone(), fqdn.clone()));
+        primary.add(Record::a(fqdn.clone(), container.ipv4_addr()));
+        primary.secondaries.push(container.ipv4_addr());
+
+        Ok(Self {
+            container,
+            implementation: implementation.clone(),
+            verbosity: Verbosity::default(),
+            zone,
+            fqdn,
+            primary: primary.ipv4_addr(),
+            catalog_zone: primary.catalog_zone.clone(),
+            _state: Stopped,
+        })
+    }
+
+    /// Moves the server to the "Start" state where it transfers the zone from its primary and
+    /// answers client queries
+    pub fn start(self) -> Result<Secondary<Running>> {
+        let Self {
+            container,
+            implementation,
+            verbosity,
+            zone,
+            fqdn,
+            primary,
+            catalog_zone,
+            _state: _,
+        } = self;
+
+        let transports = BTreeSet::from([Transport::Do53]);
+        let config = Config::NameServer {
+            origin: &zone,
+            use_dnssec: false,
+            additional_zones: HashMap::new(),
+            udp_payload_size: None,
+            tsig_key: None,
+            transports: &transports,
+            port: Transport::Do53.port(),
+            listen_ipv6: container.ipv6_addr().is_some(),
+            threads: None,
+            secondaries: &[],
+            allow_update: false,
+            primary: Some(primary),
+            query_logging: false,
+            catalog_zone: catalog_zone.as_ref(),
+            denial_of_existence: None,
+            views: &[],
+            cookies: None,
+            rrl: None,
+            zonemd: None,
+            any_policy: AnyPolicy::Normal,
+            extra_config: None,
+        };
+
+        if let Some(conf_file_path) = implementation.conf_file_path(config.role()) {
+            container.cp(
+                conf_file_path,
+                &implementation.format_config(config.clone()),
+            )?;
+        }
+
+        for (path, contents) in implementation.extra_config_files(&config) {
+            container.cp(path, &contents)?;
+        }
+
+        container.status_ok(&["mkdir", "-p", ZONES_DIR])?;
+
+        let mut child = container.spawn(&implementation.cmd_args(&config, verbosity))?;
+        implementation.wait_until_ready(
+            &container,
+            &mut child,
+            &config,
+            implementation.readiness_timeout(),
+        )?;
+
+        Ok(Secondary {
+            container,
+            implementation,
+            verbosity,
+            zone,
+            fqdn,
+            primary,
+            catalog_zone,
+            _state: Running {
+                _child: child,
+                trust_anchor: None,
+                keys: None,
+                stats_baseline: QueryStats::default(),
+            },
+        })
+    }
+}
+
+impl Secondary<Running> {
+    /// Blocks until the secondary serves the version of the zone whose SOA serial is, at least,
+    /// `serial`
+    ///
+    /// Gives up after 30 seconds
+    pub fn wait_for_transfer(&self, serial: u32) -> Result<()> {
+        self.wait_for_zone_transfer(&self.zone, serial)
+    }
+
+    /// Like [`Self::wait_for_transfer`] but for `zone`, e.g. a member of the catalog zone of the
+    /// primary
+    pub fn wait_for_zone_transfer(&self, zone: &FQDN, serial: u32) -> Result<()> {
+        let client = Client::new(self.container.network())?;
+        let settings = *DigSettings::default().timeout(Duration::from_secs(1));
+
+        for _ in 0..30 {
+            // the secondary answers SERVFAIL until it has transferred the zone
+            let current = client
+                .dig(settings, self.ipv4_addr(), RecordType::SOA, zone)
+                .ok()
+                .and_then(|output| {
+                    output
+                        .answer
+                        .into_iter()
+                        .find_map(|record| record.try_into_soa().ok())
+                })
+                .map(|soa| soa.settings.serial);
+
+            if current.is_some_and(|current| current >= serial) {
+                return Ok(());
+            }
+
+            thread::sleep(Duration::from_secs(1));
+        }
+
+        Err(format!(
+            "{} did not transfer version {serial} of {zone} from {}",
+            self.fqdn, self.primary
+        )
+        .into())
+    }
+
+    /// Returns the logs collected so far
+    pub fn logs(&self) -> Result<String> {
+        self.container
+            .stdout(&["cat", &self.implementation.stderr_logfile(Role::NameServer)])
+    }
+}
+
+impl<S> Secondary<S> {
+    pub fn container_id(&self) -> &str {
+        self.container.id()
+    }
+
+    pub fn container_name(&self) -> &str {
+        self.container.name()
+    }
+
+    pub fn ipv4_addr(&self) -> Ipv4Addr {
+        self.container.ipv4_addr()
+    }
+
+    pub fn zone(&self) -> &FQDN {
+        &self.zone
+    }
+
+    pub fn fqdn(&self) -> &FQDN {
+        &self.fqdn
+    }
+
+    /// Returns the [`Record::A`] record for this server.
+    pub fn a(&self) -> Record {
+        Record::a(self.fqdn().clone(), self.ipv4_addr())
+    }
+}
 
 pub struct Stopped;
 
@@ -593,15 +1896,23 @@ pub struct Stopped;
 pub struct DS2 {
     pub ksk: DS,
     pub zsk: DS,
+    /// DS records for `SigningKeys::additional_ksks`, in the same order
+    pub additional_ksks: Vec<DS>,
 }
 
 impl DS2 {
-    pub(crate) fn classify(dses: Vec<DS>, zsk: &zone_file::DNSKEY, ksk: &zone_file::DNSKEY) -> DS2 {
+    pub(crate) fn classify(dses: Vec<DS>, keys: &SigningKeys) -> DS2 {
         let mut ksk_ds = None;
         let mut zsk_ds = None;
 
-        let zsk_tag = zsk.rdata().calculate_key_tag();
-        let ksk_tag = ksk.rdata().calculate_key_tag();
+        let zsk_tag = keys.zsk.public.rdata().calculate_key_tag();
+        let ksk_tag = keys.ksk.public.rdata().calculate_key_tag();
+        let additional_ksk_tags = keys
+            .additional_ksks
+            .iter()
+            .map(|ksk| ksk.public.rdata().calculate_key_tag())
+            .collect::<Vec<_>>();
+        let mut additional_ksks = vec![None; additional_ksk_tags.len()];
         for ds in dses {
             if ds.key_tag == zsk_tag {
                 assert!(zsk_ds.is_none());
@@ -609,12 +1920,22 @@ impl DS2 {
             } else if ds.key_tag == ksk_tag {
                 assert!(ksk_ds.is_none());
                 ksk_ds = Some(ds);
+            } else if let Some(index) = additional_ksk_tags
+                .iter()
+                .position(|tag| *tag == ds.key_tag)
+            {
+                assert!(additional_ksks[index].is_none());
+                additional_ksks[index] = Some(ds);
             }
         }
 
         DS2 {
             ksk: ksk_ds.expect("DS for KSK not found"),
             zsk: zsk_ds.expect("DS for ZSK not found"),
+            additional_ksks: additional_ksks
+                .into_iter()
+                .map(|ds| ds.expect("DS for additional KSK not found"))
+                .collect(),
         }
     }
 }
@@ -624,6 +1945,8 @@ pub struct Signed {
     pub(crate) zsk: record::DNSKEY,
     pub(crate) ksk: record::DNSKEY,
     pub(crate) signed: ZoneFile,
+    pub(crate) keys: SigningKeys,
+    pub(crate) settings: SignSettings,
     pub(crate) use_dnssec: bool,
 }
 
@@ -645,12 +1968,30 @@ impl Signed {
 pub struct Running {
     _child: Child,
     trust_anchor: Option<TrustAnchor>,
+    keys: Option<ZoneKeys>,
+    /// Queries that [`NameServer::stats`] does not count; see [`NameServer::reset_stats`]
+    stats_baseline: QueryStats,
+}
+
+/// Keys used to re-sign the zone of a running name server
+struct ZoneKeys {
+    settings: SignSettings,
+    ksk: Keypair,
+    additional_ksks: Vec<Keypair>,
+    /// signs the zone
+    zsk: Keypair,
+    /// published in the zone but not used to sign it
+    standby_zsks: Vec<Keypair>,
 }
 
 fn primary_ns(ns_count: usize, zone: &FQDN) -> FQDN {
     FQDN(format!("primary{ns_count}.{}", expand_zone(zone))).unwrap()
 }
 
+fn secondary_ns(ns_count: usize, zone: &FQDN) -> FQDN {
+    FQDN(format!("secondary{ns_count}.{}", expand_zone(zone))).unwrap()
+}
+
 fn admin_ns(ns_count: usize, zone: &FQDN) -> FQDN {
     FQDN(format!("admin{ns_count}.{}", expand_zone(zone))).unwrap()
 }
@@ -659,12 +2000,10 @@ fn expand_zone(zone: &FQDN) -> String {
     if zone == &FQDN::ROOT {
         FQDN::TEST_DOMAIN.as_str().to_string()
     } else if zone.num_labels() == 1 {
-        if *zone == FQDN::TEST_TLD {
-            FQDN::TEST_DOMAIN.as_str().to_string()
-        } else if *zone == FQDN::COM_TLD {
+        if *zone == FQDN::COM_TLD {
             "nameservers.com.".to_string()
         } else {
-            unimplemented!()
+            FQDN::TEST_DOMAIN.as_str().to_string()
         }
     } else {
         zone.to_string()
@@ -674,18 +2013,80 @@ fn expand_zone(zone: &FQDN) -> String {
 #[cfg(test)]
 mod tests {
     use std::thread;
-    use std::time::Duration;
+    use std::time::{Duration, Instant};
 
-    use crate::client::{Client, DigSettings};
+    use crate::client::{
+        Client, DigOutput, DigSettings, DigStatus, DnsClient, NativeClient, Protocol, Update,
+    };
     use crate::record::{A, NS, RecordType};
+    use crate::{TsigAlgorithm, TsigError};
 
     use super::*;
 
+    #[test]
+    fn map_parallel_keeps_order_and_reports_the_failed_zone() {
+        let zones = [FQDN::ROOT, FQDN::TEST_TLD, FQDN::TEST_DOMAIN, FQDN::COM_TLD];
+
+        let labels = map_parallel(zones.to_vec(), 2, FQDN::clone, |zone| {
+            // finish out of order
+            thread::sleep(Duration::from_millis(10 * (4 - zone.num_labels() as u64)));
+            Ok(zone.num_labels())
+        })
+        .unwrap();
+        assert_eq!(
+            zones.iter().map(FQDN::num_labels).collect::<Vec<_>>(),
+            labels
+        );
+
+        let error = map_parallel(zones.to_vec(), 2, FQDN::clone, |zone| {
+            if zone.num_labels() == 1 {
+                Err("signing failed".into())
+            } else {
+                Ok(())
+            }
+        })
+        .unwrap_err();
+        assert_eq!(
+            format!("{}: signing failed", FQDN::TEST_TLD),
+            error.to_string()
+        );
+    }
+
+    /// Prints how long building a signed root -> TLD -> domain graph takes one name server at a
+    /// time versus concurrently; run with `cargo test -- --ignored --nocapture`
+    #[test]
+    #[ignore = "benchmark"]
+    fn graph_build_parallel_speedup() -> Result<()> {
+        let network = Network::new()?;
+        let mut timings = vec![];
+        for parallelism in [1, MAX_PARALLEL_STARTUPS] {
+            let leaf = NameServer::new(&Implementation::bind(), FQDN::TEST_DOMAIN, &network)?;
+            let sign = Sign::Yes {
+                settings: SignSettings::default(),
+            };
+
+            let start = Instant::now();
+            let graph = Graph::build_with_parallelism(leaf, sign, HashMap::new(), parallelism)?;
+            timings.push(start.elapsed());
+
+            assert_eq!(3, graph.nameservers.len());
+            assert!(graph.trust_anchor.is_some());
+        }
+
+        let [sequential, parallel] = timings.try_into().unwrap();
+        eprintln!(
+            "sequential: {sequential:?}, parallel: {parallel:?}, speedup: {:.2}x",
+            sequential.as_secs_f64() / parallel.as_secs_f64()
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn simplest() -> Result<()> {
         let network = Network::new()?;
         let tld_ns =
-            NameServer::new(&Implementation::Unbound, FQDN::TEST_TLD, &network)?.start()?;
+            NameServer::new(&Implementation::unbound(), FQDN::TEST_TLD, &network)?.start()?;
         let ip_addr = tld_ns.ipv4_addr();
 
         let client = Client::new(&network)?;
@@ -701,11 +2102,86 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn hickory_simplest() -> Result<()> {
+        answers_soa_and_ns(&Implementation::hickory())
+    }
+
+    #[test]
+    fn powerdns_simplest() -> Result<()> {
+        answers_soa_and_ns(&Implementation::PowerDns)
+    }
+
+    fn answers_soa_and_ns(implementation: &Implementation) -> Result<()> {
+        let network = Network::new()?;
+        let ns = NameServer::new(implementation, FQDN::ROOT, &network)?.start()?;
+
+        let client = Client::new(&network)?;
+        for record_type in [RecordType::SOA, RecordType::NS] {
+            let output = client.dig(
+                DigSettings::default(),
+                ns.ipv4_addr(),
+                record_type,
+                &FQDN::ROOT,
+            )?;
+
+            assert!(output.status.is_noerror());
+            assert!(output.flags.authoritative_answer);
+            assert_eq!(1, output.answer.len());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn udp_payload_size_truncates_large_responses() -> Result<()> {
+        let network = Network::new()?;
+        let ns = NameServer::builder(Implementation::bind(), FQDN::ROOT, network.clone())
+            .udp_payload_size(512)
+            .build()?
+            .sign(SignSettings::rsasha256())?
+            .start()?;
+
+        let client = Client::new(&network)?;
+        // two 2048-bit RSA keys plus their signature do not fit in 512 bytes
+        let settings = *DigSettings::default().dnssec().bufsize(4096).ignore();
+        let output = client.dig(settings, ns.ipv4_addr(), RecordType::DNSKEY, &FQDN::ROOT)?;
+
+        assert!(output.status.is_noerror());
+        assert!(output.flags.truncation);
+        assert_eq!(Some(Protocol::Udp), output.protocol);
+
+        // without `+ignore`, dig retries over TCP
+        let settings = *DigSettings::default().dnssec().bufsize(4096);
+        let output = client.dig(settings, ns.ipv4_addr(), RecordType::DNSKEY, &FQDN::ROOT)?;
+
+        assert!(output.status.is_noerror());
+        assert!(!output.flags.truncation);
+        assert_eq!(Some(Protocol::Tcp), output.protocol);
+
+        Ok(())
+    }
+
+    #[test]
+    fn hickory_pidfile() -> Result<()> {
+        let network = Network::new()?;
+        let implementation = Implementation::hickory();
+        let ns = NameServer::new(&implementation, FQDN::ROOT, &network)?.start()?;
+
+        let pidfile = implementation.pidfile(Role::NameServer).unwrap();
+        let comm =
+            ns.container()
+                .stdout(&["sh", "-c", &format!("cat /proc/$(cat {pidfile})/comm")])?;
+        assert_eq!("hickory-dns", comm);
+
+        Ok(())
+    }
+
     #[test]
     fn with_referral() -> Result<()> {
         let network = Network::new()?;
         let expected_ip_addr = Ipv4Addr::new(172, 17, 200, 1);
-        let mut root_ns = NameServer::new(&Implementation::Unbound, FQDN::ROOT, &network)?;
+        let mut root_ns = NameServer::new(&Implementation::unbound(), FQDN::ROOT, &network)?;
         root_ns.referral(
             FQDN::TEST_TLD,
             FQDN("primary.tld-server.com.")?,
@@ -733,7 +2209,7 @@ mod tests {
     #[test]
     fn signed() -> Result<()> {
         let network = Network::new()?;
-        let ns = NameServer::new(&Implementation::Unbound, FQDN::ROOT, &network)?
+        let ns = NameServer::new(&Implementation::unbound(), FQDN::ROOT, &network)?
             .sign(SignSettings::default())?;
 
         eprintln!("KSK:\n{}", ns.key_signing_key());
@@ -762,13 +2238,831 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn publishes_cds() -> Result<()> {
+        let network = Network::new()?;
+        let settings = SignSettings::default();
+        let mut ns = NameServer::new(&Implementation::unbound(), FQDN::ROOT, &network)?;
+
+        let signer = Signer::new(ns.container(), settings.clone())?;
+        let keys = signer.generate_keys(&FQDN::ROOT)?;
+        let cds = signer.cds(&keys.ksk.public)?;
+        ns.add(cds);
+
+        let ns = ns.sign_with_keys(settings, &keys)?.start()?;
+
+        let client = Client::new(&network)?;
+        let output = client.dig(
+            DigSettings::default(),
+            ns.ipv4_addr(),
+            RecordType::CDS,
+            &FQDN::ROOT,
+        )?;
+
+        assert!(output.status.is_noerror());
+        let [cds] = output.answer.try_into().unwrap();
+        let cds = cds.try_into_cds().unwrap();
+        assert_eq!(keys.ksk.public.rdata().calculate_key_tag(), cds.key_tag);
+        assert_eq!(2, cds.digest_type);
+
+        Ok(())
+    }
+
+    #[test]
+    fn signed_zone_has_a_valid_zonemd() -> Result<()> {
+        let network = Network::new()?;
+        let settings = SignSettings::default().zonemd(ZonemdScheme::SimpleSha384);
+        let mut ns = NameServer::new(&Implementation::unbound(), FQDN::TEST_DOMAIN, &network)?;
+        ns.add(Record::a(
+            FQDN::EXAMPLE_SUBDOMAIN,
+            Ipv4Addr::new(1, 2, 3, 4),
+        ));
+        let ns = ns.sign(settings)?;
+
+        let zonemd = ns
+            .signed_zone_file()
+            .records
+            .iter()
+            .find_map(|record| record.clone().try_into_zonemd().ok())
+            .expect("the signed zone has a ZONEMD record");
+        assert_eq!(ns.zone_file().soa.settings.serial, zonemd.serial);
+        assert_eq!((1, 1), (zonemd.scheme, zonemd.hash_algorithm));
+
+        let client = Client::new(&network)?;
+        client.verify_zonemd(ns.signed_zone_file())?;
+
+        let mut tampered = ns.signed_zone_file().clone();
+        for record in &mut tampered.records {
+            if let Record::A(a) = record {
+                a.ipv4_addr = Ipv4Addr::new(5, 6, 7, 8);
+            }
+        }
+        assert!(client.verify_zonemd(&tampered).is_err());
+
+        let error = NameServer::builder(Implementation::unbound(), FQDN::TEST_DOMAIN, network)
+            .zonemd(ZonemdScheme::SimpleSha384)
+            .build()
+            .err()
+            .unwrap();
+        assert!(
+            error.to_string().contains("SignSettings::zonemd"),
+            "{error}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn seeded_keys_are_the_same_in_every_run() -> Result<()> {
+        let network = Network::new()?;
+        let settings = SignSettings::ed25519().seed(42);
+        let ns = NameServer::new(&Implementation::unbound(), FQDN::TEST_DOMAIN, &network)?
+            .sign(settings.clone())?;
+
+        let ds = &ns.ds().ksk;
+        assert_eq!(56925, ds.key_tag);
+        assert_eq!(2, ds.digest_type);
+        assert!(
+            ds.digest.eq_ignore_ascii_case(
+                "f251e982d1543d2e3aced02d70059ec77b72d8c3bc2a7c68e1bfd02add28521c"
+            ),
+            "{}",
+            ds.digest
+        );
+        assert_eq!(22648, ns.zone_signing_key().rdata.calculate_key_tag());
+
+        let signer = Signer::new(ns.container(), settings)?;
+        let keys = signer.generate_keys(&FQDN::TEST_DOMAIN)?;
+        assert_eq!(
+            ns.key_signing_key().rdata.public_key,
+            keys.ksk.public.rdata().public_key
+        );
+
+        let signer = Signer::new(ns.container(), SignSettings::rsasha256().seed(42))?;
+        let error = signer.generate_keys(&FQDN::TEST_DOMAIN).err().unwrap();
+        assert!(error.to_string().contains("cannot be derived"), "{error}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn serves_per_record_ttls() -> Result<()> {
+        let network = Network::new()?;
+        let short = FQDN::TEST_DOMAIN.push_label("short");
+        let long = FQDN::TEST_DOMAIN.push_label("long");
+
+        let mut ns = NameServer::new(&Implementation::bind(), FQDN::TEST_DOMAIN, &network)?;
+        for (fqdn, ttl) in [(&short, 1), (&long, 300)] {
+            ns.add(A {
+                fqdn: fqdn.clone(),
+                ttl,
+                ipv4_addr: Ipv4Addr::new(192, 0, 2, 1),
+            });
+        }
+        let ns = ns.start()?;
+
+        let client = Client::new(&network)?;
+        for (fqdn, ttl) in [(&short, 1), (&long, 300)] {
+            let output = client.dig(DigSettings::default(), ns.ipv4_addr(), RecordType::A, fqdn)?;
+
+            assert!(output.status.is_noerror());
+            let [a] = output.answer.try_into().unwrap();
+            assert_eq!(ttl, a.try_into_a().unwrap().ttl);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn serves_soa_settings() -> Result<()> {
+        let network = Network::new()?;
+        let ns = NameServer::builder(Implementation::bind(), FQDN::TEST_DOMAIN, network.clone())
+            .soa_settings(SoaSettings {
+                serial: 42,
+                refresh: 60,
+                retry: 30,
+                expire: 3600,
+                minimum: 2,
+            })
+            .build()?
+            .start()?;
+
+        let client = Client::new(&network)?;
+        let output = client.dig(
+            DigSettings::default(),
+            ns.ipv4_addr(),
+            RecordType::SOA,
+            &FQDN::TEST_DOMAIN,
+        )?;
+
+        assert!(output.status.is_noerror());
+        let [soa] = output.answer.try_into().unwrap();
+        let soa = soa.try_into_soa().unwrap();
+        assert_eq!(
+            "42 60 30 3600 2",
+            soa.settings.to_string(),
+            "unexpected SOA record: {soa}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn bind_tsig_transfer() -> Result<()> {
+        tsig_transfer(&Implementation::bind())
+    }
+
+    #[test]
+    fn nsd_tsig_transfer() -> Result<()> {
+        tsig_transfer(&Implementation::unbound())
+    }
+
+    fn tsig_transfer(implementation: &Implementation) -> Result<()> {
+        let network = Network::new()?;
+        let tsig_key = TsigKey {
+            name: "transfer-key".to_string(),
+            algorithm: TsigAlgorithm::HmacSha256,
+            secret: "9CXmNn9WKDdQuj4KjqZRDoh8Mf8G/p9dgn5MQ6AVWH0=".to_string(),
+        };
+        let ns = NameServer::builder(implementation.clone(), FQDN::ROOT, network.clone())
+            .tsig_key(tsig_key.clone())
+            .build()?
+            .start()?;
+
+        let client = Client::new(&network)?;
+        let records = client.axfr(ns.ipv4_addr(), &FQDN::ROOT, Some(&tsig_key))?;
+        // the SOA record starts and ends the transfer
+        assert!(records.first().is_some_and(Record::is_soa));
+        assert!(records.last().is_some_and(Record::is_soa));
+
+        let wrong_key = TsigKey {
+            secret: "MTIzNDU2Nzg5MDEyMzQ1Njc4OTAxMjM0NTY3ODkwMTI=".to_string(),
+            ..tsig_key.clone()
+        };
+        assert!(
+            client
+                .axfr(ns.ipv4_addr(), &FQDN::ROOT, Some(&wrong_key))
+                .is_err()
+        );
+        assert!(client.axfr(ns.ipv4_addr(), &FQDN::ROOT, None).is_err());
+
+        let settings = DigSettings::default();
+        let output = client.dig_signed(
+            settings,
+            &tsig_key,
+            ns.ipv4_addr(),
+            RecordType::SOA,
+            &FQDN::ROOT,
+        )?;
+        assert!(output.status.is_noerror());
+        assert_eq!(None, output.tsig_error);
+
+        let output = client.dig_signed(
+            settings,
+            &wrong_key,
+            ns.ipv4_addr(),
+            RecordType::SOA,
+            &FQDN::ROOT,
+        )?;
+        assert_eq!(DigStatus::NOTAUTH, output.status);
+        assert_eq!(Some(TsigError::BADSIG), output.tsig_error);
+
+        let unknown_key = TsigKey {
+            name: "unknown-key".to_string(),
+            ..wrong_key
+        };
+        let output = client.dig_signed(
+            settings,
+            &unknown_key,
+            ns.ipv4_addr(),
+            RecordType::SOA,
+            &FQDN::ROOT,
+        )?;
+        assert_eq!(DigStatus::NOTAUTH, output.status);
+        assert_eq!(Some(TsigError::BADKEY), output.tsig_error);
+
+        Ok(())
+    }
+
+    #[test]
+    fn bind_dynamic_update() -> Result<()> {
+        let network = Network::new()?;
+        let tsig_key = TsigKey {
+            name: "update-key".to_string(),
+            algorithm: TsigAlgorithm::HmacSha256,
+            secret: "9CXmNn9WKDdQuj4KjqZRDoh8Mf8G/p9dgn5MQ6AVWH0=".to_string(),
+        };
+        let ns = NameServer::builder(Implementation::bind(), FQDN::TEST_DOMAIN, network.clone())
+            .tsig_key(tsig_key.clone())
+            .allow_update()
+            .build()?
+            .start()?;
+
+        let client = Client::new(&network)?;
+        let fqdn = FQDN::EXAMPLE_SUBDOMAIN;
+        let updates = [Update::Add(Record::a(
+            fqdn.clone(),
+            Ipv4Addr::new(192, 0, 2, 1),
+        ))];
+        // unsigned updates are refused
+        assert!(
+            client
+                .nsupdate(ns.ipv4_addr(), &FQDN::TEST_DOMAIN, &updates, None)
+                .is_err()
+        );
+        client.nsupdate(
+            ns.ipv4_addr(),
+            &FQDN::TEST_DOMAIN,
+            &updates,
+            Some(&tsig_key),
+        )?;
+
+        let output = client.dig(DigSettings::default(), ns.ipv4_addr(), RecordType::A, &fqdn)?;
+        assert!(output.status.is_noerror());
+        let [a] = output.answer.try_into().unwrap();
+        assert_eq!(
+            Ipv4Addr::new(192, 0, 2, 1),
+            a.try_into_a().unwrap().ipv4_addr
+        );
+
+        let updates = [Update::Delete {
+            fqdn: fqdn.clone(),
+            record_type: RecordType::A,
+        }];
+        client.nsupdate(
+            ns.ipv4_addr(),
+            &FQDN::TEST_DOMAIN,
+            &updates,
+            Some(&tsig_key),
+        )?;
+
+        let output = client.dig(DigSettings::default(), ns.ipv4_addr(), RecordType::A, &fqdn)?;
+        assert!(output.status.is_nxdomain());
+
+        Ok(())
+    }
+
+    #[test]
+    fn hickory_does_not_allow_update() {
+        let network = Network::new().unwrap();
+        let error = NameServer::builder(Implementation::hickory(), FQDN::TEST_DOMAIN, network)
+            .allow_update()
+            .build()
+            .err()
+            .unwrap();
+
+        assert!(
+            error
+                .to_string()
+                .ends_with("name server does not support dynamic updates"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn reload() -> Result<()> {
+        let network = Network::new()?;
+        let mut ns =
+            NameServer::new(&Implementation::bind(), FQDN::TEST_DOMAIN, &network)?.start()?;
+        let client = Client::new(&network)?;
+
+        let needle_fqdn = FQDN::EXAMPLE_SUBDOMAIN;
+        let expected_ipv4_addr = Ipv4Addr::new(1, 2, 3, 4);
+        ns.zone_file_mut()
+            .add(Record::a(needle_fqdn.clone(), expected_ipv4_addr));
+        ns.reload()?;
+        dig_when_reloaded(&client, &ns, RecordType::SOA)?;
+
+        let output = client.dig(
+            DigSettings::default(),
+            ns.ipv4_addr(),
+            RecordType::A,
+            &needle_fqdn,
+        )?;
+        assert!(output.status.is_noerror());
+        let [answer] = output.answer.try_into().unwrap();
+        assert_eq!(expected_ipv4_addr, answer.try_into_a().unwrap().ipv4_addr);
+
+        Ok(())
+    }
+
+    #[test]
+    fn bind_stats() -> Result<()> {
+        stats(&Implementation::bind())
+    }
+
+    #[test]
+    fn hickory_stats() -> Result<()> {
+        stats(&Implementation::hickory())
+    }
+
+    // the cache of the resolver answers the second query
+    fn stats(implementation: &Implementation) -> Result<()> {
+        let network = Network::new()?;
+        let mut ns = NameServer::new(implementation, FQDN::ROOT, &network)?;
+        ns.add(Record::a(
+            FQDN::EXAMPLE_SUBDOMAIN,
+            Ipv4Addr::new(1, 2, 3, 4),
+        ));
+        let mut ns = ns.start()?;
+        let resolver = crate::Resolver::new(&network, ns.root_hint())
+            .start_with_subject(&Implementation::unbound())?;
+        // leave out the queries the resolver sent while starting up
+        ns.reset_stats()?;
+
+        let client = Client::new(&network)?;
+        let settings = *DigSettings::default().recurse();
+        for _ in 0..2 {
+            let output = client.dig(
+                settings,
+                resolver.ipv4_addr(),
+                RecordType::A,
+                &FQDN::EXAMPLE_SUBDOMAIN,
+            )?;
+            assert!(output.status.is_noerror());
+        }
+
+        let stats = ns.stats()?;
+        assert_eq!(1, stats.count(&FQDN::EXAMPLE_SUBDOMAIN, &RecordType::A));
+        assert_eq!(
+            1,
+            stats.count_over(&FQDN::EXAMPLE_SUBDOMAIN, &RecordType::A, Protocol::Udp)
+        );
+
+        ns.reset_stats()?;
+        assert_eq!(0, ns.stats()?.total());
+
+        Ok(())
+    }
+
+    #[test]
+    fn reload_zone() -> Result<()> {
+        let network = Network::new()?;
+        let mut ns =
+            NameServer::new(&Implementation::unbound(), FQDN::TEST_DOMAIN, &network)?.start()?;
+        let client = Client::new(&network)?;
+        let served_serial = ns.zone_file().soa.settings.serial;
+
+        let needle_fqdn = FQDN::EXAMPLE_SUBDOMAIN;
+        let expected_ipv4_addr = Ipv4Addr::new(1, 2, 3, 4);
+        let mut zone_file = ns.zone_file().clone();
+        zone_file.soa.settings.serial = 0;
+        zone_file.add(Record::a(needle_fqdn.clone(), expected_ipv4_addr));
+        ns.reload_zone(zone_file)?;
+        assert!(ns.zone_file().soa.settings.serial > served_serial);
+        dig_when_reloaded(&client, &ns, RecordType::SOA)?;
+
+        let output = client.dig(
+            DigSettings::default(),
+            ns.ipv4_addr(),
+            RecordType::A,
+            &needle_fqdn,
+        )?;
+        assert!(output.status.is_noerror());
+        let [answer] = output.answer.try_into().unwrap();
+        assert_eq!(expected_ipv4_addr, answer.try_into_a().unwrap().ipv4_addr);
+
+        let other_zone = ZoneFile::new(SOA {
+            zone: FQDN::TEST_TLD,
+            ..ns.zone_file().soa.clone()
+        });
+        let error = ns.reload_zone(other_zone).unwrap_err();
+        assert!(error.to_string().contains("not for the"), "{error}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn hickory_cannot_reload() -> Result<()> {
+        let network = Network::new()?;
+        let mut ns =
+            NameServer::new(&Implementation::hickory(), FQDN::TEST_DOMAIN, &network)?.start()?;
+
+        let error = ns.reload().unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .ends_with("cannot reload its zone files without restarting"),
+            "{error}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn native_client_agrees_with_dig() -> Result<()> {
+        let network = Network::new()?;
+        let ns = NameServer::new(&Implementation::bind(), FQDN::TEST_DOMAIN, &network)?
+            .sign(SignSettings::default())?
+            .start()?;
+        let client = Client::new(&network)?;
+        let settings = *DigSettings::default().dnssec();
+
+        let outputs = [&client as &dyn DnsClient, &NativeClient]
+            .into_iter()
+            .map(|dns_client| {
+                dns_client.query(
+                    settings,
+                    ns.ipv4_addr().into(),
+                    RecordType::SOA,
+                    &FQDN::TEST_DOMAIN,
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let [dig, native] = outputs.try_into().unwrap();
+
+        assert!(native.status.is_noerror());
+        assert_eq!(dig.flags, native.flags);
+        assert_eq!(dig.edns_version, native.edns_version);
+        assert!(native.dnssec_ok_flag);
+        assert_eq!(Some(Protocol::Udp), native.protocol);
+        assert_eq!(dig.server, native.server);
+        assert!(native.query_time.is_some());
+        let answers = |output: &DigOutput| {
+            output
+                .answer
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        };
+        // the SOA record and its RRSIG
+        assert_eq!(2, answers(&native).len());
+        assert_eq!(answers(&dig), answers(&native));
+
+        let message = client.query_raw(
+            settings,
+            ns.ipv4_addr(),
+            RecordType::SOA,
+            &FQDN::TEST_DOMAIN,
+        )?;
+        assert_eq!(vec![(FQDN::TEST_DOMAIN, RecordType::SOA)], message.question);
+        assert_eq!(0, message.rcode);
+
+        Ok(())
+    }
+
+    #[test]
+    fn transfers_large_signed_zone() -> Result<()> {
+        let network = Network::new()?;
+        let tsig_key = TsigKey {
+            name: "transfer-key".to_string(),
+            algorithm: TsigAlgorithm::HmacSha256,
+            secret: "9CXmNn9WKDdQuj4KjqZRDoh8Mf8G/p9dgn5MQ6AVWH0=".to_string(),
+        };
+        let mut ns =
+            NameServer::builder(Implementation::bind(), FQDN::TEST_DOMAIN, network.clone())
+                .tsig_key(tsig_key.clone())
+                .build()?;
+        // far more than fits in a single 64 KiB message
+        for i in 0..2_000 {
+            ns.add(Record::a(
+                FQDN::TEST_DOMAIN.push_label(&format!("host{i}")),
+                Ipv4Addr::new(192, 0, 2, 1),
+            ));
+        }
+        let ns = ns.sign(SignSettings::default())?;
+        let expected = ns.signed_zone_file().records.len();
+        let ns = ns.start()?;
+
+        let client = Client::new(&network)?;
+        let records = client.axfr(ns.ipv4_addr(), &FQDN::TEST_DOMAIN, Some(&tsig_key))?;
+
+        // the SOA record is sent twice, at the start and at the end of the transfer
+        assert_eq!(expected + 2, records.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn incremental_transfer() -> Result<()> {
+        let network = Network::new()?;
+        let tsig_key = TsigKey {
+            name: "transfer-key".to_string(),
+            algorithm: TsigAlgorithm::HmacSha256,
+            secret: "9CXmNn9WKDdQuj4KjqZRDoh8Mf8G/p9dgn5MQ6AVWH0=".to_string(),
+        };
+        let mut ns =
+            NameServer::builder(Implementation::bind(), FQDN::TEST_DOMAIN, network.clone())
+                .tsig_key(tsig_key.clone())
+                .build()?
+                .start()?;
+        let old_serial = ns.zone_file().soa.settings.serial;
+
+        let needle_fqdn = FQDN::TEST_DOMAIN.push_label("added");
+        ns.publish(Record::a(needle_fqdn.clone(), Ipv4Addr::new(1, 2, 3, 4)))?;
+        let client = Client::new(&network)?;
+        dig_when_reloaded(&client, &ns, RecordType::SOA)?;
+
+        let records = client.ixfr(
+            ns.ipv4_addr(),
+            &FQDN::TEST_DOMAIN,
+            old_serial,
+            Some(&tsig_key),
+        )?;
+
+        let serials = records
+            .iter()
+            .filter_map(|record| match record {
+                Record::SOA(soa) => Some(soa.settings.serial),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        // current, old (deletions), new (additions), current
+        assert_eq!(
+            vec![old_serial + 1, old_serial, old_serial + 1, old_serial + 1],
+            serials
+        );
+        let added = records
+            .iter()
+            .filter_map(|record| record.clone().try_into_a().ok())
+            .collect::<Vec<_>>();
+        let [added] = added.try_into().unwrap();
+        assert_eq!(needle_fqdn, added.fqdn);
+
+        // nothing changed since the current serial
+        let records = client.ixfr(
+            ns.ipv4_addr(),
+            &FQDN::TEST_DOMAIN,
+            old_serial + 1,
+            Some(&tsig_key),
+        )?;
+        assert_eq!(1, records.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn hickory_refuses_transfer() -> Result<()> {
+        let network = Network::new()?;
+        let ns =
+            NameServer::new(&Implementation::hickory(), FQDN::TEST_DOMAIN, &network)?.start()?;
+
+        let client = Client::new(&network)?;
+        let error = client
+            .axfr(ns.ipv4_addr(), &FQDN::TEST_DOMAIN, None)
+            .unwrap_err();
+
+        assert!(error.to_string().contains("Transfer failed"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn bind_secondary() -> Result<()> {
+        secondary(&Implementation::bind())
+    }
+
+    #[test]
+    fn nsd_secondary() -> Result<()> {
+        secondary(&Implementation::unbound())
+    }
+
+    fn secondary(implementation: &Implementation) -> Result<()> {
+        let network = Network::new()?;
+        let needle_fqdn = FQDN::TEST_DOMAIN.push_label("before");
+
+        let mut primary = NameServer::new(implementation, FQDN::TEST_DOMAIN, &network)?;
+        primary.add(Record::a(needle_fqdn.clone(), Ipv4Addr::new(1, 2, 3, 4)));
+        let secondary = Secondary::new(implementation, &mut primary)?;
+        let serial = primary.zone_file().soa.settings.serial;
+
+        let mut primary = primary.start()?;
+        let secondary = secondary.start()?;
+        secondary.wait_for_transfer(serial)?;
+
+        let client = Client::new(&network)?;
+        let settings = *DigSettings::default().recurse();
+        let output = client.dig(settings, secondary.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+
+        assert!(output.status.is_noerror());
+        assert!(output.flags.authoritative_answer);
+
+        // both servers are listed as authoritative in the zone
+        let output = client.dig(
+            settings,
+            secondary.ipv4_addr(),
+            RecordType::NS,
+            &FQDN::TEST_DOMAIN,
+        )?;
+        assert_eq!(2, output.answer.len());
+
+        // changes to the zone reach the secondary
+        let needle_fqdn = FQDN::TEST_DOMAIN.push_label("after");
+        primary.publish(Record::a(needle_fqdn.clone(), Ipv4Addr::new(5, 6, 7, 8)))?;
+        secondary.wait_for_transfer(serial + 1)?;
+
+        let output = client.dig(settings, secondary.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+
+        assert!(output.status.is_noerror());
+        let [answer] = output.answer.try_into().unwrap();
+        assert_eq!(
+            Ipv4Addr::new(5, 6, 7, 8),
+            answer.try_into_a().unwrap().ipv4_addr
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn bind_secondary_transfers_on_notify() -> Result<()> {
+        transfers_on_notify(&Implementation::bind())
+    }
+
+    #[test]
+    fn nsd_secondary_transfers_on_notify() -> Result<()> {
+        transfers_on_notify(&Implementation::unbound())
+    }
+
+    fn transfers_on_notify(implementation: &Implementation) -> Result<()> {
+        let network = Network::new()?;
+        let mut primary = NameServer::new(implementation, FQDN::TEST_DOMAIN, &network)?;
+        let secondary = Secondary::new(implementation, &mut primary)?;
+        let serial = primary.zone_file().soa.settings.serial;
+        // the secondary would not check for a new version of the zone on its own
+        assert!(primary.zone_file().soa.settings.refresh >= 60);
+
+        let mut primary = primary.start()?;
+        let secondary = secondary.start()?;
+        secondary.wait_for_transfer(serial)?;
+
+        primary.publish(Record::a(
+            FQDN::TEST_DOMAIN.push_label("notified"),
+            Ipv4Addr::new(1, 2, 3, 4),
+        ))?;
+        let reloaded = Instant::now();
+        secondary.wait_for_transfer(serial + 1)?;
+
+        // `wait_for_transfer` checks once per second
+        let elapsed = reloaded.elapsed();
+        assert!(elapsed < Duration::from_secs(3), "took {elapsed:?}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn catalog_zone() -> Result<()> {
+        let member_zone = |zone: &str| -> Result<(FQDN, ZoneFile)> {
+            let zone = FQDN(zone.to_string())?;
+            let nameserver = zone.push_label("ns");
+            let mut zone_file = ZoneFile::new(SOA {
+                zone: zone.clone(),
+                ttl: 86400,
+                nameserver: nameserver.clone(),
+                admin: zone.push_label("admin"),
+                settings: SoaSettings::default(),
+            });
+            zone_file.add(Record::ns(zone.clone(), nameserver.clone()));
+            zone_file.add(Record::a(nameserver, Ipv4Addr::new(192, 0, 2, 1)));
+            Ok((zone, zone_file))
+        };
+
+        let implementation = Implementation::bind();
+        let network = Network::new()?;
+        let mut primary = NameServer::new(&implementation, FQDN::TEST_DOMAIN, &network)?;
+        let (member, zone_file) = member_zone("member.testing.")?;
+        let serial = zone_file.soa.settings.serial;
+        primary.add_zone(member.clone(), zone_file);
+        primary.catalog_zone(FQDN("catalog.invalid.")?)?;
+        let secondary = Secondary::new(&implementation, &mut primary)?;
+
+        let mut primary = primary.start()?;
+        let secondary = secondary.start()?;
+        // the secondary has no configuration of its own for the members of the catalog
+        secondary.wait_for_zone_transfer(&member, serial)?;
+
+        let (new_member, zone_file) = member_zone("new-member.testing.")?;
+        primary.publish_zone(new_member.clone(), zone_file)?;
+        secondary.wait_for_zone_transfer(&new_member, serial)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn bind_zsk_rollover() -> Result<()> {
+        zsk_rollover(&Implementation::bind())
+    }
+
+    #[test]
+    fn nsd_zsk_rollover() -> Result<()> {
+        zsk_rollover(&Implementation::unbound())
+    }
+
+    fn zsk_rollover(implementation: &Implementation) -> Result<()> {
+        let network = Network::new()?;
+        let ns =
+            NameServer::new(implementation, FQDN::ROOT, &network)?.sign(SignSettings::default())?;
+        let ksk = ns.key_signing_key().rdata.calculate_key_tag();
+        let mut ns = ns.start()?;
+        let client = Client::new(&network)?;
+
+        let old_zsk = ns.zone_signing_key_tag().expect("zone is signed");
+        let new_zsk = ns.add_zsk()?;
+        assert_ne!(old_zsk, new_zsk);
+
+        let output = dig_when_reloaded(&client, &ns, RecordType::DNSKEY)?;
+        let mut key_tags = output
+            .answer
+            .into_iter()
+            .filter_map(|record| match record {
+                Record::DNSKEY(dnskey) => Some(dnskey.rdata.calculate_key_tag()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        key_tags.sort_unstable();
+        let mut expected = vec![old_zsk, new_zsk, ksk];
+        expected.sort_unstable();
+        assert_eq!(expected, key_tags);
+        assert_eq!(vec![old_zsk], soa_signers(&client, &ns)?);
+
+        ns.resign_with(new_zsk)?;
+        assert_eq!(Some(new_zsk), ns.zone_signing_key_tag());
+
+        dig_when_reloaded(&client, &ns, RecordType::SOA)?;
+        assert_eq!(vec![new_zsk], soa_signers(&client, &ns)?);
+
+        Ok(())
+    }
+
+    /// Polls `ns` until it serves the current SOA serial, then sends a `record_type` query
+    fn dig_when_reloaded(
+        client: &Client,
+        ns: &NameServer<Running>,
+        record_type: RecordType,
+    ) -> Result<DigOutput> {
+        let settings = *DigSettings::default().dnssec();
+        let serial = ns.zone_file().soa.settings.serial;
+        for _ in 0..10 {
+            let output = client.dig(settings, ns.ipv4_addr(), RecordType::SOA, ns.zone())?;
+            let served = output
+                .answer
+                .into_iter()
+                .find_map(|record| record.try_into_soa().ok());
+            if served.is_some_and(|soa| soa.settings.serial == serial) {
+                return client.dig(settings, ns.ipv4_addr(), record_type, ns.zone());
+            }
+            thread::sleep(Duration::from_millis(500));
+        }
+
+        Err(format!("{} did not load serial {serial}", ns.implementation).into())
+    }
+
+    /// Key tags of the RRSIGs covering the SOA record served by `ns`
+    fn soa_signers(client: &Client, ns: &NameServer<Running>) -> Result<Vec<u16>> {
+        let settings = *DigSettings::default().dnssec();
+        let output = client.dig(settings, ns.ipv4_addr(), RecordType::SOA, ns.zone())?;
+        Ok(output
+            .answer
+            .into_iter()
+            .filter_map(|record| record.try_into_rrsig().ok())
+            .filter(|rrsig| rrsig.type_covered == RecordType::SOA)
+            .map(|rrsig| rrsig.key_tag)
+            .collect())
+    }
+
     #[test]
     fn nsd_logs_works() -> Result<()> {
         let network = Network::new()?;
-        let ns = NameServer::new(&Implementation::Unbound, FQDN::ROOT, &network)?.start()?;
-        // no way to block until the server has finished starting up so we just give it some
-        // arbitrary amount of time
-        thread::sleep(Duration::from_secs(1));
+        let ns = NameServer::new(&Implementation::unbound(), FQDN::ROOT, &network)?.start()?;
         let logs = ns.logs()?;
 
         assert!(logs.contains("nsd starting"));
@@ -779,10 +3073,7 @@ mod tests {
     #[test]
     fn named_logs_works() -> Result<()> {
         let network = Network::new()?;
-        let ns = NameServer::new(&Implementation::Bind, FQDN::ROOT, &network)?.start()?;
-        // no way to block until the server has finished starting up so we just give it some
-        // arbitrary amount of time
-        thread::sleep(Duration::from_secs(1));
+        let ns = NameServer::new(&Implementation::bind(), FQDN::ROOT, &network)?.start()?;
         let logs = ns.logs()?;
 
         eprintln!("{logs}");
@@ -796,10 +3087,6 @@ mod tests {
         let network = Network::new()?;
         let ns = NameServer::new(&Implementation::hickory(), FQDN::ROOT, &network)?.start()?;
 
-        // no way to block until the server has finished starting up so we just give it some
-        // arbitrary amount of time
-        thread::sleep(Duration::from_secs(1));
-
         let logs = ns.logs()?;
 
         eprintln!("{logs}");
@@ -816,7 +3103,7 @@ mod tests {
 
     #[test]
     fn bind_multizone_works() -> Result<()> {
-        multizone_test(&Implementation::Bind)?;
+        multizone_test(&Implementation::bind())?;
         Ok(())
     }
 
@@ -828,7 +3115,7 @@ mod tests {
 
     #[test]
     fn unbound_multizone_works() -> Result<()> {
-        multizone_test(&Implementation::Unbound)?;
+        multizone_test(&Implementation::unbound())?;
         Ok(())
     }
 
@@ -863,7 +3150,6 @@ mod tests {
         ns.add_zone(FQDN("domain.testing.")?, zone_file);
 
         let ns = ns.start()?;
-        thread::sleep(Duration::from_secs(2));
 
         let client = Client::new(&network)?;
         let dig_settings = DigSettings::default();
@@ -0,0 +1,438 @@
+//! `tcpdump` packet captures and a reader for the pcap files it writes
+//!
+//! Unlike [`crate::tshark`], which only looks at DNS over UDP, this captures whatever matches a
+//! pcap filter, TCP included, and keeps the raw capture around for inspection with other tools
+
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, Lines};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::process::{ChildStderr, Command, Stdio};
+use std::sync::atomic::{self, AtomicUsize};
+
+use crate::client::Protocol;
+use crate::container::{Child, Container};
+use crate::{FQDN, Result};
+
+static ID: AtomicUsize = AtomicUsize::new(0);
+
+/// The only link type `tcpdump -i eth0` produces
+const LINKTYPE_ETHERNET: u32 = 1;
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86dd;
+const ETHERTYPE_VLAN: u16 = 0x8100;
+
+const IPPROTO_TCP: u8 = 6;
+const IPPROTO_UDP: u8 = 17;
+
+impl Container {
+    /// Starts capturing, with `tcpdump`, the packets that match `filter`, e.g. `port 53`
+    ///
+    /// `filter` uses the pcap-filter(7) syntax; an empty filter captures every packet
+    pub fn start_capture(&self, filter: &str) -> Result<Capture> {
+        let id = ID.fetch_add(1, atomic::Ordering::Relaxed);
+        let pidfile = pid_file(id);
+        let pcap_file = pcap_file(id);
+
+        // `-U` writes each packet as soon as it is captured so none is lost when `tcpdump` is
+        // stopped
+        let tcpdump = format!(
+            "echo $$ > {pidfile}
+exec tcpdump -i eth0 -n -U -Z root -w {pcap_file} '{filter}'"
+        );
+        let mut child = self.spawn(&["sh", "-c", &tcpdump])?;
+
+        let stderr = child.stderr()?;
+        let mut stderr = BufReader::new(stderr).lines();
+
+        // e.g. `tcpdump: listening on eth0, link-type EN10MB (Ethernet), snapshot length 262144
+        // bytes`
+        let mut is_listening = false;
+        for res in stderr.by_ref() {
+            let line = res?;
+
+            if line.contains("listening on") {
+                is_listening = true;
+                break;
+            }
+        }
+        if !is_listening {
+            return Err(format!("`tcpdump` did not start capturing with filter `{filter}`").into());
+        }
+
+        Ok(Capture {
+            child,
+            container: self.clone(),
+            id,
+            stderr,
+        })
+    }
+}
+
+fn pid_file(id: usize) -> String {
+    format!("/tmp/tcpdump{id}.pid")
+}
+
+fn pcap_file(id: usize) -> String {
+    format!("/tmp/capture{id}.pcap")
+}
+
+/// An ongoing `tcpdump` capture; see [`Container::start_capture`]
+pub struct Capture {
+    child: Child,
+    container: Container,
+    id: usize,
+    stderr: Lines<BufReader<ChildStderr>>,
+}
+
+impl Capture {
+    /// Stops `tcpdump` and copies the pcap file it wrote to a temporary directory of the host
+    ///
+    /// Returns the path of the copy, which [`read`] parses; the file is left in place after the
+    /// test so it can be opened with, e.g., Wireshark
+    pub fn stop(self) -> Result<PathBuf> {
+        let pidfile = pid_file(self.id);
+        let kill = format!("test -f {pidfile} || sleep 1; kill $(cat {pidfile})");
+
+        self.container.status_ok(&["sh", "-c", &kill])?;
+        let output = self.child.wait()?;
+
+        if !output.status.success() {
+            return Err("could not terminate the `tcpdump` process".into());
+        }
+
+        // `tcpdump` prints how many packets it captured and closes stderr
+        for res in self.stderr {
+            res?;
+        }
+
+        let host_path = env::temp_dir().join(format!("{}-{}.pcap", self.container.name(), self.id));
+        let mut command = Command::new("docker");
+        command
+            .arg("cp")
+            .arg(format!("{}:{}", self.container.id(), pcap_file(self.id)))
+            .arg(&host_path)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        if !command.status()?.success() {
+            return Err(format!("`{command:?}` failed").into());
+        }
+
+        Ok(host_path)
+    }
+
+    /// Like [`Self::stop`] but also [`read`]s the capture
+    pub fn stop_and_read(self) -> Result<Vec<PacketSummary>> {
+        read(&self.stop()?)
+    }
+}
+
+/// What a captured packet carried, as far as DNS is concerned
+#[derive(Clone, Debug, PartialEq)]
+pub struct PacketSummary {
+    pub src: SocketAddr,
+    pub dst: SocketAddr,
+    pub protocol: Protocol,
+    /// Size of the UDP or TCP payload, in bytes
+    pub len: usize,
+    /// The payload is a DNS message with the QR bit cleared
+    pub is_query: bool,
+    /// Name in the question section of the DNS message, if the payload is one
+    pub qname: Option<FQDN>,
+}
+
+/// Parses the pcap file at `path`, e.g. one returned by [`Capture::stop`]
+///
+/// Packets that are neither UDP nor TCP, e.g. ARP, are skipped. A TCP segment is parsed as a DNS
+/// message only when it starts with one, i.e. with its 2-byte length prefix; segments without
+/// payload, like the ones of the TCP handshake, are summarized with `is_query` unset and no
+/// `qname`
+pub fn read(path: &Path) -> Result<Vec<PacketSummary>> {
+    parse(&fs::read(path)?)
+}
+
+fn parse(pcap: &[u8]) -> Result<Vec<PacketSummary>> {
+    let header = pcap
+        .get(..24)
+        .ok_or("pcap file is shorter than its header")?;
+    let magic: [u8; 4] = header[..4].try_into().unwrap();
+    // the magic number is written in the byte order of the host that made the capture;
+    // nanosecond resolution files use a different one but the timestamps are not read
+    let is_big_endian = match magic {
+        [0xa1, 0xb2, 0xc3, 0xd4] | [0xa1, 0xb2, 0x3c, 0x4d] => true,
+        [0xd4, 0xc3, 0xb2, 0xa1] | [0x4d, 0x3c, 0xb2, 0xa1] => false,
+        _ => return Err(format!("not a pcap file; magic number: {magic:02x?}").into()),
+    };
+    let u32_at = |bytes: &[u8], offset: usize| {
+        let bytes: [u8; 4] = bytes[offset..offset + 4].try_into().unwrap();
+        if is_big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        }
+    };
+
+    let link_type = u32_at(header, 20);
+    if link_type != LINKTYPE_ETHERNET {
+        return Err(format!("unsupported pcap link type: {link_type}").into());
+    }
+
+    let mut summaries = vec![];
+    let mut rest = &pcap[24..];
+    while !rest.is_empty() {
+        let record = rest.get(..16).ok_or("truncated pcap record header")?;
+        let captured_len = u32_at(record, 8) as usize;
+        let frame = rest
+            .get(16..16 + captured_len)
+            .ok_or("truncated pcap record")?;
+        rest = &rest[16 + captured_len..];
+
+        summaries.extend(summarize(frame));
+    }
+
+    Ok(summaries)
+}
+
+fn summarize(frame: &[u8]) -> Option<PacketSummary> {
+    let mut ethertype = u16::from_be_bytes(frame.get(12..14)?.try_into().ok()?);
+    let mut packet = frame.get(14..)?;
+    if ethertype == ETHERTYPE_VLAN {
+        ethertype = u16::from_be_bytes(packet.get(2..4)?.try_into().ok()?);
+        packet = packet.get(4..)?;
+    }
+
+    let (src, dst, ip_protocol, segment) = match ethertype {
+        ETHERTYPE_IPV4 => {
+            let header_len = usize::from(packet.first()? & 0x0f) * 4;
+            let total_len = usize::from(u16::from_be_bytes(packet.get(2..4)?.try_into().ok()?));
+            let src: [u8; 4] = packet.get(12..16)?.try_into().ok()?;
+            let dst: [u8; 4] = packet.get(16..20)?.try_into().ok()?;
+            (
+                IpAddr::from(Ipv4Addr::from(src)),
+                IpAddr::from(Ipv4Addr::from(dst)),
+                *packet.get(9)?,
+                // Ethernet pads short frames so the payload ends where the IP header says
+                packet.get(header_len..total_len)?,
+            )
+        }
+        // extension headers are not expected in the test networks
+        ETHERTYPE_IPV6 => {
+            let payload_len = usize::from(u16::from_be_bytes(packet.get(4..6)?.try_into().ok()?));
+            let src: [u8; 16] = packet.get(8..24)?.try_into().ok()?;
+            let dst: [u8; 16] = packet.get(24..40)?.try_into().ok()?;
+            (
+                IpAddr::from(Ipv6Addr::from(src)),
+                IpAddr::from(Ipv6Addr::from(dst)),
+                *packet.get(6)?,
+                packet.get(40..40 + payload_len)?,
+            )
+        }
+        _ => return None,
+    };
+
+    let src_port = u16::from_be_bytes(segment.get(0..2)?.try_into().ok()?);
+    let dst_port = u16::from_be_bytes(segment.get(2..4)?.try_into().ok()?);
+    let (protocol, payload, message) = match ip_protocol {
+        IPPROTO_UDP => {
+            let payload = segment.get(8..)?;
+            (Protocol::Udp, payload, payload)
+        }
+        IPPROTO_TCP => {
+            let header_len = usize::from(segment.get(12)? >> 4) * 4;
+            let payload = segment.get(header_len..)?;
+            // DNS messages over TCP are prefixed with their length
+            (Protocol::Tcp, payload, payload.get(2..).unwrap_or_default())
+        }
+        _ => return None,
+    };
+
+    let (is_query, qname) = match dns_question(message) {
+        Some((is_query, qname)) => (is_query, Some(qname)),
+        None => (false, None),
+    };
+
+    Some(PacketSummary {
+        src: SocketAddr::new(src, src_port),
+        dst: SocketAddr::new(dst, dst_port),
+        protocol,
+        len: payload.len(),
+        is_query,
+        qname,
+    })
+}
+
+/// Returns whether `message` is a query, and the name in its question section
+fn dns_question(message: &[u8]) -> Option<(bool, FQDN)> {
+    let is_query = message.get(2)? & 0x80 == 0;
+    let question_count = u16::from_be_bytes(message.get(4..6)?.try_into().ok()?);
+    if question_count == 0 {
+        return None;
+    }
+
+    // the name of the first question comes right after the header so it cannot be compressed
+    let mut labels = vec![];
+    let mut offset = 12;
+    loop {
+        let len = usize::from(*message.get(offset)?);
+        offset += 1;
+        if len == 0 {
+            break;
+        }
+        let label = message.get(offset..offset + len)?;
+        labels.push(String::from_utf8_lossy(label).into_owned());
+        offset += len;
+    }
+
+    let qname = if labels.is_empty() {
+        FQDN::ROOT
+    } else {
+        FQDN(format!("{}.", labels.join("."))).ok()?
+    };
+
+    Some((is_query, qname))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::{Client, DigSettings};
+    use crate::name_server::NameServer;
+    use crate::record::RecordType;
+    use crate::{Implementation, Network};
+
+    use super::*;
+
+    const CLIENT: [u8; 4] = [172, 18, 0, 3];
+    const SERVER: [u8; 4] = [192, 0, 2, 1];
+
+    fn pcap(frames: &[Vec<u8>]) -> Vec<u8> {
+        // little endian, version 2.4, no time zone offset, 262144 bytes snapshot length
+        let mut pcap = vec![0xd4, 0xc3, 0xb2, 0xa1, 2, 0, 4, 0];
+        pcap.extend([0; 8]);
+        pcap.extend(262144_u32.to_le_bytes());
+        pcap.extend(LINKTYPE_ETHERNET.to_le_bytes());
+
+        for frame in frames {
+            let len = u32::try_from(frame.len()).unwrap();
+            pcap.extend([0; 8]);
+            pcap.extend(len.to_le_bytes());
+            pcap.extend(len.to_le_bytes());
+            pcap.extend(frame);
+        }
+
+        pcap
+    }
+
+    fn frame(ip_protocol: u8, segment: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0; 12];
+        frame.extend(ETHERTYPE_IPV4.to_be_bytes());
+
+        let total_len = u16::try_from(20 + segment.len()).unwrap();
+        frame.extend([0x45, 0]);
+        frame.extend(total_len.to_be_bytes());
+        frame.extend([0, 0, 0, 0, 64, ip_protocol, 0, 0]);
+        frame.extend(CLIENT);
+        frame.extend(SERVER);
+        frame.extend(segment);
+
+        frame
+    }
+
+    // `example.` A query with RD set
+    fn query() -> Vec<u8> {
+        let mut message = vec![0x12, 0x34, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0];
+        message.extend(b"\x07example\x00");
+        message.extend([0, 1, 0, 1]);
+        message
+    }
+
+    #[test]
+    fn parses_udp_and_tcp_queries() -> Result<()> {
+        let query = query();
+
+        let mut udp = vec![];
+        udp.extend(41234_u16.to_be_bytes());
+        udp.extend(53_u16.to_be_bytes());
+        udp.extend(u16::try_from(8 + query.len()).unwrap().to_be_bytes());
+        udp.extend([0, 0]);
+        udp.extend(&query);
+
+        let mut tcp = vec![];
+        tcp.extend(41235_u16.to_be_bytes());
+        tcp.extend(53_u16.to_be_bytes());
+        tcp.extend([0; 8]);
+        // 20-byte header, PSH + ACK
+        tcp.extend([0x50, 0x18, 0xff, 0xff, 0, 0, 0, 0]);
+        tcp.extend(u16::try_from(query.len()).unwrap().to_be_bytes());
+        tcp.extend(&query);
+
+        let summaries = parse(&pcap(&[frame(IPPROTO_UDP, &udp), frame(IPPROTO_TCP, &tcp)]))?;
+
+        let server = IpAddr::from(SERVER);
+        let qname = Some(FQDN("example.")?);
+        assert_eq!(
+            vec![
+                PacketSummary {
+                    src: SocketAddr::new(IpAddr::from(CLIENT), 41234),
+                    dst: SocketAddr::new(server, 53),
+                    protocol: Protocol::Udp,
+                    len: query.len(),
+                    is_query: true,
+                    qname: qname.clone(),
+                },
+                PacketSummary {
+                    src: SocketAddr::new(IpAddr::from(CLIENT), 41235),
+                    dst: SocketAddr::new(server, 53),
+                    protocol: Protocol::Tcp,
+                    len: 2 + query.len(),
+                    is_query: true,
+                    qname,
+                },
+            ],
+            summaries
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_other_formats() {
+        assert!(parse(b"not a pcap file but long enough").is_err());
+    }
+
+    #[test]
+    fn captures_udp_and_tcp_queries() -> Result<()> {
+        let network = Network::new()?;
+        let ns = NameServer::new(&Implementation::unbound(), FQDN::ROOT, &network)?.start()?;
+        let capture = ns.container().start_capture("port 53")?;
+
+        let client = Client::new(&network)?;
+        let settings = DigSettings::default();
+        client.dig(settings, ns.ipv4_addr(), RecordType::SOA, &FQDN::ROOT)?;
+        client.dig(
+            *settings.clone().tcp(),
+            ns.ipv4_addr(),
+            RecordType::SOA,
+            &FQDN::ROOT,
+        )?;
+
+        let path = capture.stop()?;
+        assert!(path.exists());
+
+        let queries = read(&path)?
+            .into_iter()
+            .filter(|summary| summary.is_query && summary.dst.ip() == IpAddr::from(ns.ipv4_addr()))
+            .map(|summary| (summary.protocol, summary.qname))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (Protocol::Udp, Some(FQDN::ROOT)),
+                (Protocol::Tcp, Some(FQDN::ROOT))
+            ],
+            queries
+        );
+
+        Ok(())
+    }
+}
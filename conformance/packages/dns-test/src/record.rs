@@ -4,8 +4,9 @@ use core::result::Result as CoreResult;
 use core::str::FromStr;
 use core::{array, fmt};
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::fmt::Write;
-use std::net::Ipv4Addr;
+use std::net::{Ipv4Addr, Ipv6Addr};
 use std::{any, mem};
 
 use crate::{DEFAULT_TTL, Error, FQDN, Result};
@@ -13,9 +14,9 @@ use crate::{DEFAULT_TTL, Error, FQDN, Result};
 const CLASS: &str = "IN"; // "internet"
 
 macro_rules! record_types {
-    ($($variant:ident),*) => {
+    ($($variant:ident = $code:literal),*) => {
         #[allow(clippy::upper_case_acronyms)]
-        #[derive(Debug, PartialEq, Clone)]
+        #[derive(Debug, PartialEq, Eq, Hash, Clone)]
         pub enum RecordType {
             $($variant),*,
             Unknown(u16),
@@ -28,6 +29,23 @@ macro_rules! record_types {
                     Self::Unknown(code) => Cow::Owned(format!("type{code}")),
                 }
             }
+
+            /// The number that identifies the type on the wire
+            pub fn code(&self) -> u16 {
+                match self {
+                    $(Self::$variant => $code),*,
+                    Self::Unknown(code) => *code,
+                }
+            }
+        }
+
+        impl From<u16> for RecordType {
+            fn from(code: u16) -> Self {
+                match code {
+                    $($code => Self::$variant),*,
+                    _ => Self::Unknown(code),
+                }
+            }
         }
 
         impl FromStr for RecordType {
@@ -58,24 +76,54 @@ macro_rules! record_types {
 }
 
 record_types!(
-    A, AAAA, CAA, CNAME, DNSKEY, DS, MX, NS, NSEC, NSEC3, NSEC3PARAM, RRSIG, SOA, TXT
+    A = 1,
+    AAAA = 28,
+    CAA = 257,
+    CDNSKEY = 60,
+    CDS = 59,
+    CNAME = 5,
+    DNSKEY = 48,
+    DS = 43,
+    HTTPS = 65,
+    MX = 15,
+    NS = 2,
+    NSEC = 47,
+    NSEC3 = 50,
+    NSEC3PARAM = 51,
+    PTR = 12,
+    RRSIG = 46,
+    SOA = 6,
+    SRV = 33,
+    SVCB = 64,
+    TLSA = 52,
+    TXT = 16,
+    ZONEMD = 63
 );
 
 #[derive(Debug, Clone)]
 #[allow(clippy::upper_case_acronyms)]
 pub enum Record {
     A(A),
+    AAAA(AAAA),
     CAA(CAA),
+    CDNSKEY(CDNSKEY),
+    CDS(CDS),
     CNAME(CNAME),
     DNSKEY(DNSKEY),
     DS(DS),
+    HTTPS(HTTPS),
     NS(NS),
     NSEC(NSEC),
     NSEC3(NSEC3),
     NSEC3PARAM(NSEC3PARAM),
+    PTR(PTR),
     RRSIG(RRSIG),
     SOA(SOA),
+    SRV(SRV),
+    SVCB(SVCB),
+    TLSA(TLSA),
     TXT(TXT),
+    ZONEMD(ZONEMD),
     Unknown(UnknownRdata),
 }
 
@@ -97,12 +145,66 @@ impl From<DS> for Record {
     }
 }
 
+impl From<CDNSKEY> for Record {
+    fn from(v: CDNSKEY) -> Self {
+        Self::CDNSKEY(v)
+    }
+}
+
+impl From<CDS> for Record {
+    fn from(v: CDS) -> Self {
+        Self::CDS(v)
+    }
+}
+
+impl From<SVCB> for Record {
+    fn from(v: SVCB) -> Self {
+        Self::SVCB(v)
+    }
+}
+
+impl From<HTTPS> for Record {
+    fn from(v: HTTPS) -> Self {
+        Self::HTTPS(v)
+    }
+}
+
+impl From<CAA> for Record {
+    fn from(v: CAA) -> Self {
+        Self::CAA(v)
+    }
+}
+
+impl From<SRV> for Record {
+    fn from(v: SRV) -> Self {
+        Self::SRV(v)
+    }
+}
+
+impl From<TLSA> for Record {
+    fn from(v: TLSA) -> Self {
+        Self::TLSA(v)
+    }
+}
+
+impl From<ZONEMD> for Record {
+    fn from(v: ZONEMD) -> Self {
+        Self::ZONEMD(v)
+    }
+}
+
 impl From<A> for Record {
     fn from(v: A) -> Self {
         Self::A(v)
     }
 }
 
+impl From<AAAA> for Record {
+    fn from(v: AAAA) -> Self {
+        Self::AAAA(v)
+    }
+}
+
 impl From<CNAME> for Record {
     fn from(v: CNAME) -> Self {
         Self::CNAME(v)
@@ -115,6 +217,12 @@ impl From<NS> for Record {
     }
 }
 
+impl From<PTR> for Record {
+    fn from(v: PTR) -> Self {
+        Self::PTR(v)
+    }
+}
+
 impl From<RRSIG> for Record {
     fn from(v: RRSIG) -> Self {
         Self::RRSIG(v)
@@ -144,6 +252,14 @@ impl Record {
         }
     }
 
+    pub fn try_into_aaaa(self) -> CoreResult<AAAA, Self> {
+        if let Self::AAAA(v) = self {
+            Ok(v)
+        } else {
+            Err(self)
+        }
+    }
+
     pub fn try_into_cname(self) -> CoreResult<CNAME, Self> {
         if let Self::CNAME(v) = self {
             Ok(v)
@@ -152,6 +268,13 @@ impl Record {
         }
     }
 
+    pub fn try_into_ptr(self) -> CoreResult<PTR, Self> {
+        match self {
+            Self::PTR(v) => Ok(v),
+            _ => Err(self),
+        }
+    }
+
     pub fn try_into_rrsig(self) -> CoreResult<RRSIG, Self> {
         if let Self::RRSIG(v) = self {
             Ok(v)
@@ -189,6 +312,15 @@ impl Record {
         .into()
     }
 
+    pub fn aaaa(fqdn: FQDN, ipv6_addr: Ipv6Addr) -> Self {
+        AAAA {
+            fqdn,
+            ttl: DEFAULT_TTL,
+            ipv6_addr,
+        }
+        .into()
+    }
+
     pub fn cname(fqdn: FQDN, target: FQDN) -> Self {
         CNAME {
             fqdn,
@@ -207,6 +339,14 @@ impl Record {
         .into()
     }
 
+    pub fn try_into_dnskey(self) -> CoreResult<DNSKEY, Self> {
+        if let Self::DNSKEY(v) = self {
+            Ok(v)
+        } else {
+            Err(self)
+        }
+    }
+
     pub fn try_into_ds(self) -> CoreResult<DS, Self> {
         if let Self::DS(v) = self {
             Ok(v)
@@ -215,6 +355,22 @@ impl Record {
         }
     }
 
+    pub fn try_into_cds(self) -> CoreResult<CDS, Self> {
+        if let Self::CDS(v) = self {
+            Ok(v)
+        } else {
+            Err(self)
+        }
+    }
+
+    pub fn try_into_cdnskey(self) -> CoreResult<CDNSKEY, Self> {
+        if let Self::CDNSKEY(v) = self {
+            Ok(v)
+        } else {
+            Err(self)
+        }
+    }
+
     pub fn try_into_nsec3(self) -> CoreResult<NSEC3, Self> {
         if let Self::NSEC3(v) = self {
             Ok(v)
@@ -229,6 +385,41 @@ impl Record {
             _ => Err(self),
         }
     }
+
+    pub fn try_into_srv(self) -> CoreResult<SRV, Self> {
+        match self {
+            Self::SRV(v) => Ok(v),
+            _ => Err(self),
+        }
+    }
+
+    pub fn try_into_tlsa(self) -> CoreResult<TLSA, Self> {
+        match self {
+            Self::TLSA(v) => Ok(v),
+            _ => Err(self),
+        }
+    }
+
+    pub fn try_into_zonemd(self) -> CoreResult<ZONEMD, Self> {
+        match self {
+            Self::ZONEMD(v) => Ok(v),
+            _ => Err(self),
+        }
+    }
+
+    pub fn try_into_svcb(self) -> CoreResult<SVCB, Self> {
+        match self {
+            Self::SVCB(v) => Ok(v),
+            _ => Err(self),
+        }
+    }
+
+    pub fn try_into_https(self) -> CoreResult<HTTPS, Self> {
+        match self {
+            Self::HTTPS(v) => Ok(v),
+            _ => Err(self),
+        }
+    }
 }
 
 impl FromStr for Record {
@@ -242,17 +433,26 @@ impl FromStr for Record {
 
         let record = match record_type {
             "A" => Record::A(input.parse()?),
+            "AAAA" => Record::AAAA(input.parse()?),
             "CAA" => Record::CAA(input.parse()?),
+            "CDNSKEY" => Record::CDNSKEY(input.parse()?),
+            "CDS" => Record::CDS(input.parse()?),
             "CNAME" => Record::CNAME(input.parse()?),
             "DNSKEY" => Record::DNSKEY(input.parse()?),
             "DS" => Record::DS(input.parse()?),
+            "HTTPS" => Record::HTTPS(input.parse()?),
             "NS" => Record::NS(input.parse()?),
             "NSEC" => Record::NSEC(input.parse()?),
             "NSEC3" => Record::NSEC3(input.parse()?),
             "NSEC3PARAM" => Record::NSEC3PARAM(input.parse()?),
+            "PTR" => Record::PTR(input.parse()?),
             "RRSIG" => Record::RRSIG(input.parse()?),
             "SOA" => Record::SOA(input.parse()?),
+            "SRV" => Record::SRV(input.parse()?),
+            "SVCB" => Record::SVCB(input.parse()?),
+            "TLSA" => Record::TLSA(input.parse()?),
             "TXT" => Record::TXT(input.parse()?),
+            "ZONEMD" => Record::ZONEMD(input.parse()?),
             _ => {
                 if record_type.starts_with("TYPE") {
                     Record::Unknown(input.parse()?)
@@ -270,17 +470,26 @@ impl fmt::Display for Record {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Record::A(a) => write!(f, "{a}"),
+            Record::AAAA(aaaa) => write!(f, "{aaaa}"),
             Record::CAA(caa) => write!(f, "{caa}"),
+            Record::CDNSKEY(cdnskey) => write!(f, "{cdnskey}"),
+            Record::CDS(cds) => write!(f, "{cds}"),
             Record::CNAME(cname) => write!(f, "{cname}"),
             Record::DS(ds) => write!(f, "{ds}"),
             Record::DNSKEY(dnskey) => write!(f, "{dnskey}"),
+            Record::HTTPS(https) => write!(f, "{https}"),
             Record::NS(ns) => write!(f, "{ns}"),
             Record::NSEC(nsec) => write!(f, "{nsec}"),
             Record::NSEC3(nsec3) => write!(f, "{nsec3}"),
             Record::NSEC3PARAM(nsec3param) => write!(f, "{nsec3param}"),
+            Record::PTR(ptr) => write!(f, "{ptr}"),
             Record::RRSIG(rrsig) => write!(f, "{rrsig}"),
             Record::SOA(soa) => write!(f, "{soa}"),
+            Record::SRV(srv) => write!(f, "{srv}"),
+            Record::SVCB(svcb) => write!(f, "{svcb}"),
+            Record::TLSA(tlsa) => write!(f, "{tlsa}"),
             Record::TXT(txt) => write!(f, "{txt}"),
+            Record::ZONEMD(zonemd) => write!(f, "{zonemd}"),
             Record::Unknown(other) => write!(f, "{other}"),
         }
     }
@@ -335,6 +544,55 @@ impl fmt::Display for A {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct AAAA {
+    pub fqdn: FQDN,
+    pub ttl: u32,
+    pub ipv6_addr: Ipv6Addr,
+}
+
+impl FromStr for AAAA {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let mut columns = input.split_whitespace();
+
+        let [
+            Some(fqdn),
+            Some(ttl),
+            Some(class),
+            Some(record_type),
+            Some(ipv6_addr),
+            None,
+        ] = array::from_fn(|_| columns.next())
+        else {
+            return Err("expected 5 columns".into());
+        };
+
+        check_record_type::<Self>(record_type)?;
+        check_class(class)?;
+
+        Ok(Self {
+            fqdn: fqdn.parse()?,
+            ttl: ttl.parse()?,
+            ipv6_addr: ipv6_addr.parse()?,
+        })
+    }
+}
+
+impl fmt::Display for AAAA {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            fqdn,
+            ttl,
+            ipv6_addr,
+        } = self;
+
+        let record_type = unqualified_type_name::<Self>();
+        write!(f, "{fqdn}\t{ttl}\t{CLASS}\t{record_type}\t{ipv6_addr}")
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CNAME {
     pub fqdn: FQDN,
@@ -380,6 +638,52 @@ impl fmt::Display for CNAME {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct PTR {
+    /// e.g. `1.2.0.192.in-addr.arpa.`
+    pub fqdn: FQDN,
+    pub ttl: u32,
+    pub target: FQDN,
+}
+
+impl FromStr for PTR {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let mut columns = input.split_whitespace();
+
+        let [
+            Some(fqdn),
+            Some(ttl),
+            Some(class),
+            Some(record_type),
+            Some(target),
+            None,
+        ] = array::from_fn(|_| columns.next())
+        else {
+            return Err("expected 5 columns".into());
+        };
+
+        check_record_type::<Self>(record_type)?;
+        check_class(class)?;
+
+        Ok(Self {
+            fqdn: fqdn.parse()?,
+            ttl: ttl.parse()?,
+            target: target.parse()?,
+        })
+    }
+}
+
+impl fmt::Display for PTR {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { fqdn, ttl, target } = self;
+
+        let record_type = unqualified_type_name::<Self>();
+        write!(f, "{fqdn}\t{ttl}\t{CLASS}\t{record_type}\t{target}")
+    }
+}
+
 // integer types chosen based on bit sizes in section 2.1 of RFC4034
 #[derive(Clone, Debug)]
 pub struct DNSKEY {
@@ -425,45 +729,50 @@ impl DNSKEY {
 impl FromStr for DNSKEY {
     type Err = Error;
 
-    fn from_str(mut input: &str) -> Result<Self> {
-        if let Some((rr, _comment)) = input.rsplit_once(" ;") {
-            input = rr.trim_end();
-        }
-
-        let mut columns = input.split_whitespace();
-
-        let [
-            Some(zone),
-            Some(ttl),
-            Some(class),
-            Some(record_type),
-            Some(flags),
-            Some(protocol),
-            Some(algorithm),
-        ] = array::from_fn(|_| columns.next())
-        else {
-            return Err("expected at least 7 columns".into());
-        };
+    fn from_str(input: &str) -> Result<Self> {
+        parse_dnskey::<Self>(input)
+    }
+}
 
-        check_record_type::<Self>(record_type)?;
-        check_class(class)?;
+/// parses a record that has the DNSKEY layout, but whose type is `T`
+fn parse_dnskey<T>(mut input: &str) -> Result<DNSKEY> {
+    if let Some((rr, _comment)) = input.rsplit_once(" ;") {
+        input = rr.trim_end();
+    }
+
+    let mut columns = input.split_whitespace();
+
+    let [
+        Some(zone),
+        Some(ttl),
+        Some(class),
+        Some(record_type),
+        Some(flags),
+        Some(protocol),
+        Some(algorithm),
+    ] = array::from_fn(|_| columns.next())
+    else {
+        return Err("expected at least 7 columns".into());
+    };
 
-        let mut public_key = String::new();
-        for column in columns {
-            public_key.push_str(column);
-        }
+    check_record_type::<T>(record_type)?;
+    check_class(class)?;
 
-        Ok(Self {
-            zone: zone.parse()?,
-            ttl: ttl.parse()?,
-            rdata: DNSKEYRData {
-                flags: flags.parse()?,
-                protocol: protocol.parse()?,
-                algorithm: algorithm.parse()?,
-                public_key,
-            },
-        })
+    let mut public_key = String::new();
+    for column in columns {
+        public_key.push_str(column);
     }
+
+    Ok(DNSKEY {
+        zone: zone.parse()?,
+        ttl: ttl.parse()?,
+        rdata: DNSKEYRData {
+            flags: flags.parse()?,
+            protocol: protocol.parse()?,
+            algorithm: algorithm.parse()?,
+            public_key,
+        },
+    })
 }
 
 impl fmt::Display for DNSKEY {
@@ -548,37 +857,58 @@ impl FromStr for DS {
     type Err = Error;
 
     fn from_str(input: &str) -> Result<Self> {
-        let mut columns = input.split_whitespace();
+        parse_ds::<Self>(input)
+    }
+}
 
-        let [
-            Some(zone),
-            Some(ttl),
-            Some(class),
-            Some(record_type),
-            Some(key_tag),
-            Some(algorithm),
-            Some(digest_type),
-        ] = array::from_fn(|_| columns.next())
-        else {
-            return Err("expected at least 7 columns".into());
-        };
+/// parses a record that has the DS layout, but whose type is `T`
+fn parse_ds<T>(input: &str) -> Result<DS> {
+    let mut columns = input.split_whitespace();
+
+    let [
+        Some(zone),
+        Some(ttl),
+        Some(class),
+        Some(record_type),
+        Some(key_tag),
+        Some(algorithm),
+        Some(digest_type),
+    ] = array::from_fn(|_| columns.next())
+    else {
+        return Err("expected at least 7 columns".into());
+    };
 
-        check_record_type::<Self>(record_type)?;
-        check_class(class)?;
+    check_record_type::<T>(record_type)?;
+    check_class(class)?;
 
-        let mut digest = String::new();
-        for column in columns {
-            digest.push_str(column);
-        }
+    let mut digest = String::new();
+    for column in columns {
+        digest.push_str(column);
+    }
 
-        Ok(Self {
-            zone: zone.parse()?,
-            ttl: ttl.parse()?,
-            key_tag: key_tag.parse()?,
-            algorithm: algorithm.parse()?,
-            digest_type: digest_type.parse()?,
+    Ok(DS {
+        zone: zone.parse()?,
+        ttl: ttl.parse()?,
+        key_tag: key_tag.parse()?,
+        algorithm: algorithm.parse()?,
+        digest_type: digest_type.parse()?,
+        digest,
+    })
+}
+
+impl DS {
+    /// formats the DS record as a `static-ds` entry of a `trust-anchors` block
+    pub(super) fn delv(&self) -> String {
+        let Self {
+            zone,
+            key_tag,
+            algorithm,
+            digest_type,
             digest,
-        })
+            ..
+        } = self;
+
+        format!("{zone} static-ds {key_tag} {algorithm} {digest_type} \"{digest}\";\n")
     }
 }
 
@@ -603,14 +933,152 @@ impl fmt::Display for DS {
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct NS {
+/// Child DS (RFC 7344): a DS record the child zone publishes for its parent to pick up
+#[derive(Clone, Debug)]
+pub struct CDS {
     pub zone: FQDN,
     pub ttl: u32,
-    pub nameserver: FQDN,
-}
-
-impl fmt::Display for NS {
+    pub key_tag: u16,
+    pub algorithm: u8,
+    pub digest_type: u8,
+    pub digest: String,
+}
+
+impl From<DS> for CDS {
+    fn from(ds: DS) -> Self {
+        let DS {
+            zone,
+            ttl,
+            key_tag,
+            algorithm,
+            digest_type,
+            digest,
+        } = ds;
+
+        Self {
+            zone,
+            ttl,
+            key_tag,
+            algorithm,
+            digest_type,
+            digest,
+        }
+    }
+}
+
+impl From<CDS> for DS {
+    fn from(cds: CDS) -> Self {
+        let CDS {
+            zone,
+            ttl,
+            key_tag,
+            algorithm,
+            digest_type,
+            digest,
+        } = cds;
+
+        Self {
+            zone,
+            ttl,
+            key_tag,
+            algorithm,
+            digest_type,
+            digest,
+        }
+    }
+}
+
+impl FromStr for CDS {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        parse_ds::<Self>(input).map(Self::from)
+    }
+}
+
+impl fmt::Display for CDS {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            zone,
+            ttl,
+            key_tag,
+            algorithm,
+            digest_type,
+            digest,
+        } = self;
+
+        let record_type = unqualified_type_name::<Self>();
+        write!(
+            f,
+            "{zone}\t{ttl}\t{CLASS}\t{record_type}\t{key_tag} {algorithm} {digest_type}"
+        )?;
+
+        write_split_long_string(f, digest)
+    }
+}
+
+/// Child DNSKEY (RFC 7344): the DNSKEY the child zone wants its parent to create a DS record for
+#[derive(Clone, Debug)]
+pub struct CDNSKEY {
+    pub zone: FQDN,
+    pub ttl: u32,
+    pub rdata: DNSKEYRData,
+}
+
+impl From<DNSKEY> for CDNSKEY {
+    fn from(dnskey: DNSKEY) -> Self {
+        let DNSKEY { zone, ttl, rdata } = dnskey;
+        Self { zone, ttl, rdata }
+    }
+}
+
+impl From<CDNSKEY> for DNSKEY {
+    fn from(cdnskey: CDNSKEY) -> Self {
+        let CDNSKEY { zone, ttl, rdata } = cdnskey;
+        Self { zone, ttl, rdata }
+    }
+}
+
+impl FromStr for CDNSKEY {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        parse_dnskey::<Self>(input).map(Self::from)
+    }
+}
+
+impl fmt::Display for CDNSKEY {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            zone,
+            ttl,
+            rdata:
+                DNSKEYRData {
+                    flags,
+                    protocol,
+                    algorithm,
+                    public_key,
+                },
+        } = self;
+
+        let record_type = unqualified_type_name::<Self>();
+        write!(
+            f,
+            "{zone}\t{ttl}\t{CLASS}\t{record_type}\t{flags} {protocol} {algorithm}"
+        )?;
+
+        write_split_long_string(f, public_key)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NS {
+    pub zone: FQDN,
+    pub ttl: u32,
+    pub nameserver: FQDN,
+}
+
+impl fmt::Display for NS {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let Self {
             zone,
@@ -1159,106 +1627,657 @@ impl FromStr for TXT {
             return Err("expected at least 5 columns".into());
         }
 
-        Ok(Self {
-            zone: zone.parse()?,
-            ttl: ttl.parse()?,
-            character_strings,
-        })
+        Ok(Self {
+            zone: zone.parse()?,
+            ttl: ttl.parse()?,
+            character_strings,
+        })
+    }
+}
+
+impl fmt::Display for TXT {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            zone,
+            ttl,
+            character_strings,
+        } = self;
+
+        let record_type = unqualified_type_name::<Self>();
+        write!(f, "{zone}\t{ttl}\t{CLASS}\t{record_type}")?;
+        let mut is_first = true;
+        for string in character_strings.iter() {
+            if is_first {
+                write!(f, "\t\"{string}\"")?;
+                is_first = false;
+            } else {
+                write!(f, " \"{string}\"")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone)]
+pub struct CAA {
+    pub zone: FQDN,
+    pub ttl: u32,
+    pub flags: u8,
+    pub tag: String,
+    pub value: String,
+}
+
+impl FromStr for CAA {
+    type Err = Error;
+
+    fn from_str(input: &str) -> CoreResult<Self, Self::Err> {
+        let mut rest = input;
+        let [
+            Some(zone),
+            Some(ttl),
+            Some(class),
+            Some(record_type),
+            Some(flags),
+            Some(tag),
+        ] = array::from_fn(|_| {
+            let (column, remainder) = split_column(rest)?;
+            rest = remainder;
+            Some(column)
+        })
+        else {
+            return Err("expected 7 columns".into());
+        };
+
+        check_record_type::<Self>(record_type)?;
+        check_class(class)?;
+
+        // dig emits the value as a quoted string, which can contain whitespace
+        let value = rest.trim();
+        let value = if let Some(quoted) = value.strip_prefix('"') {
+            quoted
+                .strip_suffix('"')
+                .ok_or("quoted string in CAA record was not closed")?
+        } else if value.is_empty() || value.contains(char::is_whitespace) {
+            return Err("expected 7 columns".into());
+        } else {
+            value
+        };
+
+        if value.contains('\\') {
+            return Err("backslash escapes in CAA records are not supported".into());
+        }
+
+        Ok(Self {
+            zone: zone.parse()?,
+            ttl: ttl.parse()?,
+            flags: flags.parse()?,
+            tag: tag.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for CAA {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self {
+            zone,
+            ttl,
+            flags,
+            tag,
+            value,
+        } = self;
+
+        let record_type = unqualified_type_name::<Self>();
+        write!(f, "{zone}\t{ttl}\t{CLASS}\t{record_type}\t{flags} {tag} ")?;
+        if value.is_empty() || value.contains(char::is_whitespace) {
+            write!(f, "\"{value}\"")?;
+        } else {
+            f.write_str(value)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Service location record (RFC 2782)
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone)]
+pub struct SRV {
+    /// e.g. `_ldap._tcp.example.com.`
+    pub fqdn: FQDN,
+    pub ttl: u32,
+    /// lower values are preferred
+    pub priority: u16,
+    /// relative weight among the records that have the same priority
+    pub weight: u16,
+    pub port: u16,
+    pub target: FQDN,
+}
+
+impl FromStr for SRV {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let mut columns = input.split_whitespace();
+
+        let [
+            Some(fqdn),
+            Some(ttl),
+            Some(class),
+            Some(record_type),
+            Some(priority),
+            Some(weight),
+            Some(port),
+            Some(target),
+            None,
+        ] = array::from_fn(|_| columns.next())
+        else {
+            return Err("expected 8 columns".into());
+        };
+
+        check_record_type::<Self>(record_type)?;
+        check_class(class)?;
+
+        Ok(Self {
+            fqdn: fqdn.parse()?,
+            ttl: ttl.parse()?,
+            priority: priority.parse()?,
+            weight: weight.parse()?,
+            port: port.parse()?,
+            target: target.parse()?,
+        })
+    }
+}
+
+impl fmt::Display for SRV {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            fqdn,
+            ttl,
+            priority,
+            weight,
+            port,
+            target,
+        } = self;
+
+        let record_type = unqualified_type_name::<Self>();
+        write!(
+            f,
+            "{fqdn}\t{ttl}\t{CLASS}\t{record_type}\t{priority} {weight} {port} {target}"
+        )
+    }
+}
+
+/// TLSA record used by DANE (RFC 6698)
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone)]
+pub struct TLSA {
+    /// e.g. `_443._tcp.example.com.`
+    pub fqdn: FQDN,
+    pub ttl: u32,
+    pub cert_usage: u8,
+    pub selector: u8,
+    pub matching_type: u8,
+    /// hexadecimal string; dig splits it across several whitespace-separated columns
+    pub cert_data: String,
+}
+
+impl FromStr for TLSA {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let mut columns = input.split_whitespace();
+
+        let [
+            Some(fqdn),
+            Some(ttl),
+            Some(class),
+            Some(record_type),
+            Some(cert_usage),
+            Some(selector),
+            Some(matching_type),
+        ] = array::from_fn(|_| columns.next())
+        else {
+            return Err("expected at least 8 columns".into());
+        };
+
+        check_record_type::<Self>(record_type)?;
+        check_class(class)?;
+
+        let mut cert_data = String::new();
+        // `dig +multiline` wraps the data in parentheses
+        for column in columns.filter(|column| !matches!(*column, "(" | ")")) {
+            cert_data.push_str(column);
+        }
+
+        if cert_data.is_empty() {
+            return Err("expected at least 8 columns".into());
+        }
+
+        Ok(Self {
+            fqdn: fqdn.parse()?,
+            ttl: ttl.parse()?,
+            cert_usage: cert_usage.parse()?,
+            selector: selector.parse()?,
+            matching_type: matching_type.parse()?,
+            cert_data,
+        })
+    }
+}
+
+impl fmt::Display for TLSA {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            fqdn,
+            ttl,
+            cert_usage,
+            selector,
+            matching_type,
+            cert_data,
+        } = self;
+
+        let record_type = unqualified_type_name::<Self>();
+        write!(
+            f,
+            "{fqdn}\t{ttl}\t{CLASS}\t{record_type}\t{cert_usage} {selector} {matching_type}"
+        )?;
+
+        write_split_long_string(f, cert_data)
+    }
+}
+
+/// Message digest of the whole zone (RFC 8976)
+#[derive(Debug, Clone)]
+#[allow(clippy::upper_case_acronyms)]
+pub struct ZONEMD {
+    pub zone: FQDN,
+    pub ttl: u32,
+    /// SOA serial of the version of the zone the digest covers
+    pub serial: u32,
+    pub scheme: u8,
+    pub hash_algorithm: u8,
+    /// hexadecimal string; dig splits it across several whitespace-separated columns
+    pub digest: String,
+}
+
+impl FromStr for ZONEMD {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let mut columns = input.split_whitespace();
+
+        let [
+            Some(zone),
+            Some(ttl),
+            Some(class),
+            Some(record_type),
+            Some(serial),
+            Some(scheme),
+            Some(hash_algorithm),
+        ] = array::from_fn(|_| columns.next())
+        else {
+            return Err("expected at least 8 columns".into());
+        };
+
+        check_record_type::<Self>(record_type)?;
+        check_class(class)?;
+
+        let mut digest = String::new();
+        // `dig +multiline` wraps the digest in parentheses
+        for column in columns.filter(|column| !matches!(*column, "(" | ")")) {
+            digest.push_str(column);
+        }
+
+        if digest.is_empty() {
+            return Err("expected at least 8 columns".into());
+        }
+
+        Ok(Self {
+            zone: zone.parse()?,
+            ttl: ttl.parse()?,
+            serial: serial.parse()?,
+            scheme: scheme.parse()?,
+            hash_algorithm: hash_algorithm.parse()?,
+            digest,
+        })
+    }
+}
+
+impl fmt::Display for ZONEMD {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            zone,
+            ttl,
+            serial,
+            scheme,
+            hash_algorithm,
+            digest,
+        } = self;
+
+        let record_type = unqualified_type_name::<Self>();
+        write!(
+            f,
+            "{zone}\t{ttl}\t{CLASS}\t{record_type}\t{serial} {scheme} {hash_algorithm}"
+        )?;
+
+        write_split_long_string(f, digest)
+    }
+}
+
+/// Service binding record (RFC 9460)
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone)]
+pub struct SVCB {
+    pub zone: FQDN,
+    pub ttl: u32,
+    /// `0` denotes AliasMode; any other value denotes ServiceMode
+    pub priority: u16,
+    pub target: FQDN,
+    pub params: BTreeMap<SvcParamKey, SvcParamValue>,
+}
+
+impl FromStr for SVCB {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        parse_svcb::<Self>(input)
+    }
+}
+
+/// parses a record that has the SVCB layout, but whose type is `T`
+fn parse_svcb<T>(input: &str) -> Result<SVCB> {
+    let mut columns = input.split_whitespace();
+
+    let [
+        Some(zone),
+        Some(ttl),
+        Some(class),
+        Some(record_type),
+        Some(priority),
+        Some(target),
+    ] = array::from_fn(|_| columns.next())
+    else {
+        return Err("expected at least 6 columns".into());
+    };
+
+    check_record_type::<T>(record_type)?;
+    check_class(class)?;
+
+    Ok(SVCB {
+        zone: zone.parse()?,
+        ttl: ttl.parse()?,
+        priority: priority.parse()?,
+        target: target.parse()?,
+        params: parse_svc_params(columns)?,
+    })
+}
+
+impl fmt::Display for SVCB {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            zone,
+            ttl,
+            priority,
+            target,
+            params,
+        } = self;
+
+        let record_type = unqualified_type_name::<Self>();
+        write!(
+            f,
+            "{zone}\t{ttl}\t{CLASS}\t{record_type}\t{priority} {target}"
+        )?;
+        write_svc_params(f, params)
+    }
+}
+
+/// SVCB record specific to HTTP(S) origins (RFC 9460)
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone)]
+pub struct HTTPS {
+    pub zone: FQDN,
+    pub ttl: u32,
+    /// `0` denotes AliasMode; any other value denotes ServiceMode
+    pub priority: u16,
+    pub target: FQDN,
+    pub params: BTreeMap<SvcParamKey, SvcParamValue>,
+}
+
+impl From<SVCB> for HTTPS {
+    fn from(svcb: SVCB) -> Self {
+        let SVCB {
+            zone,
+            ttl,
+            priority,
+            target,
+            params,
+        } = svcb;
+
+        Self {
+            zone,
+            ttl,
+            priority,
+            target,
+            params,
+        }
+    }
+}
+
+impl FromStr for HTTPS {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        parse_svcb::<Self>(input).map(Self::from)
     }
 }
 
-impl fmt::Display for TXT {
+impl fmt::Display for HTTPS {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let Self {
             zone,
             ttl,
-            character_strings,
+            priority,
+            target,
+            params,
         } = self;
 
         let record_type = unqualified_type_name::<Self>();
-        write!(f, "{zone}\t{ttl}\t{CLASS}\t{record_type}")?;
-        let mut is_first = true;
-        for string in character_strings.iter() {
-            if is_first {
-                write!(f, "\t\"{string}\"")?;
-                is_first = false;
-            } else {
-                write!(f, " \"{string}\"")?;
-            }
-        }
-        Ok(())
+        write!(
+            f,
+            "{zone}\t{ttl}\t{CLASS}\t{record_type}\t{priority} {target}"
+        )?;
+        write_svc_params(f, params)
     }
 }
 
-#[allow(clippy::upper_case_acronyms)]
-#[derive(Debug, Clone)]
-pub struct CAA {
-    pub zone: FQDN,
-    pub ttl: u32,
-    pub flags: u8,
-    pub tag: String,
-    pub value: String,
+/// The key of a SvcParam, ordered by key number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SvcParamKey {
+    Mandatory,
+    Alpn,
+    NoDefaultAlpn,
+    Port,
+    Ipv4Hint,
+    Ech,
+    Ipv6Hint,
+    /// `key65534` and the like
+    Unknown(u16),
 }
 
-impl FromStr for CAA {
+impl FromStr for SvcParamKey {
     type Err = Error;
 
-    fn from_str(input: &str) -> CoreResult<Self, Self::Err> {
-        let mut columns = input.split_whitespace();
+    fn from_str(input: &str) -> Result<Self> {
+        let key = match input {
+            "mandatory" => Self::Mandatory,
+            "alpn" => Self::Alpn,
+            "no-default-alpn" => Self::NoDefaultAlpn,
+            "port" => Self::Port,
+            "ipv4hint" => Self::Ipv4Hint,
+            "ech" => Self::Ech,
+            "ipv6hint" => Self::Ipv6Hint,
+            _ => {
+                let Some(number) = input.strip_prefix("key") else {
+                    return Err(format!("unknown SvcParam key: {input}").into());
+                };
+                Self::Unknown(number.parse()?)
+            }
+        };
 
-        let [
-            Some(zone),
-            Some(ttl),
-            Some(class),
-            Some(record_type),
-            Some(flags),
-            Some(tag),
-            Some(value),
-            None,
-        ] = array::from_fn(|_| columns.next())
-        else {
-            return Err("expected 7 columns".into());
+        Ok(key)
+    }
+}
+
+impl fmt::Display for SvcParamKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Mandatory => "mandatory",
+            Self::Alpn => "alpn",
+            Self::NoDefaultAlpn => "no-default-alpn",
+            Self::Port => "port",
+            Self::Ipv4Hint => "ipv4hint",
+            Self::Ech => "ech",
+            Self::Ipv6Hint => "ipv6hint",
+            Self::Unknown(number) => return write!(f, "key{number}"),
         };
+        f.write_str(s)
+    }
+}
 
-        check_record_type::<Self>(record_type)?;
-        check_class(class)?;
+/// The value of a SvcParam
+#[derive(Debug, Clone, PartialEq)]
+pub enum SvcParamValue {
+    /// `mandatory`
+    Keys(Vec<SvcParamKey>),
+    /// `alpn`
+    Alpn(Vec<String>),
+    /// `no-default-alpn` takes no value
+    Empty,
+    Port(u16),
+    Ipv4Hint(Vec<Ipv4Addr>),
+    Ipv6Hint(Vec<Ipv6Addr>),
+    /// `ech` and keys unknown to this crate, in their presentation format
+    Opaque(String),
+}
 
-        let value = if value == "\"\"" {
-            "".to_string()
-        } else {
-            value.to_string()
+impl SvcParamValue {
+    fn parse(key: SvcParamKey, value: Option<&str>) -> Result<Self> {
+        if key == SvcParamKey::NoDefaultAlpn {
+            return if value.is_some() {
+                Err("SvcParam `no-default-alpn` does not take a value".into())
+            } else {
+                Ok(Self::Empty)
+            };
+        }
+
+        if let SvcParamKey::Unknown(_) = key {
+            return Ok(Self::Opaque(value.unwrap_or_default().to_string()));
+        }
+
+        let value = value.ok_or_else(|| format!("SvcParam `{key}` is missing its value"))?;
+        let list = value.split(',');
+        let value = match key {
+            SvcParamKey::Mandatory => Self::Keys(list.map(str::parse).collect::<Result<_>>()?),
+            SvcParamKey::Alpn => Self::Alpn(list.map(String::from).collect()),
+            SvcParamKey::Port => Self::Port(value.parse()?),
+            SvcParamKey::Ipv4Hint => {
+                Self::Ipv4Hint(list.map(str::parse).collect::<CoreResult<_, _>>()?)
+            }
+            SvcParamKey::Ipv6Hint => {
+                Self::Ipv6Hint(list.map(str::parse).collect::<CoreResult<_, _>>()?)
+            }
+            SvcParamKey::Ech => Self::Opaque(value.to_string()),
+            SvcParamKey::NoDefaultAlpn | SvcParamKey::Unknown(_) => unreachable!(),
         };
 
-        Ok(Self {
-            zone: zone.parse()?,
-            ttl: ttl.parse()?,
-            flags: flags.parse()?,
-            tag: tag.to_string(),
-            value,
-        })
+        Ok(value)
     }
 }
 
-impl fmt::Display for CAA {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let Self {
-            zone,
-            ttl,
-            flags,
-            tag,
-            value,
-        } = self;
+impl fmt::Display for SvcParamValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Keys(keys) => write_list(f, keys),
+            Self::Alpn(protocols) => {
+                f.write_char('"')?;
+                write_list(f, protocols)?;
+                f.write_char('"')
+            }
+            Self::Empty => Ok(()),
+            Self::Port(port) => write!(f, "{port}"),
+            Self::Ipv4Hint(addrs) => write_list(f, addrs),
+            Self::Ipv6Hint(addrs) => write_list(f, addrs),
+            Self::Opaque(value) => write!(f, "\"{value}\""),
+        }
+    }
+}
 
-        let record_type = unqualified_type_name::<Self>();
-        write!(f, "{zone}\t{ttl}\t{CLASS}\t{record_type}\t{flags} {tag} ")?;
-        if value.is_empty() {
-            write!(f, "\"\"")?;
-        } else {
-            f.write_str(value)?;
+fn write_list(f: &mut fmt::Formatter<'_>, items: &[impl fmt::Display]) -> fmt::Result {
+    for (index, item) in items.iter().enumerate() {
+        if index != 0 {
+            f.write_char(',')?;
         }
+        write!(f, "{item}")?;
+    }
 
-        Ok(())
+    Ok(())
+}
+
+/// parses the SvcParams of a SVCB or HTTPS record, e.g. `alpn="h2,h3" ipv4hint=192.0.2.1`
+fn parse_svc_params<'a>(
+    columns: impl Iterator<Item = &'a str>,
+) -> Result<BTreeMap<SvcParamKey, SvcParamValue>> {
+    let mut params = BTreeMap::new();
+    for column in columns {
+        let (key, value) = match column.split_once('=') {
+            Some((key, value)) => (key.parse()?, Some(unquote(value)?)),
+            None => (column.parse()?, None),
+        };
+
+        let value = SvcParamValue::parse(key, value)?;
+        if params.insert(key, value).is_some() {
+            return Err(format!("duplicate SvcParam key: {key}").into());
+        }
+    }
+
+    Ok(params)
+}
+
+/// removes the quotes around a SvcParam value, if any
+fn unquote(value: &str) -> Result<&str> {
+    let unquoted = match value.strip_prefix('"') {
+        Some(rest) => rest
+            .strip_suffix('"')
+            .ok_or("whitespace in quoted SvcParam values is not supported")?,
+        None => value,
+    };
+
+    if unquoted.contains('\\') {
+        return Err("backslash escapes in SvcParam values are not supported".into());
+    }
+
+    Ok(unquoted)
+}
+
+/// formats the SvcParams in key number order, each one preceded by a space
+fn write_svc_params(
+    f: &mut fmt::Formatter<'_>,
+    params: &BTreeMap<SvcParamKey, SvcParamValue>,
+) -> fmt::Result {
+    for (key, value) in params {
+        write!(f, " {key}")?;
+        let is_empty = match value {
+            SvcParamValue::Empty => true,
+            SvcParamValue::Opaque(value) => value.is_empty(),
+            _ => false,
+        };
+        if !is_empty {
+            write!(f, "={value}")?;
+        }
     }
+
+    Ok(())
 }
 
 /// A record of unknown type.
@@ -1336,6 +2355,17 @@ impl fmt::Display for UnknownRdata {
     }
 }
 
+/// splits off the first whitespace-separated column of `input`, returning it and the rest of
+/// the input
+pub(crate) fn split_column(input: &str) -> Option<(&str, &str)> {
+    let input = input.trim_start();
+    if input.is_empty() {
+        return None;
+    }
+
+    Some(input.split_once(char::is_whitespace).unwrap_or((input, "")))
+}
+
 fn check_class(class: &str) -> Result<()> {
     if class != "IN" {
         return Err(format!("unknown class: {class}").into());
@@ -1399,6 +2429,30 @@ mod tests {
         Ok(())
     }
 
+    // dig AAAA a.root-servers.net
+    const AAAA_INPUT: &str = "a.root-servers.net.	77859	IN	AAAA	2001:503:ba3e::2:30";
+
+    #[test]
+    fn aaaa() -> Result<()> {
+        let aaaa @ AAAA {
+            fqdn,
+            ttl,
+            ipv6_addr,
+        } = &AAAA_INPUT.parse()?;
+
+        assert_eq!("a.root-servers.net.", fqdn.as_str());
+        assert_eq!(77859, *ttl);
+        assert_eq!(
+            Ipv6Addr::new(0x2001, 0x503, 0xba3e, 0, 0, 0, 0x2, 0x30),
+            *ipv6_addr
+        );
+
+        let output = aaaa.to_string();
+        assert_eq!(AAAA_INPUT, output);
+
+        Ok(())
+    }
+
     // dig CNAME www.isc.org
     const CNAME_INPUT: &str = "www.isc.org.	277	IN	CNAME	isc.map.fastlydns.net.";
 
@@ -1537,6 +2591,250 @@ mod tests {
         Ok(())
     }
 
+    const CDS_INPUT: &str = "com.	7612	IN	CDS	19718 13 2 8ACBB0CD28F41250A80A491389424D341522D946B0DA0C0291F2D3D7 71D7805A";
+
+    #[test]
+    fn cds() -> Result<()> {
+        let cds @ CDS {
+            zone,
+            ttl,
+            key_tag,
+            algorithm,
+            digest_type,
+            digest,
+        } = &CDS_INPUT.parse()?;
+
+        assert_eq!(FQDN("com.")?, *zone);
+        assert_eq!(7612, *ttl);
+        assert_eq!(19718, *key_tag);
+        assert_eq!(13, *algorithm);
+        assert_eq!(2, *digest_type);
+        let expected = "8ACBB0CD28F41250A80A491389424D341522D946B0DA0C0291F2D3D771D7805A";
+        assert_eq!(expected, digest);
+
+        let output = cds.to_string();
+        assert_eq!(CDS_INPUT, output);
+        assert!(output.parse::<DS>().is_err());
+
+        let ds = DS::from(cds.clone());
+        assert_eq!(DS_INPUT, ds.to_string());
+
+        Ok(())
+    }
+
+    const CDNSKEY_INPUT: &str = ".	1116	IN	CDNSKEY	257 3 8 AwEAAaz/tAm8yTn4Mfeh5eyI96WSVexTBAvkMgJzkKTOiW1vkIbzxeF3 +/4RgWOq7HrxRixHlFlExOLAJr5emLvN7SWXgnLh4+B5xQlNVz8Og8kv ArMtNROxVQuCaSnIDdD5LKyWbRd2n9WGe2R8PzgCmr3EgVLrjyBxWezF 0jLHwVN8efS3rCj/EWgvIWgb9tarpVUDK/b58Da+sqqls3eNbuv7pr+e oZG+SrDK6nWeL3c6H5Apxz7LjVc1uTIdsIXxuOLYA4/ilBmSVIzuDWfd RUfhHdY6+cn8HFRm+2hM8AnXGXws9555KrUB5qihylGa8subX2Nn6UwN R1AkUTV74bU=";
+
+    #[test]
+    fn cdnskey() -> Result<()> {
+        let cdnskey: CDNSKEY = CDNSKEY_INPUT.parse()?;
+
+        assert_eq!(FQDN::ROOT, cdnskey.zone);
+        assert_eq!(1116, cdnskey.ttl);
+        assert_eq!(257, cdnskey.rdata.flags);
+        assert_eq!(20326, cdnskey.rdata.calculate_key_tag());
+
+        let output = cdnskey.to_string();
+        assert_eq!(CDNSKEY_INPUT, output);
+
+        let dnskey = DNSKEY::from(cdnskey);
+        assert_eq!(DNSKEY_INPUT, dnskey.to_string());
+        let cdnskey = CDNSKEY::from(dnskey);
+        assert_eq!(CDNSKEY_INPUT, cdnskey.to_string());
+
+        let record: Record = CDNSKEY_INPUT.parse()?;
+        assert!(matches!(record, Record::CDNSKEY(_)));
+        assert_eq!(CDNSKEY_INPUT, record.to_string());
+
+        Ok(())
+    }
+
+    const PTR_INPUT: &str = "1.2.0.192.in-addr.arpa.	86400	IN	PTR	host.example.com.";
+
+    #[test]
+    fn ptr() -> Result<()> {
+        let ptr @ PTR { fqdn, ttl, target } = &PTR_INPUT.parse()?;
+
+        assert_eq!(FQDN::reverse(Ipv4Addr::new(192, 0, 2, 1)), *fqdn);
+        assert_eq!(86400, *ttl);
+        assert_eq!(FQDN("host.example.com.")?, *target);
+
+        let output = ptr.to_string();
+        assert_eq!(PTR_INPUT, output);
+
+        Ok(())
+    }
+
+    const SRV_INPUT: &str = "_ldap._tcp.example.com.	3600	IN	SRV	10 60 389 ldap1.example.com.";
+
+    #[test]
+    fn srv() -> Result<()> {
+        let srv @ SRV {
+            fqdn,
+            ttl,
+            priority,
+            weight,
+            port,
+            target,
+        } = &SRV_INPUT.parse()?;
+
+        assert_eq!(FQDN("_ldap._tcp.example.com.")?, *fqdn);
+        assert_eq!(3600, *ttl);
+        assert_eq!(10, *priority);
+        assert_eq!(60, *weight);
+        assert_eq!(389, *port);
+        assert_eq!(FQDN("ldap1.example.com.")?, *target);
+
+        let output = srv.to_string();
+        assert_eq!(SRV_INPUT, output);
+
+        Ok(())
+    }
+
+    const TLSA_INPUT: &str = "_443._tcp.example.com.	3600	IN	TLSA	3 1 2 0E2E4A8CE0A91DD8A2DE4B4A9D2C27EF087DDE0CFA0DB4E5CE7E98C2 0E7F5F4E7B70DA99B6602A1325B4DA7E6E1911BE27953C3A96D0A8F2 81F68A9E6DDD061E";
+
+    #[test]
+    fn tlsa() -> Result<()> {
+        let tlsa @ TLSA {
+            fqdn,
+            ttl,
+            cert_usage,
+            selector,
+            matching_type,
+            cert_data,
+        } = &TLSA_INPUT.parse()?;
+
+        assert_eq!(FQDN("_443._tcp.example.com.")?, *fqdn);
+        assert_eq!(3600, *ttl);
+        assert_eq!(3, *cert_usage);
+        assert_eq!(1, *selector);
+        assert_eq!(2, *matching_type);
+        let expected = "0E2E4A8CE0A91DD8A2DE4B4A9D2C27EF087DDE0CFA0DB4E5CE7E98C20E7F5F4E7B70DA99B6602A1325B4DA7E6E1911BE27953C3A96D0A8F281F68A9E6DDD061E";
+        assert_eq!(expected, cert_data);
+
+        let output = tlsa.to_string();
+        assert_eq!(TLSA_INPUT, output);
+
+        let multiline = "_443._tcp.example.com. 3600 IN TLSA 3 1 2 ( 0E2E4A8CE0A91DD8A2DE4B4A9D2C27EF087DDE0CFA0DB4E5CE7E98C2 0E7F5F4E7B70DA99B6602A1325B4DA7E6E1911BE27953C3A96D0A8F2 81F68A9E6DDD061E )";
+        let tlsa: TLSA = multiline.parse()?;
+        assert_eq!(expected, tlsa.cert_data);
+
+        Ok(())
+    }
+
+    const ZONEMD_INPUT: &str = "example.com.	86400	IN	ZONEMD	2018031900 1 1 C68090D90A7AED716BC459F9340E3D7C1370D4D24B7E2FC3A1DDC0B9 A87153B9A9713B3C9AE5CC27777F98B8E730044C";
+
+    #[test]
+    fn zonemd() -> Result<()> {
+        let zonemd @ ZONEMD {
+            zone,
+            ttl,
+            serial,
+            scheme,
+            hash_algorithm,
+            digest,
+        } = &ZONEMD_INPUT.parse()?;
+
+        assert_eq!(FQDN("example.com.")?, *zone);
+        assert_eq!(86400, *ttl);
+        assert_eq!(2018031900, *serial);
+        assert_eq!(1, *scheme);
+        assert_eq!(1, *hash_algorithm);
+        let expected = "C68090D90A7AED716BC459F9340E3D7C1370D4D24B7E2FC3A1DDC0B9A87153B9A9713B3C9AE5CC27777F98B8E730044C";
+        assert_eq!(expected, digest);
+
+        let output = zonemd.to_string();
+        assert_eq!(ZONEMD_INPUT, output);
+
+        Ok(())
+    }
+
+    const HTTPS_INPUT: &str = "example.com.	300	IN	HTTPS	1 . alpn=\"h2,h3\" port=8443 ipv4hint=192.0.2.1,192.0.2.2 ipv6hint=2001:db8::1";
+
+    #[test]
+    fn https() -> Result<()> {
+        let https @ HTTPS {
+            zone,
+            ttl,
+            priority,
+            target,
+            params,
+        } = &HTTPS_INPUT.parse()?;
+
+        assert_eq!(FQDN("example.com.")?, *zone);
+        assert_eq!(300, *ttl);
+        assert_eq!(1, *priority);
+        assert_eq!(FQDN::ROOT, *target);
+
+        let expected = BTreeMap::from([
+            (
+                SvcParamKey::Alpn,
+                SvcParamValue::Alpn(vec!["h2".to_string(), "h3".to_string()]),
+            ),
+            (SvcParamKey::Port, SvcParamValue::Port(8443)),
+            (
+                SvcParamKey::Ipv4Hint,
+                SvcParamValue::Ipv4Hint(vec![
+                    Ipv4Addr::new(192, 0, 2, 1),
+                    Ipv4Addr::new(192, 0, 2, 2),
+                ]),
+            ),
+            (
+                SvcParamKey::Ipv6Hint,
+                SvcParamValue::Ipv6Hint(vec!["2001:db8::1".parse()?]),
+            ),
+        ]);
+        assert_eq!(expected, *params);
+
+        let output = https.to_string();
+        assert_eq!(HTTPS_INPUT, output);
+
+        let record: Record = HTTPS_INPUT.parse()?;
+        assert!(matches!(record, Record::HTTPS(_)));
+
+        Ok(())
+    }
+
+    const SVCB_INPUT: &str = "_dns.example.com.	300	IN	SVCB	1 dns.example.com. mandatory=alpn,port alpn=\"dot\" no-default-alpn port=853 key65534=\"opaque\"";
+
+    #[test]
+    fn svcb() -> Result<()> {
+        let svcb: SVCB = SVCB_INPUT.parse()?;
+
+        assert_eq!(FQDN("dns.example.com.")?, svcb.target);
+        assert_eq!(
+            Some(&SvcParamValue::Keys(vec![
+                SvcParamKey::Alpn,
+                SvcParamKey::Port
+            ])),
+            svcb.params.get(&SvcParamKey::Mandatory)
+        );
+        assert_eq!(
+            Some(&SvcParamValue::Empty),
+            svcb.params.get(&SvcParamKey::NoDefaultAlpn)
+        );
+        assert_eq!(
+            Some(&SvcParamValue::Opaque("opaque".to_string())),
+            svcb.params.get(&SvcParamKey::Unknown(65534))
+        );
+
+        let output = svcb.to_string();
+        assert_eq!(SVCB_INPUT, output);
+
+        Ok(())
+    }
+
+    #[test]
+    fn svcb_alias_mode() -> Result<()> {
+        const INPUT: &str = "example.com.	300	IN	SVCB	0 svc.example.net.";
+
+        let svcb: SVCB = INPUT.parse()?;
+        assert_eq!(0, svcb.priority);
+        assert!(svcb.params.is_empty());
+        assert_eq!(INPUT, svcb.to_string());
+
+        Ok(())
+    }
+
     // dig NS .
     const NS_INPUT: &str = ".	86400	IN	NS	f.root-servers.net.";
 
@@ -1768,9 +3066,51 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn caa_quoted_value() -> Result<()> {
+        const INPUT: &str = "example.com.	86400	IN	CAA	0 issue \"letsencrypt.org\"";
+
+        let caa: CAA = INPUT.parse()?;
+        assert_eq!(0, caa.flags);
+        assert_eq!("issue", caa.tag);
+        assert_eq!("letsencrypt.org", caa.value);
+
+        const SPACES: &str = "example.com.	86400	IN	CAA	0 iodef \"mailto:a b@example.com\"";
+        let caa: CAA = SPACES.parse()?;
+        assert_eq!("mailto:a b@example.com", caa.value);
+        assert_eq!(SPACES, caa.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn caa_critical_flag() -> Result<()> {
+        const INPUT: &str = "example.com.	86400	IN	CAA	128 tbs \"unknown\"";
+
+        let caa: CAA = INPUT.parse()?;
+        assert_eq!(128, caa.flags);
+        assert_eq!("tbs", caa.tag);
+        assert_eq!("unknown", caa.value);
+
+        Ok(())
+    }
+
+    #[test]
+    fn caa_long_tag() -> Result<()> {
+        const INPUT: &str = "example.com.	86400	IN	CAA	0 averyveryverylongtag value";
+
+        let caa: CAA = INPUT.parse()?;
+        assert_eq!("averyveryverylongtag", caa.tag);
+        assert!(caa.tag.len() > 15);
+        assert_eq!(INPUT, caa.to_string());
+
+        Ok(())
+    }
+
     #[test]
     fn any() -> Result<()> {
         assert!(matches!(A_INPUT.parse()?, Record::A(..)));
+        assert!(matches!(AAAA_INPUT.parse()?, Record::AAAA(..)));
         assert!(matches!(CAA_INPUT.parse()?, Record::CAA(..)));
         assert!(matches!(DNSKEY_INPUT.parse()?, Record::DNSKEY(..)));
         assert!(matches!(DS_INPUT.parse()?, Record::DS(..)));
@@ -1,18 +1,32 @@
 use core::fmt::Write;
-use std::net::Ipv4Addr;
+use std::collections::BTreeSet;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::process::ExitStatus;
 
-use crate::container::{Child, Container, Network};
-use crate::implementation::{Config, Role};
-use crate::record::DNSKEY;
+use ipnet::Ipv6Net;
+
+use crate::container::{Child, Container, LogStream, Network, Signal};
+use crate::implementation::{
+    Config, ConfigBuilder, Cookies, ExtraConfig, QnameMinimization, Role, ServeStale,
+    TransportRestriction, Verbosity,
+};
+use crate::logs::{self, LogEvent};
+use crate::record::{DNSKEY, DS};
+use crate::transport;
 use crate::trust_anchor::TrustAnchor;
 use crate::tshark::Tshark;
 use crate::zone_file::Root;
-use crate::{Implementation, Result};
+use crate::{Implementation, Result, Transport};
 
 pub struct Resolver {
     container: Container,
-    _child: Child,
+    /// `None` once the server process was stopped
+    child: Option<Child>,
     implementation: Implementation,
+    /// the command that starts the server process; see [`Resolver::restart`]
+    cmd_args: Vec<String>,
+    transports: BTreeSet<Transport>,
+    port: u16,
 }
 
 impl Resolver {
@@ -21,10 +35,30 @@ impl Resolver {
         ResolverSettings {
             ede: false,
             network: network.clone(),
+            connected_networks: vec![],
             roots: vec![root],
             trust_anchor: TrustAnchor::empty(),
             custom_config: None,
             case_randomization: false,
+            verbosity: Verbosity::default(),
+            cache_enabled: true,
+            max_cache_ttl: None,
+            min_cache_ttl: None,
+            udp_payload_size: None,
+            query_logging: false,
+            netmasks: vec![],
+            recursion: true,
+            transports: BTreeSet::from([Transport::Do53]),
+            port: Transport::Do53.port(),
+            threads: None,
+            qname_minimization: None,
+            serve_stale: None,
+            transport_restriction: None,
+            dns64_prefix: None,
+            edns_client_subnet: false,
+            forwarding_views: vec![],
+            cookies: None,
+            extra_configs: vec![],
         }
     }
 
@@ -48,6 +82,32 @@ impl Resolver {
         self.container.ipv4_addr()
     }
 
+    pub fn ipv6_addr(&self) -> Option<Ipv6Addr> {
+        self.container.ipv6_addr()
+    }
+
+    /// Returns the IPv4 address of the resolver in `network`; `None` if it is not attached to
+    /// it
+    pub fn ipv4_addr_in(&self, network: &Network) -> Option<Ipv4Addr> {
+        self.container.ipv4_addr_in(network)
+    }
+
+    /// Certificate presented by the DoT and DoH listeners, to pass to [`Client::dig_tls`]
+    ///
+    /// Fails if the resolver does not listen on any of those transports
+    ///
+    /// [`Client::dig_tls`]: crate::client::Client::dig_tls
+    pub fn tls_certificate(&self) -> Result<String> {
+        transport::read_tls_cert(&self.container)
+    }
+
+    /// Returns the events parsed from the logs collected so far
+    ///
+    /// See [`logs::parse`] for the events each implementation logs
+    pub fn log_events(&self) -> Result<Vec<LogEvent>> {
+        Ok(logs::parse(&self.implementation, &self.logs()?))
+    }
+
     /// Returns the logs collected so far
     pub fn logs(&self) -> Result<String> {
         if self.implementation.is_hickory() {
@@ -57,6 +117,107 @@ impl Resolver {
         }
     }
 
+    /// Streams the lines the server writes to its stdout and stderr, starting from the first one
+    pub fn logs_stream(&self) -> Result<LogStream> {
+        self.container.tail_logs(&[
+            &self.implementation.stdout_logfile(Role::Resolver),
+            &self.implementation.stderr_logfile(Role::Resolver),
+        ])
+    }
+
+    /// Stops the server process with `signal` and returns its exit status; the container, and
+    /// with it the cache files and logs, stays around
+    pub fn stop(&mut self, signal: Signal) -> Result<ExitStatus> {
+        let implementation = &self.implementation;
+        let pidfile = implementation
+            .pidfile(Role::Resolver)
+            .ok_or_else(|| format!("the {implementation} resolver has no pidfile"))?;
+        let child = self.child.take().ok_or("the resolver is already stopped")?;
+        self.container.kill(pidfile, signal)?;
+
+        Ok(child.wait()?.status)
+    }
+
+    /// Starts the server process again, with the same command line and configuration, and waits
+    /// until it answers queries; the resolver must have been [`Self::stop`]ped
+    pub fn restart(&mut self) -> Result<()> {
+        if self.child.is_some() {
+            return Err("the resolver is still running".into());
+        }
+
+        let mut child = self.container.spawn(&self.cmd_args)?;
+        self.implementation.wait_until_ready(
+            &self.container,
+            &mut child,
+            &readiness_config(self.port),
+            self.implementation.readiness_timeout(),
+        )?;
+
+        self.child = Some(child);
+        Ok(())
+    }
+
+    /// Writes the configuration of `settings` to the container and makes the running server
+    /// process load it, e.g. to turn DNSSEC validation on; the process is not restarted
+    ///
+    /// The transports and the port the resolver listens on cannot change, and the networks of
+    /// `settings` are ignored. Fails, with the output of the control command, if the server
+    /// rejects the new configuration; never falls back to restarting the server. See
+    /// [`Implementation::supports_config_reload`]
+    pub fn reload(&mut self, settings: &ResolverSettings) -> Result<()> {
+        let reload = self.reload_cmd()?;
+        let implementation = &self.implementation;
+        if settings.transports != self.transports || settings.port != self.port {
+            return Err(
+                "the transports and the port of a running resolver cannot be changed".into(),
+            );
+        }
+        let child = self.child.as_mut().ok_or("the resolver is stopped")?;
+        settings.check(implementation)?;
+
+        self.cmd_args = settings.write_config(implementation, &self.container)?;
+        self.container.status_ok_with_output(reload)?;
+
+        implementation.wait_until_ready(
+            &self.container,
+            child,
+            &readiness_config(self.port),
+            implementation.readiness_timeout(),
+        )
+    }
+
+    /// Exit status of the server process if it exited on its own, e.g. because it crashed;
+    /// `None` while it runs
+    pub fn exit_status(&mut self) -> Result<Option<ExitStatus>> {
+        match &mut self.child {
+            Some(child) => child.try_wait(),
+            None => Err("the resolver was stopped".into()),
+        }
+    }
+
+    /// Command that makes the server load its configuration file again
+    fn reload_cmd(&self) -> Result<&'static [&'static str]> {
+        let implementation = &self.implementation;
+        if !implementation.supports_config_reload() {
+            return Err(format!(
+                "the {implementation} resolver cannot load a new configuration without restarting"
+            )
+            .into());
+        }
+
+        Ok(match implementation {
+            Implementation::Bind { .. } => &["rndc", "reconfig"],
+
+            Implementation::Unbound { .. } => &["unbound-control", "reload"],
+
+            Implementation::Dnslib
+            | Implementation::EdeDotCom
+            | Implementation::Hickory { .. }
+            | Implementation::Knot
+            | Implementation::PowerDns => unreachable!(),
+        })
+    }
+
     fn stdout(&self) -> Result<String> {
         self.container
             .stdout(&["cat", &self.implementation.stdout_logfile(Role::Resolver)])
@@ -68,14 +229,40 @@ impl Resolver {
     }
 }
 
+/// Configuration that [`Implementation::wait_until_ready`] probes a resolver with; the probe only
+/// depends on the role and the port
+fn readiness_config(port: u16) -> Config<'static> {
+    ConfigBuilder::resolver().port(port).build()
+}
+
 pub struct ResolverSettings {
     /// Extended DNS Errors (RFC8914)
     ede: bool,
     network: Network,
+    connected_networks: Vec<Network>,
     roots: Vec<Root>,
     trust_anchor: TrustAnchor,
     custom_config: Option<String>,
     case_randomization: bool,
+    verbosity: Verbosity,
+    cache_enabled: bool,
+    max_cache_ttl: Option<u32>,
+    min_cache_ttl: Option<u32>,
+    udp_payload_size: Option<u16>,
+    query_logging: bool,
+    netmasks: Vec<String>,
+    recursion: bool,
+    transports: BTreeSet<Transport>,
+    port: u16,
+    threads: Option<usize>,
+    qname_minimization: Option<QnameMinimization>,
+    serve_stale: Option<ServeStale>,
+    transport_restriction: Option<TransportRestriction>,
+    dns64_prefix: Option<Ipv6Net>,
+    edns_client_subnet: bool,
+    forwarding_views: Vec<(String, Vec<Ipv4Addr>)>,
+    cookies: Option<Cookies>,
+    extra_configs: Vec<ExtraConfig>,
 }
 
 impl ResolverSettings {
@@ -90,9 +277,128 @@ impl ResolverSettings {
     ///
     /// This server is not an authoritative name server; it does not serve a zone file to clients
     pub fn start_with_subject(&self, implementation: &Implementation) -> Result<Resolver> {
+        implementation.check_role(Role::Resolver)?;
+        implementation.check_transports(Role::Resolver, &self.transports)?;
+        self.check(implementation)?;
+
         let image = implementation.clone().into();
         let container = Container::run(&image, &self.network)?;
+        for network in &self.connected_networks {
+            container.connect(network)?;
+        }
+
+        let cmd_args = self.write_config(implementation, &container)?;
 
+        if self.transports.iter().any(Transport::uses_tls) {
+            transport::generate_tls_cert(&container, container.name())?;
+        }
+
+        let mut child = container.spawn(&cmd_args)?;
+        implementation.wait_until_ready(
+            &container,
+            &mut child,
+            &readiness_config(self.port),
+            implementation.readiness_timeout(),
+        )?;
+
+        Ok(Resolver {
+            child: Some(child),
+            container,
+            implementation: implementation.clone(),
+            cmd_args,
+            transports: self.transports.clone(),
+            port: self.port,
+        })
+    }
+
+    /// Fails if `implementation` does not support one of the settings
+    fn check(&self, implementation: &Implementation) -> Result<()> {
+        if let Some(mode) = self.qname_minimization {
+            if !implementation.supports_qname_minimization(mode) {
+                return Err(format!(
+                    "the {implementation} resolver does not support {mode} QNAME minimization"
+                )
+                .into());
+            }
+        }
+        if self.threads.is_some() && !implementation.supports_threads() {
+            return Err(format!(
+                "the {implementation} resolver does not support setting the number of threads"
+            )
+            .into());
+        }
+        if self.serve_stale.is_some() && !implementation.supports_serve_stale() {
+            return Err(format!(
+                "the {implementation} resolver does not support serving stale data"
+            )
+            .into());
+        }
+        if let Some(restriction) = self.transport_restriction {
+            if !implementation.supports_transport_restriction(restriction) {
+                return Err(format!(
+                    "the {implementation} resolver cannot be restricted to {restriction} queries"
+                )
+                .into());
+            }
+        }
+        if let Some(prefix) = self.dns64_prefix {
+            if !implementation.supports_dns64() {
+                return Err(format!("the {implementation} resolver does not support DNS64").into());
+            }
+            if ![32, 40, 48, 56, 64, 96].contains(&prefix.prefix_len()) {
+                return Err(format!(
+                    "{prefix} is not a DNS64 prefix; RFC6052 only allows /32, /40, /48, /56, /64 \
+                     and /96 prefixes"
+                )
+                .into());
+            }
+        }
+        if self.edns_client_subnet && !implementation.supports_edns_client_subnet() {
+            return Err(format!(
+                "the {implementation} resolver does not support EDNS Client Subnet"
+            )
+            .into());
+        }
+        if !self.forwarding_views.is_empty() && !implementation.supports_views() {
+            return Err(format!("the {implementation} resolver does not support views").into());
+        }
+        if self.cookies.is_some() && !implementation.supports_cookies(Role::Resolver) {
+            return Err(format!(
+                "the {implementation} resolver does not support configuring DNS cookies"
+            )
+            .into());
+        }
+        if self.cookies.as_ref().is_some_and(|cookies| cookies.require)
+            && !implementation.supports_required_cookies()
+        {
+            return Err(format!(
+                "the {implementation} resolver does not support requiring server cookies"
+            )
+            .into());
+        }
+
+        if implementation.is_hickory() && !self.trust_anchor.dses().is_empty() {
+            return Err(format!(
+                "the {implementation} resolver does not support trust anchors in DS form"
+            )
+            .into());
+        }
+        if self.query_logging && !implementation.supports_query_logging(Role::Resolver) {
+            return Err(
+                format!("the {implementation} resolver does not support logging queries").into(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Writes the root hints, configuration files and trust anchor to `container`; returns the
+    /// command that starts the server process
+    fn write_config(
+        &self,
+        implementation: &Implementation,
+        container: &Container,
+    ) -> Result<Vec<String>> {
         let mut hints = String::new();
         for root in &self.roots {
             writeln!(hints, "{root}").unwrap();
@@ -101,21 +407,69 @@ impl ResolverSettings {
         container.cp("/etc/root.hints", &hints)?;
 
         let use_dnssec = !self.trust_anchor.is_empty();
+        let netmasks = if self.netmasks.is_empty() {
+            let mut netmasks = vec![self.network.netmask()];
+            netmasks.extend(self.network.ipv6_netmask());
+            netmasks.extend(self.connected_networks.iter().map(Network::netmask));
+            netmasks
+        } else {
+            self.netmasks.iter().map(String::as_str).collect()
+        };
+        // the name servers are on the networks of the resolver
+        let mut name_server_netmasks = vec![];
+        for network in [&self.network].into_iter().chain(&self.connected_networks) {
+            name_server_netmasks.push(network.netmask());
+            name_server_netmasks.extend(network.ipv6_netmask());
+        }
+        // the name servers are on the same network as the resolver
+        let edns_client_subnet = if self.edns_client_subnet {
+            vec![self.network.netmask()]
+        } else {
+            vec![]
+        };
+        let forwarding_views = self
+            .forwarding_views
+            .iter()
+            .map(|(netmask, forwarders)| (netmask.as_str(), forwarders.as_slice()))
+            .collect::<Vec<_>>();
+        let extra_config = ExtraConfig::snippets_for(&self.extra_configs, implementation);
+        let config = Config::Resolver {
+            use_dnssec,
+            netmasks: &netmasks,
+            name_server_netmasks: &name_server_netmasks,
+            recursion: self.recursion,
+            ede: self.ede,
+            case_randomization: self.case_randomization,
+            cache_enabled: self.cache_enabled,
+            max_cache_ttl: self.max_cache_ttl,
+            min_cache_ttl: self.min_cache_ttl,
+            udp_payload_size: self.udp_payload_size,
+            query_logging: self.query_logging,
+            transports: &self.transports,
+            port: self.port,
+            threads: self.threads,
+            qname_minimization: self.qname_minimization,
+            serve_stale: self.serve_stale,
+            transport_restriction: self.transport_restriction,
+            dns64_prefix: self.dns64_prefix,
+            edns_client_subnet: &edns_client_subnet,
+            forwarding_views: &forwarding_views,
+            cookies: self.cookies.as_ref(),
+            extra_config: extra_config.as_deref(),
+        };
         let config_contents = if let Some(custom_config) = &self.custom_config {
             custom_config
         } else {
-            let config = Config::Resolver {
-                use_dnssec,
-                netmask: self.network.netmask(),
-                ede: self.ede,
-                case_randomization: self.case_randomization,
-            };
-            &implementation.format_config(config)
+            &implementation.format_config(config.clone())
         };
         if let Some(conf_file_path) = implementation.conf_file_path(Role::Resolver) {
             container.cp(conf_file_path, config_contents)?;
         }
 
+        for (path, contents) in implementation.extra_config_files(&config) {
+            container.cp(path, &contents)?;
+        }
+
         if use_dnssec {
             let path = if implementation.is_bind() {
                 "/etc/bind/bind.keys"
@@ -132,13 +486,100 @@ impl ResolverSettings {
             container.cp(path, &contents)?;
         }
 
-        let child = container.spawn(&implementation.cmd_args(Role::Resolver))?;
+        Ok(implementation.cmd_args(&config, self.verbosity))
+    }
 
-        Ok(Resolver {
-            _child: child,
-            container,
-            implementation: implementation.clone(),
-        })
+    /// Attaches the resolver to `network` as well as to the network it was created for, e.g. to
+    /// reach name servers that are only in `network`; its clients may be in either
+    pub fn connect(&mut self, network: &Network) -> &mut Self {
+        self.connected_networks.push(network.clone());
+        self
+    }
+
+    /// Makes the resolver listen on `transport` as well, e.g. DNS over TLS; it always listens
+    /// for plain DNS, on port 53 unless changed with [`Self::port`]
+    pub fn transport(&mut self, transport: Transport) -> &mut Self {
+        self.transports.insert(transport);
+        self
+    }
+
+    /// Makes the resolver listen for plain DNS on `port` instead of 53; clients reach it with
+    /// [`DigSettings::port`]
+    ///
+    /// [`DigSettings::port`]: crate::client::DigSettings::port
+    pub fn port(&mut self, port: u16) -> &mut Self {
+        self.port = port;
+        self
+    }
+
+    /// Runs the resolver with `threads` worker threads instead of the implementation's default,
+    /// e.g. a single one to serialize the handling of queries
+    pub fn threads(&mut self, threads: usize) -> &mut Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    /// Sets the QNAME minimization (RFC9156) mode; otherwise the implementation's default is used
+    pub fn qname_minimization(&mut self, mode: QnameMinimization) -> &mut Self {
+        self.qname_minimization = Some(mode);
+        self
+    }
+
+    /// Makes the resolver answer from expired cache entries when the name servers are
+    /// unreachable (RFC8767)
+    pub fn serve_stale(&mut self, serve_stale: ServeStale) -> &mut Self {
+        self.serve_stale = Some(serve_stale);
+        self
+    }
+
+    /// Makes the resolver query name servers over a single protocol; see [`TransportRestriction`]
+    pub fn transport_restriction(&mut self, restriction: TransportRestriction) -> &mut Self {
+        self.transport_restriction = Some(restriction);
+        self
+    }
+
+    /// Makes the resolver synthesize AAAA records under `prefix`, e.g. the well-known prefix
+    /// `64:ff9b::/96`, for names that only have A records (DNS64, RFC6147)
+    pub fn dns64_prefix(&mut self, prefix: Ipv6Net) -> &mut Self {
+        self.dns64_prefix = Some(prefix);
+        self
+    }
+
+    /// Configures the DNS cookies (RFC7873) of the resolver, instead of the implementation's
+    /// defaults
+    pub fn cookies(&mut self, cookies: Cookies) -> &mut Self {
+        self.cookies = Some(cookies);
+        self
+    }
+
+    /// Forwards the EDNS Client Subnet option (RFC7871) of client queries to the name servers;
+    /// otherwise the implementation's default is used, which is to not forward it
+    pub fn edns_client_subnet(&mut self) -> &mut Self {
+        self.edns_client_subnet = true;
+        self
+    }
+
+    /// Forwards the queries of the clients in the `match_clients` network, e.g. `10.0.0.0/8`, to
+    /// `forwarders` instead of resolving them from the root
+    ///
+    /// Views are matched in the order they were added; the queries of clients that match none of
+    /// them are resolved as usual
+    pub fn forwarding_view(&mut self, match_clients: &str, forwarders: &[Ipv4Addr]) -> &mut Self {
+        self.forwarding_views
+            .push((match_clients.to_string(), forwarders.to_vec()));
+        self
+    }
+
+    /// Appends the snippet of `extra_config` verbatim to the main block of the server's
+    /// configuration, for directives that have no setting of their own, if the server runs the
+    /// implementation the snippet is for
+    ///
+    /// Can be called once per implementation, or several times for the same one; the syntax,
+    /// indentation included, is that of the implementation's configuration file, e.g. TOML for
+    /// hickory
+    pub fn extra_config(&mut self, extra_config: ExtraConfig) -> &mut Self {
+        self.extra_configs.push(extra_config);
+        self
     }
 
     /// Enables the Extended DNS Errors (RFC8914) feature
@@ -158,11 +599,20 @@ impl ResolverSettings {
         self
     }
 
-    /// Adds all the keys in the `other` trust anchor to ours
+    /// Adds a DS record to the trust anchor
+    pub fn trust_anchor_ds(&mut self, ds: DS) -> &mut Self {
+        self.trust_anchor.add_ds(ds);
+        self
+    }
+
+    /// Adds all the keys, and DS records, in the `other` trust anchor to ours
     pub fn trust_anchor(&mut self, other: &TrustAnchor) -> &mut Self {
         for key in other.keys() {
             self.trust_anchor.add(key.clone());
         }
+        for ds in other.dses() {
+            self.trust_anchor.add_ds(ds.clone());
+        }
         self
     }
 
@@ -177,25 +627,84 @@ impl ResolverSettings {
         self.case_randomization = true;
         self
     }
+
+    /// Disables caching of the records the resolver fetches.
+    ///
+    /// Not every implementation supports this; starting those panics.
+    pub fn disable_cache(&mut self) -> &mut Self {
+        self.cache_enabled = false;
+        self
+    }
+
+    /// Caps the TTL, in seconds, of the records the resolver caches.
+    pub fn max_cache_ttl(&mut self, ttl: u32) -> &mut Self {
+        self.max_cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Raises the TTL, in seconds, of cached records whose TTL is lower than `ttl`.
+    pub fn min_cache_ttl(&mut self, ttl: u32) -> &mut Self {
+        self.min_cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Sets the EDNS UDP payload size, in bytes, the resolver advertises in its queries and
+    /// uses as the limit for the responses it sends over UDP.
+    pub fn udp_payload_size(&mut self, udp_payload_size: u16) -> &mut Self {
+        self.udp_payload_size = Some(udp_payload_size);
+        self
+    }
+
+    /// Logs every query the resolver receives, e.g. to match what a client sent with what the
+    /// resolver received
+    pub fn query_logging(&mut self) -> &mut Self {
+        self.query_logging = true;
+        self
+    }
+
+    /// Allows the clients in `netmask` to query the resolver
+    ///
+    /// When no netmask is given the resolver serves the whole network. Otherwise it refuses
+    /// queries from clients outside the given netmasks.
+    pub fn allow_netmask(&mut self, netmask: &str) -> &mut Self {
+        self.netmasks.push(netmask.to_string());
+        self
+    }
+
+    /// Stops the resolver from resolving queries on behalf of its clients.
+    ///
+    /// Not every implementation supports this; starting those panics.
+    pub fn recursion_disabled(&mut self) -> &mut Self {
+        self.recursion = false;
+        self
+    }
+
+    /// Overrides how much the resolver logs.
+    pub fn verbosity(&mut self, verbosity: Verbosity) -> &mut Self {
+        self.verbosity = verbosity;
+        self
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::{thread, time::Duration};
 
-    use crate::{FQDN, name_server::NameServer};
+    use crate::FQDN;
+    use crate::client::{Client, DigSettings, DigStatus};
+    use crate::name_server::{Graph, NameServer, Sign};
+    use crate::record::{Record, RecordType};
+    use crate::tshark::Direction;
+    use crate::zone_file::SignSettings;
 
     use super::*;
 
     #[test]
     fn unbound_logs_works() -> Result<()> {
         let network = Network::new()?;
-        let ns = NameServer::new(&Implementation::Unbound, FQDN::ROOT, &network)?.start()?;
-        let resolver =
-            Resolver::new(&network, ns.root_hint()).start_with_subject(&Implementation::Unbound)?;
-        // no way to block until the server has finished starting up so we just give it some
-        // arbitrary amount of time
-        thread::sleep(Duration::from_secs(1));
+        let ns = NameServer::new(&Implementation::unbound(), FQDN::ROOT, &network)?.start()?;
+        let resolver = Resolver::new(&network, ns.root_hint())
+            .start_with_subject(&Implementation::unbound())?;
         let logs = resolver.logs()?;
 
         eprintln!("{logs}");
@@ -204,15 +713,195 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn start_fails_when_the_server_exits() -> Result<()> {
+        let network = Network::new()?;
+        let ns = NameServer::new(&Implementation::unbound(), FQDN::ROOT, &network)?.start()?;
+        let error = Resolver::new(&network, ns.root_hint())
+            .custom_config("not a valid configuration".to_string())
+            .start_with_subject(&Implementation::unbound())
+            .err()
+            .expect("unbound should reject the configuration");
+
+        assert!(
+            error
+                .to_string()
+                .starts_with("the unbound resolver exited before it was ready"),
+            "{error}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn restarts_after_being_stopped() -> Result<()> {
+        let network = Network::new()?;
+        let ns = NameServer::new(&Implementation::unbound(), FQDN::ROOT, &network)?.start()?;
+        let mut resolver = Resolver::new(&network, ns.root_hint())
+            .start_with_subject(&Implementation::unbound())?;
+        assert!(resolver.exit_status()?.is_none());
+
+        let client = Client::new(&network)?;
+        let settings = *DigSettings::default()
+            .recurse()
+            .timeout(Duration::from_secs(1));
+        let resolver_addr = resolver.ipv4_addr();
+        let dig = || client.dig(settings, resolver_addr, RecordType::SOA, &FQDN::ROOT);
+        assert!(dig()?.status.is_noerror());
+
+        resolver.stop(Signal::Term)?;
+        assert!(dig().is_err());
+        resolver.restart()?;
+        assert!(dig()?.status.is_noerror());
+
+        let status = resolver.stop(Signal::Kill)?;
+        assert!(!status.success());
+        resolver.restart()?;
+        assert!(dig()?.status.is_noerror());
+        assert!(resolver.exit_status()?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn bind_reloads_its_configuration() -> Result<()> {
+        reloads_its_configuration(&Implementation::bind())
+    }
+
+    #[test]
+    fn unbound_reloads_its_configuration() -> Result<()> {
+        reloads_its_configuration(&Implementation::unbound())
+    }
+
+    fn reloads_its_configuration(implementation: &Implementation) -> Result<()> {
+        let network = Network::new()?;
+        let ns = NameServer::new(&Implementation::unbound(), FQDN::ROOT, &network)?.start()?;
+        let mut settings = Resolver::new(&network, ns.root_hint());
+        let mut resolver = settings.start_with_subject(implementation)?;
+
+        let client = Client::new(&network)?;
+        let dig_settings = *DigSettings::default().recurse();
+        let resolver_addr = resolver.ipv4_addr();
+        let dig = || client.dig(dig_settings, resolver_addr, RecordType::SOA, &FQDN::ROOT);
+        assert!(dig()?.status.is_noerror());
+
+        resolver.reload(settings.recursion_disabled())?;
+        assert_eq!(DigStatus::REFUSED, dig()?.status);
+        assert!(resolver.exit_status()?.is_none());
+
+        let error = resolver.reload(settings.port(5353)).unwrap_err();
+        assert!(error.to_string().contains("cannot be changed"), "{error}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn hickory_rejects_ds_trust_anchors() -> Result<()> {
+        let network = Network::new()?;
+        let root = Root::new(FQDN("a.root-servers.net.")?, Ipv4Addr::new(192, 0, 2, 1));
+        let ds = ".\t86400\tIN\tDS\t20326 8 2 E06D44B80B8F1D39A95C0B0D7C65D08458E880409BBC683457104237 C7F8EC8D".parse()?;
+
+        let result = Resolver::new(&network, root)
+            .trust_anchor_ds(ds)
+            .start_with_subject(&Implementation::hickory());
+        let error = result.err().expect("start should have failed");
+        assert!(
+            error
+                .to_string()
+                .ends_with("resolver does not support trust anchors in DS form"),
+            "{error}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn hickory_cannot_reload_its_configuration() -> Result<()> {
+        let network = Network::new()?;
+        let ns = NameServer::new(&Implementation::unbound(), FQDN::ROOT, &network)?.start()?;
+        let mut settings = Resolver::new(&network, ns.root_hint());
+        let mut resolver = settings.start_with_subject(&Implementation::hickory())?;
+
+        let error = resolver.reload(settings.recursion_disabled()).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .ends_with("cannot load a new configuration without restarting"),
+            "{error}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn extra_config_lands_in_the_configuration_file() -> Result<()> {
+        let network = Network::new()?;
+        let ns = NameServer::new(&Implementation::unbound(), FQDN::ROOT, &network)?.start()?;
+        let resolver = Resolver::new(&network, ns.root_hint())
+            .extra_config(ExtraConfig::for_implementation(
+                &Implementation::unbound(),
+                "    serve-expired: yes",
+            ))
+            .extra_config(ExtraConfig::for_implementation(
+                &Implementation::bind(),
+                "    stale-answer-enable yes;",
+            ))
+            .start_with_subject(&Implementation::unbound())?;
+
+        let conf_file_path = Implementation::unbound()
+            .conf_file_path(Role::Resolver)
+            .unwrap();
+        let conf = resolver.container.stdout(&["cat", conf_file_path])?;
+        let (server, _remote_control) = conf.split_once("remote-control:").unwrap();
+        assert!(server.contains("\n    serve-expired: yes\n"), "{conf}");
+        assert!(!conf.contains("stale-answer-enable"), "{conf}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn unbound_quiet_verbosity() -> Result<()> {
+        let network = Network::new()?;
+        let ns = NameServer::new(&Implementation::unbound(), FQDN::ROOT, &network)?.start()?;
+        let resolver = Resolver::new(&network, ns.root_hint())
+            .verbosity(Verbosity::Quiet)
+            .start_with_subject(&Implementation::unbound())?;
+        let logs = resolver.logs()?;
+
+        eprintln!("{logs}");
+        // logged at verbosity level 1
+        assert!(!logs.contains("start of service"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn unbound_query_logging() -> Result<()> {
+        let network = Network::new()?;
+        let ns = NameServer::new(&Implementation::unbound(), FQDN::ROOT, &network)?.start()?;
+        let resolver = Resolver::new(&network, ns.root_hint())
+            .query_logging()
+            .start_with_subject(&Implementation::unbound())?;
+        let client = Client::new(&network)?;
+
+        let settings = *DigSettings::default().recurse();
+        client.dig(settings, resolver.ipv4_addr(), RecordType::SOA, &FQDN::ROOT)?;
+        let logs = resolver.logs()?;
+
+        eprintln!("{logs}");
+        assert!(logs.lines().any(
+            |line| line.contains(&client.ipv4_addr().to_string()) && line.contains(". SOA IN")
+        ));
+
+        Ok(())
+    }
+
     #[test]
     fn bind_logs_works() -> Result<()> {
         let network = Network::new()?;
-        let ns = NameServer::new(&Implementation::Unbound, FQDN::ROOT, &network)?.start()?;
+        let ns = NameServer::new(&Implementation::unbound(), FQDN::ROOT, &network)?.start()?;
         let resolver =
-            Resolver::new(&network, ns.root_hint()).start_with_subject(&Implementation::Bind)?;
-        // no way to block until the server has finished starting up so we just give it some
-        // arbitrary amount of time
-        thread::sleep(Duration::from_secs(1));
+            Resolver::new(&network, ns.root_hint()).start_with_subject(&Implementation::bind())?;
         let logs = resolver.logs()?;
 
         eprintln!("{logs}");
@@ -224,12 +913,9 @@ mod tests {
     #[test]
     fn hickory_logs_works() -> Result<()> {
         let network = Network::new()?;
-        let ns = NameServer::new(&Implementation::Unbound, FQDN::ROOT, &network)?.start()?;
+        let ns = NameServer::new(&Implementation::unbound(), FQDN::ROOT, &network)?.start()?;
         let resolver = Resolver::new(&network, ns.root_hint())
             .start_with_subject(&Implementation::hickory())?;
-        // no way to block until the server has finished starting up so we just give it some
-        // arbitrary amount of time
-        thread::sleep(Duration::from_secs(1));
         let logs = resolver.logs()?;
 
         eprintln!("{logs}");
@@ -243,4 +929,360 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn hickory_resolves_with_one_thread() -> Result<()> {
+        let network = Network::new()?;
+        let ns = NameServer::new(&Implementation::unbound(), FQDN::ROOT, &network)?.start()?;
+        let resolver = Resolver::new(&network, ns.root_hint())
+            .threads(1)
+            .start_with_subject(&Implementation::hickory())?;
+
+        let client = Client::new(&network)?;
+        let settings = *DigSettings::default().recurse();
+        let output = client.dig(settings, resolver.ipv4_addr(), RecordType::SOA, &FQDN::ROOT)?;
+        assert!(output.status.is_noerror());
+
+        // only hickory can have its number of threads set
+        let error = Resolver::new(&network, ns.root_hint())
+            .threads(1)
+            .start_with_subject(&Implementation::unbound())
+            .err()
+            .expect("unbound must be rejected");
+        assert!(error.to_string().contains("number of threads"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn max_cache_ttl_expires_cached_records() -> Result<()> {
+        let network = Network::new()?;
+        let needle_fqdn = FQDN::EXAMPLE_SUBDOMAIN;
+
+        let mut leaf_ns = NameServer::new(&Implementation::unbound(), FQDN::TEST_DOMAIN, &network)?;
+        leaf_ns.add(Record::a(needle_fqdn.clone(), Ipv4Addr::new(1, 2, 3, 4)));
+
+        let Graph {
+            nameservers, root, ..
+        } = Graph::build(leaf_ns, Sign::No)?;
+
+        let resolver = Resolver::new(&network, root)
+            .max_cache_ttl(1)
+            .start_with_subject(&Implementation::unbound())?;
+
+        let client = Client::new(&network)?;
+        let settings = *DigSettings::default().recurse();
+        let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+        assert!(output.status.is_noerror());
+
+        let mut tshark = resolver.eavesdrop()?;
+        thread::sleep(Duration::from_secs(2));
+        let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+        assert!(output.status.is_noerror());
+
+        tshark.wait_for_capture()?;
+        let captures = tshark.terminate()?;
+
+        // the cached answer has expired so the name servers are queried again
+        let queried_name_servers = captures.iter().any(|capture| match capture.direction {
+            Direction::Outgoing { destination } => {
+                nameservers.iter().any(|ns| ns.ipv4_addr() == destination)
+            }
+            _ => false,
+        });
+        assert!(queried_name_servers);
+
+        Ok(())
+    }
+
+    #[test]
+    fn udp_payload_size_is_advertised_in_queries() -> Result<()> {
+        let network = Network::new()?;
+        let leaf_ns = NameServer::new(&Implementation::unbound(), FQDN::TEST_DOMAIN, &network)?;
+        let Graph {
+            nameservers, root, ..
+        } = Graph::build(leaf_ns, Sign::No)?;
+
+        let resolver = Resolver::new(&network, root)
+            .udp_payload_size(512)
+            .start_with_subject(&Implementation::unbound())?;
+        let mut tshark = resolver.eavesdrop()?;
+
+        let client = Client::new(&network)?;
+        let settings = *DigSettings::default().recurse();
+        let output = client.dig(
+            settings,
+            resolver.ipv4_addr(),
+            RecordType::SOA,
+            &FQDN::TEST_DOMAIN,
+        )?;
+        assert!(output.status.is_noerror());
+
+        tshark.wait_for_capture()?;
+        let captures = tshark.terminate()?;
+
+        let mut outgoing_queries = 0;
+        for capture in &captures {
+            let Direction::Outgoing { destination } = capture.direction else {
+                continue;
+            };
+
+            if nameservers.iter().any(|ns| ns.ipv4_addr() == destination) {
+                outgoing_queries += 1;
+                assert_eq!(Some(512), capture.message.udp_payload_size());
+            }
+        }
+        assert_ne!(0, outgoing_queries);
+
+        Ok(())
+    }
+
+    #[test]
+    fn allow_netmask_refuses_other_clients() -> Result<()> {
+        refuses_clients_outside_netmask(&Implementation::unbound())
+    }
+
+    #[test]
+    fn hickory_allow_netmask_refuses_other_clients() -> Result<()> {
+        refuses_clients_outside_netmask(&Implementation::hickory())
+    }
+
+    fn refuses_clients_outside_netmask(implementation: &Implementation) -> Result<()> {
+        let network = Network::new()?;
+        let ns = NameServer::new(&Implementation::unbound(), FQDN::ROOT, &network)?.start()?;
+
+        let allowed_client = Client::new(&network)?;
+        let refused_client = Client::new(&network)?;
+        let resolver = Resolver::new(&network, ns.root_hint())
+            .allow_netmask(&format!("{}/32", allowed_client.ipv4_addr()))
+            .allow_netmask("192.0.2.0/24")
+            .start_with_subject(implementation)?;
+
+        let settings = *DigSettings::default().recurse();
+        let output =
+            allowed_client.dig(settings, resolver.ipv4_addr(), RecordType::SOA, &FQDN::ROOT)?;
+        assert!(output.status.is_noerror());
+
+        let settings = *DigSettings::default()
+            .recurse()
+            .timeout(Duration::from_secs(2))
+            .tries(1);
+        let outcome = refused_client.dig_outcome(
+            settings,
+            resolver.ipv4_addr(),
+            RecordType::SOA,
+            &FQDN::ROOT,
+        )?;
+        // hickory drops the queries of clients outside its `allow_networks` instead of refusing them
+        if implementation.is_hickory() {
+            assert!(outcome.is_timeout());
+        } else {
+            assert_eq!(DigStatus::REFUSED, outcome.answer()?.status);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn bind_recursion_disabled() -> Result<()> {
+        refuses_queries_with_recursion_disabled(&Implementation::bind())
+    }
+
+    #[test]
+    fn unbound_recursion_disabled() -> Result<()> {
+        refuses_queries_with_recursion_disabled(&Implementation::unbound())
+    }
+
+    fn refuses_queries_with_recursion_disabled(implementation: &Implementation) -> Result<()> {
+        let network = Network::new()?;
+        let mut root_ns = NameServer::new(&Implementation::unbound(), FQDN::ROOT, &network)?;
+        root_ns.add(Record::a(
+            FQDN::EXAMPLE_SUBDOMAIN,
+            Ipv4Addr::new(1, 2, 3, 4),
+        ));
+        let root_ns = root_ns.start()?;
+
+        let resolver = Resolver::new(&network, root_ns.root_hint())
+            .recursion_disabled()
+            .start_with_subject(implementation)?;
+
+        let client = Client::new(&network)?;
+        let settings = *DigSettings::default().recurse();
+        let output = client.dig(
+            settings,
+            resolver.ipv4_addr(),
+            RecordType::A,
+            &FQDN::EXAMPLE_SUBDOMAIN,
+        )?;
+
+        assert_eq!(DigStatus::REFUSED, output.status);
+        assert!(!output.flags.recursion_available);
+
+        Ok(())
+    }
+
+    #[test]
+    fn answers_ipv6_clients() -> Result<()> {
+        answers_clients_in_ipv6_netmask(&Implementation::unbound())
+    }
+
+    #[test]
+    fn hickory_answers_ipv6_clients() -> Result<()> {
+        answers_clients_in_ipv6_netmask(&Implementation::hickory())
+    }
+
+    fn answers_clients_in_ipv6_netmask(implementation: &Implementation) -> Result<()> {
+        let network = Network::with_ipv6()?;
+        let ipv6_netmask = network
+            .ipv6_netmask()
+            .expect("network has IPv6 enabled")
+            .to_string();
+        let ns = NameServer::new(&Implementation::unbound(), FQDN::ROOT, &network)?.start()?;
+
+        let resolver = Resolver::new(&network, ns.root_hint())
+            .allow_netmask(&ipv6_netmask)
+            .start_with_subject(implementation)?;
+
+        let client = Client::new(&network)?;
+        let settings = *DigSettings::default().recurse();
+        let output = client.dig(
+            settings,
+            resolver.ipv6_addr().expect("resolver has an IPv6 address"),
+            RecordType::SOA,
+            &FQDN::ROOT,
+        )?;
+        assert!(output.status.is_noerror());
+
+        Ok(())
+    }
+
+    #[test]
+    fn knot_validates_signed_a_record() -> Result<()> {
+        validates_signed_a_record(
+            &Implementation::Knot,
+            &Implementation::Knot,
+            SignSettings::default(),
+        )
+    }
+
+    #[test]
+    fn powerdns_validates_signed_a_record() -> Result<()> {
+        validates_signed_a_record(
+            &Implementation::unbound(),
+            &Implementation::PowerDns,
+            SignSettings::default(),
+        )
+    }
+
+    #[test]
+    fn unbound_validates_ed25519_signed_a_record() -> Result<()> {
+        validates_signed_a_record(
+            &Implementation::bind(),
+            &Implementation::unbound(),
+            SignSettings::ed25519(),
+        )
+    }
+
+    fn validates_signed_a_record(
+        name_server: &Implementation,
+        resolver: &Implementation,
+        settings: SignSettings,
+    ) -> Result<()> {
+        let network = Network::new()?;
+        let needle_fqdn = FQDN::EXAMPLE_SUBDOMAIN;
+        let expected_ipv4_addr = Ipv4Addr::new(1, 2, 3, 4);
+
+        let mut leaf_ns = NameServer::new(name_server, FQDN::TEST_DOMAIN, &network)?;
+        leaf_ns.add(Record::a(needle_fqdn.clone(), expected_ipv4_addr));
+
+        let Graph {
+            nameservers: _nameservers,
+            root,
+            trust_anchor,
+        } = Graph::build(leaf_ns, Sign::Yes { settings })?;
+
+        let resolver = Resolver::new(&network, root)
+            .trust_anchor(&trust_anchor.unwrap())
+            .start_with_subject(resolver)?;
+
+        let client = Client::new(&network)?;
+        let settings = *DigSettings::default().recurse().authentic_data();
+        let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &needle_fqdn)?;
+
+        assert!(output.status.is_noerror());
+        assert!(output.flags.authenticated_data);
+
+        let [a] = output.answer.try_into().unwrap();
+        let a = a.try_into_a().unwrap();
+        assert_eq!(expected_ipv4_addr, a.ipv4_addr);
+
+        Ok(())
+    }
+
+    #[test]
+    fn listens_on_other_port() -> Result<()> {
+        let network = Network::new()?;
+        let ns = NameServer::new(&Implementation::unbound(), FQDN::ROOT, &network)?.start()?;
+        let resolver = Resolver::new(&network, ns.root_hint())
+            .port(5353)
+            .start_with_subject(&Implementation::unbound())?;
+        let client = Client::new(&network)?;
+
+        let settings = *DigSettings::default().recurse().port(5353);
+        let output = client.dig(settings, resolver.ipv4_addr(), RecordType::SOA, &FQDN::ROOT)?;
+        assert!(output.status.is_noerror());
+
+        let settings = *DigSettings::default()
+            .recurse()
+            .timeout(Duration::from_secs(1));
+        let output = client.dig(settings, resolver.ipv4_addr(), RecordType::SOA, &FQDN::ROOT);
+        assert!(output.is_err(), "{output:?}");
+
+        Ok(())
+    }
+    #[test]
+    fn reaches_name_servers_only_through_connected_networks() -> Result<()> {
+        let network_a = Network::with_label("a")?;
+        let network_b = Network::with_label("b")?;
+        let ns = NameServer::new(&Implementation::unbound(), FQDN::ROOT, &network_b)?.start()?;
+
+        // nothing in network A can reach the name server
+        let client = Client::new(&network_a)?;
+        let settings = *DigSettings::default().timeout(Duration::from_secs(1));
+        let output = client.dig(settings, ns.ipv4_addr(), RecordType::SOA, &FQDN::ROOT);
+        assert!(output.is_err(), "{output:?}");
+
+        // so the queries of a resolver that is only in network A time out
+        let isolated = Resolver::new(&network_a, ns.root_hint())
+            .start_with_subject(&Implementation::unbound())?;
+        let settings = *DigSettings::default().recurse();
+        let output = client.dig(settings, isolated.ipv4_addr(), RecordType::SOA, &FQDN::ROOT);
+        assert!(
+            output
+                .as_ref()
+                .map_or(true, |output| output.status.is_servfail()),
+            "{output:?}"
+        );
+
+        // once attached to network B as well, it resolves for the clients in network A
+        let connected = Resolver::new(&network_a, ns.root_hint())
+            .connect(&network_b)
+            .start_with_subject(&Implementation::unbound())?;
+        assert_eq!(
+            Some(connected.ipv4_addr()),
+            connected.ipv4_addr_in(&network_a)
+        );
+        assert!(connected.ipv4_addr_in(&network_b).is_some());
+
+        let settings = *DigSettings::default().recurse();
+        let output = client.dig(
+            settings,
+            connected.ipv4_addr(),
+            RecordType::SOA,
+            &FQDN::ROOT,
+        )?;
+        assert!(output.status.is_noerror());
+
+        Ok(())
+    }
 }
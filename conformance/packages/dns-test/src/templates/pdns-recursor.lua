@@ -0,0 +1,3 @@
+-- replace the built-in root trust anchor with the one provided by the test
+clearTA(".")
+readTrustAnchorsFromFile("/etc/trusted-key.key", 0)
@@ -0,0 +1,252 @@
+//! One-call set up of a root -> TLD -> domain hierarchy of name servers
+//!
+//! [`Topology`] wraps [`Graph::build`] and the resolver settings that go with it, so that a test
+//! only spells out the records of its leaf zone and, if it is about them, which delegations are
+//! not secure.
+
+use std::collections::HashMap;
+
+use crate::name_server::{Graph, NameServer, Running, Sign};
+use crate::record::DS;
+use crate::resolver::ResolverSettings;
+use crate::zone_file::{Root, SignSettings, ZoneFile};
+use crate::{FQDN, Implementation, Network, Resolver, Result, TrustAnchor};
+
+/// How a parent zone of a signed topology vouches for a child zone
+pub(crate) enum Delegation<'a> {
+    /// The parent zone has no DS record for the child zone, which is signed nonetheless
+    Insecure,
+    /// The parent zone has the DS record of the child zone, as modified by the closure
+    Broken(Box<dyn FnOnce(&mut DS) + 'a>),
+}
+
+/// Adds records to the leaf zone
+type Populate<'a> = Box<dyn FnOnce(&mut ZoneFile) + 'a>;
+
+/// Builder of a hierarchy of name servers, from the root zone down to a leaf zone
+///
+/// The hierarchy is the one of [`Graph::build`]: the leaf zone, the zone of the name servers'
+/// own names, `FQDN::TEST_DOMAIN`, and all their parent zones. All the name servers run the same
+/// implementation
+pub struct Topology<'a> {
+    implementation: Implementation,
+    network: Network,
+    sign: Option<SignSettings>,
+    leaf_zone: FQDN,
+    populate: Option<Populate<'a>>,
+    delegations: HashMap<FQDN, Delegation<'a>>,
+}
+
+impl<'a> Topology<'a> {
+    /// A topology whose zones are all signed, with the default [`SignSettings`], and whose
+    /// delegations are all secure
+    pub fn signed(implementation: &Implementation, network: &Network) -> Self {
+        Self::new(implementation, network, Some(SignSettings::default()))
+    }
+
+    /// A topology whose zones are not signed
+    pub fn unsigned(implementation: &Implementation, network: &Network) -> Self {
+        Self::new(implementation, network, None)
+    }
+
+    fn new(implementation: &Implementation, network: &Network, sign: Option<SignSettings>) -> Self {
+        Self {
+            implementation: implementation.clone(),
+            network: network.clone(),
+            sign,
+            leaf_zone: FQDN::TEST_DOMAIN,
+            populate: None,
+            delegations: HashMap::new(),
+        }
+    }
+
+    /// Signs the zones with `settings` instead of the default ones
+    ///
+    /// # Panics
+    ///
+    /// If the topology is unsigned
+    pub fn sign_settings(mut self, settings: SignSettings) -> Self {
+        assert!(
+            self.sign.is_some(),
+            "an unsigned topology has no sign settings"
+        );
+        self.sign = Some(settings);
+        self
+    }
+
+    /// Makes `zone` the leaf zone, instead of `FQDN::TEST_DOMAIN`, and lets `populate` add
+    /// records to it
+    pub fn with_leaf_zone(mut self, zone: FQDN, populate: impl FnOnce(&mut ZoneFile) + 'a) -> Self {
+        self.leaf_zone = zone;
+        self.populate = Some(Box::new(populate));
+        self
+    }
+
+    /// Leaves the DS record of `zone` out of its parent zone, which makes the delegation to it
+    /// insecure; `zone` is still signed, as an island of security
+    pub fn insecure_delegation(mut self, zone: FQDN) -> Self {
+        self.delegations.insert(zone, Delegation::Insecure);
+        self
+    }
+
+    /// Passes the DS record of `zone` through `mutate` before it is added to the parent zone,
+    /// e.g. to make it not match the keys of `zone`
+    pub fn broken_delegation(mut self, zone: FQDN, mutate: impl FnOnce(&mut DS) + 'a) -> Self {
+        self.delegations
+            .insert(zone, Delegation::Broken(Box::new(mutate)));
+        self
+    }
+
+    /// Signs, if needed, and starts all the name servers of the topology
+    pub fn build(self) -> Result<Deployment> {
+        let Self {
+            implementation,
+            network,
+            sign,
+            leaf_zone,
+            populate,
+            delegations,
+        } = self;
+
+        if sign.is_none() && !delegations.is_empty() {
+            return Err("the delegations of an unsigned topology are all insecure".into());
+        }
+        if delegations.contains_key(&FQDN::ROOT) {
+            return Err("the root zone has no delegation".into());
+        }
+        if let Some(zone) = delegations
+            .keys()
+            .find(|zone| !leaf_zone.is_in_zone(zone) && !FQDN::TEST_DOMAIN.is_in_zone(zone))
+        {
+            return Err(format!("{zone} is not a zone of the topology").into());
+        }
+
+        let mut leaf = NameServer::new(&implementation, leaf_zone.clone(), &network)?;
+        if let Some(populate) = populate {
+            populate(leaf.zone_file_mut());
+        }
+
+        let sign = match sign {
+            Some(settings) => Sign::Yes { settings },
+            None => Sign::No,
+        };
+        let Graph {
+            nameservers,
+            root,
+            trust_anchor,
+        } = Graph::build_with_delegations(leaf, sign, delegations)?;
+
+        Ok(Deployment {
+            nameservers,
+            root,
+            trust_anchor,
+            leaf_zone,
+            network,
+        })
+    }
+}
+
+/// The running name servers of a [`Topology`]
+pub struct Deployment {
+    /// Sorted from leaf zone to root zone
+    pub nameservers: Vec<NameServer<Running>>,
+    pub root: Root,
+    /// Keys of the root zone; `None` when the topology is unsigned
+    pub trust_anchor: Option<TrustAnchor>,
+    leaf_zone: FQDN,
+    network: Network,
+}
+
+impl Deployment {
+    /// The name server of the leaf zone
+    pub fn leaf(&self) -> &NameServer<Running> {
+        self.nameserver(&self.leaf_zone)
+            .expect("the leaf zone is part of the topology")
+    }
+
+    /// Zone file of the leaf zone, BEFORE signing
+    pub fn leaf_zone_file(&self) -> &ZoneFile {
+        self.leaf().zone_file()
+    }
+
+    /// The name server of `zone`, if the topology has one
+    pub fn nameserver(&self, zone: &FQDN) -> Option<&NameServer<Running>> {
+        self.nameservers
+            .iter()
+            .find(|nameserver| nameserver.zone() == zone)
+    }
+
+    /// Settings of a resolver that starts from the root zone of the topology and, when it is
+    /// signed, validates with its trust anchor
+    pub fn resolver(&self) -> ResolverSettings {
+        let mut settings = Resolver::new(&self.network, self.root.clone());
+        if let Some(trust_anchor) = &self.trust_anchor {
+            settings.trust_anchor(trust_anchor);
+        }
+        settings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::record::Record;
+
+    use super::*;
+
+    fn ds_records(deployment: &Deployment, zone: &FQDN) -> Vec<DS> {
+        deployment
+            .nameserver(zone)
+            .unwrap()
+            .zone_file()
+            .records
+            .iter()
+            .filter_map(|record| match record {
+                Record::DS(ds) => Some(ds.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn delegations() -> Result<()> {
+        let network = Network::new()?;
+        let insecure_zone = FQDN::TEST_TLD.push_label("insecure");
+        let broken_zone = FQDN::TEST_DOMAIN.push_label("broken");
+
+        let deployment = Topology::signed(&Implementation::bind(), &network)
+            .with_leaf_zone(broken_zone.clone(), |_| {})
+            .insecure_delegation(FQDN::TEST_DOMAIN)
+            .broken_delegation(broken_zone.clone(), |ds| ds.key_tag = !ds.key_tag)
+            .build()?;
+
+        assert_eq!(4, deployment.nameservers.len());
+        assert_eq!(&broken_zone, deployment.leaf().zone());
+        assert!(deployment.trust_anchor.is_some());
+
+        // `testing.` has no DS for `hickory-dns.testing.` but the root still has one for it
+        let [ds] = ds_records(&deployment, &FQDN::ROOT).try_into().unwrap();
+        assert_eq!(FQDN::TEST_TLD, ds.zone);
+        assert!(ds_records(&deployment, &FQDN::TEST_TLD).is_empty());
+
+        let [ds] = ds_records(&deployment, &FQDN::TEST_DOMAIN)
+            .try_into()
+            .unwrap();
+        assert_eq!(broken_zone, ds.zone);
+
+        let error = Topology::unsigned(&Implementation::bind(), &network)
+            .insecure_delegation(FQDN::TEST_TLD)
+            .build()
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("unsigned"), "{error}");
+
+        let error = Topology::signed(&Implementation::bind(), &network)
+            .insecure_delegation(insecure_zone)
+            .build()
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("not a zone"), "{error}");
+
+        Ok(())
+    }
+}
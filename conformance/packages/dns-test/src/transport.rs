@@ -0,0 +1,89 @@
+//! Transports, in addition to plain DNS over port 53, that servers can listen on
+
+use core::fmt;
+
+use crate::Result;
+use crate::container::Container;
+
+/// Path, in the server's container, of the certificate that DNS-over-TLS and DNS-over-HTTPS
+/// listeners present
+pub(crate) const TLS_CERT_PATH: &str = "/etc/tls/cert.pem";
+/// Path, in the server's container, of the private key of [`TLS_CERT_PATH`]
+pub(crate) const TLS_KEY_PATH: &str = "/etc/tls/key.pem";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Transport {
+    /// DNS over UDP and TCP, port 53; servers always listen on it
+    #[default]
+    Do53,
+    /// DNS over TLS (RFC7858), port 853
+    DoT,
+    /// DNS over HTTPS (RFC8484), port 443, at the `/dns-query` path
+    DoH,
+}
+
+impl Transport {
+    pub const ALL: [Self; 3] = [Self::Do53, Self::DoT, Self::DoH];
+
+    /// The port the server listens on
+    pub fn port(&self) -> u16 {
+        match self {
+            Self::Do53 => 53,
+            Self::DoT => 853,
+            Self::DoH => 443,
+        }
+    }
+
+    /// Whether the server needs a certificate to listen on this transport
+    pub fn uses_tls(&self) -> bool {
+        match self {
+            Self::Do53 => false,
+            Self::DoT | Self::DoH => true,
+        }
+    }
+}
+
+impl fmt::Display for Transport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Do53 => "Do53",
+            Self::DoT => "DoT",
+            Self::DoH => "DoH",
+        })
+    }
+}
+
+/// Reads the certificate generated by [`generate_tls_cert`]; being self-signed, it is also the CA
+/// certificate that clients verify the server against
+pub(crate) fn read_tls_cert(container: &Container) -> Result<String> {
+    container.stdout(&["cat", TLS_CERT_PATH])
+}
+
+/// Generates a self-signed certificate, for `name` and the container's IP address, at
+/// [`TLS_CERT_PATH`] and its private key at [`TLS_KEY_PATH`]
+pub(crate) fn generate_tls_cert(container: &Container, name: &str) -> Result<()> {
+    let ipv4_addr = container.ipv4_addr();
+    container.status_ok(&["mkdir", "-p", "/etc/tls"])?;
+    container.status_ok(&[
+        "openssl",
+        "req",
+        "-x509",
+        "-newkey",
+        "ec",
+        "-pkeyopt",
+        "ec_paramgen_curve:prime256v1",
+        "-nodes",
+        "-days",
+        "1",
+        "-subj",
+        &format!("/CN={name}"),
+        "-addext",
+        &format!("subjectAltName=DNS:{name},IP:{ipv4_addr}"),
+        "-keyout",
+        TLS_KEY_PATH,
+        "-out",
+        TLS_CERT_PATH,
+    ])?;
+
+    Ok(())
+}
@@ -2,16 +2,20 @@ use core::fmt;
 
 use crate::{
     DEFAULT_TTL, FQDN,
-    record::{DNSKEY, DNSKEYRData},
+    record::{DNSKEY, DNSKEYRData, DS},
 };
 
 pub struct TrustAnchor {
     keys: Vec<DNSKEY>,
+    dses: Vec<DS>,
 }
 
 impl TrustAnchor {
     pub fn empty() -> Self {
-        Self { keys: Vec::new() }
+        Self {
+            keys: Vec::new(),
+            dses: Vec::new(),
+        }
     }
 
     pub fn public_dns() -> Self {
@@ -40,7 +44,7 @@ impl TrustAnchor {
     }
 
     pub fn is_empty(&self) -> bool {
-        self.keys.is_empty()
+        self.keys.is_empty() && self.dses.is_empty()
     }
 
     pub fn add(&mut self, key: DNSKEY) -> &mut Self {
@@ -48,10 +52,20 @@ impl TrustAnchor {
         self
     }
 
+    /// Adds a trust anchor in DS form; the key it digests must be in the zone's DNSKEY RRset
+    pub fn add_ds(&mut self, ds: DS) -> &mut Self {
+        self.dses.push(ds);
+        self
+    }
+
     pub(crate) fn keys(&self) -> &[DNSKEY] {
         &self.keys
     }
 
+    pub(crate) fn dses(&self) -> &[DS] {
+        &self.dses
+    }
+
     /// formats the `TrustAnchor` in the format `delv` expects
     pub(super) fn delv(&self) -> String {
         let mut buf = "trust-anchors {".to_string();
@@ -60,6 +74,10 @@ impl TrustAnchor {
             buf.push_str(&key.delv());
         }
 
+        for ds in &self.dses {
+            buf.push_str(&ds.delv());
+        }
+
         buf.push_str("};");
         buf
     }
@@ -70,6 +88,9 @@ impl fmt::Display for TrustAnchor {
         for key in &self.keys {
             writeln!(f, "{key}")?;
         }
+        for ds in &self.dses {
+            writeln!(f, "{ds}")?;
+        }
         Ok(())
     }
 }
@@ -78,6 +99,7 @@ impl FromIterator<DNSKEY> for TrustAnchor {
     fn from_iter<T: IntoIterator<Item = DNSKEY>>(iter: T) -> Self {
         Self {
             keys: iter.into_iter().collect(),
+            dses: Vec::new(),
         }
     }
 }
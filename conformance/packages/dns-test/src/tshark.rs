@@ -2,7 +2,7 @@
 
 use core::result::Result as CoreResult;
 use std::io::{self, BufRead, BufReader, Lines, Read};
-use std::net::Ipv4Addr;
+use std::net::{IpAddr, Ipv4Addr};
 use std::process::ChildStderr;
 use std::sync::atomic::{self, AtomicUsize};
 use std::thread::{self, JoinHandle};
@@ -11,6 +11,7 @@ use serde::Deserialize;
 use serde_with::{DisplayFromStr, serde_as};
 
 use crate::Result;
+use crate::client::ClientSubnet;
 use crate::container::{Child, Container};
 
 static ID: AtomicUsize = AtomicUsize::new(0);
@@ -203,6 +204,26 @@ impl Message {
         &self.inner
     }
 
+    /// Returns the EDNS Client Subnet option (RFC7871) of the OPT pseudo-RR
+    ///
+    /// Returns `None` if there's no OPT record, or it has no such option
+    pub fn client_subnet(&self) -> Option<ClientSubnet> {
+        let option = find_opt_option(self.opt_record()?, "8")?;
+        let field = |name: &str| option.get(name)?.as_str();
+
+        let address = match field("dns.opt.client.family")? {
+            "1" => IpAddr::V4(field("dns.opt.client.addr4")?.parse().ok()?),
+            "2" => IpAddr::V6(field("dns.opt.client.addr6")?.parse().ok()?),
+            _ => return None,
+        };
+
+        Some(ClientSubnet {
+            address,
+            source_prefix_length: field("dns.opt.client.netmask")?.parse().ok()?,
+            scope_prefix_length: field("dns.opt.client.scope")?.parse().ok()?,
+        })
+    }
+
     pub fn is_ad_flag_set(&self) -> bool {
         let Some(authenticated) = self.inner["dns.flags_tree"]
             .as_object()
@@ -227,6 +248,23 @@ impl Message {
         None
     }
 
+    pub fn is_tc_flag_set(&self) -> bool {
+        let Some(truncated) = self.inner["dns.flags_tree"]
+            .as_object()
+            .unwrap()
+            .get("dns.flags.truncated")
+        else {
+            return false;
+        };
+
+        let truncated = truncated.as_str().unwrap();
+        match truncated {
+            "1" => true,
+            "0" => false,
+            _ => panic!("unexpected value for dns.flags.truncated: {truncated}"),
+        }
+    }
+
     pub fn is_rd_flag_set(&self) -> bool {
         let Some(recursion_desired) = self.inner["dns.flags_tree"]
             .as_object()
@@ -245,6 +283,18 @@ impl Message {
     }
 }
 
+/// Finds, at any depth of the `OPT` pseudo-RR, the EDNS option with the given `code`
+fn find_opt_option<'a>(value: &'a serde_json::Value, code: &str) -> Option<&'a serde_json::Value> {
+    let object = value.as_object()?;
+    if object.get("dns.opt.code").and_then(|value| value.as_str()) == Some(code) {
+        return Some(value);
+    }
+
+    object
+        .values()
+        .find_map(|value| find_opt_option(value, code))
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum Direction {
     Incoming { source: Ipv4Addr },
@@ -317,7 +367,7 @@ mod tests {
     #[test]
     fn nameserver() -> Result<()> {
         let network = &Network::new()?;
-        let ns = NameServer::new(&Implementation::Unbound, FQDN::ROOT, network)?.start()?;
+        let ns = NameServer::new(&Implementation::unbound(), FQDN::ROOT, network)?.start()?;
         let mut tshark = ns.eavesdrop()?;
 
         let client = Client::new(network)?;
@@ -351,11 +401,11 @@ mod tests {
     #[test]
     fn resolver() -> Result<()> {
         let network = &Network::new()?;
-        let mut root_ns = NameServer::new(&Implementation::Unbound, FQDN::ROOT, network)?;
-        let mut com_ns = NameServer::new(&Implementation::Unbound, FQDN::TEST_TLD, network)?;
+        let mut root_ns = NameServer::new(&Implementation::unbound(), FQDN::ROOT, network)?;
+        let mut com_ns = NameServer::new(&Implementation::unbound(), FQDN::TEST_TLD, network)?;
 
         let mut nameservers_ns =
-            NameServer::new(&Implementation::Unbound, FQDN::TEST_DOMAIN, network)?;
+            NameServer::new(&Implementation::unbound(), FQDN::TEST_DOMAIN, network)?;
         nameservers_ns.add(root_ns.a()).add(com_ns.a());
         let nameservers_ns = nameservers_ns.start()?;
 
@@ -366,7 +416,7 @@ mod tests {
         let root_ns = root_ns.start()?;
 
         let resolver = Resolver::new(network, root_ns.root_hint())
-            .start_with_subject(&Implementation::Unbound)?;
+            .start_with_subject(&Implementation::unbound())?;
         let mut tshark = resolver.eavesdrop()?;
         let resolver_addr = resolver.ipv4_addr();
 
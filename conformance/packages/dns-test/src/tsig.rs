@@ -0,0 +1,97 @@
+//! Transaction signatures (RFC 8945)
+
+use core::fmt;
+use core::str::FromStr;
+
+use serde::Serialize;
+
+use crate::Error;
+
+/// A key shared between a name server and its clients, used to authenticate zone transfers and
+/// dynamic updates
+#[derive(Clone, Debug, Serialize)]
+pub struct TsigKey {
+    /// e.g. `transfer-key`
+    pub name: String,
+    pub algorithm: TsigAlgorithm,
+    /// base64 encoded
+    pub secret: String,
+}
+
+impl TsigKey {
+    /// Formats the key as the argument of `dig -y`
+    pub(crate) fn dig_arg(&self) -> String {
+        let Self {
+            name,
+            algorithm,
+            secret,
+        } = self;
+
+        format!("{}:{name}:{secret}", algorithm.as_str())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub enum TsigAlgorithm {
+    #[serde(rename = "hmac-sha256")]
+    #[default]
+    HmacSha256,
+    #[serde(rename = "hmac-sha512")]
+    HmacSha512,
+}
+
+impl TsigAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::HmacSha256 => "hmac-sha256",
+            Self::HmacSha512 => "hmac-sha512",
+        }
+    }
+}
+
+/// Verification failure reported in the error field of a response's TSIG record
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TsigError {
+    /// The MAC of the request does not verify
+    BADSIG,
+    /// The server does not know the key, or its algorithm
+    BADKEY,
+    /// The request was signed outside of the allowed time window
+    BADTIME,
+    /// The MAC of the request was truncated below the allowed length
+    BADTRUNC,
+}
+
+impl TsigError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::BADSIG => "BADSIG",
+            Self::BADKEY => "BADKEY",
+            Self::BADTIME => "BADTIME",
+            Self::BADTRUNC => "BADTRUNC",
+        }
+    }
+}
+
+impl FromStr for TsigError {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let error = match input {
+            "BADSIG" => Self::BADSIG,
+            "BADKEY" => Self::BADKEY,
+            "BADTIME" => Self::BADTIME,
+            "BADTRUNC" => Self::BADTRUNC,
+            _ => return Err(format!("unknown TSIG error: {input}").into()),
+        };
+
+        Ok(error)
+    }
+}
+
+impl fmt::Display for TsigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -3,22 +3,28 @@
 //! Note that
 //! - the `@` syntax is not used to avoid relying on the order of the entries
 //! - relative domain names are not used; all domain names must be in fully-qualified form
+//! - every record is written with its own TTL, which takes precedence over the `$TTL` default
 
 use core::fmt;
 use std::array;
-use std::net::Ipv4Addr;
+use std::mem;
+use std::net::{Ipv4Addr, Ipv6Addr};
 use std::str::FromStr;
 
-use crate::record::{self, DNSKEYRData, RRSIG, Record, RecordType, SOA, write_split_long_string};
+use crate::record::{
+    self, DNSKEYRData, PTR, RRSIG, Record, RecordType, SOA, split_column, write_split_long_string,
+};
 use crate::{DEFAULT_TTL, Error, FQDN, Result};
 
 mod signer;
 
-pub use signer::{Nsec, SignSettings, Signer};
+pub use signer::{Algorithm, DenialOfExistence, SignSettings, Signer};
 
 #[derive(Clone)]
 pub struct ZoneFile {
     origin: FQDN,
+    /// Written as the `$TTL` directive
+    pub default_ttl: u32,
     pub soa: SOA,
     pub records: Vec<Record>,
 }
@@ -28,6 +34,7 @@ impl ZoneFile {
     pub fn new(soa: SOA) -> Self {
         Self {
             origin: soa.zone.clone(),
+            default_ttl: DEFAULT_TTL,
             soa,
             records: Vec::new(),
         }
@@ -52,6 +59,36 @@ impl ZoneFile {
         self.add(Record::a(nameserver, ipv4_addr));
     }
 
+    /// Shortcut method for adding a CNAME record that makes `alias` point to `canonical`
+    ///
+    /// `canonical` does not need to be part of this zone
+    pub fn cname(&mut self, alias: FQDN, canonical: FQDN) {
+        self.add(Record::cname(alias, canonical));
+    }
+
+    /// PTR records that map back to their names the addresses of the A records within
+    /// `reverse_zone`, e.g. `2.0.192.in-addr.arpa.`
+    ///
+    /// Adding them to a name server that has authority over `reverse_zone` makes it serve the
+    /// reverse zone of this zone file.
+    pub fn ptr_records(&self, reverse_zone: &FQDN) -> Vec<PTR> {
+        self.records
+            .iter()
+            .filter_map(|record| match record {
+                Record::A(a) => Some(a),
+                _ => None,
+            })
+            .filter_map(|a| {
+                let fqdn = FQDN::reverse(a.ipv4_addr);
+                fqdn.is_in_zone(reverse_zone).then(|| PTR {
+                    fqdn,
+                    ttl: a.ttl,
+                    target: a.fqdn.clone(),
+                })
+            })
+            .collect()
+    }
+
     pub(crate) fn origin(&self) -> &FQDN {
         &self.origin
     }
@@ -59,8 +96,15 @@ impl ZoneFile {
 
 impl fmt::Display for ZoneFile {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let Self { soa, records, .. } = self;
+        let Self {
+            origin,
+            default_ttl,
+            soa,
+            records,
+        } = self;
 
+        writeln!(f, "$ORIGIN {origin}")?;
+        writeln!(f, "$TTL {default_ttl}")?;
         writeln!(f, "{soa}")?;
         for record in records {
             writeln!(f, "{record}")?;
@@ -74,22 +118,65 @@ impl FromStr for ZoneFile {
     type Err = Error;
 
     fn from_str(input: &str) -> Result<Self> {
+        Self::parse(input)
+    }
+}
+
+impl ZoneFile {
+    /// Parses the text of a zone file
+    ///
+    /// Besides what this type's `Display` implementation writes, this understands the output of
+    /// `ldns-signzone` and `dnssec-signzone`: comments, records that span several lines within
+    /// parentheses, and records that omit their owner, TTL or class. Names in the data of the
+    /// records must be fully qualified
+    pub fn parse(input: &str) -> Result<Self> {
         let mut records = vec![];
         let mut maybe_soa = None;
+        let mut maybe_origin = None;
+        let mut default_ttl = DEFAULT_TTL;
+        let mut previous_owner = None;
 
-        for line in input.lines() {
-            let mut line = line.trim();
-
-            // When using dnssec-signzone, comments are inserted; remove them.
-            if let Some((item, _)) = line.split_once(';') {
-                line = item.trim_matches('\n');
+        for entry in entries(input)? {
+            if let Some(origin) = entry.strip_prefix("$ORIGIN") {
+                maybe_origin = Some(origin.trim().parse()?);
+                continue;
             }
 
-            if line.is_empty() {
+            if let Some(ttl) = entry.strip_prefix("$TTL") {
+                default_ttl = ttl.trim().parse()?;
                 continue;
             }
 
-            let record: Record = line.parse()?;
+            if entry.starts_with('$') {
+                return Err(format!("unsupported directive: {entry}").into());
+            }
+
+            let (owner, rest) = if entry.starts_with(char::is_whitespace) {
+                let owner = previous_owner
+                    .clone()
+                    .ok_or("the first record does not have an owner")?;
+                (owner, entry.as_str())
+            } else {
+                let (owner, rest) = split_column(&entry).ok_or("empty entry")?;
+                (absolute_name(owner, maybe_origin.as_ref())?, rest)
+            };
+
+            let mut ttl = None;
+            let mut rest = rest;
+            while let Some((column, after)) = split_column(rest) {
+                if ttl.is_none() && column.parse::<u32>().is_ok() {
+                    ttl = Some(column);
+                } else if column != "IN" {
+                    break;
+                }
+
+                rest = after;
+            }
+            let ttl = ttl.map_or_else(|| default_ttl.to_string(), str::to_string);
+
+            let record: Record = format!("{owner}\t{ttl}\tIN\t{}", rest.trim()).parse()?;
+            previous_owner = Some(owner);
+
             if let Record::SOA(soa) = record {
                 if maybe_soa.is_some() {
                     return Err("found more than one SOA record".into());
@@ -103,17 +190,101 @@ impl FromStr for ZoneFile {
 
         let soa = maybe_soa.ok_or("no SOA record found in zone file")?;
         Ok(Self {
-            origin: soa.zone.clone(),
+            origin: maybe_origin.unwrap_or_else(|| soa.zone.clone()),
+            default_ttl,
             soa,
             records,
         })
     }
 }
 
+/// Splits the text of a zone file into its entries, directives and records, dropping comments
+/// and joining the lines of the entries that span several lines within parentheses
+///
+/// Entries that start with whitespace keep it as they omit their owner
+fn entries(input: &str) -> Result<Vec<String>> {
+    let mut entries = vec![];
+    let mut entry = String::new();
+    let mut parentheses = 0_usize;
+    let mut quoted = false;
+    let mut escaped = false;
+    let mut comment = false;
+
+    for c in input.chars() {
+        if comment && c != '\n' {
+            continue;
+        }
+        comment = false;
+
+        if quoted {
+            quoted = escaped || c != '"';
+            escaped = !escaped && c == '\\';
+            entry.push(c);
+            continue;
+        }
+
+        match c {
+            '"' => {
+                quoted = true;
+                entry.push(c);
+            }
+            ';' => comment = true,
+            '(' => {
+                parentheses += 1;
+                entry.push(' ');
+            }
+            ')' => {
+                parentheses = parentheses
+                    .checked_sub(1)
+                    .ok_or("unbalanced parentheses in zone file")?;
+                entry.push(' ');
+            }
+            '\n' if parentheses == 0 => {
+                if entry.trim().is_empty() {
+                    entry.clear();
+                } else {
+                    entries.push(mem::take(&mut entry));
+                }
+            }
+            '\n' => entry.push(' '),
+            _ => entry.push(c),
+        }
+    }
+
+    if parentheses != 0 || quoted {
+        return Err("zone file ends in the middle of a record".into());
+    }
+
+    if !entry.trim().is_empty() {
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+/// Qualifies the relative owner `name`, or `@`, with `origin`
+fn absolute_name(name: &str, origin: Option<&FQDN>) -> Result<String> {
+    if name.ends_with('.') {
+        return Ok(name.to_string());
+    }
+
+    let origin = origin.ok_or_else(|| format!("relative name `{name}` without an $ORIGIN"))?;
+    Ok(if name == "@" {
+        origin.to_string()
+    } else if origin == &FQDN::ROOT {
+        format!("{name}.")
+    } else {
+        format!("{name}.{origin}")
+    })
+}
+
 /// A root (server) hint
+///
+/// At least one of the addresses must be set
 #[derive(Clone)]
 pub struct Root {
-    pub ipv4_addr: Ipv4Addr,
+    pub ipv4_addr: Option<Ipv4Addr>,
+    pub ipv6_addr: Option<Ipv6Addr>,
     pub ns: FQDN,
     pub ttl: u32,
 }
@@ -122,7 +293,18 @@ impl Root {
     /// Convenience constructor that uses "reasonable" defaults
     pub fn new(ns: FQDN, ipv4_addr: Ipv4Addr) -> Self {
         Self {
-            ipv4_addr,
+            ipv4_addr: Some(ipv4_addr),
+            ipv6_addr: None,
+            ns,
+            ttl: DEFAULT_TTL,
+        }
+    }
+
+    /// Like [`Self::new`] but for a root server that is only reachable over IPv6
+    pub fn ipv6(ns: FQDN, ipv6_addr: Ipv6Addr) -> Self {
+        Self {
+            ipv4_addr: None,
+            ipv6_addr: Some(ipv6_addr),
             ns,
             ttl: DEFAULT_TTL,
         }
@@ -130,7 +312,8 @@ impl Root {
 
     pub fn public_dns() -> Root {
         Root {
-            ipv4_addr: Ipv4Addr::new(198, 41, 0, 4),
+            ipv4_addr: Some(Ipv4Addr::new(198, 41, 0, 4)),
+            ipv6_addr: None,
             ns: FQDN("a.root-servers.net.").unwrap(),
             ttl: DEFAULT_TTL,
         }
@@ -139,10 +322,27 @@ impl Root {
 
 impl fmt::Display for Root {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let Self { ipv4_addr, ns, ttl } = self;
+        let Self {
+            ipv4_addr,
+            ipv6_addr,
+            ns,
+            ttl,
+        } = self;
 
-        writeln!(f, ".\t{ttl}\tNS\t{ns}")?;
-        write!(f, "{ns}\t{ttl}\tA\t{ipv4_addr}")
+        assert!(
+            ipv4_addr.is_some() || ipv6_addr.is_some(),
+            "root hint without addresses"
+        );
+
+        write!(f, ".\t{ttl}\tNS\t{ns}")?;
+        if let Some(ipv4_addr) = ipv4_addr {
+            write!(f, "\n{ns}\t{ttl}\tA\t{ipv4_addr}")?;
+        }
+        if let Some(ipv6_addr) = ipv6_addr {
+            write!(f, "\n{ns}\t{ttl}\tAAAA\t{ipv6_addr}")?;
+        }
+
+        Ok(())
     }
 }
 
@@ -244,6 +444,8 @@ pub struct Keypair {
 pub struct SigningKeys {
     pub ksk: Keypair,
     pub zsk: Keypair,
+    /// More KSKs that also sign the DNSKEY RRset; see `Signer::add_ksk`
+    pub additional_ksks: Vec<Keypair>,
 }
 
 #[cfg(test)]
@@ -279,7 +481,8 @@ mod tests {
 
     #[test]
     fn roundtrip() -> Result<()> {
-        // `ldns-signzone`'s output minus trailing comments; long trailing fields have been split as well
+        // `ldns-signzone`'s output minus trailing comments, plus the `$ORIGIN` and `$TTL`
+        // directives; long trailing fields have been split as well
         let input = include_str!("muster.zone");
         let zone: ZoneFile = input.parse()?;
         let output = zone.to_string();
@@ -287,4 +490,133 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn directives() -> Result<()> {
+        let input = "$ORIGIN example.com.
+$TTL 300
+example.com.	86400	IN	SOA	primary.example.com. admin.example.com. 2024010101 1800 900 604800 86400
+short.example.com.	1	IN	A	192.0.2.1
+long.example.com.	300	IN	A	192.0.2.2
+";
+        let zone: ZoneFile = input.parse()?;
+
+        assert_eq!(&FQDN("example.com.")?, zone.origin());
+        assert_eq!(300, zone.default_ttl);
+        let ttls = zone
+            .records
+            .iter()
+            .filter_map(|record| record.clone().try_into_a().ok())
+            .map(|a| a.ttl)
+            .collect::<Vec<_>>();
+        assert_eq!([1, 300], *ttls);
+        assert_eq!(input, zone.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn signed_by_ldns_and_bind() -> Result<()> {
+        // the same signed zone as written by `ldns-signzone` and by `dnssec-signzone` with its
+        // default output format
+        let ldns = ZoneFile::parse(include_str!("ldns.signed.zone"))?;
+        let bind = ZoneFile::parse(include_str!("bind.signed.zone"))?;
+
+        for zone in [&ldns, &bind] {
+            assert_eq!(FQDN::ROOT, zone.soa.zone);
+            assert_eq!(2024022028, zone.soa.settings.serial);
+
+            let dnskeys = zone
+                .records
+                .iter()
+                .filter_map(|record| record.clone().try_into_dnskey().ok())
+                .collect::<Vec<_>>();
+            assert_eq!(2, dnskeys.len());
+            let [ksk] = dnskeys
+                .iter()
+                .filter(|dnskey| dnskey.is_key_signing_key())
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap();
+            assert_eq!(11245, ksk.rdata.calculate_key_tag());
+
+            let rrsig = zone
+                .records
+                .iter()
+                .find_map(|record| record.clone().try_into_rrsig().ok())
+                .unwrap();
+            assert_eq!(RecordType::SOA, rrsig.type_covered);
+            assert_eq!(11387, rrsig.key_tag);
+        }
+
+        assert_eq!(ldns.to_string(), bind.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn omitted_columns() -> Result<()> {
+        let input = "$ORIGIN example.com.
+$TTL 300
+@	86400	IN	SOA	primary.example.com. admin.example.com. (
+		2024010101 1800 900 604800 86400 )
+host	IN	A	192.0.2.1 ; relative owner, no TTL
+	60	TXT	\"a;b\" ; previous owner, no class
+";
+        let zone = ZoneFile::parse(input)?;
+
+        assert_eq!(FQDN("example.com.")?, zone.soa.zone);
+        let [a, txt] = zone.records.try_into().unwrap();
+        let a = a.try_into_a().unwrap();
+        assert_eq!(FQDN("host.example.com.")?, a.fqdn);
+        assert_eq!(300, a.ttl);
+        let txt = txt.try_into_txt().unwrap();
+        assert_eq!(a.fqdn, txt.zone);
+        assert_eq!(60, txt.ttl);
+        assert_eq!(["a;b"], *txt.character_strings);
+
+        assert!(ZoneFile::parse("$INCLUDE other.zone").is_err());
+        assert!(ZoneFile::parse(". 86400 IN SOA primary. admin. ( 1 2 3 4 5").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn ptr_records() -> Result<()> {
+        let soa = "example.com.	86400	IN	SOA	primary.example.com. admin.example.com. 2024010101 1800 900 604800 86400".parse()?;
+        let mut zone = ZoneFile::new(soa);
+        let host = FQDN("host.example.com.")?;
+        zone.add(Record::a(host.clone(), Ipv4Addr::new(192, 0, 2, 1)));
+        zone.add(Record::a(
+            FQDN("other.example.com.")?,
+            Ipv4Addr::new(198, 51, 100, 1),
+        ));
+
+        let [ptr] = zone
+            .ptr_records(&FQDN("2.0.192.in-addr.arpa.")?)
+            .try_into()
+            .unwrap();
+        assert_eq!(FQDN::reverse(Ipv4Addr::new(192, 0, 2, 1)), ptr.fqdn);
+        assert_eq!(host, ptr.target);
+
+        Ok(())
+    }
+
+    #[test]
+    fn root_hint() -> Result<()> {
+        let ns = FQDN("primary0.nameservers.com.")?;
+
+        assert_eq!(
+            ".\t86400\tNS\tprimary0.nameservers.com.\n\
+             primary0.nameservers.com.\t86400\tA\t192.0.2.1",
+            Root::new(ns.clone(), Ipv4Addr::new(192, 0, 2, 1)).to_string()
+        );
+        assert_eq!(
+            ".\t86400\tNS\tprimary0.nameservers.com.\n\
+             primary0.nameservers.com.\t86400\tAAAA\t2001:db8::1",
+            Root::ipv6(ns, "2001:db8::1".parse()?).to_string()
+        );
+
+        Ok(())
+    }
 }
@@ -1,13 +1,16 @@
 use std::{
+    cell::Cell,
     fmt,
     time::{SystemTime, UNIX_EPOCH},
 };
 
+use base64::prelude::*;
+
 use crate::{
-    FQDN,
+    FQDN, ZonemdScheme,
     container::Container,
     name_server::{DS2, Signed},
-    record::DS,
+    record::{CDS, DNSKEYRData, DS},
 };
 
 use super::{DNSKEY, Keypair, SigningKeys, ZoneFile};
@@ -27,8 +30,10 @@ pub struct SignSettings {
     algorithm: Algorithm,
     expiration: Option<u64>,
     inception: Option<u64>,
-    nsec: Nsec,
+    nsec: DenialOfExistence,
     implementation: Implementation,
+    seed: Option<u64>,
+    zonemd: Option<ZonemdScheme>,
 }
 
 impl SignSettings {
@@ -39,8 +44,10 @@ impl SignSettings {
             ksk_bits: Some(2_048),
             expiration: None,
             inception: None,
-            nsec: Nsec::default(),
+            nsec: DenialOfExistence::default(),
             implementation: Implementation::default(),
+            seed: None,
+            zonemd: None,
         }
     }
 
@@ -51,8 +58,10 @@ impl SignSettings {
             ksk_bits: Some(1_024),
             expiration: None,
             inception: None,
-            nsec: Nsec::default(),
+            nsec: DenialOfExistence::default(),
             implementation: Implementation::default(),
+            seed: None,
+            zonemd: None,
         }
     }
 
@@ -63,8 +72,10 @@ impl SignSettings {
             ksk_bits: Some(2_048),
             expiration: None,
             inception: None,
-            nsec: Nsec::default(),
+            nsec: DenialOfExistence::default(),
             implementation: Implementation::default(),
+            seed: None,
+            zonemd: None,
         }
     }
 
@@ -76,11 +87,15 @@ impl SignSettings {
             ksk_bits: Some(2_048),
             expiration: None,
             inception: None,
-            nsec: Nsec::_3 {
+            nsec: DenialOfExistence::Nsec3 {
+                // `dnssec-signzone`'s default
+                iterations: 0,
                 salt: None,
                 opt_out: true,
             },
             implementation: Implementation::Bindutils,
+            seed: None,
+            zonemd: None,
         }
     }
 
@@ -92,8 +107,10 @@ impl SignSettings {
             ksk_bits: Some(2_048),
             expiration: None,
             inception: None,
-            nsec: Nsec::default(),
+            nsec: DenialOfExistence::default(),
             implementation: Implementation::default(),
+            seed: None,
+            zonemd: None,
         }
     }
 
@@ -104,11 +121,36 @@ impl SignSettings {
             ksk_bits: None,
             expiration: None,
             inception: None,
-            nsec: Nsec::default(),
+            nsec: DenialOfExistence::default(),
+            implementation: Implementation::default(),
+            seed: None,
+            zonemd: None,
+        }
+    }
+
+    pub fn ed25519() -> Self {
+        Self {
+            algorithm: Algorithm::ED25519,
+            zsk_bits: None,
+            ksk_bits: None,
+            expiration: None,
+            inception: None,
+            nsec: DenialOfExistence::default(),
             implementation: Implementation::default(),
+            seed: None,
+            zonemd: None,
         }
     }
 
+    /// Changes the signing algorithm; the key sizes are reset to the algorithm's defaults
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        let (zsk_bits, ksk_bits) = algorithm.key_bits();
+        self.algorithm = algorithm;
+        self.zsk_bits = zsk_bits;
+        self.ksk_bits = ksk_bits;
+        self
+    }
+
     /// Set the expiration parameter from a `u64`.
     pub fn expiration_from_u64(mut self, timestamp: u64) -> Self {
         self.expiration = Some(timestamp);
@@ -127,11 +169,34 @@ impl SignSettings {
         self
     }
 
-    /// Changes the NSEC policy (default is NSEC3; see `Nsec::default`)
-    pub fn nsec(mut self, nsec: Nsec) -> Self {
+    /// Changes the NSEC policy (default is NSEC3; see `DenialOfExistence::default`)
+    pub fn nsec(mut self, nsec: DenialOfExistence) -> Self {
         self.nsec = nsec;
         self
     }
+
+    /// Derives the keys from `seed` instead of generating random ones, so that the DNSKEY and DS
+    /// records are the same from one run to the next
+    ///
+    /// Only the ECDSA and ED25519 algorithms support it; key generation fails for the others
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Adds a ZONEMD record (RFC8976), computed with `scheme`, to the signed zone
+    ///
+    /// Only `ldns-signzone` computes ZONEMD records; signing fails with the settings of
+    /// [`SignSettings::rsasha256_nsec3_optout`]
+    pub fn zonemd(mut self, scheme: ZonemdScheme) -> Self {
+        self.zonemd = Some(scheme);
+        self
+    }
+
+    /// How the signed zone proves that names and record types don't exist
+    pub fn denial_of_existence(&self) -> &DenialOfExistence {
+        &self.nsec
+    }
 }
 
 impl Default for SignSettings {
@@ -140,30 +205,94 @@ impl Default for SignSettings {
     }
 }
 
-#[derive(Clone)]
-pub enum Nsec {
-    _1,
-    _3 { opt_out: bool, salt: Option<String> },
+/// Authenticated denial of existence records added to a signed zone
+#[derive(Clone, Debug)]
+pub enum DenialOfExistence {
+    Nsec,
+    Nsec3 {
+        /// Number of additional times the owner names are hashed
+        iterations: u16,
+        opt_out: bool,
+        /// Hex-encoded salt; `None` and the empty string both mean no salt
+        salt: Option<String>,
+    },
 }
 
-impl Default for Nsec {
+impl DenialOfExistence {
+    /// The NSEC3 salt, if any; an empty salt is reported as `None`
+    pub(crate) fn nsec3_salt(&self) -> Option<&str> {
+        match self {
+            Self::Nsec => None,
+            Self::Nsec3 { salt, .. } => salt.as_deref().filter(|salt| !salt.is_empty()),
+        }
+    }
+}
+
+impl Default for DenialOfExistence {
     fn default() -> Self {
-        Self::_3 {
+        Self::Nsec3 {
+            iterations: 1,
             opt_out: false,
             salt: None,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// DNSSEC algorithm used to generate the signing keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
 #[allow(clippy::upper_case_acronyms)]
-enum Algorithm {
+pub enum Algorithm {
     DSA,
     RSAMD5,
     RSASHA1_NSEC3,
     RSASHA256,
+    RSASHA512,
     ECDSAP256SHA256,
+    ECDSAP384SHA384,
+    ED25519,
+    ED448,
+}
+
+impl Algorithm {
+    pub const ALL: [Self; 9] = [
+        Self::DSA,
+        Self::RSAMD5,
+        Self::RSASHA1_NSEC3,
+        Self::RSASHA256,
+        Self::RSASHA512,
+        Self::ECDSAP256SHA256,
+        Self::ECDSAP384SHA384,
+        Self::ED25519,
+        Self::ED448,
+    ];
+
+    /// Algorithm number in DNSKEY, DS and RRSIG records
+    pub fn number(self) -> u8 {
+        match self {
+            Self::RSAMD5 => 1,
+            Self::DSA => 3,
+            Self::RSASHA1_NSEC3 => 7,
+            Self::RSASHA256 => 8,
+            Self::RSASHA512 => 10,
+            Self::ECDSAP256SHA256 => 13,
+            Self::ECDSAP384SHA384 => 14,
+            Self::ED25519 => 15,
+            Self::ED448 => 16,
+        }
+    }
+
+    /// (ZSK, KSK) sizes in bits; `None` for algorithms with a fixed key size
+    fn key_bits(self) -> (Option<u16>, Option<u16>) {
+        match self {
+            Self::DSA => (Some(1_024), Some(1_024)),
+            Self::RSASHA1_NSEC3 => (Some(1_024), Some(2_048)),
+            Self::RSAMD5 | Self::RSASHA256 | Self::RSASHA512 => (Some(2_048), Some(2_048)),
+            Self::ECDSAP256SHA256 | Self::ECDSAP384SHA384 | Self::ED25519 | Self::ED448 => {
+                (None, None)
+            }
+        }
+    }
 }
 
 impl fmt::Display for Algorithm {
@@ -197,6 +326,56 @@ pub fn ldns_keygen_ksk(settings: &SignSettings, zone: &FQDN) -> String {
     }
 }
 
+/// How [`Signer`] turns a digest of the seed into a private key that `openssl` loads
+struct Derivation {
+    /// Computes the digest, which is the private key as is
+    digest_cmd: &'static str,
+    /// DER encoding of the private key: PKCS#8 for ED25519, SEC1 without the public key for
+    /// ECDSA, which `openssl` then computes
+    der_prefix: &'static [u8],
+    der_suffix: &'static [u8],
+    pem_label: &'static str,
+    /// The public key is the tail of its DER (SubjectPublicKeyInfo) encoding, minus the `0x04`
+    /// prefix of the uncompressed point for ECDSA
+    public_key_len: usize,
+}
+
+impl Derivation {
+    fn of(algorithm: Algorithm) -> Option<Self> {
+        Some(match algorithm {
+            Algorithm::ECDSAP256SHA256 => Self {
+                digest_cmd: "sha256sum",
+                der_prefix: &[0x30, 0x31, 0x02, 0x01, 0x01, 0x04, 0x20],
+                // [0] prime256v1
+                der_suffix: &[
+                    0xa0, 0x0a, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07,
+                ],
+                pem_label: "EC PRIVATE KEY",
+                public_key_len: 64,
+            },
+            Algorithm::ECDSAP384SHA384 => Self {
+                digest_cmd: "sha384sum",
+                der_prefix: &[0x30, 0x3e, 0x02, 0x01, 0x01, 0x04, 0x30],
+                // [0] secp384r1
+                der_suffix: &[0xa0, 0x07, 0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x22],
+                pem_label: "EC PRIVATE KEY",
+                public_key_len: 96,
+            },
+            Algorithm::ED25519 => Self {
+                digest_cmd: "sha256sum",
+                der_prefix: &[
+                    0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x04,
+                    0x22, 0x04, 0x20,
+                ],
+                der_suffix: &[],
+                pem_label: "PRIVATE KEY",
+                public_key_len: 32,
+            },
+            _ => return None,
+        })
+    }
+}
+
 fn unix_timestamp(system_time: &SystemTime) -> u64 {
     system_time
         .duration_since(UNIX_EPOCH)
@@ -209,6 +388,9 @@ pub struct Signer<'a> {
     container: &'a Container,
     /// Settings to sign with.
     settings: SignSettings,
+    /// Number of keys derived from the seed of `settings` so far; it is part of the input of
+    /// the next derivation
+    derived_keys: Cell<u32>,
 }
 
 impl<'a> Signer<'a> {
@@ -216,6 +398,7 @@ impl<'a> Signer<'a> {
         Ok(Self {
             container,
             settings,
+            derived_keys: Cell::new(0),
         })
     }
 
@@ -224,11 +407,87 @@ impl<'a> Signer<'a> {
         self.container.status_ok(&["mkdir", "-p", KEYS_DIR])?;
         let zsk = self.gen_zsk_key(zone)?;
         let ksk = self.gen_ksk_key(zone, zsk.public.rdata.calculate_key_tag())?;
-        Ok(SigningKeys { ksk, zsk })
+        Ok(SigningKeys {
+            ksk,
+            zsk,
+            additional_ksks: vec![],
+        })
+    }
+
+    /// Generates one more KSK, with a key tag different from the other KSKs', and adds it to
+    /// `keys`
+    pub fn add_ksk(&self, zone: &FQDN, keys: &mut SigningKeys) -> crate::Result<()> {
+        let zsk_keytag = keys.zsk.public.rdata.calculate_key_tag();
+        let ksk_keytags = [&keys.ksk]
+            .into_iter()
+            .chain(&keys.additional_ksks)
+            .map(|ksk| ksk.public.rdata.calculate_key_tag())
+            .collect::<Vec<_>>();
+
+        self.container.status_ok(&["mkdir", "-p", KEYS_DIR])?;
+        for _ in 0..100 {
+            let keypair = self.gen_ksk_key(zone, zsk_keytag)?;
+            if !ksk_keytags.contains(&keypair.public.rdata.calculate_key_tag()) {
+                keys.additional_ksks.push(keypair);
+                return Ok(());
+            }
+        }
+
+        Err("could not generate a KSK with a key tag different from the other KSKs'".into())
+    }
+
+    /// Generates an additional ZSK whose key tag collides with neither the KSK's nor any of
+    /// `zsk_keytags`
+    pub fn generate_zsk(
+        &self,
+        zone: &FQDN,
+        ksk_keytags: &[u16],
+        zsk_keytags: &[u16],
+    ) -> crate::Result<Keypair> {
+        self.container.status_ok(&["mkdir", "-p", KEYS_DIR])?;
+        // same `ldns-signzone` restriction as in `gen_ksk_key`
+        for _ in 0..100 {
+            let keypair = self.gen_zsk_key(zone)?;
+            let zsk_keytag = keypair.public.rdata.calculate_key_tag();
+            if !ksk_keytags.contains(&zsk_keytag)
+                && !ksk_keytags.contains(&zsk_keytag.wrapping_add(1))
+                && !zsk_keytags.contains(&zsk_keytag)
+            {
+                return Ok(keypair);
+            }
+        }
+
+        Err(
+            format!("could not generate collision-free ZSK for KSKs with keytags {ksk_keytags:?}")
+                .into(),
+        )
+    }
+
+    /// Derives the CDS record of `key`, using SHA-256 as the digest algorithm
+    ///
+    /// Adding the CDS to the zone file before signing it asks the parent zone to publish the
+    /// equivalent DS record (RFC 8078)
+    pub fn cds(&self, key: &DNSKEY) -> crate::Result<CDS> {
+        self.container.status_ok(&["mkdir", "-p", KEYS_DIR])?;
+        let key_path = format!("{KEYS_DIR}/cds.key");
+        self.container.cp(&key_path, &format!("{key}\n"))?;
+
+        // -n = print the DS record to stdout instead of writing it to a file
+        let ds: DS = self
+            .container
+            .stdout(&["ldns-key2ds", "-n", "-2", &key_path])?
+            .parse()?;
+        Ok(ds.into())
     }
 
     /// Signs the [`ZoneFile`] with the [`SignSettings`].
     pub fn sign_zone(&self, zone_file: &ZoneFile, keys: &SigningKeys) -> crate::Result<Signed> {
+        if self.settings.zonemd.is_some() {
+            if let Implementation::Bindutils = self.settings.implementation {
+                return Err("`dnssec-signzone` does not add ZONEMD records".into());
+            }
+        }
+
         self.container.status_ok(&["mkdir", "-p", ZONES_DIR])?;
         let zone_file_path = zone_file_path();
         self.container.cp(&zone_file_path, &zone_file.to_string())?;
@@ -256,7 +515,21 @@ impl<'a> Signer<'a> {
             &format!("{}\n", keys.ksk.private),
         )?;
 
-        let signzone_cmd = self.sign_zone_cmd(zone, [zsk_filename, ksk_filename].iter().cloned());
+        let mut key_filenames = vec![zsk_filename, ksk_filename];
+        for (index, ksk) in keys.additional_ksks.iter().enumerate() {
+            let filename = format!("ksk{}", index + 1);
+            self.container.cp(
+                &format!("{ZONES_DIR}/{filename}.key"),
+                &format!("{}\n", ksk.public),
+            )?;
+            self.container.cp(
+                &format!("{ZONES_DIR}/{filename}.private"),
+                &format!("{}\n", ksk.private),
+            )?;
+            key_filenames.push(filename);
+        }
+
+        let signzone_cmd = self.sign_zone_cmd(zone, key_filenames.into_iter());
         let signzone = format!("cd {ZONES_DIR} && {}", signzone_cmd);
         self.container.status_ok(&["sh", "-c", &signzone])?;
 
@@ -269,7 +542,7 @@ impl<'a> Signer<'a> {
             .lines()
             .map(|line| line.parse())
             .collect::<Result<Vec<DS>, _>>()?;
-        let ds = DS2::classify(dses, &keys.zsk.public, &keys.ksk.public);
+        let ds = DS2::classify(dses, keys);
 
         let signed: ZoneFile = self
             .container
@@ -284,12 +557,17 @@ impl<'a> Signer<'a> {
             signed,
             ksk,
             zsk,
+            keys: keys.clone(),
+            settings: self.settings.clone(),
             use_dnssec: true,
         })
     }
 
     fn gen_zsk_key(&self, zone: &FQDN) -> crate::Result<Keypair> {
-        self.gen_key(&ldns_keygen_zsk(&self.settings, zone))
+        match self.settings.seed {
+            Some(seed) => self.derive_key(zone, seed, false),
+            None => self.gen_key(&ldns_keygen_zsk(&self.settings, zone)),
+        }
     }
 
     fn gen_ksk_key(&self, zone: &FQDN, zsk_keytag: u16) -> crate::Result<Keypair> {
@@ -297,7 +575,10 @@ impl<'a> Signer<'a> {
         // keytag as the ZSK, or a keytag one higher than the ZSK.
         // See https://github.com/hickory-dns/hickory-dns/issues/2555
         for _ in 0..100 {
-            let keypair = self.gen_key(&ldns_keygen_ksk(&self.settings, zone))?;
+            let keypair = match self.settings.seed {
+                Some(seed) => self.derive_key(zone, seed, true)?,
+                None => self.gen_key(&ldns_keygen_ksk(&self.settings, zone))?,
+            };
             let ksk_keytag = keypair.public.rdata.calculate_key_tag();
             if ksk_keytag != zsk_keytag && ksk_keytag != zsk_keytag.wrapping_add(1) {
                 return Ok(keypair);
@@ -324,6 +605,73 @@ impl<'a> Signer<'a> {
         })
     }
 
+    /// Derives a key pair from `seed`, `zone`, the role of the key and the number of keys
+    /// derived so far, so that the generation retries of `gen_ksk_key` and `generate_zsk` still
+    /// get different keys
+    fn derive_key(&self, zone: &FQDN, seed: u64, ksk: bool) -> crate::Result<Keypair> {
+        let algorithm = self.settings.algorithm;
+        let derivation = Derivation::of(algorithm).ok_or_else(|| {
+            format!("{algorithm} keys cannot be derived from a seed; use ECDSA or ED25519")
+        })?;
+
+        let index = self.derived_keys.get();
+        self.derived_keys.set(index + 1);
+        let flags = if ksk { 257 } else { 256 };
+
+        let input = format!("{seed}:{zone}:{flags}:{index}");
+        let digest = self.container.stdout(&[
+            "sh",
+            "-c",
+            &format!("printf %s '{input}' | {}", derivation.digest_cmd),
+        ])?;
+        let private_key = hex::decode(digest.split_whitespace().next().unwrap_or_default())?;
+
+        let der = [derivation.der_prefix, &private_key, derivation.der_suffix].concat();
+        let label = derivation.pem_label;
+        let pem_path = format!("{KEYS_DIR}/derived.pem");
+        self.container.cp(
+            &pem_path,
+            &format!(
+                "-----BEGIN {label}-----\n{}\n-----END {label}-----\n",
+                BASE64_STANDARD.encode(der)
+            ),
+        )?;
+
+        let public_pem = self
+            .container
+            .stdout(&["openssl", "pkey", "-in", &pem_path, "-pubout"])?;
+        let spki = BASE64_STANDARD.decode(
+            public_pem
+                .lines()
+                .filter(|line| !line.starts_with("-----"))
+                .collect::<String>(),
+        )?;
+        let public_key = spki
+            .get(spki.len().saturating_sub(derivation.public_key_len)..)
+            .filter(|public_key| public_key.len() == derivation.public_key_len)
+            .ok_or("`openssl pkey` printed a truncated public key")?;
+
+        // the format `ldns-keygen` writes, which `ldns-signzone` and `dnssec-signzone` read
+        let number = algorithm.number();
+        let private = format!(
+            "Private-key-format: v1.2\nAlgorithm: {number} ({algorithm})\nPrivateKey: {}",
+            BASE64_STANDARD.encode(&private_key)
+        );
+
+        Ok(Keypair {
+            public: DNSKEY {
+                zone: zone.clone(),
+                rdata: DNSKEYRData {
+                    flags,
+                    protocol: 3,
+                    algorithm: number,
+                    public_key: BASE64_STANDARD.encode(public_key),
+                },
+            },
+            private,
+        })
+    }
+
     fn sign_zone_cmd<T>(&self, zone: &FQDN, keys: T) -> String
     where
         T: Iterator<Item = String>,
@@ -341,17 +689,32 @@ impl<'a> Signer<'a> {
 
                 // NSEC3 related options
                 // -n = use NSEC3 instead of NSEC
-                if let Nsec::_3 { salt, opt_out } = &self.settings.nsec {
+                if let DenialOfExistence::Nsec3 {
+                    iterations,
+                    opt_out,
+                    ..
+                } = &self.settings.nsec
+                {
                     args.push("-n".to_string());
+                    args.push(format!("-t {iterations}"));
 
                     if *opt_out {
                         args.push("-p".to_string());
                     }
 
-                    if let Some(salt) = salt {
+                    // `ldns-signzone` defaults to an empty salt
+                    if let Some(salt) = self.settings.nsec.nsec3_salt() {
                         args.push(format!("-s {}", salt));
                     }
                 }
+
+                if let Some(zonemd) = self.settings.zonemd {
+                    args.push(format!(
+                        "-z {}:{}",
+                        zonemd.scheme(),
+                        zonemd.hash_algorithm()
+                    ));
+                }
                 args.push(ZONE_FILENAME.to_string());
 
                 args.extend(keys);
@@ -373,15 +736,21 @@ impl<'a> Signer<'a> {
 
                 // Set -3 for NSEC3, optionally followed by a salt.
                 // -A sets opt-out
-                if let Nsec::_3 { salt, opt_out } = &self.settings.nsec {
+                if let DenialOfExistence::Nsec3 {
+                    iterations,
+                    opt_out,
+                    ..
+                } = &self.settings.nsec
+                {
+                    args.push(format!("-H {iterations}"));
                     args.push("-3".to_string());
 
-                    if let Some(salt) = salt {
+                    if let Some(salt) = self.settings.nsec.nsec3_salt() {
                         args.push(salt.to_string());
                     } else {
-                        // Set no salt, or else dnssec-signzone will interepret the next
-                        // argument as a salt.
-                        args.push("''".to_string());
+                        // `-` sets no salt; the salt argument is mandatory or else
+                        // dnssec-signzone will interpret the next argument as a salt.
+                        args.push("-".to_string());
                     }
 
                     if *opt_out {
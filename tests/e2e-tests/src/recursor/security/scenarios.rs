@@ -40,7 +40,9 @@ fn tx_id_validation_test() -> Result<()> {
     let _leaf_ns = leaf_ns.start()?;
 
     thread::sleep(Duration::from_secs(2));
-    let a_settings = *DigSettings::default().recurse().timeout(7);
+    let a_settings = *DigSettings::default()
+        .recurse()
+        .timeout(Duration::from_secs(7));
     let res = client.dig(
         a_settings,
         resolver.ipv4_addr(),
@@ -93,7 +95,9 @@ fn case_randomization_enabled() -> Result<()> {
     let _root_ns = root_ns.start()?;
     let _leaf_ns = leaf_ns.start()?;
 
-    let settings = *DigSettings::default().recurse().timeout(7);
+    let settings = *DigSettings::default()
+        .recurse()
+        .timeout(Duration::from_secs(7));
     let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &target_fqdn)?;
 
     assert_eq!(output.status, DigStatus::SERVFAIL);
@@ -146,7 +150,9 @@ fn case_randomization_disabled() -> Result<()> {
     let _root_ns = root_ns.start()?;
     let _leaf_ns = leaf_ns.start()?;
 
-    let settings = *DigSettings::default().recurse().timeout(7);
+    let settings = *DigSettings::default()
+        .recurse()
+        .timeout(Duration::from_secs(7));
     let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &target_fqdn)?;
 
     assert_eq!(output.status, DigStatus::NOERROR);
@@ -204,7 +210,9 @@ fn case_randomization_tcp_fallback() -> Result<()> {
     let _root_ns = root_ns.start()?;
     let _leaf_ns = leaf_ns.start()?;
 
-    let settings = *DigSettings::default().recurse().timeout(7);
+    let settings = *DigSettings::default()
+        .recurse()
+        .timeout(Duration::from_secs(7));
     let output = client.dig(settings, resolver.ipv4_addr(), RecordType::A, &target_fqdn)?;
 
     assert_eq!(output.status, DigStatus::NOERROR);
@@ -545,7 +545,7 @@ fn compare(subdomain: &str) -> Result<DigOutput> {
 
     let unbound = Resolver::new(&network, Root::public_dns())
         .trust_anchor(&TrustAnchor::public_dns())
-        .start_with_subject(&Implementation::Unbound)?;
+        .start_with_subject(&Implementation::unbound())?;
 
     let hickory = Resolver::new(&network, Root::public_dns())
         .trust_anchor(&TrustAnchor::public_dns())
@@ -582,7 +582,7 @@ fn hermetic_compare(subdomain: &str) -> Result<DigOutput> {
 
     let unbound = Resolver::new(&network, graph.root.clone())
         .trust_anchor(graph.trust_anchor.as_ref().unwrap())
-        .start_with_subject(&Implementation::Unbound)?;
+        .start_with_subject(&Implementation::unbound())?;
     let hickory = Resolver::new(&network, graph.root.clone())
         .trust_anchor(graph.trust_anchor.as_ref().unwrap())
         .start_with_subject(&Implementation::hickory())?;
@@ -615,8 +615,8 @@ fn hermetic_compare(subdomain: &str) -> Result<DigOutput> {
 fn setup_hermetic_network(subdomain: &str, network: &Network) -> Result<(FQDN, Graph)> {
     let subdomain_fqdn = FQDN(format!("{subdomain}.extended-dns-errors.com."))?;
 
-    let mut root_ns = NameServer::new(&Implementation::Bind, FQDN::ROOT, network)?;
-    let mut tld_ns = NameServer::new(&Implementation::Bind, FQDN::COM_TLD, network)?;
+    let mut root_ns = NameServer::new(&Implementation::bind(), FQDN::ROOT, network)?;
+    let mut tld_ns = NameServer::new(&Implementation::bind(), FQDN::COM_TLD, network)?;
     let parent_ns = NameServer::new(&Implementation::EdeDotCom, FQDN::EDE_DOT_COM, network)?;
     let child_ns = NameServer::new(&Implementation::EdeDotCom, subdomain_fqdn.clone(), network)?;
 